@@ -0,0 +1,137 @@
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+
+use font_render::vulkano_text::{DrawText, TextStyle};
+
+use vulkano::command_buffer::allocator::StandardCommandBufferAllocator;
+use vulkano::device::{Device, DeviceCreateInfo, Queue, QueueCreateInfo, QueueFlags};
+use vulkano::instance::{Instance, InstanceCreateInfo};
+use vulkano::memory::allocator::StandardMemoryAllocator;
+use vulkano::VulkanLibrary;
+
+use std::sync::Arc;
+
+// string lengths in chars, a label, a paragraph and a page
+const LENGTHS: [usize; 3] = [16, 256, 4096];
+const EXTENT: [u32; 2] = [1920, 1080];
+
+// any device with a graphics queue, the benchmarks never present
+fn device() -> (Arc<Device>, Arc<Queue>) {
+    let library = VulkanLibrary::new().unwrap();
+    let instance = Instance::new(library, InstanceCreateInfo::default()).unwrap();
+    let (physical_device, queue_family_index) = instance
+        .enumerate_physical_devices()
+        .unwrap()
+        .find_map(|p| {
+            p.queue_family_properties()
+                .iter()
+                .position(|q| q.queue_flags.intersects(QueueFlags::GRAPHICS))
+                .map(|i| (p, i as u32))
+        })
+        .expect("No suitable physical device found");
+    let (device, mut queues) = Device::new(
+        physical_device,
+        DeviceCreateInfo {
+            queue_create_infos: vec![QueueCreateInfo {
+                queue_family_index,
+                ..Default::default()
+            }],
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    (device, queues.next().unwrap())
+}
+
+// wrapped into lines that fit the extent, so nothing gets culled
+fn text(len: usize) -> String {
+    "The quick brown fox jumps over the lazy dog. "
+        .chars()
+        .cycle()
+        .take(len)
+        .enumerate()
+        .map(|(i, c)| if i % 100 == 99 { '\n' } else { c })
+        .collect()
+}
+
+fn layout(c: &mut Criterion) {
+    let (device, queue) = device();
+    let draw_text = DrawText::headless(device, queue);
+    let style = TextStyle::new(16.0, [1.0; 4]);
+
+    let mut group = c.benchmark_group("layout");
+    for len in LENGTHS {
+        let text = text(len);
+        group.throughput(Throughput::Elements(len as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(len), &text, |b, text| {
+            b.iter(|| draw_text.layout_text(&style, text))
+        });
+    }
+    group.finish();
+}
+
+// queueing with the layout cache missing every time and hitting every time
+fn queue(c: &mut Criterion) {
+    let (device, queue) = device();
+    let mut draw_text = DrawText::headless(device, queue);
+    let style = TextStyle::new(16.0, [1.0; 4]);
+
+    let mut group = c.benchmark_group("queue");
+    for len in LENGTHS {
+        let text = text(len);
+        group.throughput(Throughput::Elements(len as u64));
+        for (name, capacity) in [("uncached", 0), ("cached", 1024)] {
+            draw_text.set_layout_cache_capacity(capacity);
+            group.bench_with_input(BenchmarkId::new(name, len), &text, |b, text| {
+                b.iter(|| {
+                    draw_text.queue_text_styled(0.0, 20.0, &style, text);
+                    draw_text.clear();
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+// rasterizing and uploading glyphs the atlas doesn't have yet, against building
+// quads for glyphs that are all cached
+fn quads(c: &mut Criterion) {
+    let (device, queue) = device();
+    let memory_allocator = Arc::new(StandardMemoryAllocator::new_default(device.clone()));
+    let command_buffer_allocator =
+        StandardCommandBufferAllocator::new(device.clone(), Default::default());
+    let style = TextStyle::new(16.0, [1.0; 4]);
+
+    let mut group = c.benchmark_group("quads");
+    group.sample_size(20);
+    for len in LENGTHS {
+        let text = text(len);
+        group.throughput(Throughput::Elements(len as u64));
+        group.bench_with_input(BenchmarkId::new("cold_cache", len), &text, |b, text| {
+            b.iter_batched(
+                || {
+                    let mut draw_text = DrawText::headless(device.clone(), queue.clone());
+                    draw_text.queue_text_styled(0.0, 20.0, &style, text);
+                    draw_text
+                },
+                |mut draw_text| {
+                    draw_text.generate_quads(&memory_allocator, &command_buffer_allocator, EXTENT)
+                },
+                BatchSize::PerIteration,
+            )
+        });
+
+        let mut draw_text = DrawText::headless(device.clone(), queue.clone());
+        draw_text.queue_text_styled(0.0, 20.0, &style, &text);
+        draw_text.generate_quads(&memory_allocator, &command_buffer_allocator, EXTENT);
+        group.bench_with_input(BenchmarkId::new("warm_cache", len), &text, |b, text| {
+            b.iter(|| {
+                draw_text.queue_text_styled(0.0, 20.0, &style, text);
+                draw_text.generate_quads(&memory_allocator, &command_buffer_allocator, EXTENT)
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, layout, queue, quads);
+criterion_main!(benches);
@@ -0,0 +1,260 @@
+// text on android, as a NativeActivity built with cargo apk or xbuild. the window only
+// has a surface between Resumed and Suspended, the swapchain is created and dropped
+// with it while DrawText keeps its fonts and glyph atlas
+#![cfg(target_os = "android")]
+
+use font_render::vulkano_text::{DrawText, DrawTextTrait, TextStyle};
+
+use vulkano::command_buffer::allocator::StandardCommandBufferAllocator;
+use vulkano::command_buffer::{AutoCommandBufferBuilder, ClearColorImageInfo, CommandBufferUsage};
+use vulkano::device::physical::PhysicalDeviceType;
+use vulkano::device::{
+    Device, DeviceCreateInfo, DeviceExtensions, Queue, QueueCreateInfo, QueueFlags,
+};
+use vulkano::format::ClearColorValue;
+use vulkano::image::{Image, ImageUsage};
+use vulkano::instance::{Instance, InstanceCreateInfo};
+use vulkano::memory::allocator::StandardMemoryAllocator;
+use vulkano::swapchain::{self, Surface, Swapchain, SwapchainCreateInfo, SwapchainPresentInfo};
+use vulkano::sync::{self, GpuFuture};
+use vulkano::{Validated, VulkanError, VulkanLibrary};
+
+use winit::event::{Event, WindowEvent};
+use winit::event_loop::{ControlFlow, EventLoopBuilder};
+use winit::platform::android::activity::AndroidApp;
+use winit::platform::android::EventLoopBuilderExtAndroid;
+use winit::window::{Window, WindowBuilder};
+
+use std::sync::Arc;
+
+// everything that outlives the surface, created on the first Resumed because picking
+// a queue family needs a surface
+struct Renderer {
+    device: Arc<Device>,
+    queue: Arc<Queue>,
+    command_buffer_allocator: StandardCommandBufferAllocator,
+    memory_allocator: Arc<StandardMemoryAllocator>,
+    draw_text: DrawText,
+}
+
+impl Renderer {
+    fn new(instance: &Arc<Instance>, surface: &Arc<Surface>) -> Renderer {
+        let device_extensions = DeviceExtensions {
+            khr_swapchain: true,
+            ..DeviceExtensions::empty()
+        };
+        let (physical_device, queue_family_index) = instance
+            .enumerate_physical_devices()
+            .unwrap()
+            .filter(|p| p.supported_extensions().contains(&device_extensions))
+            .filter_map(|p| {
+                p.queue_family_properties()
+                    .iter()
+                    .enumerate()
+                    .position(|(i, q)| {
+                        q.queue_flags.intersects(QueueFlags::GRAPHICS)
+                            && p.surface_support(i as u32, surface).unwrap_or(false)
+                    })
+                    .map(|i| (p, i as u32))
+            })
+            .min_by_key(|(p, _)| match p.properties().device_type {
+                PhysicalDeviceType::DiscreteGpu => 0,
+                PhysicalDeviceType::IntegratedGpu => 1,
+                _ => 2,
+            })
+            .expect("No suitable physical device found");
+
+        let (device, mut queues) = Device::new(
+            physical_device,
+            DeviceCreateInfo {
+                enabled_extensions: device_extensions,
+                queue_create_infos: vec![QueueCreateInfo {
+                    queue_family_index,
+                    ..Default::default()
+                }],
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let queue = queues.next().unwrap();
+
+        Renderer {
+            command_buffer_allocator: StandardCommandBufferAllocator::new(
+                device.clone(),
+                Default::default(),
+            ),
+            memory_allocator: Arc::new(StandardMemoryAllocator::new_default(device.clone())),
+            // gets its swapchain images from on_surface_recreated
+            draw_text: DrawText::headless(device.clone(), queue.clone()),
+            device,
+            queue,
+        }
+    }
+}
+
+fn create_swapchain(
+    device: &Arc<Device>,
+    surface: Arc<Surface>,
+    window: &Window,
+) -> (Arc<Swapchain>, Vec<Arc<Image>>) {
+    let caps = device
+        .physical_device()
+        .surface_capabilities(&surface, Default::default())
+        .unwrap();
+    let format = device
+        .physical_device()
+        .surface_formats(&surface, Default::default())
+        .unwrap()[0]
+        .0;
+    Swapchain::new(
+        device.clone(),
+        surface,
+        SwapchainCreateInfo {
+            min_image_count: caps.min_image_count,
+            image_format: format,
+            image_extent: window.inner_size().into(),
+            image_usage: ImageUsage::COLOR_ATTACHMENT | ImageUsage::TRANSFER_DST,
+            composite_alpha: caps.supported_composite_alpha.into_iter().next().unwrap(),
+            ..Default::default()
+        },
+    )
+    .unwrap()
+}
+
+#[no_mangle]
+fn android_main(app: AndroidApp) {
+    let event_loop = EventLoopBuilder::new().with_android_app(app).build();
+
+    let library = VulkanLibrary::new().unwrap();
+    let required_extensions = Surface::required_extensions(&event_loop);
+    let instance = Instance::new(
+        library,
+        InstanceCreateInfo {
+            enabled_extensions: required_extensions,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    let window = Arc::new(WindowBuilder::new().build(&event_loop).unwrap());
+    let style = TextStyle::new(48.0, [1.0, 1.0, 1.0, 1.0]);
+
+    let mut renderer: Option<Renderer> = None;
+    // only while the app is in the foreground
+    let mut swapchain: Option<(Arc<Swapchain>, Vec<Arc<Image>>)> = None;
+    let mut recreate_swapchain = false;
+    let mut previous_frame_end: Option<Box<dyn GpuFuture>> = None;
+
+    event_loop.run(move |event, _, control_flow| {
+        *control_flow = ControlFlow::Wait;
+
+        match event {
+            Event::Resumed => {
+                let surface = Surface::from_window(instance.clone(), window.clone()).unwrap();
+                let renderer = renderer.get_or_insert_with(|| Renderer::new(&instance, &surface));
+                let (new_swapchain, images) = create_swapchain(&renderer.device, surface, &window);
+                renderer.draw_text.on_surface_recreated(&images).unwrap();
+                swapchain = Some((new_swapchain, images));
+                previous_frame_end = Some(sync::now(renderer.device.clone()).boxed());
+                window.request_redraw();
+            }
+            Event::Suspended => {
+                // dropping the last frame's future waits for the gpu to finish it
+                previous_frame_end = None;
+                swapchain = None;
+                if let Some(renderer) = &mut renderer {
+                    renderer.draw_text.on_surface_lost();
+                }
+            }
+            Event::WindowEvent {
+                event: WindowEvent::Resized(_),
+                ..
+            } => {
+                recreate_swapchain = true;
+                window.request_redraw();
+            }
+            Event::RedrawRequested(_) => {
+                let (renderer, (swapchain, images), previous_frame_end) =
+                    match (&mut renderer, &mut swapchain, &mut previous_frame_end) {
+                        (Some(renderer), Some(swapchain), Some(future)) => {
+                            (renderer, swapchain, future)
+                        }
+                        _ => return,
+                    };
+                previous_frame_end.cleanup_finished();
+                if recreate_swapchain {
+                    let image_extent: [u32; 2] = window.inner_size().into();
+                    if image_extent.contains(&0) {
+                        return;
+                    }
+                    let (new_swapchain, new_images) = swapchain
+                        .recreate(SwapchainCreateInfo {
+                            image_extent,
+                            ..swapchain.create_info()
+                        })
+                        .expect("Failed to recreate swapchain");
+                    *swapchain = new_swapchain;
+                    *images = new_images;
+                    renderer.draw_text.on_surface_recreated(images).unwrap();
+                    recreate_swapchain = false;
+                }
+
+                let draw_text = &mut renderer.draw_text;
+                draw_text.queue_text_styled(40.0, 120.0, &style, "hello from android");
+
+                let (image_num, suboptimal, acquire_future) =
+                    match swapchain::acquire_next_image(swapchain.clone(), None)
+                        .map_err(Validated::unwrap)
+                    {
+                        Ok(r) => r,
+                        Err(VulkanError::OutOfDate) => {
+                            recreate_swapchain = true;
+                            draw_text.clear();
+                            return;
+                        }
+                        Err(e) => panic!("Failed to acquire next image: {:?}", e),
+                    };
+                if suboptimal {
+                    recreate_swapchain = true;
+                }
+
+                let mut builder = AutoCommandBufferBuilder::primary(
+                    &renderer.command_buffer_allocator,
+                    renderer.queue.queue_family_index(),
+                    CommandBufferUsage::OneTimeSubmit,
+                )
+                .unwrap();
+                builder
+                    .clear_color_image(ClearColorImageInfo {
+                        clear_value: ClearColorValue::Float([0.1, 0.1, 0.12, 1.0]),
+                        ..ClearColorImageInfo::image(images[image_num as usize].clone())
+                    })
+                    .unwrap()
+                    .draw_text(draw_text, &renderer.memory_allocator, image_num as usize);
+                let command_buffer = builder.build().unwrap();
+
+                let previous = std::mem::replace(
+                    previous_frame_end,
+                    sync::now(renderer.device.clone()).boxed(),
+                );
+                let future = previous
+                    .join(acquire_future)
+                    .then_execute(renderer.queue.clone(), command_buffer)
+                    .unwrap()
+                    .then_swapchain_present(
+                        renderer.queue.clone(),
+                        SwapchainPresentInfo::swapchain_image_index(swapchain.clone(), image_num),
+                    )
+                    .then_signal_fence_and_flush()
+                    .map_err(Validated::unwrap);
+
+                match future {
+                    Ok(future) => *previous_frame_end = future.boxed(),
+                    Err(VulkanError::OutOfDate) => recreate_swapchain = true,
+                    Err(e) => panic!("Failed to flush future: {:?}", e),
+                }
+            }
+            _ => {}
+        }
+    });
+}
@@ -0,0 +1,320 @@
+use font_render::vulkano_text::{
+    raw_instance, surface_from_raw, DrawText, DrawTextTrait, InputEvent, Key, Modifiers,
+    TextEditController, TextStyle,
+};
+
+use sdl2::event::{Event, WindowEvent};
+use sdl2::keyboard::{Keycode, Mod};
+
+use vulkano::command_buffer::allocator::StandardCommandBufferAllocator;
+use vulkano::command_buffer::{AutoCommandBufferBuilder, ClearColorImageInfo, CommandBufferUsage};
+use vulkano::device::physical::PhysicalDeviceType;
+use vulkano::device::{Device, DeviceCreateInfo, DeviceExtensions, QueueCreateInfo, QueueFlags};
+use vulkano::format::ClearColorValue;
+use vulkano::image::ImageUsage;
+use vulkano::instance::{Instance, InstanceCreateInfo, InstanceExtensions};
+use vulkano::memory::allocator::StandardMemoryAllocator;
+use vulkano::swapchain::{self, SurfaceApi, Swapchain, SwapchainCreateInfo, SwapchainPresentInfo};
+use vulkano::sync::{self, GpuFuture};
+use vulkano::{Validated, VulkanError, VulkanLibrary};
+
+use std::sync::Arc;
+use std::time::Instant;
+
+// where the field's baseline starts
+const FIELD: [f32; 2] = [40.0, 60.0];
+
+// the same text field as the text_input example, in a window made by SDL2 instead of
+// winit. SDL2 creates the surface, surface_from_raw hands it to vulkano
+fn main() {
+    let sdl = sdl2::init().unwrap();
+    let video = sdl.video().unwrap();
+    let window = video
+        .window("sdl2", 800, 600)
+        .vulkan()
+        .resizable()
+        .allow_highdpi()
+        .build()
+        .unwrap();
+
+    let library = VulkanLibrary::new().unwrap();
+    let instance = Instance::new(
+        library,
+        InstanceCreateInfo {
+            enabled_extensions: InstanceExtensions::from_iter(
+                window.vulkan_instance_extensions().unwrap(),
+            ),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    // the sdl window outlives the surface, it is dropped at the end of main
+    let raw_surface = window
+        .vulkan_create_surface(raw_instance(&instance))
+        .unwrap();
+    let surface = unsafe { surface_from_raw(instance.clone(), raw_surface, surface_api(), None) };
+
+    let device_extensions = DeviceExtensions {
+        khr_swapchain: true,
+        ..DeviceExtensions::empty()
+    };
+
+    let (physical_device, queue_family_index) = instance
+        .enumerate_physical_devices()
+        .unwrap()
+        .filter(|p| p.supported_extensions().contains(&device_extensions))
+        .filter_map(|p| {
+            p.queue_family_properties()
+                .iter()
+                .enumerate()
+                .position(|(i, q)| {
+                    q.queue_flags.intersects(QueueFlags::GRAPHICS)
+                        && p.surface_support(i as u32, &surface).unwrap_or(false)
+                })
+                .map(|i| (p, i as u32))
+        })
+        .min_by_key(|(p, _)| match p.properties().device_type {
+            PhysicalDeviceType::DiscreteGpu => 0,
+            PhysicalDeviceType::IntegratedGpu => 1,
+            _ => 2,
+        })
+        .expect("No suitable physical device found");
+
+    let (device, mut queues) = Device::new(
+        physical_device,
+        DeviceCreateInfo {
+            enabled_extensions: device_extensions,
+            queue_create_infos: vec![QueueCreateInfo {
+                queue_family_index,
+                ..Default::default()
+            }],
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    let queue = queues.next().unwrap();
+
+    let (mut swapchain, images) = {
+        let caps = device
+            .physical_device()
+            .surface_capabilities(&surface, Default::default())
+            .unwrap();
+        let format = device
+            .physical_device()
+            .surface_formats(&surface, Default::default())
+            .unwrap()[0]
+            .0;
+        let (width, height) = window.vulkan_drawable_size();
+        Swapchain::new(
+            device.clone(),
+            surface.clone(),
+            SwapchainCreateInfo {
+                min_image_count: caps.min_image_count,
+                image_format: format,
+                image_extent: [width, height],
+                image_usage: ImageUsage::COLOR_ATTACHMENT | ImageUsage::TRANSFER_DST,
+                composite_alpha: caps.supported_composite_alpha.into_iter().next().unwrap(),
+                ..Default::default()
+            },
+        )
+        .unwrap()
+    };
+
+    let command_buffer_allocator =
+        StandardCommandBufferAllocator::new(device.clone(), Default::default());
+    let memory_allocator = Arc::new(StandardMemoryAllocator::new_default(device.clone()));
+
+    let mut draw_text = DrawText::new(device.clone(), queue.clone(), &images).unwrap();
+    draw_text.set_scale_factor(scale_factor(&window));
+    let mut images = images;
+    let mut field = TextEditController::new("type here");
+    let style = TextStyle::new(28.0, [1.0, 1.0, 1.0, 1.0]);
+
+    video.text_input().start();
+    let mut event_pump = sdl.event_pump().unwrap();
+    let mut recreate_swapchain = false;
+    let mut redraw = true;
+    let mut previous_frame_end = Some(sync::now(device.clone()).boxed());
+
+    'running: loop {
+        // sleep until input arrives or the caret has to blink
+        let event = match draw_text.next_redraw() {
+            _ if redraw => event_pump.poll_event(),
+            Some(at) => {
+                let timeout = at.saturating_duration_since(Instant::now());
+                event_pump.wait_event_timeout(timeout.as_millis() as u32)
+            }
+            None => Some(event_pump.wait_event()),
+        };
+
+        let mut events: Vec<Event> = event.into_iter().collect();
+        events.extend(event_pump.poll_iter());
+        for event in events {
+            match event {
+                Event::Quit { .. } => break 'running,
+                Event::Window {
+                    win_event: WindowEvent::SizeChanged(..),
+                    ..
+                } => {
+                    recreate_swapchain = true;
+                    redraw = true;
+                }
+                event => {
+                    for input in input_events(event) {
+                        redraw |= field.handle_event(&input);
+                    }
+                }
+            }
+        }
+        if draw_text
+            .next_redraw()
+            .map_or(false, |at| at <= Instant::now())
+        {
+            redraw = true;
+        }
+        if !redraw {
+            continue;
+        }
+        redraw = false;
+
+        previous_frame_end.as_mut().unwrap().cleanup_finished();
+        if recreate_swapchain {
+            let (width, height) = window.vulkan_drawable_size();
+            // minimized
+            if width == 0 || height == 0 {
+                continue;
+            }
+            let (new_swapchain, new_images) = swapchain
+                .recreate(SwapchainCreateInfo {
+                    image_extent: [width, height],
+                    ..swapchain.create_info()
+                })
+                .expect("Failed to recreate swapchain");
+            swapchain = new_swapchain;
+            images = new_images;
+            draw_text.on_surface_recreated(&images).unwrap();
+            draw_text.set_scale_factor(scale_factor(&window));
+            recreate_swapchain = false;
+        }
+
+        field.queue(&mut draw_text, FIELD[0], FIELD[1], &style);
+
+        let (image_num, suboptimal, acquire_future) =
+            match swapchain::acquire_next_image(swapchain.clone(), None).map_err(Validated::unwrap)
+            {
+                Ok(r) => r,
+                Err(VulkanError::OutOfDate) => {
+                    recreate_swapchain = true;
+                    redraw = true;
+                    draw_text.clear();
+                    continue;
+                }
+                Err(e) => panic!("Failed to acquire next image: {:?}", e),
+            };
+        if suboptimal {
+            recreate_swapchain = true;
+        }
+
+        let mut builder = AutoCommandBufferBuilder::primary(
+            &command_buffer_allocator,
+            queue.queue_family_index(),
+            CommandBufferUsage::OneTimeSubmit,
+        )
+        .unwrap();
+        builder
+            .clear_color_image(ClearColorImageInfo {
+                clear_value: ClearColorValue::Float([0.1, 0.1, 0.12, 1.0]),
+                ..ClearColorImageInfo::image(images[image_num as usize].clone())
+            })
+            .unwrap()
+            .draw_text(&mut draw_text, &memory_allocator, image_num as usize);
+        let command_buffer = builder.build().unwrap();
+
+        let future = previous_frame_end
+            .take()
+            .unwrap()
+            .join(acquire_future)
+            .then_execute(queue.clone(), command_buffer)
+            .unwrap()
+            .then_swapchain_present(
+                queue.clone(),
+                SwapchainPresentInfo::swapchain_image_index(swapchain.clone(), image_num),
+            )
+            .then_signal_fence_and_flush()
+            .map_err(Validated::unwrap);
+
+        previous_frame_end = match future {
+            Ok(future) => Some(future.boxed()),
+            Err(VulkanError::OutOfDate) => {
+                recreate_swapchain = true;
+                Some(sync::now(device.clone()).boxed())
+            }
+            Err(e) => panic!("Failed to flush future: {:?}", e),
+        };
+    }
+}
+
+// which platform api SDL2 made the surface with
+fn surface_api() -> SurfaceApi {
+    if cfg!(target_os = "windows") {
+        SurfaceApi::Win32
+    } else if cfg!(any(target_os = "macos", target_os = "ios")) {
+        SurfaceApi::Metal
+    } else if std::env::var("SDL_VIDEODRIVER").map_or(false, |driver| driver == "wayland") {
+        SurfaceApi::Wayland
+    } else {
+        SurfaceApi::Xlib
+    }
+}
+
+// drawable pixels per window pixel, SDL2's hidpi scale factor
+fn scale_factor(window: &sdl2::video::Window) -> f64 {
+    let (width, _) = window.vulkan_drawable_size();
+    let (logical, _) = window.size();
+    width as f64 / logical.max(1) as f64
+}
+
+// SDL2 events as the text field's input events
+fn input_events(event: Event) -> Vec<InputEvent> {
+    match event {
+        Event::TextInput { text, .. } => vec![InputEvent::Commit(text)],
+        Event::TextEditing { text, start, .. } => {
+            // start counts chars, the field wants a byte index
+            let caret = text
+                .char_indices()
+                .nth(start as usize)
+                .map_or(text.len(), |(i, _)| i);
+            vec![InputEvent::Preedit(text, Some(caret))]
+        }
+        Event::KeyDown {
+            keycode: Some(keycode),
+            keymod,
+            ..
+        } => {
+            let modifiers = InputEvent::Modifiers(Modifiers {
+                shift: keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD),
+                ctrl: keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD),
+            });
+            let key = match keycode {
+                Keycode::Left => Key::Left,
+                Keycode::Right => Key::Right,
+                Keycode::Home => Key::Home,
+                Keycode::End => Key::End,
+                Keycode::Backspace => Key::Backspace,
+                Keycode::Delete => Key::Delete,
+                Keycode::Return | Keycode::KpEnter => Key::Enter,
+                Keycode::PageUp => Key::PageUp,
+                Keycode::PageDown => Key::PageDown,
+                // sdl keycodes of letters and digits are their lowercase ascii
+                _ => match char::from_u32(keycode as i32 as u32) {
+                    Some(c) if c.is_ascii_lowercase() || c.is_ascii_digit() => Key::Character(c),
+                    _ => return vec![modifiers],
+                },
+            };
+            vec![modifiers, InputEvent::KeyPressed(key)]
+        }
+        Event::MouseWheel { y, .. } => vec![InputEvent::Scroll(y as f32)],
+        _ => vec![],
+    }
+}
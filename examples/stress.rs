@@ -0,0 +1,374 @@
+use font_render::vulkano_text::{DrawText, DrawTextTrait, TextStyle};
+
+use vulkano::command_buffer::allocator::StandardCommandBufferAllocator;
+use vulkano::command_buffer::{AutoCommandBufferBuilder, ClearColorImageInfo, CommandBufferUsage};
+use vulkano::device::physical::PhysicalDeviceType;
+use vulkano::device::{Device, DeviceCreateInfo, DeviceExtensions, QueueCreateInfo, QueueFlags};
+use vulkano::format::ClearColorValue;
+use vulkano::image::ImageUsage;
+use vulkano::instance::{Instance, InstanceCreateInfo};
+use vulkano::memory::allocator::StandardMemoryAllocator;
+use vulkano::query::{QueryPool, QueryPoolCreateInfo, QueryResultFlags, QueryType};
+use vulkano::swapchain::{self, Surface, Swapchain, SwapchainCreateInfo, SwapchainPresentInfo};
+use vulkano::sync::{self, GpuFuture, PipelineStage};
+use vulkano::{Validated, VulkanError, VulkanLibrary};
+
+use winit::event::{Event, WindowEvent};
+use winit::event_loop::{ControlFlow, EventLoop};
+use winit::window::WindowBuilder;
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+// 2000 lines of 100 chars, a few hundred of them on screen at once
+const LINES: usize = 2000;
+const COLUMNS: usize = 100;
+const LINE_HEIGHT: f32 = 14.0;
+// logical pixels per frame, whole pixels keep hitting the layout cache
+const SCROLL_SPEED: f32 = 2.0;
+const REPORT_EVERY: Duration = Duration::from_secs(1);
+
+// times summed up since the last report
+#[derive(Default)]
+struct Stats {
+    frames: u32,
+    queue: Duration,
+    record: Duration,
+    gpu: Duration,
+    gpu_frames: u32,
+    glyphs: usize,
+}
+
+impl Stats {
+    fn report(&self, elapsed: Duration, draw_text: &DrawText) {
+        let frames = self.frames.max(1);
+        let ms = |total: Duration, n: u32| total.as_secs_f64() * 1000.0 / n.max(1) as f64;
+        let gpu = if self.gpu_frames > 0 {
+            format!("{:.3} ms", ms(self.gpu, self.gpu_frames))
+        } else {
+            "n/a".to_owned()
+        };
+        let stats = draw_text.cache_stats();
+        println!(
+            "{:.1} fps, {} glyphs, queue {:.3} ms, record {:.3} ms, gpu {}, atlas {} pages {:.1}%",
+            self.frames as f64 / elapsed.as_secs_f64(),
+            self.glyphs / frames as usize,
+            ms(self.queue, frames),
+            ms(self.record, frames),
+            gpu,
+            stats.pages,
+            stats.used_area_pct,
+        );
+    }
+}
+
+// two timestamps per swapchain image, read back the next time the image comes around
+fn timestamp_pool(device: &Arc<Device>, images: usize) -> Arc<QueryPool> {
+    QueryPool::new(
+        device.clone(),
+        QueryPoolCreateInfo {
+            query_count: images as u32 * 2,
+            ..QueryPoolCreateInfo::query_type(QueryType::Timestamp)
+        },
+    )
+    .unwrap()
+}
+
+// scrolls LINES * COLUMNS glyphs through the window as fast as it presents, and
+// prints cpu times and the gpu time of draw_text, measured with timestamp queries,
+// once a second. a baseline to compare changes to batching and caching against
+fn main() {
+    let event_loop = EventLoop::new();
+
+    let library = VulkanLibrary::new().unwrap();
+    let required_extensions = Surface::required_extensions(&event_loop);
+    let instance = Instance::new(
+        library,
+        InstanceCreateInfo {
+            enabled_extensions: required_extensions,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    let device_extensions = DeviceExtensions {
+        khr_swapchain: true,
+        ..DeviceExtensions::empty()
+    };
+
+    let window = Arc::new(
+        WindowBuilder::new()
+            .with_title("stress")
+            .build(&event_loop)
+            .unwrap(),
+    );
+    let surface = Surface::from_window(instance.clone(), window.clone()).unwrap();
+
+    let (physical_device, queue_family_index) = instance
+        .enumerate_physical_devices()
+        .unwrap()
+        .filter(|p| p.supported_extensions().contains(&device_extensions))
+        .filter_map(|p| {
+            p.queue_family_properties()
+                .iter()
+                .enumerate()
+                .position(|(i, q)| {
+                    q.queue_flags.intersects(QueueFlags::GRAPHICS)
+                        && p.surface_support(i as u32, &surface).unwrap_or(false)
+                })
+                .map(|i| (p, i as u32))
+        })
+        .min_by_key(|(p, _)| match p.properties().device_type {
+            PhysicalDeviceType::DiscreteGpu => 0,
+            PhysicalDeviceType::IntegratedGpu => 1,
+            _ => 2,
+        })
+        .expect("No suitable physical device found");
+
+    // nanoseconds per tick, and whether the queue can write timestamps at all
+    let timestamp_period = physical_device.properties().timestamp_period as f64;
+    let timestamps = physical_device.queue_family_properties()[queue_family_index as usize]
+        .timestamp_valid_bits
+        .is_some();
+    if !timestamps {
+        println!("the queue doesn't support timestamps, only cpu times are shown");
+    }
+
+    let (device, mut queues) = Device::new(
+        physical_device,
+        DeviceCreateInfo {
+            enabled_extensions: device_extensions,
+            queue_create_infos: vec![QueueCreateInfo {
+                queue_family_index,
+                ..Default::default()
+            }],
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    let queue = queues.next().unwrap();
+
+    let (mut swapchain, images) = {
+        let caps = device
+            .physical_device()
+            .surface_capabilities(&surface, Default::default())
+            .unwrap();
+        let format = device
+            .physical_device()
+            .surface_formats(&surface, Default::default())
+            .unwrap()[0]
+            .0;
+        Swapchain::new(
+            device.clone(),
+            surface.clone(),
+            SwapchainCreateInfo {
+                min_image_count: caps.min_image_count,
+                image_format: format,
+                image_extent: window.inner_size().into(),
+                image_usage: ImageUsage::COLOR_ATTACHMENT | ImageUsage::TRANSFER_DST,
+                composite_alpha: caps.supported_composite_alpha.into_iter().next().unwrap(),
+                ..Default::default()
+            },
+        )
+        .unwrap()
+    };
+
+    let command_buffer_allocator =
+        StandardCommandBufferAllocator::new(device.clone(), Default::default());
+    let memory_allocator = Arc::new(StandardMemoryAllocator::new_default(device.clone()));
+
+    let mut query_pool = timestamp_pool(&device, images.len());
+    // which pairs of queries hold results
+    let mut written = vec![false; images.len()];
+
+    let mut draw_text = DrawText::new(device.clone(), queue.clone(), &images).unwrap();
+    draw_text.set_scale_factor(window.scale_factor());
+    let mut images = images;
+    let style = TextStyle::new(12.0, [0.85, 0.85, 0.85, 1.0]);
+    let lines: Vec<String> = (0..LINES)
+        .map(|line| {
+            let prefix = format!("{:>5} ", line);
+            let filler = "The quick brown fox jumps over the lazy dog. "
+                .chars()
+                .cycle();
+            prefix
+                .chars()
+                .chain(filler.skip(line % 45))
+                .take(COLUMNS)
+                .collect()
+        })
+        .collect();
+
+    let mut scroll = 0.0;
+    let mut stats = Stats::default();
+    let mut last_report = Instant::now();
+
+    let mut recreate_swapchain = false;
+    let mut previous_frame_end = Some(sync::now(device.clone()).boxed());
+
+    event_loop.run(move |event, _, control_flow| {
+        *control_flow = ControlFlow::Poll;
+
+        match event {
+            Event::WindowEvent {
+                event: WindowEvent::CloseRequested,
+                ..
+            } => {
+                *control_flow = ControlFlow::Exit;
+            }
+            Event::WindowEvent {
+                event: WindowEvent::Resized(_),
+                ..
+            } => {
+                recreate_swapchain = true;
+            }
+            Event::WindowEvent {
+                event: WindowEvent::ScaleFactorChanged { scale_factor, .. },
+                ..
+            } => {
+                draw_text.set_scale_factor(scale_factor);
+                recreate_swapchain = true;
+            }
+            Event::MainEventsCleared => {
+                window.request_redraw();
+            }
+            Event::RedrawRequested(_) => {
+                previous_frame_end.as_mut().unwrap().cleanup_finished();
+                if recreate_swapchain {
+                    let image_extent: [u32; 2] = window.inner_size().into();
+                    // minimized
+                    if image_extent.contains(&0) {
+                        return;
+                    }
+                    let (new_swapchain, new_images) = swapchain
+                        .recreate(SwapchainCreateInfo {
+                            image_extent,
+                            ..swapchain.create_info()
+                        })
+                        .expect("Failed to recreate swapchain");
+                    swapchain = new_swapchain;
+                    images = new_images;
+                    draw_text.on_surface_recreated(&images).unwrap();
+                    query_pool = timestamp_pool(&device, images.len());
+                    written = vec![false; images.len()];
+                    recreate_swapchain = false;
+                }
+
+                // every line is queued, the ones outside the window are culled
+                let queue_start = Instant::now();
+                let total_height = LINES as f32 * LINE_HEIGHT;
+                scroll = (scroll + SCROLL_SPEED) % total_height;
+                for (i, line) in lines.iter().enumerate() {
+                    let y = (i as f32 + 1.0) * LINE_HEIGHT - scroll;
+                    draw_text.queue_text_styled(10.0, y, &style, line);
+                }
+                stats.queue += queue_start.elapsed();
+                stats.glyphs += LINES * COLUMNS;
+
+                let (image_num, suboptimal, acquire_future) =
+                    match swapchain::acquire_next_image(swapchain.clone(), None)
+                        .map_err(Validated::unwrap)
+                    {
+                        Ok(r) => r,
+                        Err(VulkanError::OutOfDate) => {
+                            recreate_swapchain = true;
+                            draw_text.clear();
+                            return;
+                        }
+                        Err(e) => panic!("Failed to acquire next image: {:?}", e),
+                    };
+                if suboptimal {
+                    recreate_swapchain = true;
+                }
+
+                // the last frame on this image, if the gpu is done with it
+                let slot = image_num as usize;
+                let queries = image_num * 2..image_num * 2 + 2;
+                if timestamps && written[slot] {
+                    let mut ticks = [0u64; 2];
+                    let ready = query_pool
+                        .get_results(queries.clone(), &mut ticks, QueryResultFlags::empty())
+                        .unwrap();
+                    if ready {
+                        let nanos = (ticks[1] - ticks[0]) as f64 * timestamp_period;
+                        stats.gpu += Duration::from_nanos(nanos as u64);
+                        stats.gpu_frames += 1;
+                    }
+                }
+
+                let record_start = Instant::now();
+                let mut builder = AutoCommandBufferBuilder::primary(
+                    &command_buffer_allocator,
+                    queue.queue_family_index(),
+                    CommandBufferUsage::OneTimeSubmit,
+                )
+                .unwrap();
+                builder
+                    .clear_color_image(ClearColorImageInfo {
+                        clear_value: ClearColorValue::Float([0.1, 0.1, 0.12, 1.0]),
+                        ..ClearColorImageInfo::image(images[slot].clone())
+                    })
+                    .unwrap();
+                if timestamps {
+                    unsafe {
+                        builder
+                            .reset_query_pool(query_pool.clone(), queries.clone())
+                            .unwrap()
+                            .write_timestamp(
+                                query_pool.clone(),
+                                queries.start,
+                                PipelineStage::TopOfPipe,
+                            )
+                            .unwrap();
+                    }
+                }
+                builder.draw_text(&mut draw_text, &memory_allocator, slot);
+                if timestamps {
+                    unsafe {
+                        builder
+                            .write_timestamp(
+                                query_pool.clone(),
+                                queries.start + 1,
+                                PipelineStage::BottomOfPipe,
+                            )
+                            .unwrap();
+                    }
+                    written[slot] = true;
+                }
+                let command_buffer = builder.build().unwrap();
+                stats.record += record_start.elapsed();
+
+                let future = previous_frame_end
+                    .take()
+                    .unwrap()
+                    .join(acquire_future)
+                    .then_execute(queue.clone(), command_buffer)
+                    .unwrap()
+                    .then_swapchain_present(
+                        queue.clone(),
+                        SwapchainPresentInfo::swapchain_image_index(swapchain.clone(), image_num),
+                    )
+                    .then_signal_fence_and_flush()
+                    .map_err(Validated::unwrap);
+
+                previous_frame_end = match future {
+                    Ok(future) => Some(future.boxed()),
+                    Err(VulkanError::OutOfDate) => {
+                        recreate_swapchain = true;
+                        Some(sync::now(device.clone()).boxed())
+                    }
+                    Err(e) => panic!("Failed to flush future: {:?}", e),
+                };
+
+                stats.frames += 1;
+                let elapsed = last_report.elapsed();
+                if elapsed >= REPORT_EVERY {
+                    stats.report(elapsed, &draw_text);
+                    stats = Stats::default();
+                    last_report = Instant::now();
+                }
+            }
+            _ => {}
+        }
+    });
+}
@@ -0,0 +1,353 @@
+use font_render::vulkano_text::{
+    DrawText, DrawTextTrait, InputEvent, TextEditController, TextStyle,
+};
+
+use vulkano::command_buffer::allocator::StandardCommandBufferAllocator;
+use vulkano::command_buffer::{AutoCommandBufferBuilder, ClearColorImageInfo, CommandBufferUsage};
+use vulkano::device::physical::PhysicalDeviceType;
+use vulkano::device::{Device, DeviceCreateInfo, DeviceExtensions, QueueCreateInfo, QueueFlags};
+use vulkano::format::ClearColorValue;
+use vulkano::image::ImageUsage;
+use vulkano::instance::{Instance, InstanceCreateInfo};
+use vulkano::memory::allocator::StandardMemoryAllocator;
+use vulkano::swapchain::{self, Surface, Swapchain, SwapchainCreateInfo, SwapchainPresentInfo};
+use vulkano::sync::{self, GpuFuture};
+use vulkano::{Validated, VulkanError, VulkanLibrary};
+
+use renderdoc::{RenderDoc, V110};
+
+use winit::dpi::{LogicalPosition, PhysicalPosition};
+use winit::event::{ElementState, Event, KeyboardInput, MouseButton, VirtualKeyCode, WindowEvent};
+use winit::event_loop::{ControlFlow, EventLoop};
+use winit::window::WindowBuilder;
+
+use std::ptr;
+use std::sync::Arc;
+
+// where the field's baseline starts
+const FIELD: [f32; 2] = [40.0, 60.0];
+// set to anything to capture the first frame
+const CAPTURE_VAR: &str = "FONT_RENDER_CAPTURE";
+
+// captures a single frame through RenderDoc's in-application API, for attaching to
+// bug reports. only works when the example is launched from RenderDoc, F12 or
+// FONT_RENDER_CAPTURE ask for a capture
+struct Capture {
+    renderdoc: Option<RenderDoc<V110>>,
+    requested: bool,
+    capturing: bool,
+}
+
+impl Capture {
+    fn new() -> Capture {
+        let renderdoc = RenderDoc::new().ok();
+        let requested = std::env::var_os(CAPTURE_VAR).is_some();
+        if requested && renderdoc.is_none() {
+            println!(
+                "{} is set, but the example isn't running under RenderDoc",
+                CAPTURE_VAR
+            );
+        }
+        Capture {
+            renderdoc,
+            requested,
+            capturing: false,
+        }
+    }
+
+    fn request(&mut self) {
+        if self.renderdoc.is_none() {
+            println!("not running under RenderDoc, nothing to capture with");
+        }
+        self.requested = true;
+    }
+
+    // before anything of the frame is recorded
+    fn begin_frame(&mut self) {
+        if let (true, Some(renderdoc)) = (self.requested, &mut self.renderdoc) {
+            renderdoc.start_frame_capture(ptr::null(), ptr::null());
+            self.capturing = true;
+        }
+        self.requested = false;
+    }
+
+    // after the frame is submitted and presented
+    fn end_frame(&mut self) {
+        if let (true, Some(renderdoc)) = (self.capturing, &mut self.renderdoc) {
+            renderdoc.end_frame_capture(ptr::null(), ptr::null());
+            println!("captured a frame, open it from RenderDoc's capture list");
+        }
+        self.capturing = false;
+    }
+}
+
+fn main() {
+    let event_loop = EventLoop::new();
+
+    let library = VulkanLibrary::new().unwrap();
+    let required_extensions = Surface::required_extensions(&event_loop);
+    let instance = Instance::new(
+        library,
+        InstanceCreateInfo {
+            enabled_extensions: required_extensions,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    let device_extensions = DeviceExtensions {
+        khr_swapchain: true,
+        ..DeviceExtensions::empty()
+    };
+
+    let window = Arc::new(
+        WindowBuilder::new()
+            .with_title("text input")
+            .build(&event_loop)
+            .unwrap(),
+    );
+    let surface = Surface::from_window(instance.clone(), window.clone()).unwrap();
+
+    let (physical_device, queue_family_index) = instance
+        .enumerate_physical_devices()
+        .unwrap()
+        .filter(|p| p.supported_extensions().contains(&device_extensions))
+        .filter_map(|p| {
+            p.queue_family_properties()
+                .iter()
+                .enumerate()
+                .position(|(i, q)| {
+                    q.queue_flags.intersects(QueueFlags::GRAPHICS)
+                        && p.surface_support(i as u32, &surface).unwrap_or(false)
+                })
+                .map(|i| (p, i as u32))
+        })
+        .min_by_key(|(p, _)| match p.properties().device_type {
+            PhysicalDeviceType::DiscreteGpu => 0,
+            PhysicalDeviceType::IntegratedGpu => 1,
+            _ => 2,
+        })
+        .expect("No suitable physical device found");
+
+    let (device, mut queues) = Device::new(
+        physical_device,
+        DeviceCreateInfo {
+            enabled_extensions: device_extensions,
+            queue_create_infos: vec![QueueCreateInfo {
+                queue_family_index,
+                ..Default::default()
+            }],
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    let queue = queues.next().unwrap();
+
+    window.set_ime_allowed(true);
+
+    let (mut swapchain, images) = {
+        let caps = device
+            .physical_device()
+            .surface_capabilities(&surface, Default::default())
+            .unwrap();
+        let format = device
+            .physical_device()
+            .surface_formats(&surface, Default::default())
+            .unwrap()[0]
+            .0;
+        Swapchain::new(
+            device.clone(),
+            surface.clone(),
+            SwapchainCreateInfo {
+                min_image_count: caps.min_image_count,
+                image_format: format,
+                image_extent: window.inner_size().into(),
+                image_usage: ImageUsage::COLOR_ATTACHMENT | ImageUsage::TRANSFER_DST,
+                composite_alpha: caps.supported_composite_alpha.into_iter().next().unwrap(),
+                ..Default::default()
+            },
+        )
+        .unwrap()
+    };
+
+    let command_buffer_allocator =
+        StandardCommandBufferAllocator::new(device.clone(), Default::default());
+    let memory_allocator = Arc::new(StandardMemoryAllocator::new_default(device.clone()));
+
+    let mut draw_text = DrawText::new(device.clone(), queue.clone(), &images).unwrap();
+    let mut scale_factor = window.scale_factor();
+    draw_text.set_scale_factor(scale_factor);
+    let mut images = images;
+    let mut field = TextEditController::new("type here");
+    let style = TextStyle::new(28.0, [1.0, 1.0, 1.0, 1.0]);
+    let mut cursor = PhysicalPosition::new(0.0, 0.0);
+    let mut capture = Capture::new();
+
+    let mut recreate_swapchain = false;
+    let mut previous_frame_end = Some(sync::now(device.clone()).boxed());
+
+    event_loop.run(move |event, _, control_flow| {
+        // sleep until input arrives or the caret has to blink
+        *control_flow = match draw_text.next_redraw() {
+            Some(at) => ControlFlow::WaitUntil(at),
+            None => ControlFlow::Wait,
+        };
+
+        match event {
+            Event::WindowEvent {
+                event: WindowEvent::CloseRequested,
+                ..
+            } => {
+                *control_flow = ControlFlow::Exit;
+            }
+            Event::WindowEvent {
+                event: WindowEvent::Resized(_),
+                ..
+            } => {
+                recreate_swapchain = true;
+            }
+            Event::WindowEvent {
+                event:
+                    WindowEvent::ScaleFactorChanged {
+                        scale_factor: new, ..
+                    },
+                ..
+            } => {
+                scale_factor = new;
+                draw_text.set_scale_factor(scale_factor);
+                recreate_swapchain = true;
+            }
+            Event::WindowEvent {
+                event: WindowEvent::CursorMoved { position, .. },
+                ..
+            } => {
+                cursor = position;
+            }
+            Event::WindowEvent {
+                event:
+                    WindowEvent::MouseInput {
+                        state: ElementState::Pressed,
+                        button: MouseButton::Left,
+                        ..
+                    },
+                ..
+            } => {
+                // the layout is in logical pixels
+                let layout = field.layout(&draw_text, &style);
+                let cursor: LogicalPosition<f32> = cursor.to_logical(scale_factor);
+                field.click(&layout, cursor.x - FIELD[0], cursor.y - FIELD[1], false);
+                window.request_redraw();
+            }
+            Event::WindowEvent {
+                event:
+                    WindowEvent::KeyboardInput {
+                        input:
+                            KeyboardInput {
+                                state: ElementState::Pressed,
+                                virtual_keycode: Some(VirtualKeyCode::F12),
+                                ..
+                            },
+                        ..
+                    },
+                ..
+            } => {
+                capture.request();
+                window.request_redraw();
+            }
+            Event::WindowEvent { event, .. } => {
+                let changed = InputEvent::from_winit(&event)
+                    .map_or(false, |input| field.handle_event(&input));
+                if changed {
+                    window.request_redraw();
+                }
+            }
+            Event::NewEvents(_) => {
+                // nothing is queued between frames, so only the blink timer counts here
+                let now = std::time::Instant::now();
+                if draw_text.next_redraw().map_or(false, |at| at <= now) {
+                    window.request_redraw();
+                }
+            }
+            Event::RedrawRequested(_) => {
+                previous_frame_end.as_mut().unwrap().cleanup_finished();
+                if recreate_swapchain {
+                    let image_extent: [u32; 2] = window.inner_size().into();
+                    // minimized
+                    if image_extent.contains(&0) {
+                        return;
+                    }
+                    let (new_swapchain, new_images) = swapchain
+                        .recreate(SwapchainCreateInfo {
+                            image_extent,
+                            ..swapchain.create_info()
+                        })
+                        .expect("Failed to recreate swapchain");
+                    swapchain = new_swapchain;
+                    images = new_images;
+                    draw_text.on_surface_recreated(&images).unwrap();
+                    recreate_swapchain = false;
+                }
+
+                let layout = field.queue(&mut draw_text, FIELD[0], FIELD[1], &style);
+                let [ime_x, ime_y] = field.ime_position(&layout, FIELD[0], FIELD[1]);
+                window.set_ime_position(LogicalPosition::new(ime_x, ime_y));
+
+                let (image_num, suboptimal, acquire_future) =
+                    match swapchain::acquire_next_image(swapchain.clone(), None)
+                        .map_err(Validated::unwrap)
+                    {
+                        Ok(r) => r,
+                        Err(VulkanError::OutOfDate) => {
+                            recreate_swapchain = true;
+                            draw_text.clear();
+                            return;
+                        }
+                        Err(e) => panic!("Failed to acquire next image: {:?}", e),
+                    };
+                if suboptimal {
+                    recreate_swapchain = true;
+                }
+
+                capture.begin_frame();
+                let mut builder = AutoCommandBufferBuilder::primary(
+                    &command_buffer_allocator,
+                    queue.queue_family_index(),
+                    CommandBufferUsage::OneTimeSubmit,
+                )
+                .unwrap();
+                builder
+                    .clear_color_image(ClearColorImageInfo {
+                        clear_value: ClearColorValue::Float([0.1, 0.1, 0.12, 1.0]),
+                        ..ClearColorImageInfo::image(images[image_num as usize].clone())
+                    })
+                    .unwrap()
+                    .draw_text(&mut draw_text, &memory_allocator, image_num as usize);
+                let command_buffer = builder.build().unwrap();
+
+                let future = previous_frame_end
+                    .take()
+                    .unwrap()
+                    .join(acquire_future)
+                    .then_execute(queue.clone(), command_buffer)
+                    .unwrap()
+                    .then_swapchain_present(
+                        queue.clone(),
+                        SwapchainPresentInfo::swapchain_image_index(swapchain.clone(), image_num),
+                    )
+                    .then_signal_fence_and_flush()
+                    .map_err(Validated::unwrap);
+
+                previous_frame_end = match future {
+                    Ok(future) => Some(future.boxed()),
+                    Err(VulkanError::OutOfDate) => {
+                        recreate_swapchain = true;
+                        Some(sync::now(device.clone()).boxed())
+                    }
+                    Err(e) => panic!("Failed to flush future: {:?}", e),
+                };
+                capture.end_frame();
+            }
+            _ => {}
+        }
+    });
+}
@@ -0,0 +1,15 @@
+#[cfg(all(feature = "vulkano-032", feature = "vulkano-034"))]
+compile_error!("the vulkano-032 and vulkano-034 features can't be on together");
+#[cfg(all(
+    feature = "gpu",
+    not(any(feature = "vulkano-032", feature = "vulkano-034"))
+))]
+compile_error!("the gpu feature needs one of the vulkano-032 and vulkano-034 features");
+
+// so the library and vulkano-shaders' generated code find 0.32 under the usual names
+#[cfg(feature = "vulkano-032")]
+extern crate vulkano_032 as vulkano;
+#[cfg(feature = "vulkano-032")]
+extern crate vulkano_shaders_032 as vulkano_shaders;
+
+pub mod vulkano_text;
@@ -1,36 +1,29 @@
-mod vulkano_text;
-
 use vulkano::command_buffer::allocator::StandardCommandBufferAllocator;
 use vulkano::command_buffer::{
-    AutoCommandBufferBuilder, CommandBufferUsage, RenderPassBeginInfo, SubpassContents,
+    AutoCommandBufferBuilder, CommandBufferUsage, RenderPassBeginInfo, SubpassBeginInfo,
+    SubpassEndInfo,
 };
 use vulkano::device::physical::PhysicalDevice;
 use vulkano::device::physical::PhysicalDeviceType;
-use vulkano::device::{Device, DeviceCreateInfo, DeviceExtensions, QueueCreateInfo};
+use vulkano::device::{Device, DeviceCreateInfo, DeviceExtensions, QueueCreateInfo, QueueFlags};
 use vulkano::format::Format;
 use vulkano::image::view::ImageView;
-use vulkano::image::{AttachmentImage, ImageAccess, SwapchainImage};
+use vulkano::image::{Image, ImageCreateInfo, ImageType, ImageUsage};
 use vulkano::instance::{Instance, InstanceCreateInfo};
-use vulkano::memory::allocator::StandardMemoryAllocator;
+use vulkano::memory::allocator::{AllocationCreateInfo, StandardMemoryAllocator};
 use vulkano::pipeline::graphics::viewport::Viewport;
 use vulkano::pipeline::DynamicState;
 use vulkano::render_pass::{Framebuffer, FramebufferCreateInfo, RenderPass};
-use vulkano::swapchain::{
-    self, AcquireError, Swapchain, SwapchainCreateInfo, SwapchainCreationError,
-    SwapchainPresentInfo,
-};
+use vulkano::swapchain::{self, Surface, Swapchain, SwapchainCreateInfo, SwapchainPresentInfo};
 use vulkano::sync;
-use vulkano::sync::{FlushError, GpuFuture};
-use vulkano::Version;
-use vulkano::VulkanLibrary;
-
-use vulkano_win::VkSurfaceBuild;
+use vulkano::sync::GpuFuture;
+use vulkano::{Validated, Version, VulkanError, VulkanLibrary};
 
-use vulkano_text::{DrawText, DrawTextTrait};
+use font_render::vulkano_text::{DrawText, DrawTextTrait};
 
 use winit::event::{DeviceEvent, Event, KeyboardInput, WindowEvent};
 use winit::event_loop::{ControlFlow, EventLoop};
-use winit::window::{Window, WindowBuilder};
+use winit::window::WindowBuilder;
 
 use std::borrow::Borrow;
 use std::env;
@@ -38,21 +31,32 @@ use std::sync::Arc;
 use std::time::Instant;
 
 fn window_size_dependent_setup(
-    allocator: &StandardMemoryAllocator,
-    images: &[Arc<SwapchainImage>],
+    allocator: &Arc<StandardMemoryAllocator>,
+    images: &[Arc<Image>],
     render_pass: Arc<RenderPass>,
     viewport: &mut Viewport,
 ) -> Vec<Arc<Framebuffer>> {
-    let dimensions = images[0].dimensions().width_height();
+    let extent = images[0].extent();
 
     let viewport = Viewport {
-        origin: [0.0, 0.0],
-        dimensions: [dimensions[0] as f32, dimensions[1] as f32],
-        depth_range: 0.0..1.0,
+        offset: [0.0, 0.0],
+        extent: [extent[0] as f32, extent[1] as f32],
+        depth_range: 0.0..=1.0,
     };
 
     let depth_buffer = ImageView::new_default(
-        AttachmentImage::transient(allocator, dimensions, Format::D16_UNORM).unwrap(),
+        Image::new(
+            allocator.clone(),
+            ImageCreateInfo {
+                image_type: ImageType::Dim2d,
+                format: Format::D16_UNORM,
+                extent,
+                usage: ImageUsage::DEPTH_STENCIL_ATTACHMENT | ImageUsage::TRANSIENT_ATTACHMENT,
+                ..Default::default()
+            },
+            AllocationCreateInfo::default(),
+        )
+        .unwrap(),
     )
     .unwrap();
 
@@ -146,8 +150,10 @@ fn main() {
         "Vivamus volutpat fermentum mauris vel mattis.",
     );
 
+    let event_loop = EventLoop::new();
+
     let library = VulkanLibrary::new().unwrap();
-    let required_extensions = vulkano_win::required_extensions(&library);
+    let required_extensions = Surface::required_extensions(&event_loop);
     let instance = Instance::new(
         library,
         InstanceCreateInfo {
@@ -163,10 +169,8 @@ fn main() {
         ..DeviceExtensions::empty()
     };
 
-    let event_loop = EventLoop::new();
-    let surface = WindowBuilder::new()
-        .build_vk_surface(&event_loop, instance.clone())
-        .unwrap();
+    let window = Arc::new(WindowBuilder::new().build(&event_loop).unwrap());
+    let surface = Surface::from_window(instance.clone(), window.clone()).unwrap();
 
     let (physical_device, queue_family_index) = instance
         .enumerate_physical_devices()
@@ -177,7 +181,8 @@ fn main() {
                 .iter()
                 .enumerate()
                 .position(|(i, q)| {
-                    q.queue_flags.graphics && p.surface_support(i as u32, &surface).unwrap_or(false)
+                    q.queue_flags.intersects(QueueFlags::GRAPHICS)
+                        && p.surface_support(i as u32, &surface).unwrap_or(false)
                 })
                 .map(|i| (p, i as u32))
         })
@@ -193,7 +198,7 @@ fn main() {
 
     let device_ext = DeviceExtensions {
         khr_swapchain: true,
-        ..DeviceExtensions::none()
+        ..DeviceExtensions::empty()
     };
     let (device, mut queues) = Device::new(
         physical_device,
@@ -214,16 +219,13 @@ fn main() {
             .surface_capabilities(&surface, Default::default())
             .unwrap();
         let usage = caps.supported_usage_flags;
-        let alpha = caps.supported_composite_alpha.iter().next().unwrap();
-        let format = Some(
-            device
-                .physical_device()
-                .surface_formats(&surface, Default::default())
-                .unwrap()[0]
-                .0,
-        );
-
-        let window = surface.object().unwrap().downcast_ref::<Window>().unwrap();
+        let alpha = caps.supported_composite_alpha.into_iter().next().unwrap();
+        let format = device
+            .physical_device()
+            .surface_formats(&surface, Default::default())
+            .unwrap()[0]
+            .0;
+
         let image_extent: [u32; 2] = window.inner_size().into();
         Swapchain::new(
             device.clone(),
@@ -248,17 +250,17 @@ fn main() {
         device.clone(),
         attachments: {
             color: {
-                load: Clear,
-                store: Store,
                 format: swapchain.image_format(),
                 samples: 1,
+                load_op: Clear,
+                store_op: Store,
             },
             depth: {
-                load: Clear,
-                store: DontCare,
                 format: Format::D16_UNORM,
                 samples: 1,
-            }
+                load_op: Clear,
+                store_op: DontCare,
+            },
         },
         pass: {
             color: [color],
@@ -271,9 +273,10 @@ fn main() {
         StandardCommandBufferAllocator::new(device.clone(), Default::default());
     let memory_allocator = Arc::new(StandardMemoryAllocator::new_default(device.clone()));
 
-    let mut draw_text = DrawText::new(device.clone(), queue.clone(), swapchain.clone(), &images);
+    let mut draw_text = DrawText::new(device.clone(), queue.clone(), &images).unwrap();
+    #[cfg(feature = "shader_reload")]
+    draw_text.watch_shaders(concat!(env!("CARGO_MANIFEST_DIR"), "/src/shaders"));
 
-    let window = surface.object().unwrap().downcast_ref::<Window>().unwrap();
     let (width, height): (u32, u32) = window.inner_size().into();
     let mut x = 0.0;
     let mut y = 0.0;
@@ -281,9 +284,9 @@ fn main() {
     let mut color = [1.0, 1.0, 1.0, 1.0];
 
     let mut viewport = Viewport {
-        origin: [0.0, 0.0],
-        dimensions: [0.0, 0.0],
-        depth_range: 0.0..1.0,
+        offset: [0.0, 0.0],
+        extent: [0.0, 0.0],
+        depth_range: 0.0..=1.0,
     };
 
     let mut framebuffers = window_size_dependent_setup(
@@ -328,6 +331,10 @@ fn main() {
                         font_size -= 5.0;
                         recreate_swapchain = true;
                     }
+                    #[cfg(feature = "screenshot")]
+                    Some(winit::event::VirtualKeyCode::F12) => {
+                        draw_text.capture_next_frame("screenshot.png");
+                    }
                     _ => {}
                 },
                 _ => {}
@@ -337,17 +344,18 @@ fn main() {
         Event::RedrawEventsCleared => {
             previous_frame_end.as_mut().unwrap().cleanup_finished();
             if recreate_swapchain {
-                let window = surface.object().unwrap().downcast_ref::<Window>().unwrap();
                 let image_extent: [u32; 2] = window.inner_size().into();
+                // minimized
+                if image_extent.contains(&0) {
+                    return;
+                }
 
-                let (new_swapchain, new_images) = match swapchain.recreate(SwapchainCreateInfo {
-                    image_extent,
-                    ..swapchain.create_info()
-                }) {
-                    Ok(r) => r,
-                    Err(SwapchainCreationError::ImageExtentNotSupported { .. }) => return,
-                    Err(e) => panic!("Failed to recreate swapchain: {:?}", e),
-                };
+                let (new_swapchain, new_images) = swapchain
+                    .recreate(SwapchainCreateInfo {
+                        image_extent,
+                        ..swapchain.create_info()
+                    })
+                    .expect("Failed to recreate swapchain");
 
                 swapchain = new_swapchain;
                 framebuffers = window_size_dependent_setup(
@@ -357,12 +365,7 @@ fn main() {
                     &mut viewport,
                 );
 
-                draw_text = DrawText::new(
-                    device.clone(),
-                    queue.clone(),
-                    swapchain.clone(),
-                    &new_images,
-                );
+                draw_text.on_surface_recreated(&new_images).unwrap();
 
                 recreate_swapchain = false;
             }
@@ -373,9 +376,11 @@ fn main() {
             }
 
             let (image_num, suboptimal, acquire_future) =
-                match swapchain::acquire_next_image(swapchain.clone(), None) {
+                match swapchain::acquire_next_image(swapchain.clone(), None)
+                    .map_err(Validated::unwrap)
+                {
                     Ok(r) => r,
-                    Err(AcquireError::OutOfDate) => {
+                    Err(VulkanError::OutOfDate) => {
                         recreate_swapchain = true;
                         return;
                     }
@@ -400,10 +405,10 @@ fn main() {
                         clear_values,
                         ..RenderPassBeginInfo::framebuffer(framebuffers[image_num as usize].clone())
                     },
-                    SubpassContents::Inline,
+                    SubpassBeginInfo::default(),
                 )
                 .unwrap()
-                .end_render_pass()
+                .end_render_pass(SubpassEndInfo::default())
                 .unwrap()
                 .draw_text(&mut draw_text, &memory_allocator, image_num as usize);
 
@@ -419,13 +424,14 @@ fn main() {
                     queue.clone(),
                     SwapchainPresentInfo::swapchain_image_index(swapchain.clone(), image_num),
                 )
-                .then_signal_fence_and_flush();
+                .then_signal_fence_and_flush()
+                .map_err(Validated::unwrap);
 
             match future {
                 Ok(future) => {
                     previous_frame_end = Some(Box::new(future) as Box<_>);
                 }
-                Err(FlushError::OutOfDate) => {
+                Err(VulkanError::OutOfDate) => {
                     recreate_swapchain = true;
                     previous_frame_end = Some(Box::new(sync::now(device.clone())) as Box<_>);
                 }
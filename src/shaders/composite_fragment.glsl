@@ -0,0 +1,10 @@
+#version 450
+
+layout(location = 0) in vec2 v_tex_position;
+layout(location = 0) out vec4 f_color;
+
+layout(set = 0, binding = 0) uniform sampler2D overlay;
+
+void main() {
+    f_color = texture(overlay, v_tex_position);
+}
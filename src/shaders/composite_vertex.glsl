@@ -0,0 +1,9 @@
+#version 450
+
+layout(location = 0) out vec2 v_tex_position;
+
+void main() {
+    // one triangle covering the whole screen, no vertex buffer needed
+    v_tex_position = vec2((gl_VertexIndex << 1) & 2, gl_VertexIndex & 2);
+    gl_Position = vec4(v_tex_position * 2.0 - 1.0, 0.0, 1.0);
+}
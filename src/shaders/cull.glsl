@@ -0,0 +1,65 @@
+#version 450
+
+layout(local_size_x = 64, local_size_y = 1, local_size_z = 1) in;
+
+// GlyphInstance in drawtext.rs, corners in NDC
+struct Glyph {
+    vec2 corners[4];
+    vec4 uv_rect;
+    vec4 colors[4];
+    vec3 fade;
+    float page;
+};
+
+struct DrawCommand {
+    uint vertex_count;
+    uint instance_count;
+    uint first_vertex;
+    uint first_instance;
+};
+
+// every glyph queued this frame, the passes back to back
+layout(set = 0, binding = 0) readonly buffer Queued {
+    Glyph queued[];
+};
+
+// same layout, but only what survived culling
+layout(set = 0, binding = 1) writeonly buffer Visible {
+    Glyph visible[];
+};
+
+// one draw per pass of every layer, vertex counts start at 0 and first_vertex is where
+// the pass starts
+layout(set = 0, binding = 2) buffer Commands {
+    DrawCommand commands[];
+};
+
+// how much of the buffers is this frame's, they are reused and only ever grow
+layout(push_constant) uniform Counts {
+    uint glyph_count;
+    uint pass_count;
+};
+
+void main() {
+    uint i = gl_GlobalInvocationID.x;
+    if (i >= glyph_count) {
+        return;
+    }
+
+    Glyph glyph = queued[i];
+    vec2 lo = min(min(glyph.corners[0], glyph.corners[1]), min(glyph.corners[2], glyph.corners[3]));
+    vec2 hi = max(max(glyph.corners[0], glyph.corners[1]), max(glyph.corners[2], glyph.corners[3]));
+    if (any(greaterThan(lo, vec2(1.0))) || any(lessThan(hi, vec2(-1.0)))) {
+        return;
+    }
+
+    // the last pass starting at or before this glyph is the one it belongs to
+    uint pass = 0;
+    for (uint p = 1; p < pass_count; p++) {
+        if (commands[p].first_vertex <= i * 6) {
+            pass = p;
+        }
+    }
+    uint slot = atomicAdd(commands[pass].vertex_count, 6) / 6;
+    visible[commands[pass].first_vertex / 6 + slot] = glyph;
+}
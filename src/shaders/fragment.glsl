@@ -1,11 +1,21 @@
 #version 450
 
-layout(location = 0) in vec2 v_tex_position;
+// uv and atlas page
+layout(location = 0) in vec3 v_tex_position;
 layout(location = 1) in vec4 v_color;
+layout(location = 2) flat in vec3 v_fade;
 layout(location = 0) out vec4 f_color;
 
-layout(set = 0, binding = 0) uniform sampler2D tex;
+layout(set = 0, binding = 0) uniform sampler2DArray tex;
 
 void main() {
-    f_color = v_color * texture(tex, v_tex_position)[0];
+    // negative coordinates mark solid quads like underlines
+    float coverage = v_tex_position.x < 0.0 ? 1.0 : texture(tex, v_tex_position)[0];
+    // linear fade between the two positions, a hard cut when they are the same.
+    // they are x positions, or y ones for vertical text
+    float along = v_fade.z > 0.5 ? gl_FragCoord.y : gl_FragCoord.x;
+    float fade = v_fade.y > v_fade.x
+        ? clamp((v_fade.y - along) / (v_fade.y - v_fade.x), 0.0, 1.0)
+        : step(along, v_fade.y);
+    f_color = v_color * coverage * fade;
 }
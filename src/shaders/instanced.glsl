@@ -0,0 +1,35 @@
+#version 450
+
+// GlyphInstance in drawtext.rs, corners in NDC
+struct Glyph {
+    vec2 corners[4];
+    vec4 uv_rect;
+    vec4 colors[4];
+    vec3 fade;
+    float page;
+};
+
+// written by cull.glsl, six vertices per glyph
+layout(set = 1, binding = 0) readonly buffer Visible {
+    Glyph glyphs[];
+};
+
+layout(location = 0) out vec3 v_tex_position;
+layout(location = 1) out vec4 v_color;
+layout(location = 2) flat out vec3 v_fade;
+
+// bottom left, top left, top right, top right, bottom right, bottom left
+const uint CORNERS[6] = uint[](0, 1, 2, 2, 3, 0);
+
+void main() {
+    Glyph glyph = glyphs[gl_VertexIndex / 6];
+    uint corner = CORNERS[gl_VertexIndex % 6];
+    gl_Position = vec4(glyph.corners[corner], 0.0, 1.0);
+    vec2 uv = vec2(
+        corner >= 2 ? glyph.uv_rect.z : glyph.uv_rect.x,
+        corner == 1 || corner == 2 ? glyph.uv_rect.y : glyph.uv_rect.w
+    );
+    v_tex_position = vec3(uv, glyph.page);
+    v_color = glyph.colors[corner];
+    v_fade = glyph.fade;
+}
@@ -0,0 +1,37 @@
+#version 450
+
+// textured_fragment.glsl, with the texture being a multi-channel signed distance field
+// atlas whose distances are turned into coverage at whatever size the quad is drawn
+layout(location = 0) in vec3 v_tex_position;
+layout(location = 1) in vec4 v_color;
+layout(location = 2) flat in vec3 v_fade;
+layout(location = 3) in vec2 v_fill_uv;
+layout(location = 0) out vec4 f_color;
+
+layout(set = 0, binding = 0) uniform sampler2DArray tex;
+layout(set = 1, binding = 0) uniform sampler2D msdf;
+
+layout(push_constant) uniform Field {
+    // how far distances reach, in pixels of the atlas
+    float distance_range;
+} field;
+
+float median(vec3 v) {
+    return max(min(v.r, v.g), min(max(v.r, v.g), v.b));
+}
+
+void main() {
+    // msdf quads are solid, the atlas stays bound like for the other pipelines
+    float mask = v_tex_position.x < 0.0 ? 1.0 : texture(tex, v_tex_position)[0];
+    // the distance range in screen pixels, at least one so small text stays smooth
+    vec2 unit_range = vec2(field.distance_range) / vec2(textureSize(msdf, 0));
+    vec2 screen_size = vec2(1.0) / fwidth(v_fill_uv);
+    float screen_range = max(0.5 * dot(unit_range, screen_size), 1.0);
+    float distance = median(texture(msdf, v_fill_uv).rgb) - 0.5;
+    float coverage = clamp(distance * screen_range + 0.5, 0.0, 1.0) * mask;
+    float along = v_fade.z > 0.5 ? gl_FragCoord.y : gl_FragCoord.x;
+    float fade = v_fade.y > v_fade.x
+        ? clamp((v_fade.y - along) / (v_fade.y - v_fade.x), 0.0, 1.0)
+        : step(along, v_fade.y);
+    f_color = v_color * coverage * fade;
+}
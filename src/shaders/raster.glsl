@@ -0,0 +1,52 @@
+#version 450
+
+// one invocation per pixel, one workgroup layer per glyph
+layout(local_size_x = 8, local_size_y = 8, local_size_z = 1) in;
+
+// x0, y0, x1, y1 in pixels relative to the glyph's top left
+layout(set = 0, binding = 0) readonly buffer Lines {
+    vec4 lines[];
+};
+
+// first line, line count, width | height << 16, first pixel in coverage
+layout(set = 0, binding = 1) readonly buffer Glyphs {
+    uvec4 glyphs[];
+};
+
+// 0 to 255 per pixel
+layout(set = 0, binding = 2) writeonly buffer Coverage {
+    uint coverage[];
+};
+
+// samples per pixel along each axis
+const uint GRID = 4;
+
+void main() {
+    uvec4 glyph = glyphs[gl_WorkGroupID.z];
+    uint width = glyph.z & 0xffff;
+    uint height = glyph.z >> 16;
+    uvec2 pixel = gl_GlobalInvocationID.xy;
+    if (pixel.x >= width || pixel.y >= height) {
+        return;
+    }
+
+    // non-zero winding, counted along a ray to the right of every sample
+    uint inside = 0;
+    for (uint sy = 0; sy < GRID; sy++) {
+        for (uint sx = 0; sx < GRID; sx++) {
+            vec2 p = vec2(pixel) + (vec2(sx, sy) + 0.5) / float(GRID);
+            int winding = 0;
+            for (uint i = glyph.x; i < glyph.x + glyph.y; i++) {
+                vec4 line = lines[i];
+                if ((line.y <= p.y) != (line.w <= p.y)) {
+                    float t = (p.y - line.y) / (line.w - line.y);
+                    if (line.x + t * (line.z - line.x) > p.x) {
+                        winding += line.w > line.y ? 1 : -1;
+                    }
+                }
+            }
+            inside += winding != 0 ? 1 : 0;
+        }
+    }
+    coverage[glyph.w + pixel.y * width + pixel.x] = (inside * 255 + GRID * GRID / 2) / (GRID * GRID);
+}
@@ -0,0 +1,47 @@
+// vertex.glsl and fragment.glsl for the wgpu backend
+
+struct VertexInput {
+    @location(0) position: vec2<f32>,
+    // uv and atlas page
+    @location(1) tex_position: vec3<f32>,
+    @location(2) color: vec4<f32>,
+    @location(3) fade: vec3<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) tex_position: vec3<f32>,
+    @location(1) color: vec4<f32>,
+    @location(2) @interpolate(flat) fade: vec3<f32>,
+};
+
+@group(0) @binding(0) var atlas: texture_2d_array<f32>;
+@group(0) @binding(1) var atlas_sampler: sampler;
+
+@vertex
+fn vs_main(in: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+    // the positions are vulkan's, y points down
+    out.position = vec4<f32>(in.position.x, -in.position.y, 0.0, 1.0);
+    out.tex_position = in.tex_position;
+    out.color = in.color;
+    out.fade = in.fade;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // sampled outside of the branch, sampling has to happen in uniform control flow
+    let sampled = textureSample(atlas, atlas_sampler, in.tex_position.xy, i32(in.tex_position.z)).r;
+    // negative coordinates mark solid quads like underlines
+    let coverage = select(sampled, 1.0, in.tex_position.x < 0.0);
+    // linear fade between the two positions, a hard cut when they are the same.
+    // they are x positions, or y ones for vertical text
+    let along = select(in.position.x, in.position.y, in.fade.z > 0.5);
+    let fade = select(
+        step(along, in.fade.y),
+        clamp((in.fade.y - along) / (in.fade.y - in.fade.x), 0.0, 1.0),
+        in.fade.y > in.fade.x
+    );
+    return in.color * coverage * fade;
+}
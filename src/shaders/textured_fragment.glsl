@@ -0,0 +1,20 @@
+#version 450
+
+// fragment.glsl, with the coverage masking a texture stretched over the text
+layout(location = 0) in vec3 v_tex_position;
+layout(location = 1) in vec4 v_color;
+layout(location = 2) flat in vec3 v_fade;
+layout(location = 3) in vec2 v_fill_uv;
+layout(location = 0) out vec4 f_color;
+
+layout(set = 0, binding = 0) uniform sampler2DArray tex;
+layout(set = 1, binding = 0) uniform sampler2D fill;
+
+void main() {
+    float coverage = v_tex_position.x < 0.0 ? 1.0 : texture(tex, v_tex_position)[0];
+    float along = v_fade.z > 0.5 ? gl_FragCoord.y : gl_FragCoord.x;
+    float fade = v_fade.y > v_fade.x
+        ? clamp((v_fade.y - along) / (v_fade.y - v_fade.x), 0.0, 1.0)
+        : step(along, v_fade.y);
+    f_color = texture(fill, v_fill_uv) * v_color * coverage * fade;
+}
@@ -1,13 +1,19 @@
 #version 450
 
 layout(location = 0) in vec2 position;
-layout(location = 1) in vec2 tex_position;
+layout(location = 1) in vec3 tex_position;
 layout(location = 2) in vec4 color;
-layout(location = 0) out vec2 v_tex_position;
+layout(location = 3) in vec3 fade;
+layout(location = 4) in vec2 fill_uv;
+layout(location = 0) out vec3 v_tex_position;
 layout(location = 1) out vec4 v_color;
+layout(location = 2) flat out vec3 v_fade;
+layout(location = 3) out vec2 v_fill_uv;
 
 void main() {
     gl_Position = vec4(position, 0.0, 1.0);
     v_tex_position = tex_position;
     v_color = color;
+    v_fade = fade;
+    v_fill_uv = fill_uv;
 }
@@ -0,0 +1,72 @@
+use super::drawtext::{TextRendererCore, TextRole};
+
+use accesskit::{Node, NodeBuilder, NodeClassSet, NodeId, Rect, Role, Tree, TreeUpdate};
+
+use rusttype::Rect as PixelRect;
+
+// tells AccessKit about the text a DrawText drew, for apps whose accessible content
+// is that text. every drawn string becomes a node under root, which ids after root's
+// are handed out to
+pub struct AccessKitAdapter {
+    root: NodeId,
+    classes: NodeClassSet,
+    reported: Option<Vec<(String, TextRole, PixelRect<f32>)>>,
+}
+
+impl AccessKitAdapter {
+    // from now on draw_text keeps the strings of what it queues
+    pub fn new(root: NodeId, draw_text: &mut TextRendererCore) -> AccessKitAdapter {
+        draw_text.keep_labels();
+        AccessKitAdapter {
+            root,
+            classes: NodeClassSet::new(),
+            reported: None,
+        }
+    }
+
+    // the texts of the frame drawn last, in physical pixels like winit's adapter wants
+    // them. None when they are the ones reported last time, the first update also
+    // sets up the tree
+    pub fn update(&mut self, draw_text: &TextRendererCore) -> Option<TreeUpdate> {
+        let labels = draw_text.drawn_labels();
+        if self.reported.as_deref() == Some(labels) {
+            return None;
+        }
+        let tree = self.reported.is_none().then(|| Tree::new(self.root));
+        // texts past u64::MAX are left out, for roots near the end of the id range
+        let ids: Vec<NodeId> = (1..=labels.len() as u64)
+            .map_while(|i| self.root.0.checked_add(i).map(NodeId))
+            .collect();
+        // a text field queued with TextEditController keeps the focus, the first one
+        // if there are several
+        let focus = ids
+            .iter()
+            .zip(labels)
+            .find(|(_, (_, role, _))| *role == TextRole::Input)
+            .map_or(self.root, |(id, _)| *id);
+        let mut root = NodeBuilder::new(Role::Window);
+        root.set_children(ids.clone());
+        let mut nodes = vec![(self.root, root.build(&mut self.classes))];
+        for (id, (string, role, rect)) in ids.into_iter().zip(labels) {
+            nodes.push((id, self.text_node(string, *role, rect)));
+        }
+        self.reported = Some(labels.to_vec());
+        Some(TreeUpdate { nodes, tree, focus })
+    }
+
+    fn text_node(&mut self, string: &str, role: TextRole, rect: &PixelRect<f32>) -> Node {
+        let mut node = NodeBuilder::new(match role {
+            TextRole::Text => Role::StaticText,
+            TextRole::Heading => Role::Heading,
+            TextRole::Input => Role::TextInput,
+        });
+        node.set_name(string);
+        node.set_bounds(Rect {
+            x0: rect.min.x as f64,
+            y0: rect.min.y as f64,
+            x1: rect.max.x as f64,
+            y1: rect.max.y as f64,
+        });
+        node.build(&mut self.classes)
+    }
+}
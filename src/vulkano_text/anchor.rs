@@ -0,0 +1,94 @@
+use std::array;
+
+// the point of the screen a text is pinned to, and the same point of the text's box
+// sits on it. e.g. BottomRight puts the text's bottom right corner into the screen's
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Anchor {
+    #[default]
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl Anchor {
+    // 0 at the left or top, 0.5 in the middle and 1 at the right or bottom
+    pub(crate) fn factors(self) -> [f32; 2] {
+        match self {
+            Anchor::TopLeft => [0.0, 0.0],
+            Anchor::Top => [0.5, 0.0],
+            Anchor::TopRight => [1.0, 0.0],
+            Anchor::Left => [0.0, 0.5],
+            Anchor::Center => [0.5, 0.5],
+            Anchor::Right => [1.0, 0.5],
+            Anchor::BottomLeft => [0.0, 1.0],
+            Anchor::Bottom => [0.5, 1.0],
+            Anchor::BottomRight => [1.0, 1.0],
+        }
+    }
+}
+
+// where y = 0 is for the positions handed to DrawText. with BottomLeft y grows
+// upwards, each queue call is placed at the point given and laid out downwards from
+// there as usual
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum CoordinateOrigin {
+    #[default]
+    TopLeft,
+    BottomLeft,
+}
+
+// distance from the anchor, away from the edge it is pinned to. centered anchors
+// move right and down
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Offset {
+    // logical pixels
+    Pixels(f32),
+    // of the screen's width or height, 0 to 100
+    Percent(f32),
+}
+
+impl Offset {
+    fn resolve(self, length: f32, scale: f32) -> f32 {
+        match self {
+            Offset::Pixels(pixels) => pixels * scale,
+            Offset::Percent(percent) => percent / 100.0 * length,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct TextAnchor {
+    pub(crate) anchor: Anchor,
+    pub(crate) offset: [Offset; 2],
+    // how far the text was moved already, in physical pixels
+    pub(crate) applied: [f32; 2],
+}
+
+impl TextAnchor {
+    // moves what was laid out at y to y above the bottom edge, see CoordinateOrigin
+    pub(crate) fn flip(y: f32, scale: f32) -> TextAnchor {
+        TextAnchor {
+            anchor: Anchor::BottomLeft,
+            offset: [Offset::Pixels(0.0), Offset::Pixels(y)],
+            // whole pixels like position, so the move is too
+            applied: [0.0, (y * scale).round()],
+        }
+    }
+
+    // where the anchor lands on a screen of this many physical pixels, on whole
+    // pixels so the glyphs keep their subpixel positions and cache entries
+    pub(crate) fn position(&self, screen: [f32; 2], scale: f32) -> [f32; 2] {
+        let factors = self.anchor.factors();
+        array::from_fn(|axis| {
+            let inward = if factors[axis] == 1.0 { -1.0 } else { 1.0 };
+            let offset = self.offset[axis].resolve(screen[axis], scale);
+            (factors[axis] * screen[axis] + inward * offset).round()
+        })
+    }
+}
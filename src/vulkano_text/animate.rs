@@ -0,0 +1,176 @@
+use super::drawtext::TextRendererCore;
+use super::style::{Overflow, TextStyle};
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use std::time::{Duration, Instant};
+
+// blank between the end of a marquee's text and its next repetition, in ems
+const MARQUEE_GAP: f32 = 2.0;
+
+// text scrolling to the left through a box, over and over
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Marquee {
+    // of the box, starting at the text's x. replaces the style's max_width
+    pub width: f32,
+    // pixels per second
+    pub speed: f32,
+}
+
+// time driven effects for notifications, subtitles and tickers. it only keeps the
+// time, the application steps it with advance every frame and queues text through it
+// instead of through DrawText directly. effects combine freely
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TextAnimator {
+    elapsed: Duration,
+    fade_in: Duration,
+    // when fading out starts and how long it takes
+    fade_out: Option<(Duration, Duration)>,
+    // grapheme clusters revealed per second
+    typewriter: Option<f32>,
+    marquee: Option<Marquee>,
+}
+
+impl TextAnimator {
+    pub fn new() -> TextAnimator {
+        TextAnimator::default()
+    }
+
+    pub fn with_fade_in(mut self, duration: Duration) -> TextAnimator {
+        self.fade_in = duration;
+        self
+    }
+
+    // after is counted from the start, not from the end of the fade in
+    pub fn with_fade_out(mut self, after: Duration, duration: Duration) -> TextAnimator {
+        self.fade_out = Some((after, duration));
+        self
+    }
+
+    // the text appears a grapheme cluster at a time
+    pub fn with_typewriter(mut self, clusters_per_second: f32) -> TextAnimator {
+        self.typewriter = Some(clusters_per_second);
+        self
+    }
+
+    // horizontal text only
+    pub fn with_marquee(mut self, width: f32, speed: f32) -> TextAnimator {
+        self.marquee = Some(Marquee { width, speed });
+        self
+    }
+
+    pub fn advance(&mut self, dt: Duration) {
+        self.elapsed += dt;
+    }
+
+    pub fn restart(&mut self) {
+        self.elapsed = Duration::ZERO;
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    // 0 before the fade in starts and after the fade out ends
+    pub fn opacity(&self) -> f32 {
+        let t = self.elapsed.as_secs_f32();
+        let mut opacity = if self.fade_in.is_zero() {
+            1.0
+        } else {
+            (t / self.fade_in.as_secs_f32()).min(1.0)
+        };
+        if let Some((after, duration)) = self.fade_out {
+            let out = (t - after.as_secs_f32()) / duration.as_secs_f32().max(f32::EPSILON);
+            opacity = opacity.min(1.0 - out.clamp(0.0, 1.0));
+        }
+        opacity
+    }
+
+    // faded out for good, the text can be dropped
+    pub fn is_finished(&self) -> bool {
+        self.fade_out
+            .map_or(false, |(after, duration)| self.elapsed >= after + duration)
+    }
+
+    // bytes of text the typewriter shows so far
+    pub fn revealed(&self, text: &str) -> usize {
+        let clusters = match self.typewriter {
+            Some(rate) => (self.elapsed.as_secs_f32() * rate) as usize,
+            None => return text.len(),
+        };
+        text.grapheme_indices(true)
+            .nth(clusters)
+            .map_or(text.len(), |(byte, _)| byte)
+    }
+
+    // whether the next frame looks any different from this one
+    pub fn is_running(&self, text: &str) -> bool {
+        let fading_in = self.elapsed < self.fade_in;
+        let fading_out = self.fade_out.map_or(false, |(after, duration)| {
+            self.elapsed >= after && self.elapsed < after + duration
+        });
+        fading_in || fading_out || self.revealed(text) < text.len() || self.marquee.is_some()
+    }
+
+    // queues text as it looks at the current time, and asks draw_text for another
+    // frame while anything is still moving
+    pub fn queue(
+        &self,
+        draw_text: &mut TextRendererCore,
+        x: f32,
+        y: f32,
+        style: &TextStyle,
+        text: &str,
+    ) {
+        if self.is_running(text) && !self.is_finished() {
+            draw_text.schedule_redraw(Instant::now());
+        }
+        let opacity = self.opacity();
+        if opacity <= 0.0 {
+            return;
+        }
+
+        let style = style.clone().with_opacity(opacity);
+        let shown = &text[..self.revealed(text)];
+        match self.marquee {
+            Some(marquee) => draw_text.from_origin(y, |draw_text| {
+                self.queue_marquee(draw_text, x, y, &style, shown, marquee)
+            }),
+            None => draw_text.queue_text_styled(x, y, &style, shown),
+        }
+    }
+
+    // every repetition of the text crossing the box is queued clipped to its right
+    // edge. clusters leave the box on the left whole, there is no clipping on that side
+    fn queue_marquee(
+        &self,
+        draw_text: &mut TextRendererCore,
+        x: f32,
+        y: f32,
+        style: &TextStyle,
+        text: &str,
+        marquee: Marquee,
+    ) {
+        if text.is_empty() {
+            return;
+        }
+        let layout = draw_text.layout_text(style, text);
+        let period = layout.line_length() + MARQUEE_GAP * style.size;
+        let mut start = -(self.elapsed.as_secs_f32() * marquee.speed % period);
+        while start < marquee.width {
+            // the first cluster that is still entirely inside the box
+            let first = layout
+                .cluster_starts()
+                .find(|&byte| start + layout.x_at(byte) >= 0.0)
+                .unwrap_or(text.len());
+            let left = start + layout.x_at(first);
+            if first < text.len() && left < marquee.width {
+                let style = style
+                    .clone()
+                    .with_max_width(marquee.width - left, Overflow::Clip);
+                draw_text.queue_text_styled(x + left, y, &style, &text[first..]);
+            }
+            start += period;
+        }
+    }
+}
@@ -0,0 +1,208 @@
+use super::style::{Decorations, TextStyle};
+
+// the usual xterm colors, normal then bright
+const PALETTE: [[u8; 3]; 16] = [
+    [0, 0, 0],
+    [205, 0, 0],
+    [0, 205, 0],
+    [205, 205, 0],
+    [0, 0, 238],
+    [205, 0, 205],
+    [0, 205, 205],
+    [229, 229, 229],
+    [127, 127, 127],
+    [255, 0, 0],
+    [0, 255, 0],
+    [255, 255, 0],
+    [92, 92, 255],
+    [255, 0, 255],
+    [0, 255, 255],
+    [255, 255, 255],
+];
+// steps of the 6x6x6 color cube
+const CUBE: [u8; 6] = [0, 95, 135, 175, 215, 255];
+// faint text keeps this much of its color
+const DIM: f32 = 0.66;
+const BOLD_WEIGHT: u16 = 700;
+const TAB_WIDTH: usize = 8;
+
+fn rgb(r: u8, g: u8, b: u8) -> [f32; 4] {
+    [r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0, 1.0]
+}
+
+// one of the 256 colors of 38;5;n and 48;5;n
+fn indexed(n: u8) -> [f32; 4] {
+    match n {
+        0..=15 => {
+            let [r, g, b] = PALETTE[n as usize];
+            rgb(r, g, b)
+        }
+        16..=231 => {
+            let n = n - 16;
+            rgb(
+                CUBE[n as usize / 36],
+                CUBE[n as usize / 6 % 6],
+                CUBE[n as usize % 6],
+            )
+        }
+        _ => {
+            let gray = 8 + (n - 232) * 10;
+            rgb(gray, gray, gray)
+        }
+    }
+}
+
+// what the SGR sequences so far have set, None for the base style's colors
+#[derive(Debug, Clone, Default)]
+struct Graphics {
+    foreground: Option<[f32; 4]>,
+    background: Option<[f32; 4]>,
+    bold: bool,
+    dim: bool,
+    italic: bool,
+    decorations: Decorations,
+    inverse: bool,
+}
+
+impl Graphics {
+    fn apply(&mut self, params: &[Option<u16>]) {
+        // left out parameters count as 0, so an empty sequence resets too
+        let mut params = params.iter().map(|p| p.unwrap_or(0));
+        while let Some(param) = params.next() {
+            match param {
+                0 => *self = Graphics::default(),
+                1 => self.bold = true,
+                2 => self.dim = true,
+                3 => self.italic = true,
+                4 => self.decorations.underline = true,
+                7 => self.inverse = true,
+                9 => self.decorations.strikethrough = true,
+                22 => {
+                    self.bold = false;
+                    self.dim = false;
+                }
+                23 => self.italic = false,
+                24 => self.decorations.underline = false,
+                27 => self.inverse = false,
+                29 => self.decorations.strikethrough = false,
+                30..=37 => self.foreground = Some(indexed(param as u8 - 30)),
+                38 => self.foreground = extended(&mut params).or(self.foreground),
+                39 => self.foreground = None,
+                40..=47 => self.background = Some(indexed(param as u8 - 40)),
+                48 => self.background = extended(&mut params).or(self.background),
+                49 => self.background = None,
+                53 => self.decorations.overline = true,
+                55 => self.decorations.overline = false,
+                90..=97 => self.foreground = Some(indexed(param as u8 - 90 + 8)),
+                100..=107 => self.background = Some(indexed(param as u8 - 100 + 8)),
+                _ => {}
+            }
+        }
+    }
+
+    fn style(&self, base: &TextStyle) -> TextStyle {
+        let mut style = base.clone();
+        let mut foreground = self.foreground.unwrap_or(base.color);
+        let mut background = self.background.or(base.background);
+        if self.inverse {
+            // without a background the text is cut out of a block of its color
+            let back = background.unwrap_or([0.0, 0.0, 0.0, 1.0]);
+            background = Some(foreground);
+            foreground = back;
+        }
+        if self.dim {
+            for channel in &mut foreground[..3] {
+                *channel *= DIM;
+            }
+        }
+        style.color = foreground;
+        style.background = background;
+        if self.bold {
+            style.weight = BOLD_WEIGHT;
+        }
+        style.italic |= self.italic;
+        style.decorations = Decorations {
+            underline: base.decorations.underline || self.decorations.underline,
+            strikethrough: base.decorations.strikethrough || self.decorations.strikethrough,
+            overline: base.decorations.overline || self.decorations.overline,
+        };
+        style
+    }
+}
+
+// the color after 38 or 48, 5;n or 2;r;g;b
+fn extended(params: &mut impl Iterator<Item = u16>) -> Option<[f32; 4]> {
+    match params.next()? {
+        5 => Some(indexed(params.next()?.min(255) as u8)),
+        2 => {
+            let mut channel = || params.next().map(|c| c.min(255) as u8);
+            Some(rgb(channel()?, channel()?, channel()?))
+        }
+        _ => None,
+    }
+}
+
+// text split at its SGR sequences into lines of styled spans. tabs become spaces up
+// to the next stop, other control chars and escape sequences, cursor movement and the
+// like, are dropped
+pub(crate) fn parse(text: &str, base: &TextStyle) -> Vec<Vec<(TextStyle, String)>> {
+    let mut graphics = Graphics::default();
+    let mut lines = vec![];
+    for line in text.split('\n') {
+        let mut spans = vec![];
+        let mut current = String::new();
+        let mut column = 0;
+        let mut chars = line.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '\u{1b}' => match chars.next() {
+                    // CSI, parameters up to a final byte
+                    Some('[') => {
+                        let mut sequence = String::new();
+                        let mut last = None;
+                        for c in chars.by_ref() {
+                            if ('\u{40}'..='\u{7e}').contains(&c) {
+                                last = Some(c);
+                                break;
+                            }
+                            sequence.push(c);
+                        }
+                        if last == Some('m') {
+                            if !current.is_empty() {
+                                spans.push((graphics.style(base), std::mem::take(&mut current)));
+                            }
+                            let params: Vec<_> =
+                                sequence.split(';').map(|p| p.parse().ok()).collect();
+                            graphics.apply(&params);
+                        }
+                    }
+                    // OSC, up to BEL or ST
+                    Some(']') => {
+                        while let Some(c) = chars.next() {
+                            if c == '\u{7}' || (c == '\u{1b}' && chars.next_if_eq(&'\\').is_some())
+                            {
+                                break;
+                            }
+                        }
+                    }
+                    _ => {}
+                },
+                '\t' => {
+                    let spaces = TAB_WIDTH - column % TAB_WIDTH;
+                    current.extend(std::iter::repeat(' ').take(spaces));
+                    column += spaces;
+                }
+                c if c.is_control() => {}
+                c => {
+                    current.push(c);
+                    column += 1;
+                }
+            }
+        }
+        if !current.is_empty() {
+            spans.push((graphics.style(base), current));
+        }
+        lines.push(spans);
+    }
+    lines
+}
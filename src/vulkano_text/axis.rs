@@ -0,0 +1,161 @@
+use super::drawtext::TextRendererCore;
+use super::style::{FontFeature, TextStyle, WritingMode};
+
+// round tick values for a range, all formatted with the same number of decimals
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ticks {
+    pub values: Vec<f64>,
+    pub step: f64,
+    // enough to tell neighbouring ticks apart
+    pub decimals: usize,
+}
+
+impl Ticks {
+    // steps of 1, 2 or 5 times a power of ten, at most max_ticks of them between min
+    // and max. none for an empty range
+    pub fn nice(min: f64, max: f64, max_ticks: usize) -> Ticks {
+        let (min, max) = (min.min(max), min.max(max));
+        let range = max - min;
+        if !range.is_normal() || max_ticks == 0 {
+            return Ticks {
+                values: vec![],
+                step: 0.0,
+                decimals: 0,
+            };
+        }
+        let raw = range / max_ticks as f64;
+        let magnitude = 10f64.powf(raw.log10().floor());
+        let step = [1.0, 2.0, 5.0, 10.0]
+            .into_iter()
+            .map(|nice| nice * magnitude)
+            .find(|&step| step >= raw)
+            .unwrap_or(10.0 * magnitude);
+        // multiples of step, so they don't pick up rounding errors from adding it up
+        let first = (min / step).ceil() as i64;
+        let last = (max / step).floor() as i64;
+        let values = (first..=last).map(|i| i as f64 * step).collect();
+        Ticks {
+            values,
+            step,
+            decimals: (-step.log10().floor()).max(0.0) as usize,
+        }
+    }
+
+    pub fn format(&self, value: f64) -> String {
+        // no -0 for ticks that are a hair below zero
+        let value = if value.abs() < self.step / 2.0 {
+            0.0
+        } else {
+            value
+        };
+        format!("{:.*}", self.decimals, value)
+    }
+}
+
+// which side of the axis line the labels go on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AxisSide {
+    Bottom,
+    Top,
+    Left,
+    Right,
+}
+
+// numbers along a graph's axis, for telemetry dashboards and the like. queue picks the
+// ticks for the visible range every time, so the labels follow a scrolling graph
+#[derive(Debug, Clone)]
+pub struct AxisLabels {
+    pub style: TextStyle,
+    pub side: AxisSide,
+    // about how far apart ticks get, in pixels. anything below a pixel counts as one
+    pub spacing: f32,
+    // between the axis line and the labels
+    pub gap: f32,
+    // labels of horizontal axes read top to bottom, for long numbers under narrow ticks
+    pub rotated: bool,
+    // after every number, like " ms"
+    pub suffix: String,
+}
+
+impl AxisLabels {
+    // with tabular figures, so labels of the same length are the same width
+    pub fn new(style: TextStyle, side: AxisSide) -> AxisLabels {
+        AxisLabels {
+            style: style.with_feature(FontFeature::TABULAR_FIGURES),
+            side,
+            spacing: 60.0,
+            gap: 4.0,
+            rotated: false,
+            suffix: String::new(),
+        }
+    }
+
+    pub fn with_rotated(mut self, rotated: bool) -> AxisLabels {
+        self.rotated = rotated;
+        self
+    }
+
+    pub fn with_suffix(mut self, suffix: &str) -> AxisLabels {
+        self.suffix = suffix.to_owned();
+        self
+    }
+
+    // labels for the values min to max along the axis line from start to end, min
+    // lands on start. vertical axes usually start at the bottom. returns the ticks, to
+    // draw grid lines at
+    pub fn queue(
+        &self,
+        draw_text: &mut TextRendererCore,
+        min: f64,
+        max: f64,
+        start: [f32; 2],
+        end: [f32; 2],
+    ) -> Ticks {
+        let end = [end[0], draw_text.flip_y(start[1], end[1])];
+        draw_text.from_origin(start[1], |draw_text| {
+            self.queue_labels(draw_text, min, max, start, end)
+        })
+    }
+
+    fn queue_labels(
+        &self,
+        draw_text: &mut TextRendererCore,
+        min: f64,
+        max: f64,
+        start: [f32; 2],
+        end: [f32; 2],
+    ) -> Ticks {
+        let length = (end[0] - start[0]).hypot(end[1] - start[1]);
+        // a spacing of 0 or less would ask for usize::MAX ticks
+        let max_ticks = (length / self.spacing.max(1.0)).floor() as usize;
+        let ticks = Ticks::nice(min, max, max_ticks);
+        let rotated = self.rotated && matches!(self.side, AxisSide::Bottom | AxisSide::Top);
+        let style = if rotated {
+            self.style
+                .clone()
+                .with_writing_mode(WritingMode::VerticalRl)
+        } else {
+            self.style.clone()
+        };
+
+        for &value in &ticks.values {
+            let t = ((value - min) / (max - min)) as f32;
+            let x = start[0] + (end[0] - start[0]) * t;
+            let y = start[1] + (end[1] - start[1]) * t;
+            let label = format!("{}{}", ticks.format(value), self.suffix);
+            let layout = draw_text.layout_text(&style, &label);
+            // vertical text is placed by the top of its column's center
+            let (x, y) = match self.side {
+                AxisSide::Bottom if rotated => (x, y + self.gap),
+                AxisSide::Top if rotated => (x, y - self.gap - layout.height()),
+                AxisSide::Bottom => (x - layout.width() / 2.0, y + self.gap),
+                AxisSide::Top => (x - layout.width() / 2.0, y - self.gap - layout.height()),
+                AxisSide::Left => (x - self.gap - layout.width(), y - layout.height() / 2.0),
+                AxisSide::Right => (x + self.gap, y - layout.height() / 2.0),
+            };
+            let y = if rotated { y } else { y - layout.line_top(0) };
+            draw_text.queue_text_styled(x, y, &style, &label);
+        }
+        ticks
+    }
+}
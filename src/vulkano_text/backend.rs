@@ -0,0 +1,809 @@
+use super::metrics::{has_rusttype_tables, tables_in_bounds};
+use super::style::{FontVariation, Hinting};
+
+use rusttype::{point, Font, Point, Rect, Scale, Segment};
+
+use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
+
+#[cfg(feature = "ab_glyph")]
+use ab_glyph::{Font as _, FontVec, OutlineCurve, PxScale, VariableFont};
+
+#[cfg(feature = "swash")]
+use swash::scale::{Render, ScaleContext, Scaler, Source};
+#[cfg(feature = "swash")]
+use swash::zeno::{Format, Vector, Verb};
+#[cfg(feature = "swash")]
+use swash::{CacheKey, FontRef, Setting};
+
+#[cfg(any(feature = "ab_glyph", feature = "swash"))]
+use std::sync::Mutex;
+
+// font data the backend can't read, not a ttf or otf font or a damaged one
+#[derive(Debug)]
+pub struct FontError;
+
+impl fmt::Display for FontError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "the data isn't a font the glyph backend can read")
+    }
+}
+
+impl Error for FontError {}
+
+// vertical metrics, ascent up from the baseline and descent down from it (so negative)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FontMetrics {
+    pub ascent: f32,
+    pub descent: f32,
+    pub line_gap: f32,
+}
+
+// a glyph in its font's glyph table, 0 is the missing glyph
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GlyphId(pub u32);
+
+// whole pixels, min inclusive and max exclusive, with y going down
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PixelBounds {
+    pub min: [i32; 2],
+    pub max: [i32; 2],
+}
+
+impl PixelBounds {
+    pub fn width(&self) -> i32 {
+        self.max[0] - self.min[0]
+    }
+
+    pub fn height(&self) -> i32 {
+        self.max[1] - self.min[1]
+    }
+}
+
+impl From<Rect<i32>> for PixelBounds {
+    fn from(rect: Rect<i32>) -> PixelBounds {
+        PixelBounds {
+            min: [rect.min.x, rect.min.y],
+            max: [rect.max.x, rect.max.y],
+        }
+    }
+}
+
+impl From<PixelBounds> for Rect<i32> {
+    fn from(bounds: PixelBounds) -> Rect<i32> {
+        Rect {
+            min: point(bounds.min[0], bounds.min[1]),
+            max: point(bounds.max[0], bounds.max[1]),
+        }
+    }
+}
+
+// coverage of one glyph, a byte per pixel row by row. bounds are in pixels, for the glyph
+// drawn at the position it was rasterized at
+#[derive(Debug, Clone, PartialEq)]
+pub struct GlyphBitmap {
+    pub bounds: PixelBounds,
+    pub coverage: Vec<u8>,
+}
+
+// segments per curve when flattening, plenty at text sizes
+const CURVE_STEPS: usize = 8;
+
+// a glyph's outline flattened to lines, for filling on the gpu. lines are x0, y0, x1, y1
+// in pixels relative to the top left of bounds, which are the same rasterize has
+#[derive(Debug, Clone, PartialEq)]
+pub struct GlyphOutline {
+    pub bounds: PixelBounds,
+    pub lines: Vec<[f32; 4]>,
+}
+
+impl GlyphOutline {
+    pub fn new(bounds: PixelBounds) -> GlyphOutline {
+        GlyphOutline {
+            bounds,
+            lines: vec![],
+        }
+    }
+
+    // a line, or a quadratic or cubic bezier curve given by its control points
+    pub fn push_curve(&mut self, points: &[[f32; 2]]) {
+        let at = |t: f32| {
+            // de casteljau
+            let mut points = points.to_vec();
+            while points.len() > 1 {
+                points = points
+                    .windows(2)
+                    .map(|w| {
+                        [
+                            w[0][0] + (w[1][0] - w[0][0]) * t,
+                            w[0][1] + (w[1][1] - w[0][1]) * t,
+                        ]
+                    })
+                    .collect();
+            }
+            points[0]
+        };
+        let steps = if points.len() > 2 { CURVE_STEPS } else { 1 };
+        let mut last = points[0];
+        for i in 1..=steps {
+            let next = at(i as f32 / steps as f32);
+            self.lines.push([last[0], last[1], next[0], next[1]]);
+            last = next;
+        }
+    }
+}
+
+// a glyph's outline as the font has it, in pixels from the glyph's origin with y going
+// down. every contour starts with a MoveTo and is closed, the last point joins the first
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PathSegment {
+    MoveTo([f32; 2]),
+    LineTo([f32; 2]),
+    // control point, end
+    QuadTo([f32; 2], [f32; 2]),
+    // two control points, end
+    CubicTo([f32; 2], [f32; 2], [f32; 2]),
+}
+
+// what DrawText needs from a font library. a size is the pixel height from descent to
+// ascent, like rusttype's uniform scale, positions are glyph origins on the baseline
+// as [x, y] with y going down. variations and hinting only matter to backends that
+// support them
+pub trait GlyphBackend: Send + Sync {
+    // the font file, for the tables DrawText reads itself and for the shaper
+    fn font_data(&self) -> &[u8];
+
+    // in font units
+    fn v_metrics_unscaled(&self) -> FontMetrics;
+
+    fn glyph_id(&self, c: char) -> GlyphId;
+
+    fn h_advance(&self, id: GlyphId, size: f32, variations: &[FontVariation]) -> f32;
+
+    fn kerning(&self, first: GlyphId, second: GlyphId, size: f32) -> f32;
+
+    // the whole pixels rasterize would touch, None for glyphs without ink
+    fn pixel_bounds(
+        &self,
+        id: GlyphId,
+        size: f32,
+        position: [f32; 2],
+        variations: &[FontVariation],
+        hinting: Hinting,
+    ) -> Option<PixelBounds>;
+
+    fn rasterize(
+        &self,
+        id: GlyphId,
+        size: f32,
+        position: [f32; 2],
+        variations: &[FontVariation],
+        hinting: Hinting,
+    ) -> Option<GlyphBitmap>;
+
+    fn supports_variations(&self) -> bool {
+        false
+    }
+
+    // whether font_data is a truetype or opentype font. text in other fonts isn't
+    // shaped and gets typical decoration metrics
+    fn is_opentype(&self) -> bool {
+        true
+    }
+
+    // whether Hinting::Full runs the font's instructions
+    fn supports_hinting(&self) -> bool {
+        false
+    }
+
+    // unhinted, for the gpu rasterizer. backends without outlines are always
+    // rasterized on the cpu
+    fn outline(
+        &self,
+        _id: GlyphId,
+        _size: f32,
+        _position: [f32; 2],
+        _variations: &[FontVariation],
+    ) -> Option<GlyphOutline> {
+        None
+    }
+
+    // the curves themselves, for meshes, colliders and effects of the caller's own
+    fn path(
+        &self,
+        _id: GlyphId,
+        _size: f32,
+        _variations: &[FontVariation],
+    ) -> Option<Vec<PathSegment>> {
+        None
+    }
+
+    fn v_metrics(&self, size: f32) -> FontMetrics {
+        let unscaled = self.v_metrics_unscaled();
+        let scale = size / (unscaled.ascent - unscaled.descent);
+        FontMetrics {
+            ascent: unscaled.ascent * scale,
+            descent: unscaled.descent * scale,
+            line_gap: unscaled.line_gap * scale,
+        }
+    }
+}
+
+// the compat backend, no variations and no hinting
+pub struct RusttypeBackend {
+    font: Font<'static>,
+    data: Arc<[u8]>,
+}
+
+impl RusttypeBackend {
+    pub fn new(data: Vec<u8>) -> Result<RusttypeBackend, FontError> {
+        if !tables_in_bounds(&data) || !has_rusttype_tables(&data) {
+            return Err(FontError);
+        }
+        let data: Arc<[u8]> = data.into();
+        Ok(RusttypeBackend {
+            font: Font::from_bytes(data.clone()).map_err(|_| FontError)?,
+            data,
+        })
+    }
+
+    fn scaled(&self, id: GlyphId, size: f32) -> rusttype::ScaledGlyph<'_> {
+        self.font
+            .glyph(rusttype::GlyphId(id.0))
+            .scaled(Scale::uniform(size))
+    }
+}
+
+impl GlyphBackend for RusttypeBackend {
+    fn font_data(&self) -> &[u8] {
+        &self.data
+    }
+
+    fn v_metrics_unscaled(&self) -> FontMetrics {
+        let v_metrics = self.font.v_metrics_unscaled();
+        FontMetrics {
+            ascent: v_metrics.ascent,
+            descent: v_metrics.descent,
+            line_gap: v_metrics.line_gap,
+        }
+    }
+
+    fn glyph_id(&self, c: char) -> GlyphId {
+        GlyphId(self.font.glyph(c).id().0)
+    }
+
+    fn h_advance(&self, id: GlyphId, size: f32, _variations: &[FontVariation]) -> f32 {
+        let glyph = self.scaled(id, size);
+        glyph.h_metrics().advance_width
+    }
+
+    fn kerning(&self, first: GlyphId, second: GlyphId, size: f32) -> f32 {
+        let first = rusttype::GlyphId(first.0);
+        let second = rusttype::GlyphId(second.0);
+        self.font.pair_kerning(Scale::uniform(size), first, second)
+    }
+
+    fn pixel_bounds(
+        &self,
+        id: GlyphId,
+        size: f32,
+        position: [f32; 2],
+        _variations: &[FontVariation],
+        _hinting: Hinting,
+    ) -> Option<PixelBounds> {
+        let glyph = self.scaled(id, size);
+        let position = point(position[0], position[1]);
+        Some(glyph.positioned(position).pixel_bounding_box()?.into())
+    }
+
+    fn rasterize(
+        &self,
+        id: GlyphId,
+        size: f32,
+        position: [f32; 2],
+        _variations: &[FontVariation],
+        _hinting: Hinting,
+    ) -> Option<GlyphBitmap> {
+        let glyph = self.scaled(id, size);
+        let glyph = glyph.positioned(point(position[0], position[1]));
+        let bounds = PixelBounds::from(glyph.pixel_bounding_box()?);
+        let width = bounds.width() as usize;
+        let mut coverage = vec![0; width * bounds.height() as usize];
+        glyph.draw(|x, y, v| {
+            coverage[y as usize * width + x as usize] = (v.min(1.0) * 255.0).round() as u8;
+        });
+        Some(GlyphBitmap { bounds, coverage })
+    }
+
+    fn outline(
+        &self,
+        id: GlyphId,
+        size: f32,
+        position: [f32; 2],
+        _variations: &[FontVariation],
+    ) -> Option<GlyphOutline> {
+        let glyph = self.scaled(id, size);
+        // y goes up in the shape
+        let contours = glyph.shape()?;
+        let bounds = glyph
+            .positioned(point(position[0], position[1]))
+            .pixel_bounding_box()?;
+        let mut outline = GlyphOutline::new(bounds.into());
+        let to_pixels = |p: Point<f32>| {
+            [
+                position[0] + p.x - bounds.min.x as f32,
+                position[1] - p.y - bounds.min.y as f32,
+            ]
+        };
+        for segment in contours.iter().flat_map(|contour| &contour.segments) {
+            match segment {
+                Segment::Line(line) => outline.push_curve(&line.p.map(to_pixels)),
+                Segment::Curve(curve) => outline.push_curve(&curve.p.map(to_pixels)),
+            }
+        }
+        Some(outline)
+    }
+
+    fn path(
+        &self,
+        id: GlyphId,
+        size: f32,
+        _variations: &[FontVariation],
+    ) -> Option<Vec<PathSegment>> {
+        let glyph = self.scaled(id, size);
+        let flip = |p: Point<f32>| [p.x, -p.y];
+        let mut path = vec![];
+        for contour in glyph.shape()? {
+            let start = match contour.segments.first()? {
+                Segment::Line(line) => line.p[0],
+                Segment::Curve(curve) => curve.p[0],
+            };
+            path.push(PathSegment::MoveTo(flip(start)));
+            for segment in &contour.segments {
+                path.push(match segment {
+                    Segment::Line(line) => PathSegment::LineTo(flip(line.p[1])),
+                    Segment::Curve(curve) => {
+                        PathSegment::QuadTo(flip(curve.p[1]), flip(curve.p[2]))
+                    }
+                });
+            }
+        }
+        Some(path)
+    }
+}
+
+// ab_glyph keeps up with ttf-parser and reads variable fonts
+#[cfg(feature = "ab_glyph")]
+pub struct AbGlyphBackend {
+    // set to whichever instance was asked for last
+    font: Mutex<(FontVec, Vec<FontVariation>)>,
+    data: Vec<u8>,
+}
+
+#[cfg(feature = "ab_glyph")]
+impl AbGlyphBackend {
+    pub fn new(data: Vec<u8>) -> Result<AbGlyphBackend, FontError> {
+        let font = FontVec::try_from_vec(data.clone()).map_err(|_| FontError)?;
+        Ok(AbGlyphBackend {
+            font: Mutex::new((font, vec![])),
+            data,
+        })
+    }
+
+    // runs f with the font set to variations, axes left out go back to their default
+    fn with_instance<R>(&self, variations: &[FontVariation], f: impl FnOnce(&FontVec) -> R) -> R {
+        let mut guard = self.font.lock().unwrap();
+        let (font, current) = &mut *guard;
+        if current.as_slice() != variations {
+            for axis in font.variations() {
+                let value = variations
+                    .iter()
+                    .find(|v| v.tag == axis.tag)
+                    .map_or(axis.default_value, |v| v.value);
+                font.set_variation(&axis.tag, value.clamp(axis.min_value, axis.max_value));
+            }
+            *current = variations.to_vec();
+        }
+        f(font)
+    }
+
+    // for what doesn't change between instances, whichever one is set
+    fn with_font<R>(&self, f: impl FnOnce(&FontVec) -> R) -> R {
+        f(&self.font.lock().unwrap().0)
+    }
+
+    fn outlined(
+        font: &FontVec,
+        id: GlyphId,
+        size: f32,
+        position: [f32; 2],
+    ) -> Option<ab_glyph::OutlinedGlyph> {
+        let glyph = ab_glyph::Glyph {
+            id: ab_glyph::GlyphId(id.0 as u16),
+            scale: PxScale::from(size),
+            position: ab_glyph::point(position[0], position[1]),
+        };
+        font.outline_glyph(glyph)
+    }
+}
+
+#[cfg(feature = "ab_glyph")]
+impl GlyphBackend for AbGlyphBackend {
+    fn font_data(&self) -> &[u8] {
+        &self.data
+    }
+
+    fn v_metrics_unscaled(&self) -> FontMetrics {
+        self.with_font(|font| FontMetrics {
+            ascent: font.ascent_unscaled(),
+            descent: font.descent_unscaled(),
+            line_gap: font.line_gap_unscaled(),
+        })
+    }
+
+    fn glyph_id(&self, c: char) -> GlyphId {
+        self.with_font(|font| GlyphId(font.glyph_id(c).0 as u32))
+    }
+
+    fn h_advance(&self, id: GlyphId, size: f32, variations: &[FontVariation]) -> f32 {
+        self.with_instance(variations, |font| {
+            let scale = size / font.height_unscaled();
+            font.h_advance_unscaled(ab_glyph::GlyphId(id.0 as u16)) * scale
+        })
+    }
+
+    fn kerning(&self, first: GlyphId, second: GlyphId, size: f32) -> f32 {
+        self.with_font(|font| {
+            let scale = size / font.height_unscaled();
+            let first = ab_glyph::GlyphId(first.0 as u16);
+            let second = ab_glyph::GlyphId(second.0 as u16);
+            font.kern_unscaled(first, second) * scale
+        })
+    }
+
+    fn pixel_bounds(
+        &self,
+        id: GlyphId,
+        size: f32,
+        position: [f32; 2],
+        variations: &[FontVariation],
+        _hinting: Hinting,
+    ) -> Option<PixelBounds> {
+        self.with_instance(variations, |font| {
+            let bounds = AbGlyphBackend::outlined(font, id, size, position)?.px_bounds();
+            Some(PixelBounds {
+                min: [bounds.min.x as i32, bounds.min.y as i32],
+                max: [bounds.max.x as i32, bounds.max.y as i32],
+            })
+        })
+    }
+
+    fn rasterize(
+        &self,
+        id: GlyphId,
+        size: f32,
+        position: [f32; 2],
+        variations: &[FontVariation],
+        _hinting: Hinting,
+    ) -> Option<GlyphBitmap> {
+        self.with_instance(variations, |font| {
+            let outlined = AbGlyphBackend::outlined(font, id, size, position)?;
+            let px_bounds = outlined.px_bounds();
+            let bounds = PixelBounds {
+                min: [px_bounds.min.x as i32, px_bounds.min.y as i32],
+                max: [px_bounds.max.x as i32, px_bounds.max.y as i32],
+            };
+            let width = bounds.width() as usize;
+            let mut coverage = vec![0; width * bounds.height() as usize];
+            outlined.draw(|x, y, v| {
+                coverage[y as usize * width + x as usize] = (v.min(1.0) * 255.0).round() as u8;
+            });
+            Some(GlyphBitmap { bounds, coverage })
+        })
+    }
+
+    fn outline(
+        &self,
+        id: GlyphId,
+        size: f32,
+        position: [f32; 2],
+        variations: &[FontVariation],
+    ) -> Option<GlyphOutline> {
+        self.with_instance(variations, |font| {
+            let px_bounds = AbGlyphBackend::outlined(font, id, size, position)?.px_bounds();
+            let bounds = PixelBounds {
+                min: [px_bounds.min.x as i32, px_bounds.min.y as i32],
+                max: [px_bounds.max.x as i32, px_bounds.max.y as i32],
+            };
+            let scale = size / font.height_unscaled();
+            let mut outline = GlyphOutline::new(bounds);
+            // unscaled with y going up
+            let to_pixels = |p: &ab_glyph::Point| {
+                [
+                    position[0] + p.x * scale - bounds.min[0] as f32,
+                    position[1] - p.y * scale - bounds.min[1] as f32,
+                ]
+            };
+            for curve in font.outline(ab_glyph::GlyphId(id.0 as u16))?.curves {
+                match curve {
+                    OutlineCurve::Line(a, b) => outline.push_curve(&[to_pixels(&a), to_pixels(&b)]),
+                    OutlineCurve::Quad(a, b, c) => {
+                        outline.push_curve(&[to_pixels(&a), to_pixels(&b), to_pixels(&c)])
+                    }
+                    OutlineCurve::Cubic(a, b, c, d) => outline.push_curve(&[
+                        to_pixels(&a),
+                        to_pixels(&b),
+                        to_pixels(&c),
+                        to_pixels(&d),
+                    ]),
+                }
+            }
+            Some(outline)
+        })
+    }
+
+    fn path(
+        &self,
+        id: GlyphId,
+        size: f32,
+        variations: &[FontVariation],
+    ) -> Option<Vec<PathSegment>> {
+        self.with_instance(variations, |font| {
+            let scale = size / font.height_unscaled();
+            let to_pixels = |p: ab_glyph::Point| [p.x * scale, -p.y * scale];
+            let mut path = vec![];
+            // curves don't say where contours end, a curve not starting where the last
+            // one ended starts a new one
+            let mut last = None;
+            for curve in font.outline(ab_glyph::GlyphId(id.0 as u16))?.curves {
+                let (start, end, segment) = match curve {
+                    OutlineCurve::Line(a, b) => (a, b, PathSegment::LineTo(to_pixels(b))),
+                    OutlineCurve::Quad(a, b, c) => {
+                        (a, c, PathSegment::QuadTo(to_pixels(b), to_pixels(c)))
+                    }
+                    OutlineCurve::Cubic(a, b, c, d) => (
+                        a,
+                        d,
+                        PathSegment::CubicTo(to_pixels(b), to_pixels(c), to_pixels(d)),
+                    ),
+                };
+                if last != Some(start) {
+                    path.push(PathSegment::MoveTo(to_pixels(start)));
+                }
+                path.push(segment);
+                last = Some(end);
+            }
+            Some(path)
+        })
+    }
+
+    fn supports_variations(&self) -> bool {
+        true
+    }
+}
+
+// swash reads variable fonts too and is the only backend running hinting instructions.
+// it only runs them for Hinting::Full, Slight just snaps the baseline like elsewhere
+#[cfg(feature = "swash")]
+pub struct SwashBackend {
+    data: Arc<[u8]>,
+    // where the font sits in data and its id in swash's caches, see font
+    offset: u32,
+    key: CacheKey,
+    // keeps outlines and hinting state between glyphs
+    context: Mutex<ScaleContext>,
+    // swash leaves kerning to its shaper, the kern table is read through rusttype like
+    // RusttypeBackend does. None for fonts rusttype can't read
+    kern: Option<Font<'static>>,
+}
+
+#[cfg(feature = "swash")]
+impl SwashBackend {
+    pub fn new(data: Vec<u8>) -> Result<SwashBackend, FontError> {
+        if !tables_in_bounds(&data) {
+            return Err(FontError);
+        }
+        let data: Arc<[u8]> = data.into();
+        let font = FontRef::from_index(&data, 0).ok_or(FontError)?;
+        let (offset, key) = (font.offset, font.key);
+        Ok(SwashBackend {
+            kern: has_rusttype_tables(&data)
+                .then(|| Font::from_bytes(data.clone()).ok())
+                .flatten(),
+            data,
+            offset,
+            key,
+            context: Mutex::new(ScaleContext::new()),
+        })
+    }
+
+    // swash fonts borrow their data, so one is made for every call
+    fn font(&self) -> FontRef<'_> {
+        FontRef {
+            data: &self.data,
+            offset: self.offset,
+            key: self.key,
+        }
+    }
+
+    // sizes are ascent to descent here, swash wants pixels per em
+    fn ppem(&self, size: f32) -> f32 {
+        let metrics = self.font().metrics(&[]);
+        size * metrics.units_per_em as f32 / (metrics.ascent + metrics.descent)
+    }
+
+    fn settings(variations: &[FontVariation]) -> impl Iterator<Item = Setting<f32>> + '_ {
+        variations.iter().map(|v| Setting {
+            tag: swash::tag_from_bytes(&v.tag),
+            value: v.value,
+        })
+    }
+
+    fn with_scaler<R>(
+        &self,
+        size: f32,
+        variations: &[FontVariation],
+        hinting: Hinting,
+        f: impl FnOnce(&mut Scaler) -> R,
+    ) -> R {
+        let mut context = self.context.lock().unwrap();
+        let mut scaler = context
+            .builder(self.font())
+            .size(self.ppem(size))
+            .hint(hinting == Hinting::Full)
+            .variations(SwashBackend::settings(variations))
+            .build();
+        f(&mut scaler)
+    }
+}
+
+#[cfg(feature = "swash")]
+impl GlyphBackend for SwashBackend {
+    fn font_data(&self) -> &[u8] {
+        &self.data
+    }
+
+    fn v_metrics_unscaled(&self) -> FontMetrics {
+        let metrics = self.font().metrics(&[]);
+        FontMetrics {
+            ascent: metrics.ascent,
+            descent: -metrics.descent,
+            line_gap: metrics.leading,
+        }
+    }
+
+    fn glyph_id(&self, c: char) -> GlyphId {
+        GlyphId(self.font().charmap().map(c) as u32)
+    }
+
+    fn h_advance(&self, id: GlyphId, size: f32, variations: &[FontVariation]) -> f32 {
+        let font = self.font();
+        let coords: Vec<_> = font
+            .variations()
+            .normalized_coords(SwashBackend::settings(variations))
+            .collect();
+        font.glyph_metrics(&coords)
+            .scale(self.ppem(size))
+            .advance_width(id.0 as u16)
+    }
+
+    // the kern table only, GPOS kerning needs the shaping feature
+    fn kerning(&self, first: GlyphId, second: GlyphId, size: f32) -> f32 {
+        self.kern.as_ref().map_or(0.0, |font| {
+            let first = rusttype::GlyphId(first.0);
+            let second = rusttype::GlyphId(second.0);
+            font.pair_kerning(Scale::uniform(size), first, second)
+        })
+    }
+
+    fn pixel_bounds(
+        &self,
+        id: GlyphId,
+        size: f32,
+        position: [f32; 2],
+        variations: &[FontVariation],
+        hinting: Hinting,
+    ) -> Option<PixelBounds> {
+        let outline = self.with_scaler(size, variations, hinting, |scaler| {
+            scaler.scale_outline(id.0 as u16)
+        })?;
+        if outline.is_empty() {
+            return None;
+        }
+        // outlines have y going up
+        let bounds = outline.bounds();
+        Some(PixelBounds {
+            min: [
+                (position[0] + bounds.min.x).floor() as i32,
+                (position[1] - bounds.max.y).floor() as i32,
+            ],
+            max: [
+                (position[0] + bounds.max.x).ceil() as i32,
+                (position[1] - bounds.min.y).ceil() as i32,
+            ],
+        })
+    }
+
+    fn rasterize(
+        &self,
+        id: GlyphId,
+        size: f32,
+        position: [f32; 2],
+        variations: &[FontVariation],
+        hinting: Hinting,
+    ) -> Option<GlyphBitmap> {
+        let x = position[0].floor();
+        let y = position[1].floor();
+        let image = self.with_scaler(size, variations, hinting, |scaler| {
+            Render::new(&[Source::Outline])
+                .format(Format::Alpha)
+                .offset(Vector::new(position[0] - x, y - position[1]))
+                .render(scaler, id.0 as u16)
+        })?;
+        let placement = image.placement;
+        if placement.width == 0 || placement.height == 0 {
+            return None;
+        }
+        let min = [x as i32 + placement.left, y as i32 - placement.top];
+        Some(GlyphBitmap {
+            bounds: PixelBounds {
+                min,
+                max: [
+                    min[0] + placement.width as i32,
+                    min[1] + placement.height as i32,
+                ],
+            },
+            coverage: image.data,
+        })
+    }
+
+    fn path(
+        &self,
+        id: GlyphId,
+        size: f32,
+        variations: &[FontVariation],
+    ) -> Option<Vec<PathSegment>> {
+        let outline = self.with_scaler(size, variations, Hinting::None, |scaler| {
+            scaler.scale_outline(id.0 as u16)
+        })?;
+        // y goes up in outlines
+        let mut points = outline.points().iter().map(|p| [p.x, -p.y]);
+        let mut next = || points.next().unwrap();
+        let mut path = vec![];
+        for verb in outline.verbs() {
+            path.push(match verb {
+                Verb::MoveTo => PathSegment::MoveTo(next()),
+                Verb::LineTo => PathSegment::LineTo(next()),
+                Verb::QuadTo => PathSegment::QuadTo(next(), next()),
+                Verb::CurveTo => PathSegment::CubicTo(next(), next(), next()),
+                Verb::Close => continue,
+            });
+        }
+        Some(path)
+    }
+
+    fn supports_variations(&self) -> bool {
+        true
+    }
+
+    fn supports_hinting(&self) -> bool {
+        true
+    }
+}
+
+// the backend add_font uses: swash or ab_glyph when their feature is on, swash if both are
+#[cfg(not(any(feature = "ab_glyph", feature = "swash")))]
+pub(crate) fn default_backend(data: Vec<u8>) -> Result<Box<dyn GlyphBackend>, FontError> {
+    Ok(Box::new(RusttypeBackend::new(data)?))
+}
+
+#[cfg(feature = "swash")]
+pub(crate) fn default_backend(data: Vec<u8>) -> Result<Box<dyn GlyphBackend>, FontError> {
+    Ok(Box::new(SwashBackend::new(data)?))
+}
+
+#[cfg(all(feature = "ab_glyph", not(feature = "swash")))]
+pub(crate) fn default_backend(data: Vec<u8>) -> Result<Box<dyn GlyphBackend>, FontError> {
+    Ok(Box::new(AbGlyphBackend::new(data)?))
+}
@@ -0,0 +1,175 @@
+use super::gpu_backend::TextGpuBackend;
+use super::layouter::TextLayouter;
+use super::quads::TextQuads;
+use super::style::TextStyle;
+use super::wgpu_backend::{WgpuBackend, WgpuUpload};
+
+use bevy::app::{App, Plugin};
+use bevy::ecs::prelude::*;
+use bevy::render::main_graph::node::CAMERA_DRIVER;
+use bevy::render::render_graph::{Node, NodeRunError, RenderGraph, RenderGraphContext};
+use bevy::render::renderer::{RenderContext, RenderDevice, RenderQueue};
+use bevy::render::view::ExtractedWindows;
+use bevy::render::{Extract, RenderApp, RenderStage};
+use bevy::window::WindowId;
+
+use std::sync::Mutex;
+
+// the render graph node drawing the overlays, after every camera
+pub const TEXT_OVERLAY_NODE: &str = "vk_font_render_text_overlay";
+
+// text drawn over the primary window, at position in physical pixels from its top
+// left corner. any entity can carry one
+#[derive(Component, Debug, Clone)]
+pub struct TextOverlay {
+    pub text: String,
+    pub position: [f32; 2],
+    pub style: TextStyle,
+}
+
+impl TextOverlay {
+    pub fn new(text: &str, x: f32, y: f32, style: TextStyle) -> TextOverlay {
+        TextOverlay {
+            text: text.to_owned(),
+            position: [x, y],
+            style,
+        }
+    }
+}
+
+// lays TextOverlay components out with TextQuads and draws them through WgpuBackend on
+// bevy's own wgpu device, no vulkano involved
+#[derive(Default)]
+pub struct VkFontRenderPlugin {
+    layouter: TextLayouter,
+}
+
+impl VkFontRenderPlugin {
+    // with the bundled font
+    pub fn new() -> VkFontRenderPlugin {
+        VkFontRenderPlugin::default()
+    }
+
+    // fonts, script bindings and scale factor for the overlays
+    pub fn with_layouter(layouter: TextLayouter) -> VkFontRenderPlugin {
+        VkFontRenderPlugin { layouter }
+    }
+}
+
+#[derive(Resource, Default)]
+struct ExtractedOverlays(Vec<TextOverlay>);
+
+// lives in the render world. TextQuads isn't Sync, the mutex is never contended
+#[derive(Resource)]
+struct OverlayRenderer {
+    quads: Mutex<TextQuads>,
+    // created with the format of the first swapchain texture
+    backend: Option<WgpuBackend>,
+}
+
+impl Plugin for VkFontRenderPlugin {
+    fn build(&self, app: &mut App) {
+        let render_app = match app.get_sub_app_mut(RenderApp) {
+            Ok(render_app) => render_app,
+            Err(_) => return,
+        };
+        let quads = TextQuads::with_layouter(self.layouter.clone());
+        render_app
+            .insert_resource(OverlayRenderer {
+                quads: Mutex::new(quads),
+                backend: None,
+            })
+            .init_resource::<ExtractedOverlays>()
+            .add_system_to_stage(RenderStage::Extract, extract_overlays)
+            .add_system_to_stage(RenderStage::Prepare, prepare_overlays);
+
+        let mut graph = render_app.world.resource_mut::<RenderGraph>();
+        graph.add_node(TEXT_OVERLAY_NODE, TextOverlayNode);
+        graph
+            .add_node_edge(CAMERA_DRIVER, TEXT_OVERLAY_NODE)
+            .unwrap();
+    }
+}
+
+fn extract_overlays(
+    mut extracted: ResMut<ExtractedOverlays>,
+    overlays: Extract<Query<&TextOverlay>>,
+) {
+    extracted.0 = overlays.iter().cloned().collect();
+}
+
+// queues the overlays and uploads the atlas and quads, the node only records the draw
+fn prepare_overlays(
+    overlays: Res<ExtractedOverlays>,
+    mut renderer: ResMut<OverlayRenderer>,
+    windows: Res<ExtractedWindows>,
+    device: Res<RenderDevice>,
+    queue: Res<RenderQueue>,
+) {
+    let window = match windows.get(&WindowId::primary()) {
+        Some(window) => window,
+        None => return,
+    };
+    let format = match window.swap_chain_texture_format {
+        Some(format) => format,
+        None => return,
+    };
+
+    let renderer = &mut *renderer;
+    let quads = renderer.quads.get_mut().unwrap();
+    for overlay in &overlays.0 {
+        let [x, y] = overlay.position;
+        quads.queue_text_styled(x, y, &overlay.style, &overlay.text);
+    }
+    let backend = renderer
+        .backend
+        .get_or_insert_with(|| WgpuBackend::new(device.wgpu_device(), format));
+    quads.upload_to(
+        backend,
+        &mut WgpuUpload {
+            device: device.wgpu_device(),
+            queue: &queue,
+        },
+        [window.physical_width, window.physical_height],
+    );
+}
+
+struct TextOverlayNode;
+
+impl Node for TextOverlayNode {
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let renderer = world.resource::<OverlayRenderer>();
+        let windows = world.resource::<ExtractedWindows>();
+        let view = windows
+            .get(&WindowId::primary())
+            .and_then(|window| window.swap_chain_texture.as_ref());
+        let (backend, view) = match (&renderer.backend, view) {
+            (Some(backend), Some(view)) => (backend, view),
+            _ => return Ok(()),
+        };
+
+        // loads the window's image, the overlays go over whatever the cameras drew
+        let mut pass =
+            render_context
+                .command_encoder
+                .begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some("text overlay"),
+                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                        view,
+                        resolve_target: None,
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Load,
+                            store: true,
+                        },
+                    })],
+                    depth_stencil_attachment: None,
+                });
+        backend.draw(&mut pass);
+        Ok(())
+    }
+}
@@ -0,0 +1,145 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+// a small take on the unicode bidirectional algorithm (UAX #9): strong left to right
+// and right to left text, numbers and the neutrals between them. no explicit
+// embeddings, isolates or mirrored brackets, enough to show hebrew and arabic words
+// and the numbers and punctuation around them in the order they are read
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Class {
+    Left,
+    Right,
+    Number,
+    Neutral,
+}
+
+fn class(c: char) -> Class {
+    match c {
+        '0'..='9' | '\u{0660}'..='\u{0669}' | '\u{06f0}'..='\u{06f9}' => Class::Number,
+        '\u{0590}'..='\u{08ff}'
+        | '\u{fb1d}'..='\u{fdff}'
+        | '\u{fe70}'..='\u{feff}'
+        | '\u{10800}'..='\u{10fff}'
+        | '\u{1e800}'..='\u{1efff}' => Class::Right,
+        c if c.is_alphabetic() => Class::Left,
+        _ => Class::Neutral,
+    }
+}
+
+// the embedding level of every char of text, even ones run left to right and odd ones
+// right to left. a paragraph, up to a '\n', takes the direction of its first strong
+// char, and so does whitespace at the end of a line, where a paragraph ends or a
+// wrapped line breaks at an offset in breaks. None when text has no right to left
+// chars, it all runs left to right then
+pub(crate) fn levels(text: &str, breaks: &[usize]) -> Option<Vec<u8>> {
+    if !text.chars().any(|c| class(c) == Class::Right) {
+        return None;
+    }
+
+    let mut levels = vec![];
+    let mut offset = 0;
+    for paragraph in text.split_inclusive('\n') {
+        // marks and joiners go with the char they belong to
+        let clusters: Vec<(usize, &str)> = paragraph
+            .grapheme_indices(true)
+            .map(|(start, cluster)| (offset + start, cluster))
+            .collect();
+        offset += paragraph.len();
+        let mut classes: Vec<Class> = clusters
+            .iter()
+            .map(|(_, cluster)| class(cluster.chars().next().unwrap()))
+            .collect();
+        let base = classes
+            .iter()
+            .copied()
+            .find(|&class| class == Class::Left || class == Class::Right)
+            .unwrap_or(Class::Left);
+
+        // numbers following left to right text are part of it
+        let mut strong = base;
+        for class in &mut classes {
+            match *class {
+                Class::Left | Class::Right => strong = *class,
+                Class::Number if strong == Class::Left => *class = Class::Left,
+                _ => {}
+            }
+        }
+
+        // neutrals between text of the same direction take it, numbers count as right
+        // to left there. any other neutrals take the paragraph's
+        let direction = |class: Class| match class {
+            Class::Number => Class::Right,
+            class => class,
+        };
+        let mut i = 0;
+        while i < classes.len() {
+            if classes[i] != Class::Neutral {
+                i += 1;
+                continue;
+            }
+            let start = i;
+            while i < classes.len() && classes[i] == Class::Neutral {
+                i += 1;
+            }
+            let before = start.checked_sub(1).map_or(base, |j| direction(classes[j]));
+            let after = classes.get(i).map_or(base, |&class| direction(class));
+            let resolved = if before == after { before } else { base };
+            classes[start..i].fill(resolved);
+        }
+
+        let base_level = (base == Class::Right) as u8;
+        let mut cluster_levels: Vec<u8> = classes
+            .iter()
+            .map(|&class| match (base, class) {
+                (Class::Left, Class::Left) => 0,
+                (_, Class::Right) => 1,
+                _ => 2,
+            })
+            .collect();
+        // whitespace before a line's end, walked from the back
+        let mut line_end = true;
+        for (i, (start, cluster)) in clusters.iter().enumerate().rev() {
+            if line_end && cluster.chars().all(char::is_whitespace) {
+                cluster_levels[i] = base_level;
+            } else {
+                line_end = false;
+            }
+            if breaks.binary_search(start).is_ok() {
+                line_end = true;
+            }
+        }
+
+        for ((_, cluster), level) in clusters.iter().zip(cluster_levels) {
+            levels.extend(cluster.chars().map(|_| level));
+        }
+    }
+    Some(levels)
+}
+
+// indices into the levels of a line in the order they are shown from left to right.
+// from the highest level down to the lowest odd one, every run at that level or above
+// is reversed
+pub(crate) fn visual_order(levels: &[u8]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..levels.len()).collect();
+    let highest = levels.iter().copied().max().unwrap_or(0);
+    let lowest_odd = levels
+        .iter()
+        .copied()
+        .filter(|level| level % 2 == 1)
+        .min()
+        .unwrap_or(highest + 1);
+    for level in (lowest_odd..=highest).rev() {
+        let mut i = 0;
+        while i < order.len() {
+            if levels[order[i]] < level {
+                i += 1;
+                continue;
+            }
+            let start = i;
+            while i < order.len() && levels[order[i]] >= level {
+                i += 1;
+            }
+            order[start..i].reverse();
+        }
+    }
+    order
+}
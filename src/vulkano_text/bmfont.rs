@@ -0,0 +1,360 @@
+use super::backend::{FontMetrics, GlyphBackend, GlyphBitmap, GlyphId, PixelBounds};
+use super::style::{FontVariation, Hinting};
+
+use rusttype::{point, Rect};
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+#[cfg(feature = "bmfont")]
+use std::io;
+#[cfg(feature = "bmfont")]
+use std::path::Path;
+
+// why a bitmap font couldn't be read, lines are counted from 1
+#[derive(Debug)]
+pub enum BmFontError {
+    // only text .fnt files are supported
+    Binary,
+    // a value that isn't a number, or a page line without an id or file
+    Malformed {
+        line: usize,
+    },
+    // there is no common line with the line height
+    NoCommon,
+    // a char is on a page that wasn't given
+    MissingPage {
+        line: usize,
+        page: usize,
+    },
+    #[cfg(feature = "bmfont")]
+    Io(io::Error),
+    #[cfg(feature = "bmfont")]
+    Png(png::DecodingError),
+}
+
+impl fmt::Display for BmFontError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BmFontError::Binary => write!(f, "only text .fnt files are supported, not binary ones"),
+            BmFontError::Malformed { line } => write!(f, "line {} of the .fnt is malformed", line),
+            BmFontError::NoCommon => write!(f, "the .fnt has no common line"),
+            BmFontError::MissingPage { line, page } => {
+                write!(
+                    f,
+                    "line {} of the .fnt uses the missing page {}",
+                    line, page
+                )
+            }
+            #[cfg(feature = "bmfont")]
+            BmFontError::Io(err) => write!(f, "reading the font failed: {}", err),
+            #[cfg(feature = "bmfont")]
+            BmFontError::Png(err) => write!(f, "decoding a page failed: {}", err),
+        }
+    }
+}
+
+impl Error for BmFontError {}
+
+#[cfg(feature = "bmfont")]
+impl From<io::Error> for BmFontError {
+    fn from(err: io::Error) -> BmFontError {
+        BmFontError::Io(err)
+    }
+}
+
+#[cfg(feature = "bmfont")]
+impl From<png::DecodingError> for BmFontError {
+    fn from(err: png::DecodingError) -> BmFontError {
+        BmFontError::Png(err)
+    }
+}
+
+// a page of a bitmap font, a byte of coverage per pixel row by row
+#[derive(Debug, Clone, PartialEq)]
+pub struct BmFontPage {
+    pub width: u32,
+    pub height: u32,
+    pub coverage: Vec<u8>,
+}
+
+#[cfg(feature = "bmfont")]
+impl BmFontPage {
+    // the alpha channel, or how bright pixels are in pages without one
+    pub fn from_png(data: &[u8]) -> Result<BmFontPage, BmFontError> {
+        let mut decoder = png::Decoder::new(data);
+        decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+        let mut reader = decoder.read_info()?;
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut pixels)?;
+        let channels = info.color_type.samples();
+        let coverage = pixels[..info.buffer_size()]
+            .chunks_exact(channels)
+            .map(|pixel| match info.color_type {
+                png::ColorType::GrayscaleAlpha | png::ColorType::Rgba => pixel[channels - 1],
+                _ => pixel.iter().copied().max().unwrap(),
+            })
+            .collect();
+        Ok(BmFontPage {
+            width: info.width,
+            height: info.height,
+            coverage,
+        })
+    }
+}
+
+// where a char sits on its page and how it's placed, in pixels of the baked size
+#[derive(Debug, Clone, Copy, Default)]
+struct BmChar {
+    page: usize,
+    rect: [u32; 4],
+    // from the pen position to the top left of rect, with y down from the line's top
+    offset: [f32; 2],
+    advance: f32,
+}
+
+// an AngelCode BMFont, the text .fnt format with its pages. glyphs are resampled from
+// the pages, so they look best at the size they were baked at or whole multiples of it.
+// there are no outlines, no variations and no hinting, and packed fonts with chars in
+// different channels aren't supported
+pub struct BmFontBackend {
+    descriptor: Vec<u8>,
+    pages: Vec<BmFontPage>,
+    // GlyphId 0 is the missing glyph, the chars follow in the order they were listed
+    chars: Vec<BmChar>,
+    ids: HashMap<char, GlyphId>,
+    kerning: HashMap<(GlyphId, GlyphId), f32>,
+    line_height: f32,
+    base: f32,
+}
+
+impl BmFontBackend {
+    // pages in the order of their ids in the descriptor
+    pub fn new(descriptor: &str, pages: Vec<BmFontPage>) -> Result<BmFontBackend, BmFontError> {
+        if descriptor.starts_with("BMF") {
+            return Err(BmFontError::Binary);
+        }
+        let mut font = BmFontBackend {
+            descriptor: descriptor.as_bytes().to_vec(),
+            pages,
+            chars: vec![BmChar::default()],
+            ids: HashMap::new(),
+            kerning: HashMap::new(),
+            line_height: 0.0,
+            base: 0.0,
+        };
+        let mut kerning = vec![];
+        for (index, line) in descriptor.lines().enumerate() {
+            let (tag, fields) = fields(line);
+            let line = index + 1;
+            let number = |key: &str| -> Result<i64, BmFontError> {
+                fields.get(key).map_or(Ok(0), |v| {
+                    v.parse().map_err(|_| BmFontError::Malformed { line })
+                })
+            };
+            match tag {
+                "common" => {
+                    font.line_height = number("lineHeight")? as f32;
+                    font.base = number("base")? as f32;
+                }
+                "char" => {
+                    let c = match char::from_u32(number("id")? as u32) {
+                        Some(c) => c,
+                        None => continue,
+                    };
+                    let page = number("page")? as usize;
+                    if page >= font.pages.len() {
+                        return Err(BmFontError::MissingPage { line, page });
+                    }
+                    let mut rect = [0; 4];
+                    for (value, key) in rect.iter_mut().zip(["x", "y", "width", "height"]) {
+                        *value = number(key)? as u32;
+                    }
+                    font.ids.insert(c, GlyphId(font.chars.len() as u32));
+                    font.chars.push(BmChar {
+                        page,
+                        rect,
+                        offset: [number("xoffset")? as f32, number("yoffset")? as f32],
+                        advance: number("xadvance")? as f32,
+                    });
+                }
+                "kerning" => kerning.push([number("first")?, number("second")?, number("amount")?]),
+                _ => {}
+            }
+        }
+        if font.line_height <= 0.0 {
+            return Err(BmFontError::NoCommon);
+        }
+        for [first, second, amount] in kerning {
+            let id = |c: i64| char::from_u32(c as u32).and_then(|c| font.ids.get(&c).copied());
+            if let (Some(first), Some(second)) = (id(first), id(second)) {
+                font.kerning.insert((first, second), amount as f32);
+            }
+        }
+        Ok(font)
+    }
+
+    // a .fnt file and the png pages it names, which are looked up next to it
+    #[cfg(feature = "bmfont")]
+    pub fn open(path: impl AsRef<Path>) -> Result<BmFontBackend, BmFontError> {
+        let path = path.as_ref();
+        let descriptor = std::fs::read_to_string(path)?;
+        let mut files = vec![];
+        for (index, line) in descriptor.lines().enumerate() {
+            let (tag, fields) = fields(line);
+            if tag == "page" {
+                let malformed = BmFontError::Malformed { line: index + 1 };
+                let (id, file) = match (fields.get("id"), fields.get("file")) {
+                    (Some(id), Some(file)) => (id.parse::<usize>().map_err(|_| malformed)?, *file),
+                    _ => return Err(malformed),
+                };
+                files.resize(files.len().max(id + 1), None);
+                files[id] = Some(file);
+            }
+        }
+        let directory = path.parent().unwrap_or(Path::new(""));
+        let mut pages = vec![];
+        // a gap in the page ids gets an empty page, chars on it draw nothing
+        for file in files {
+            pages.push(match file {
+                Some(file) => BmFontPage::from_png(&std::fs::read(directory.join(file))?)?,
+                None => BmFontPage {
+                    width: 0,
+                    height: 0,
+                    coverage: vec![],
+                },
+            });
+        }
+        BmFontBackend::new(&descriptor, pages)
+    }
+
+    fn scale(&self, size: f32) -> f32 {
+        size / self.line_height
+    }
+
+    // the glyph's quad on screen, None for chars without pixels
+    fn quad(&self, id: GlyphId, size: f32, position: [f32; 2]) -> Option<(&BmChar, Rect<f32>)> {
+        let glyph = self.chars.get(id.0 as usize).filter(|_| id.0 != 0)?;
+        let [_, _, width, height] = glyph.rect;
+        if width == 0 || height == 0 {
+            return None;
+        }
+        let scale = self.scale(size);
+        let min = point(
+            position[0] + glyph.offset[0] * scale,
+            position[1] + (glyph.offset[1] - self.base) * scale,
+        );
+        let max = point(min.x + width as f32 * scale, min.y + height as f32 * scale);
+        Some((glyph, Rect { min, max }))
+    }
+
+    // bilinear, pixels outside the char's rect are empty
+    fn sample(&self, glyph: &BmChar, u: f32, v: f32) -> f32 {
+        let page = &self.pages[glyph.page];
+        let [x, y, width, height] = glyph.rect;
+        let texel = |tx: i64, ty: i64| {
+            if tx < 0 || ty < 0 || tx >= width as i64 || ty >= height as i64 {
+                return 0.0;
+            }
+            let index = (y as i64 + ty) * page.width as i64 + x as i64 + tx;
+            page.coverage.get(index as usize).map_or(0.0, |&c| c as f32)
+        };
+        let (u0, v0) = (u.floor(), v.floor());
+        let (fu, fv) = (u - u0, v - v0);
+        let (tx, ty) = (u0 as i64, v0 as i64);
+        let top = texel(tx, ty) * (1.0 - fu) + texel(tx + 1, ty) * fu;
+        let bottom = texel(tx, ty + 1) * (1.0 - fu) + texel(tx + 1, ty + 1) * fu;
+        top * (1.0 - fv) + bottom * fv
+    }
+}
+
+// the tag a line starts with and its key=value pairs, values may be quoted
+fn fields(line: &str) -> (&str, HashMap<&str, &str>) {
+    let line = line.trim();
+    let (tag, mut rest) = line.split_once(' ').unwrap_or((line, ""));
+    let mut fields = HashMap::new();
+    while let Some((key, after)) = rest.trim_start().split_once('=') {
+        let (value, after) = match after.strip_prefix('"') {
+            Some(quoted) => quoted.split_once('"').unwrap_or((quoted, "")),
+            None => after.split_once(' ').unwrap_or((after, "")),
+        };
+        fields.insert(key, value);
+        rest = after;
+    }
+    (tag, fields)
+}
+
+impl GlyphBackend for BmFontBackend {
+    // the .fnt file, it has none of the tables DrawText reads
+    fn font_data(&self) -> &[u8] {
+        &self.descriptor
+    }
+
+    fn is_opentype(&self) -> bool {
+        false
+    }
+
+    // in pixels of the baked size
+    fn v_metrics_unscaled(&self) -> FontMetrics {
+        FontMetrics {
+            ascent: self.base,
+            descent: self.base - self.line_height,
+            line_gap: 0.0,
+        }
+    }
+
+    fn glyph_id(&self, c: char) -> GlyphId {
+        self.ids.get(&c).copied().unwrap_or(GlyphId(0))
+    }
+
+    fn h_advance(&self, id: GlyphId, size: f32, _variations: &[FontVariation]) -> f32 {
+        let advance = self
+            .chars
+            .get(id.0 as usize)
+            .map_or(0.0, |glyph| glyph.advance);
+        advance * self.scale(size)
+    }
+
+    fn kerning(&self, first: GlyphId, second: GlyphId, size: f32) -> f32 {
+        let amount = self.kerning.get(&(first, second)).copied().unwrap_or(0.0);
+        amount * self.scale(size)
+    }
+
+    fn pixel_bounds(
+        &self,
+        id: GlyphId,
+        size: f32,
+        position: [f32; 2],
+        _variations: &[FontVariation],
+        _hinting: Hinting,
+    ) -> Option<PixelBounds> {
+        let (_, quad) = self.quad(id, size, position)?;
+        Some(PixelBounds {
+            min: [quad.min.x.floor() as i32, quad.min.y.floor() as i32],
+            max: [quad.max.x.ceil() as i32, quad.max.y.ceil() as i32],
+        })
+    }
+
+    fn rasterize(
+        &self,
+        id: GlyphId,
+        size: f32,
+        position: [f32; 2],
+        variations: &[FontVariation],
+        hinting: Hinting,
+    ) -> Option<GlyphBitmap> {
+        let (glyph, quad) = self.quad(id, size, position)?;
+        let bounds = self.pixel_bounds(id, size, position, variations, hinting)?;
+        let scale = self.scale(size);
+        let mut coverage = Vec::with_capacity((bounds.width() * bounds.height()) as usize);
+        for y in bounds.min[1]..bounds.max[1] {
+            for x in bounds.min[0]..bounds.max[0] {
+                // pixel centers in texels of the char, texel centers are at .5
+                let u = (x as f32 + 0.5 - quad.min.x) / scale - 0.5;
+                let v = (y as f32 + 0.5 - quad.min.y) / scale - 0.5;
+                coverage.push(self.sample(glyph, u, v).round() as u8);
+            }
+        }
+        Some(GlyphBitmap { bounds, coverage })
+    }
+}
@@ -0,0 +1,983 @@
+use super::atlas_file::{self, Reader, Writer};
+use super::backend::{GlyphBackend, GlyphBitmap, GlyphId};
+#[cfg(feature = "gpu_raster")]
+use super::gpu_raster::{GpuBatch, GpuRasterizer};
+use super::script::FontId;
+use super::style::{FontVariation, Hinting};
+
+use rusttype::{point, Point, Rect};
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::mem;
+#[cfg(feature = "gpu")]
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+#[cfg(feature = "gpu")]
+use std::sync::Mutex;
+#[cfg(feature = "gpu")]
+use std::thread;
+
+// smaller batches are done faster on the cpu than the round trip to the gpu takes
+#[cfg(feature = "gpu_raster")]
+const GPU_BATCH: usize = 64;
+// new glyphs are rasterized and placed this many at a time, so a queue overflowing the
+// atlas stops rasterizing once it's full instead of holding every bitmap at once
+const RASTER_CHUNK: usize = 256;
+// of an atlas page, DrawText's and TextQuads' alike
+pub(crate) const CACHE_WIDTH: usize = 1000;
+pub(crate) const CACHE_HEIGHT: usize = 1000;
+
+// copies a glyph's coverage from cache_queued into pixels, the atlas pages one after
+// the other, adding pages as needed
+pub(crate) fn blit(pixels: &mut Vec<u8>, page: u32, rect: Rect<u32>, src_data: &[u8]) {
+    let page_start = page as usize * CACHE_WIDTH * CACHE_HEIGHT;
+    if pixels.len() < page_start + CACHE_WIDTH * CACHE_HEIGHT {
+        pixels.resize(page_start + CACHE_WIDTH * CACHE_HEIGHT, 0);
+    }
+    let width = (rect.max.x - rect.min.x) as usize;
+    let height = (rect.max.y - rect.min.y) as usize;
+    let mut dst_index = page_start + rect.min.y as usize * CACHE_WIDTH + rect.min.x as usize;
+    let mut src_index = 0;
+
+    for _ in 0..height {
+        let dst_slice = &mut pixels[dst_index..dst_index + width];
+        let src_slice = &src_data[src_index..src_index + width];
+        dst_slice.copy_from_slice(src_slice);
+
+        dst_index += CACHE_WIDTH;
+        src_index += width;
+    }
+}
+
+// a glyph placed by the layout
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Glyph {
+    pub(crate) id: GlyphId,
+    pub(crate) size: f32,
+    // origin on the baseline
+    pub(crate) position: Point<f32>,
+    pub(crate) advance: f32,
+    // pixels touched, None for glyphs without ink like spaces
+    pub(crate) bounds: Option<Rect<i32>>,
+    // tells variable font instances apart, 0 for the default one
+    pub(crate) instance: u64,
+    // None for backends that can't hint
+    pub(crate) hinting: Hinting,
+}
+
+// 0 for the default instance
+pub(crate) fn instance_hash(variations: &[FontVariation]) -> u64 {
+    if variations.is_empty() {
+        return 0;
+    }
+    let mut hasher = DefaultHasher::new();
+    for variation in variations {
+        variation.tag.hash(&mut hasher);
+        variation.value.to_bits().hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+// glyphs closer in size and subpixel position than the tolerances share a key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct GlyphKey {
+    font: FontId,
+    id: GlyphId,
+    size: u32,
+    offset: [u32; 2],
+    instance: u64,
+    hinting: Hinting,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    // layer of the atlas array and where the coverage sits on it, padding left out
+    page: u32,
+    texture: Rect<u32>,
+    // relative to the whole pixel the glyph's origin is in
+    bounds: Rect<i32>,
+    // the last frame it was queued in
+    used: u64,
+}
+
+// a shelf of one atlas page, glyphs are put next to each other left to right
+#[derive(Debug, Clone, Copy)]
+struct Row {
+    page: u32,
+    top: u32,
+    height: u32,
+    width: u32,
+}
+
+// how full the glyph atlas is and how much it churns, see DrawText::cache_stats. lots
+// of evictions every frame mean the atlas is too small for the text on screen
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct CacheStats {
+    // share of all pages taken up by rows of glyphs, 0 to 100
+    pub used_area_pct: f32,
+    pub pages: u32,
+    pub glyphs_resident: usize,
+    // glyphs dropped to make room for new ones
+    pub evictions_last_frame: usize,
+    // atlas pixels copied to the gpu
+    pub uploads_bytes_last_frame: usize,
+    // glyphs left out of the frame because they didn't fit even into an empty atlas
+    // of max_atlas_pages, or are bigger than a whole page
+    pub dropped_glyphs_last_frame: usize,
+    // chars cut off the end of strings longer than max_glyphs_per_call
+    pub clipped_chars_last_frame: usize,
+    // share of the rows taken up by glyphs queued recently, 0 to 100. the rest is held
+    // by glyphs that aren't drawn anymore, see DrawText::set_atlas_defragment
+    pub utilization_pct: f32,
+}
+
+// when the glyph atlas gets repacked, see DrawText::set_atlas_defragment
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AtlasDefragment {
+    // repacks once live glyphs take up less than this share of the rows, 0 to 100
+    pub min_utilization_pct: f32,
+    // glyphs not queued for this many frames aren't live, a repack drops them
+    pub max_idle_frames: u64,
+}
+
+impl Default for AtlasDefragment {
+    fn default() -> AtlasDefragment {
+        AtlasDefragment {
+            min_utilization_pct: 50.0,
+            max_idle_frames: 600,
+        }
+    }
+}
+
+// a glyph moved by a repack, padding included
+#[derive(Debug, Clone, Copy)]
+#[cfg(feature = "gpu")]
+pub(crate) struct GlyphMove {
+    pub(crate) page: u32,
+    pub(crate) rect: Rect<u32>,
+    pub(crate) to_page: u32,
+    pub(crate) to: [u32; 2],
+}
+
+// a glyph for the workers to draw
+#[cfg(feature = "gpu")]
+struct Job {
+    key: GlyphKey,
+    font: Arc<dyn GlyphBackend>,
+    size: f32,
+    offset: [f32; 2],
+    variations: Vec<FontVariation>,
+}
+
+// worker threads rasterizing glyphs off the render thread, they stop once it's dropped
+#[cfg(feature = "gpu")]
+struct Rasterizer {
+    jobs: Sender<Job>,
+    finished: Receiver<(GlyphKey, Option<GlyphBitmap>)>,
+    // sent to the workers and not back yet
+    pending: HashSet<GlyphKey>,
+}
+
+#[cfg(feature = "gpu")]
+impl Rasterizer {
+    fn new() -> Rasterizer {
+        let (jobs, waiting) = mpsc::channel::<Job>();
+        let (done, finished) = mpsc::channel();
+        let waiting = Arc::new(Mutex::new(waiting));
+        // one core stays with the render thread
+        let workers = thread::available_parallelism()
+            .map_or(2, |n| n.get())
+            .saturating_sub(1)
+            .max(1);
+        for _ in 0..workers {
+            let waiting = waiting.clone();
+            let done = done.clone();
+            thread::spawn(move || loop {
+                // the lock is only held while waiting for a job, not while drawing it
+                let job = match waiting.lock().unwrap().recv() {
+                    Ok(job) => job,
+                    Err(_) => break,
+                };
+                let bitmap = job.font.rasterize(
+                    job.key.id,
+                    job.size,
+                    job.offset,
+                    &job.variations,
+                    job.key.hinting,
+                );
+                if done.send((job.key, bitmap)).is_err() {
+                    break;
+                }
+            });
+        }
+        Rasterizer {
+            jobs,
+            finished,
+            pending: HashSet::new(),
+        }
+    }
+
+    // glyphs already on their way are only drawn once
+    fn request(&mut self, job: Job) {
+        if self.pending.insert(job.key) {
+            self.jobs.send(job).unwrap();
+        }
+    }
+
+    fn take_finished(&mut self) -> Vec<(GlyphKey, Option<GlyphBitmap>)> {
+        let finished: Vec<_> = self.finished.try_iter().collect();
+        for (key, _) in &finished {
+            self.pending.remove(key);
+        }
+        finished
+    }
+}
+
+// the glyph atlas, kept on the cpu. glyphs queued for a frame get rasterized by their
+// font's backend and packed into rows. a full page gets another one next to it, up to
+// max_pages, after that everything not queued this frame gets dropped
+pub(crate) struct GlyphCache {
+    // of a page
+    width: u32,
+    height: u32,
+    pages: u32,
+    max_pages: u32,
+    scale_tolerance: f32,
+    position_tolerance: f32,
+    // a transparent pixel around every glyph
+    pad: bool,
+    // None for glyphs without ink
+    entries: HashMap<GlyphKey, Option<Entry>>,
+    // rasterizes on worker threads when set
+    #[cfg(feature = "gpu")]
+    rasterizer: Option<Rasterizer>,
+    // fills big batches of new glyphs with a compute shader when set
+    #[cfg(feature = "gpu_raster")]
+    gpu: Option<GpuRasterizer>,
+    // submitted to the gpu and not placed yet, with the keys of their bitmaps
+    #[cfg(feature = "gpu_raster")]
+    gpu_batches: Vec<(Vec<GlyphKey>, GpuBatch)>,
+    rows: Vec<Row>,
+    queue: Vec<(GlyphKey, Vec<FontVariation>)>,
+    // glyphs that found no room since the last take_dropped
+    dropped: usize,
+    // counts cache_queued calls, for telling live glyphs from idle ones
+    frame: u64,
+}
+
+impl GlyphCache {
+    pub(crate) fn new(
+        width: u32,
+        height: u32,
+        max_pages: u32,
+        scale_tolerance: f32,
+        position_tolerance: f32,
+        pad: bool,
+    ) -> GlyphCache {
+        GlyphCache {
+            width,
+            height,
+            pages: 1,
+            max_pages: max_pages.max(1),
+            // rusttype's gpu_cache had the same lower bound
+            scale_tolerance: scale_tolerance.max(0.001),
+            position_tolerance: position_tolerance.max(0.001),
+            pad,
+            entries: HashMap::new(),
+            #[cfg(feature = "gpu")]
+            rasterizer: None,
+            #[cfg(feature = "gpu_raster")]
+            gpu: None,
+            #[cfg(feature = "gpu_raster")]
+            gpu_batches: vec![],
+            rows: vec![],
+            queue: vec![],
+            dropped: 0,
+            frame: 0,
+        }
+    }
+
+    // drops every glyph, they get rasterized again when queued
+    pub(crate) fn set_tolerances(&mut self, scale_tolerance: f32, position_tolerance: f32) {
+        self.scale_tolerance = scale_tolerance.max(0.001);
+        self.position_tolerance = position_tolerance.max(0.001);
+        self.entries.clear();
+        self.rows.clear();
+        // their keys stand for other sizes now
+        #[cfg(feature = "gpu_raster")]
+        self.gpu_batches.clear();
+    }
+
+    fn key(&self, font: FontId, glyph: &Glyph) -> GlyphKey {
+        let fract = |v: f32| v - v.floor();
+        let step = |v: f32| (fract(v) / self.position_tolerance).round() as u32;
+        GlyphKey {
+            font,
+            id: glyph.id,
+            size: (glyph.size / self.scale_tolerance).round() as u32,
+            offset: [step(glyph.position.x), step(glyph.position.y)],
+            instance: glyph.instance,
+            hinting: glyph.hinting,
+        }
+    }
+
+    // glyphs drawn on worker threads only show up in a frame once they are done, so a
+    // burst of new glyphs doesn't stall the one it first appears in
+    #[cfg(feature = "gpu")]
+    pub(crate) fn set_async(&mut self, on: bool) {
+        if on != self.rasterizer.is_some() {
+            self.rasterizer = if on { Some(Rasterizer::new()) } else { None };
+        }
+    }
+
+    #[cfg(feature = "gpu_raster")]
+    pub(crate) fn set_gpu(&mut self, gpu: Option<GpuRasterizer>) {
+        self.gpu = gpu;
+    }
+
+    // glyphs the workers or the gpu are still drawing
+    #[cfg(feature = "gpu")]
+    pub(crate) fn pending(&self) -> usize {
+        let pending = self
+            .rasterizer
+            .as_ref()
+            .map_or(0, |rasterizer| rasterizer.pending.len());
+        #[cfg(feature = "gpu_raster")]
+        let pending = pending
+            + self
+                .gpu_batches
+                .iter()
+                .map(|(keys, _)| keys.len())
+                .sum::<usize>();
+        pending
+    }
+
+    pub(crate) fn queue_glyph(&mut self, font: FontId, glyph: Glyph, variations: &[FontVariation]) {
+        let key = self.key(font, &glyph);
+        self.queue.push((key, variations.to_vec()));
+    }
+
+    pub(crate) fn glyphs_resident(&self) -> usize {
+        self.entries
+            .values()
+            .filter(|entry| entry.is_some())
+            .count()
+    }
+
+    // never goes down, emptied pages get filled again
+    pub(crate) fn pages(&self) -> u32 {
+        self.pages
+    }
+
+    // rows count as used up to their last glyph, padding and wasted row height included
+    #[cfg(feature = "gpu")]
+    pub(crate) fn used_area_pct(&self) -> f32 {
+        let used: u64 = self
+            .rows
+            .iter()
+            .map(|row| row.width as u64 * row.height as u64)
+            .sum();
+        used as f32 * 100.0 / (self.width as u64 * self.height as u64 * self.pages as u64) as f32
+    }
+
+    // the glyph's rect with its padding
+    #[cfg(feature = "gpu")]
+    fn padded(&self, entry: &Entry) -> Rect<u32> {
+        let pad = self.pad as u32;
+        Rect {
+            min: point(entry.texture.min.x - pad, entry.texture.min.y - pad),
+            max: point(entry.texture.max.x + pad, entry.texture.max.y + pad),
+        }
+    }
+
+    #[cfg(feature = "gpu")]
+    fn is_live(&self, entry: &Entry, max_idle_frames: u64) -> bool {
+        entry.used + max_idle_frames >= self.frame
+    }
+
+    // share of the rows' area taken up by live glyphs, 100 for an empty atlas
+    #[cfg(feature = "gpu")]
+    pub(crate) fn utilization_pct(&self, max_idle_frames: u64) -> f32 {
+        let rows: u64 = self
+            .rows
+            .iter()
+            .map(|row| row.width as u64 * row.height as u64)
+            .sum();
+        if rows == 0 {
+            return 100.0;
+        }
+        let live: u64 = self
+            .entries
+            .values()
+            .flatten()
+            .filter(|entry| self.is_live(entry, max_idle_frames))
+            .map(|entry| {
+                let padded = self.padded(entry);
+                padded.width() as u64 * padded.height() as u64
+            })
+            .sum();
+        live as f32 * 100.0 / rows as f32
+    }
+
+    // packs the live glyphs and the ones queued for this frame into new rows on the
+    // pages there already are, tallest first, and drops the rest. returns where every
+    // glyph that stays went, the pixels have to be moved accordingly
+    #[cfg(feature = "gpu")]
+    pub(crate) fn defragment(&mut self, max_idle_frames: u64) -> Vec<GlyphMove> {
+        let queued: HashSet<_> = self.queue.iter().map(|(key, _)| *key).collect();
+        let mut live: Vec<_> = self
+            .entries
+            .iter()
+            .filter_map(|(key, entry)| Some((*key, (*entry)?)))
+            .filter(|(key, entry)| queued.contains(key) || self.is_live(entry, max_idle_frames))
+            .collect();
+        live.sort_by_key(|(_, entry)| std::cmp::Reverse(entry.texture.height()));
+        self.entries.retain(|_, entry| entry.is_none());
+        self.rows.clear();
+
+        // never more pages than before, what doesn't fit is rasterized again if queued
+        let max_pages = mem::replace(&mut self.max_pages, self.pages);
+        let pad = self.pad as u32;
+        let mut moves = vec![];
+        for (key, mut entry) in live {
+            let rect = self.padded(&entry);
+            let (to_page, to) = match self.allocate(rect.width(), rect.height()) {
+                Some(allocated) => allocated,
+                None => continue,
+            };
+            moves.push(GlyphMove {
+                page: entry.page,
+                rect,
+                to_page,
+                to: [to.min.x, to.min.y],
+            });
+            entry.page = to_page;
+            entry.texture = Rect {
+                min: point(to.min.x + pad, to.min.y + pad),
+                max: point(to.max.x - pad, to.max.y - pad),
+            };
+            self.entries.insert(key, Some(entry));
+        }
+        self.max_pages = max_pages;
+        moves
+    }
+
+    // rasterizes what was queued and isn't in the atlas yet, upload gets the page and
+    // region of every change and its pixels. returns how many glyphs were evicted.
+    // glyphs that don't fit even after evicting everything are left out and counted
+    // in take_dropped, so they're missing from the frame instead of failing it
+    pub(crate) fn cache_queued(
+        &mut self,
+        fonts: &[Arc<dyn GlyphBackend>],
+        mut upload: impl FnMut(u32, Rect<u32>, &[u8]),
+    ) -> usize {
+        let mut queue = mem::take(&mut self.queue);
+        let mut seen = HashSet::new();
+        queue.retain(|(key, _)| seen.insert(*key));
+        self.frame += 1;
+        for (key, _) in &queue {
+            if let Some(Some(entry)) = self.entries.get_mut(key) {
+                entry.used = self.frame;
+            }
+        }
+        #[cfg(feature = "gpu_raster")]
+        let evicted = self.place_gpu_finished(&mut upload);
+        #[cfg(not(feature = "gpu_raster"))]
+        let evicted = 0;
+        #[cfg(feature = "gpu")]
+        if self.rasterizer.is_some() {
+            return evicted + self.cache_finished(fonts, &queue, &mut upload);
+        }
+
+        let missing: Vec<_> = queue
+            .iter()
+            .filter(|(key, _)| !self.entries.contains_key(key) && !self.is_on_gpu(key))
+            .collect();
+        if self.place_chunked(fonts, &missing, &mut upload) {
+            return evicted;
+        }
+
+        // full, start over with only this frame's glyphs
+        let evicted = evicted + self.glyphs_resident();
+        self.entries.clear();
+        self.rows.clear();
+        let all: Vec<_> = queue
+            .iter()
+            .filter(|(key, _)| !self.is_on_gpu(key))
+            .collect();
+        if !self.place_chunked(fonts, &all, &mut upload) {
+            self.count_dropped(all.iter().map(|(key, _)| *key));
+        }
+        evicted
+    }
+
+    pub(crate) fn take_dropped(&mut self) -> usize {
+        mem::take(&mut self.dropped)
+    }
+
+    fn count_dropped(&mut self, keys: impl IntoIterator<Item = GlyphKey>) {
+        let dropped = keys
+            .into_iter()
+            .filter(|key| !self.entries.contains_key(key))
+            .count();
+        self.dropped += dropped;
+    }
+
+    // the gpu batches that are done, placed like the glyphs the workers finish. returns
+    // how many glyphs were evicted to make room for them
+    #[cfg(feature = "gpu_raster")]
+    fn place_gpu_finished(&mut self, upload: &mut impl FnMut(u32, Rect<u32>, &[u8])) -> usize {
+        let mut finished = vec![];
+        self.gpu_batches.retain(|(keys, batch)| match batch.take() {
+            Some(bitmaps) => {
+                finished.extend(keys.iter().copied().zip(bitmaps.into_iter().map(Some)));
+                false
+            }
+            None => true,
+        });
+        if finished.is_empty() || self.place(finished.clone(), upload) {
+            return 0;
+        }
+        let evicted = self.glyphs_resident();
+        self.entries.clear();
+        self.rows.clear();
+        let keys: Vec<_> = finished.iter().map(|(key, _)| *key).collect();
+        if !self.place(finished, upload) {
+            self.count_dropped(keys);
+        }
+        evicted
+    }
+
+    // submitted to the gpu in an earlier frame and not back yet
+    #[cfg(feature = "gpu_raster")]
+    fn is_on_gpu(&self, key: &GlyphKey) -> bool {
+        self.gpu_batches.iter().any(|(keys, _)| keys.contains(key))
+    }
+
+    #[cfg(not(feature = "gpu_raster"))]
+    fn is_on_gpu(&self, _key: &GlyphKey) -> bool {
+        false
+    }
+
+    // false as soon as the atlas is full, the chunks after that aren't rasterized
+    fn place_chunked(
+        &mut self,
+        fonts: &[Arc<dyn GlyphBackend>],
+        glyphs: &[&(GlyphKey, Vec<FontVariation>)],
+        upload: &mut impl FnMut(u32, Rect<u32>, &[u8]),
+    ) -> bool {
+        glyphs.chunks(RASTER_CHUNK).all(|chunk| {
+            let bitmaps = self.rasterize(fonts, chunk);
+            self.place(bitmaps, upload)
+        })
+    }
+
+    // the async path: places what the workers finished since the last frame and hands
+    // them the glyphs still missing
+    #[cfg(feature = "gpu")]
+    fn cache_finished(
+        &mut self,
+        fonts: &[Arc<dyn GlyphBackend>],
+        queue: &[(GlyphKey, Vec<FontVariation>)],
+        upload: &mut impl FnMut(u32, Rect<u32>, &[u8]),
+    ) -> usize {
+        let finished = self.rasterizer.as_mut().unwrap().take_finished();
+        let mut evicted = 0;
+        if !self.place(finished.clone(), upload) {
+            evicted = self.glyphs_resident();
+            self.entries.clear();
+            self.rows.clear();
+            let keys: Vec<_> = finished.iter().map(|(key, _)| *key).collect();
+            if !self.place(finished, upload) {
+                self.count_dropped(keys);
+            }
+        }
+
+        for (key, variations) in queue {
+            if !self.entries.contains_key(key) {
+                let (size, offset) = self.raster_size(key);
+                self.rasterizer.as_mut().unwrap().request(Job {
+                    key: *key,
+                    font: fonts[key.font].clone(),
+                    size,
+                    offset,
+                    variations: variations.clone(),
+                });
+            }
+        }
+        evicted
+    }
+
+    // the size and subpixel offset a key stands for
+    fn raster_size(&self, key: &GlyphKey) -> (f32, [f32; 2]) {
+        let size = key.size as f32 * self.scale_tolerance;
+        let offset = [
+            key.offset[0] as f32 * self.position_tolerance,
+            key.offset[1] as f32 * self.position_tolerance,
+        ];
+        (size, offset)
+    }
+
+    fn rasterize(
+        &mut self,
+        fonts: &[Arc<dyn GlyphBackend>],
+        glyphs: &[&(GlyphKey, Vec<FontVariation>)],
+    ) -> Vec<(GlyphKey, Option<GlyphBitmap>)> {
+        #[cfg(feature = "gpu_raster")]
+        if self.gpu.is_some() && glyphs.len() >= GPU_BATCH {
+            return self.rasterize_on_gpu(fonts, glyphs);
+        }
+        glyphs
+            .iter()
+            .map(|(key, variations)| {
+                let (size, offset) = self.raster_size(key);
+                let bitmap =
+                    fonts[key.font].rasterize(key.id, size, offset, variations, key.hinting);
+                (*key, bitmap)
+            })
+            .collect()
+    }
+
+    // hinted glyphs and backends without outlines still go through the cpu, the
+    // outlines are submitted and their glyphs stay missing until the gpu is done
+    #[cfg(feature = "gpu_raster")]
+    fn rasterize_on_gpu(
+        &mut self,
+        fonts: &[Arc<dyn GlyphBackend>],
+        glyphs: &[&(GlyphKey, Vec<FontVariation>)],
+    ) -> Vec<(GlyphKey, Option<GlyphBitmap>)> {
+        let mut bitmaps = vec![];
+        let mut outlined = vec![];
+        let mut outlines = vec![];
+        for (key, variations) in glyphs {
+            let (size, offset) = self.raster_size(key);
+            let font = &fonts[key.font];
+            let outline = match key.hinting {
+                Hinting::None => font.outline(key.id, size, offset, variations),
+                _ => None,
+            };
+            match outline {
+                Some(outline) => {
+                    outlined.push(*key);
+                    outlines.push(outline);
+                }
+                None => bitmaps.push((
+                    *key,
+                    font.rasterize(key.id, size, offset, variations, key.hinting),
+                )),
+            }
+        }
+        if !outlines.is_empty() {
+            let batch = self.gpu.as_ref().unwrap().rasterize(&outlines);
+            self.gpu_batches.push((outlined, batch));
+        }
+        bitmaps
+    }
+
+    // false when the atlas ran out of room
+    fn place(
+        &mut self,
+        mut bitmaps: Vec<(GlyphKey, Option<GlyphBitmap>)>,
+        upload: &mut impl FnMut(u32, Rect<u32>, &[u8]),
+    ) -> bool {
+        // tallest first packs the rows tighter
+        bitmaps.sort_by_key(|(_, bitmap)| {
+            std::cmp::Reverse(bitmap.as_ref().map_or(0, |b| b.bounds.height()))
+        });
+        let pad = self.pad as u32;
+        for (key, bitmap) in bitmaps {
+            let bitmap = match bitmap {
+                Some(bitmap) => bitmap,
+                None => {
+                    self.entries.insert(key, None);
+                    continue;
+                }
+            };
+            let width = bitmap.bounds.width() as u32;
+            let height = bitmap.bounds.height() as u32;
+            // bigger than a whole page, it's never drawn
+            if width + 2 * pad > self.width || height + 2 * pad > self.height {
+                self.entries.insert(key, None);
+                self.dropped += 1;
+                continue;
+            }
+            let (page, padded) = match self.allocate(width + 2 * pad, height + 2 * pad) {
+                Some(allocated) => allocated,
+                None => return false,
+            };
+
+            // the padding gets written too, it may hold an evicted glyph
+            let mut pixels = vec![0; (padded.width() * padded.height()) as usize];
+            for (y, row) in bitmap.coverage.chunks_exact(width as usize).enumerate() {
+                let start = (y as u32 + pad) * padded.width() + pad;
+                pixels[start as usize..start as usize + width as usize].copy_from_slice(row);
+            }
+            upload(page, padded, &pixels);
+
+            self.entries.insert(
+                key,
+                Some(Entry {
+                    page,
+                    texture: Rect {
+                        min: point(padded.min.x + pad, padded.min.y + pad),
+                        max: point(padded.max.x - pad, padded.max.y - pad),
+                    },
+                    bounds: bitmap.bounds.into(),
+                    used: self.frame,
+                }),
+            );
+        }
+        true
+    }
+
+    // the first row tall enough without wasting much, or a new one on the first page
+    // with room for it, or on a new page
+    fn allocate(&mut self, width: u32, height: u32) -> Option<(u32, Rect<u32>)> {
+        let fits = self.rows.iter().position(|row| {
+            row.height >= height
+                && row.height <= height + height / 4 + 1
+                && row.width + width <= self.width
+        });
+        let index = match fits {
+            Some(index) => index,
+            None => {
+                let bottom = |page: u32| {
+                    self.rows
+                        .iter()
+                        .filter(|row| row.page == page)
+                        .map(|row| row.top + row.height)
+                        .max()
+                        .unwrap_or(0)
+                };
+                let free = (0..self.pages)
+                    .map(|page| (page, bottom(page)))
+                    .find(|&(_, top)| top + height <= self.height);
+                let (page, top) = match free {
+                    Some(free) => free,
+                    None if self.pages < self.max_pages => {
+                        self.pages += 1;
+                        (self.pages - 1, 0)
+                    }
+                    None => return None,
+                };
+                self.rows.push(Row {
+                    page,
+                    top,
+                    height,
+                    width: 0,
+                });
+                self.rows.len() - 1
+            }
+        };
+        let row = &mut self.rows[index];
+        let rect = Rect {
+            min: point(row.width, row.top),
+            max: point(row.width + width, row.top + height),
+        };
+        row.width += width;
+        Some((row.page, rect))
+    }
+
+    // the glyphs in the atlas and where they are, followed by its pages' pixels. glyphs
+    // of variable font instances are left out, their keys hash with std's hasher
+    pub(crate) fn save(&self, fonts: &[Arc<dyn GlyphBackend>], pixels: &[u8]) -> Vec<u8> {
+        let mut out = Writer::default();
+        out.bytes.extend_from_slice(atlas_file::MAGIC);
+        out.u32(atlas_file::VERSION);
+        for value in [self.width, self.height, self.pages] {
+            out.u32(value);
+        }
+        out.f32(self.scale_tolerance);
+        out.f32(self.position_tolerance);
+        out.u8(self.pad as u8);
+        out.u32(fonts.len() as u32);
+        for font in fonts {
+            out.u64(atlas_file::font_hash(font.font_data()));
+        }
+
+        out.u32(self.rows.len() as u32);
+        for row in &self.rows {
+            for value in [row.page, row.top, row.height, row.width] {
+                out.u32(value);
+            }
+        }
+        let mut entries: Vec<_> = self
+            .entries
+            .iter()
+            .filter(|(key, _)| key.instance == 0)
+            .collect();
+        // in a fixed order, so the same atlas saves the same bytes
+        entries.sort_by_key(|(key, _)| (key.font, key.id, key.size, key.offset, key.hinting as u8));
+        out.u32(entries.len() as u32);
+        for (key, entry) in entries {
+            for value in [
+                key.font as u32,
+                key.id.0,
+                key.size,
+                key.offset[0],
+                key.offset[1],
+            ] {
+                out.u32(value);
+            }
+            out.u8(key.hinting as u8);
+            match entry {
+                None => out.u8(0),
+                Some(entry) => {
+                    out.u8(1);
+                    out.u32(entry.page);
+                    let texture = entry.texture;
+                    for value in [texture.min.x, texture.min.y, texture.max.x, texture.max.y] {
+                        out.u32(value);
+                    }
+                    let bounds = entry.bounds;
+                    for value in [bounds.min.x, bounds.min.y, bounds.max.x, bounds.max.y] {
+                        out.i32(value);
+                    }
+                }
+            }
+        }
+
+        let len = (self.width * self.height * self.pages) as usize;
+        out.bytes
+            .extend_from_slice(&pixels[..len.min(pixels.len())]);
+        out.bytes
+            .resize(out.bytes.len() + len.saturating_sub(pixels.len()), 0);
+        out.bytes
+    }
+
+    // replaces the atlas with a saved one and returns its pixels. None, with the atlas
+    // left as it was, for data that isn't a whole atlas saved with these fonts at the
+    // same ids, page size, max pages, quality and padding
+    pub(crate) fn load(&mut self, fonts: &[Arc<dyn GlyphBackend>], data: &[u8]) -> Option<Vec<u8>> {
+        let mut input = Reader::new(data);
+        if input.take(atlas_file::MAGIC.len())? != atlas_file::MAGIC
+            || input.u32()? != atlas_file::VERSION
+            || [input.u32()?, input.u32()?] != [self.width, self.height]
+        {
+            return None;
+        }
+        let pages = input.u32()?;
+        if pages == 0
+            || pages > self.max_pages
+            || input.f32()? != self.scale_tolerance
+            || input.f32()? != self.position_tolerance
+            || input.u8()? != self.pad as u8
+        {
+            return None;
+        }
+        let font_count = input.u32()? as usize;
+        if font_count > fonts.len() {
+            return None;
+        }
+        for font in &fonts[..font_count] {
+            if input.u64()? != atlas_file::font_hash(font.font_data()) {
+                return None;
+            }
+        }
+
+        let mut rows = vec![];
+        for _ in 0..input.u32()? {
+            let [page, top, height, width] =
+                [input.u32()?, input.u32()?, input.u32()?, input.u32()?];
+            if page >= pages || top.saturating_add(height) > self.height || width > self.width {
+                return None;
+            }
+            rows.push(Row {
+                page,
+                top,
+                height,
+                width,
+            });
+        }
+        let mut entries = HashMap::new();
+        for _ in 0..input.u32()? {
+            let [font, id, size, x, y] = [
+                input.u32()?,
+                input.u32()?,
+                input.u32()?,
+                input.u32()?,
+                input.u32()?,
+            ];
+            let hinting = match input.u8()? {
+                0 => Hinting::None,
+                1 => Hinting::Slight,
+                2 => Hinting::Full,
+                _ => return None,
+            };
+            if font as usize >= font_count {
+                return None;
+            }
+            let key = GlyphKey {
+                font: font as FontId,
+                id: GlyphId(id),
+                size,
+                offset: [x, y],
+                instance: 0,
+                hinting,
+            };
+            let entry = match input.u8()? {
+                0 => None,
+                1 => {
+                    let page = input.u32()?;
+                    let [x0, y0, x1, y1] = [input.u32()?, input.u32()?, input.u32()?, input.u32()?];
+                    let bounds = [input.i32()?, input.i32()?, input.i32()?, input.i32()?];
+                    if page >= pages || x0 > x1 || y0 > y1 || x1 > self.width || y1 > self.height {
+                        return None;
+                    }
+                    Some(Entry {
+                        page,
+                        texture: Rect {
+                            min: point(x0, y0),
+                            max: point(x1, y1),
+                        },
+                        bounds: Rect {
+                            min: point(bounds[0], bounds[1]),
+                            max: point(bounds[2], bounds[3]),
+                        },
+                        used: self.frame,
+                    })
+                }
+                _ => return None,
+            };
+            entries.insert(key, entry);
+        }
+
+        let pixels = input.take((self.width * self.height * pages) as usize)?;
+        if !input.is_empty() {
+            return None;
+        }
+        self.pages = pages;
+        self.rows = rows;
+        self.entries = entries;
+        Some(pixels.to_vec())
+    }
+
+    // atlas uvs, page and screen pixels of a cached glyph, None for glyphs without ink
+    // or that weren't queued
+    pub(crate) fn rect_for(
+        &self,
+        font: FontId,
+        glyph: &Glyph,
+    ) -> Option<(Rect<f32>, u32, Rect<i32>)> {
+        let entry = (*self.entries.get(&self.key(font, glyph))?)?;
+        let uv = Rect {
+            min: point(
+                entry.texture.min.x as f32 / self.width as f32,
+                entry.texture.min.y as f32 / self.height as f32,
+            ),
+            max: point(
+                entry.texture.max.x as f32 / self.width as f32,
+                entry.texture.max.y as f32 / self.height as f32,
+            ),
+        };
+        let x = glyph.position.x.floor() as i32;
+        let y = glyph.position.y.floor() as i32;
+        let screen = Rect {
+            min: point(entry.bounds.min.x + x, entry.bounds.min.y + y),
+            max: point(entry.bounds.max.x + x, entry.bounds.max.y + y),
+        };
+        Some((uv, entry.page, screen))
+    }
+}
@@ -0,0 +1,117 @@
+// how the [f32; 4] colors handed to DrawText are meant. they are converted to what the
+// target's format expects before blending, so the same color looks the same on unorm
+// and srgb swapchains
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ColorSpace {
+    // gamma encoded, like design tools, css and color pickers give them
+    #[default]
+    Srgb,
+    // linear light, e.g. colors from a renderer that works in linear space
+    Linear,
+}
+
+impl ColorSpace {
+    // alpha stays as it is in both
+    pub fn convert(self, [r, g, b, a]: [f32; 4], to: ColorSpace) -> [f32; 4] {
+        let channel = match (self, to) {
+            (ColorSpace::Srgb, ColorSpace::Linear) => srgb_to_linear,
+            (ColorSpace::Linear, ColorSpace::Srgb) => linear_to_srgb,
+            _ => return [r, g, b, a],
+        };
+        [channel(r), channel(g), channel(b), a]
+    }
+
+    // what the shader writes for color. srgb and float formats take linear values and
+    // encode them themselves, unorm ones store what they get and are shown as srgb
+    #[cfg(feature = "gpu")]
+    pub(crate) fn output(self, color: [f32; 4], output: ColorOutput) -> [f32; 4] {
+        let target = if output.linear {
+            ColorSpace::Linear
+        } else {
+            ColorSpace::Srgb
+        };
+        let [r, g, b, a] = self.convert(color, target);
+        if output.premultiplied {
+            [r * a, g * a, b * a, a]
+        } else {
+            [r, g, b, a]
+        }
+    }
+}
+
+// how text is blended over what's already in the target
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum AlphaMode {
+    // colors as given, weighted by their alpha when blending
+    #[default]
+    Straight,
+    // colors multiplied by their alpha up front and blended with one, one minus source
+    // alpha, like most engines composite. no dark fringes around bright text on dark
+    // backgrounds
+    Premultiplied,
+}
+
+// what vertex colors are turned into for the target they are drawn into
+#[cfg(feature = "gpu")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ColorOutput {
+    // the target's format encodes the shader's values, see output
+    pub(crate) linear: bool,
+    pub(crate) premultiplied: bool,
+}
+
+fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(c: f32) -> f32 {
+    if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+// a color that knows its color space, DrawText::color turns it into the [f32; 4] its
+// functions take
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Color {
+    pub rgba: [f32; 4],
+    pub space: ColorSpace,
+}
+
+impl Color {
+    pub fn srgb(r: f32, g: f32, b: f32, a: f32) -> Color {
+        Color {
+            rgba: [r, g, b, a],
+            space: ColorSpace::Srgb,
+        }
+    }
+
+    // 0 to 255 per channel, as in a design spec
+    pub fn srgb8(r: u8, g: u8, b: u8, a: u8) -> Color {
+        let [r, g, b, a] = [r, g, b, a].map(|c| c as f32 / 255.0);
+        Color::srgb(r, g, b, a)
+    }
+
+    // 0xRRGGBBAA
+    pub fn hex(rgba: u32) -> Color {
+        let [r, g, b, a] = rgba.to_be_bytes();
+        Color::srgb8(r, g, b, a)
+    }
+
+    pub fn linear(r: f32, g: f32, b: f32, a: f32) -> Color {
+        Color {
+            rgba: [r, g, b, a],
+            space: ColorSpace::Linear,
+        }
+    }
+
+    pub fn to(self, space: ColorSpace) -> [f32; 4] {
+        self.space.convert(self.rgba, space)
+    }
+}
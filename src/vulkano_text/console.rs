@@ -0,0 +1,277 @@
+use super::drawtext::TextRendererCore;
+use super::edit::TextEditController;
+use super::input::{InputEvent, Key};
+use super::style::{Overflow, TextStyle};
+
+use rusttype::{point, Rect};
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Severity {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl Severity {
+    pub fn color(self) -> [f32; 4] {
+        match self {
+            Severity::Trace => [0.5, 0.5, 0.5, 1.0],
+            Severity::Debug => [0.6, 0.7, 0.9, 1.0],
+            Severity::Info => [0.9, 0.9, 0.9, 1.0],
+            Severity::Warn => [0.95, 0.8, 0.2, 1.0],
+            Severity::Error => [0.95, 0.3, 0.3, 1.0],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConsoleLine {
+    pub severity: Severity,
+    pub text: String,
+}
+
+// the console's lines, shared with whatever writes into them from other threads. the
+// oldest line goes when a new one doesn't fit anymore
+#[derive(Debug, Clone)]
+pub struct ConsoleLog {
+    lines: Arc<Mutex<VecDeque<ConsoleLine>>>,
+    capacity: usize,
+}
+
+impl ConsoleLog {
+    pub fn new(capacity: usize) -> ConsoleLog {
+        ConsoleLog {
+            lines: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+            capacity: capacity.max(1),
+        }
+    }
+
+    // every line of text becomes a line of its own
+    pub fn push(&self, severity: Severity, text: &str) {
+        let mut lines = self.lines.lock().unwrap();
+        for line in text.lines() {
+            if lines.len() == self.capacity {
+                lines.pop_front();
+            }
+            lines.push_back(ConsoleLine {
+                severity,
+                text: line.to_owned(),
+            });
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.lines.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear(&self) {
+        self.lines.lock().unwrap().clear();
+    }
+
+    // makes log::info! and friends show up in the console, up to level
+    #[cfg(feature = "log")]
+    pub fn install(self, level: log::LevelFilter) -> Result<(), log::SetLoggerError> {
+        log::set_boxed_logger(Box::new(self))?;
+        log::set_max_level(level);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "log")]
+impl From<log::Level> for Severity {
+    fn from(level: log::Level) -> Severity {
+        match level {
+            log::Level::Trace => Severity::Trace,
+            log::Level::Debug => Severity::Debug,
+            log::Level::Info => Severity::Info,
+            log::Level::Warn => Severity::Warn,
+            log::Level::Error => Severity::Error,
+        }
+    }
+}
+
+#[cfg(feature = "log")]
+impl log::Log for ConsoleLog {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.enabled(record.metadata()) {
+            self.push(record.level().into(), &record.args().to_string());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+// a drop-down console across the top of the screen: the log with the newest line at the
+// bottom, scrollback, and an input line. it only keeps its state, drawing goes through
+// DrawText like any other text
+pub struct Console {
+    log: ConsoleLog,
+    input: TextEditController,
+    open: bool,
+    // lines scrolled back from the newest one
+    scroll: usize,
+    pub style: TextStyle,
+    pub height: f32,
+    pub background: [f32; 4],
+    // just below DebugOverlay by default
+    pub layer: i32,
+    // in logical pixels, needed for headless renderers. the target's size otherwise
+    pub screen_size: Option<[f32; 2]>,
+}
+
+// between the console's edges and its text
+const PADDING: f32 = 6.0;
+const PROMPT: &str = "> ";
+// lines per mouse wheel notch and page
+const WHEEL_LINES: usize = 3;
+const PAGE_LINES: usize = 10;
+
+impl Console {
+    pub fn new(capacity: usize) -> Console {
+        Console {
+            log: ConsoleLog::new(capacity),
+            input: TextEditController::default(),
+            open: false,
+            scroll: 0,
+            style: TextStyle::new(14.0, [0.9, 0.9, 0.9, 1.0]),
+            height: 300.0,
+            background: [0.05, 0.05, 0.08, 0.85],
+            layer: i32::MAX - 1,
+            screen_size: None,
+        }
+    }
+
+    // a handle on the lines, for pushing from other threads or installing as a logger
+    pub fn log(&self) -> &ConsoleLog {
+        &self.log
+    }
+
+    pub fn push(&self, severity: Severity, text: &str) {
+        self.log.push(severity, text);
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn set_open(&mut self, open: bool) {
+        self.open = open;
+    }
+
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+    }
+
+    pub fn scroll_up(&mut self, lines: usize) {
+        self.scroll = (self.scroll + lines).min(self.log.len().saturating_sub(1));
+    }
+
+    pub fn scroll_down(&mut self, lines: usize) {
+        self.scroll = self.scroll.saturating_sub(lines);
+    }
+
+    pub fn scroll_to_bottom(&mut self) {
+        self.scroll = 0;
+    }
+
+    // returns the input line when enter submits it. scrolls with the mouse wheel and
+    // page up and down, everything else goes to the input line. a closed console
+    // ignores events, opening and closing it is up to the application
+    pub fn handle_event(&mut self, event: &InputEvent) -> Option<String> {
+        if !self.open {
+            return None;
+        }
+        match event {
+            InputEvent::KeyPressed(key) if self.input.preedit().is_empty() => match key {
+                Key::Enter => {
+                    let command = self.input.text().to_owned();
+                    self.input.set_text("");
+                    self.scroll_to_bottom();
+                    return Some(command);
+                }
+                Key::PageUp => self.scroll_up(PAGE_LINES),
+                Key::PageDown => self.scroll_down(PAGE_LINES),
+                _ => {
+                    self.input.handle_event(event);
+                }
+            },
+            InputEvent::Scroll(lines) => {
+                let notches = lines.abs().ceil() as usize;
+                if *lines > 0.0 {
+                    self.scroll_up(notches * WHEEL_LINES);
+                } else {
+                    self.scroll_down(notches * WHEEL_LINES);
+                }
+            }
+            _ => {
+                self.input.handle_event(event);
+            }
+        }
+        None
+    }
+
+    // along the top of the screen whatever the coordinate origin
+    pub fn queue(&self, draw_text: &mut TextRendererCore) {
+        if self.open {
+            draw_text.on_screen(|draw_text| self.queue_open(draw_text));
+        }
+    }
+
+    fn queue_open(&self, draw_text: &mut TextRendererCore) {
+        let width = match self.screen_size.or_else(|| draw_text.screen_size()) {
+            Some([width, _]) => width,
+            None => return,
+        };
+
+        let panel = Rect {
+            min: point(0.0, 0.0),
+            max: point(width, self.height),
+        };
+        draw_text.queue_rects(vec![panel], self.background, true, self.layer);
+
+        // rows from the bottom up, the input line first, every line clipped at the
+        // right edge
+        let layout = draw_text.layout_text(&self.style, PROMPT);
+        let line_height = layout.line_height();
+        let rows = ((self.height - 2.0 * PADDING) / line_height).floor() as usize;
+        let bottom = self.height - PADDING - line_height - layout.line_top(0);
+        let baseline = |row: usize| bottom - row as f32 * line_height;
+        let style = self
+            .style
+            .clone()
+            .with_layer(self.layer)
+            .with_max_width(width - 2.0 * PADDING, Overflow::Clip);
+        if rows == 0 {
+            return;
+        }
+
+        draw_text.queue_text_styled(PADDING, baseline(0), &style, PROMPT);
+        let prompt_width = layout.width();
+        let input_style = style
+            .clone()
+            .with_max_width(width - 2.0 * PADDING - prompt_width, Overflow::Clip);
+        self.input
+            .queue(draw_text, PADDING + prompt_width, baseline(0), &input_style);
+
+        let lines = self.log.lines.lock().unwrap();
+        let shown = lines.iter().rev().skip(self.scroll).take(rows - 1);
+        for (row, line) in shown.enumerate() {
+            let mut style = style.clone();
+            style.color = line.severity.color();
+            draw_text.queue_text_styled(PADDING, baseline(row + 1), &style, &line.text);
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use super::drawtext::TextRendererCore;
+use super::style::TextStyle;
+
+use rusttype::{point, Rect};
+
+use std::collections::VecDeque;
+use std::time::Instant;
+
+// frames kept for the average and the graph
+const HISTORY: usize = 120;
+// frame times at 60 and 30 fps, in milliseconds
+const GOOD_FRAME: f32 = 1000.0 / 60.0;
+const SLOW_FRAME: f32 = 1000.0 / 30.0;
+
+const GREEN: [f32; 4] = [0.3, 0.9, 0.3, 1.0];
+const YELLOW: [f32; 4] = [0.9, 0.8, 0.2, 1.0];
+const RED: [f32; 4] = [0.9, 0.2, 0.2, 1.0];
+
+// fps counter and frame time graph, drawn through DrawText like any other text. call
+// frame_start when a frame begins and frame_end, which queues the overlay, before the
+// text gets drawn
+pub struct DebugOverlay {
+    // when the current frame began
+    started: Option<Instant>,
+    // milliseconds between frame starts, oldest first
+    intervals: VecDeque<f32>,
+    // milliseconds from frame_start to frame_end, oldest first
+    cpu_times: VecDeque<f32>,
+    // top left corner
+    pub position: [f32; 2],
+    pub size: f32,
+    // above everything else by default, set_layer_targets keeps it out of captures
+    pub layer: i32,
+    // glyph atlas usage and uploads from DrawText::cache_stats
+    pub memory_stats: bool,
+}
+
+impl Default for DebugOverlay {
+    fn default() -> DebugOverlay {
+        DebugOverlay {
+            started: None,
+            intervals: VecDeque::with_capacity(HISTORY),
+            cpu_times: VecDeque::with_capacity(HISTORY),
+            position: [10.0, 10.0],
+            size: 14.0,
+            layer: i32::MAX,
+            memory_stats: false,
+        }
+    }
+}
+
+fn push_sample(samples: &mut VecDeque<f32>, value: f32) {
+    if samples.len() == HISTORY {
+        samples.pop_front();
+    }
+    samples.push_back(value);
+}
+
+fn average(samples: &VecDeque<f32>) -> f32 {
+    samples.iter().sum::<f32>() / samples.len().max(1) as f32
+}
+
+impl DebugOverlay {
+    pub fn new() -> DebugOverlay {
+        DebugOverlay::default()
+    }
+
+    pub fn with_memory_stats(mut self, memory_stats: bool) -> DebugOverlay {
+        self.memory_stats = memory_stats;
+        self
+    }
+
+    pub fn frame_start(&mut self) {
+        let now = Instant::now();
+        if let Some(started) = self.started {
+            push_sample(&mut self.intervals, (now - started).as_secs_f32() * 1000.0);
+        }
+        self.started = Some(now);
+    }
+
+    pub fn frame_end(&mut self, draw_text: &mut TextRendererCore) {
+        if let Some(started) = self.started {
+            push_sample(
+                &mut self.cpu_times,
+                started.elapsed().as_secs_f32() * 1000.0,
+            );
+        }
+        self.queue(draw_text);
+    }
+
+    // averaged over the last frames
+    pub fn fps(&self) -> f32 {
+        match average(&self.intervals) {
+            interval if interval > 0.0 => 1000.0 / interval,
+            _ => 0.0,
+        }
+    }
+
+    fn queue(&self, draw_text: &mut TextRendererCore) {
+        draw_text.from_origin(self.position[1], |draw_text| self.queue_at(draw_text));
+    }
+
+    fn queue_at(&self, draw_text: &mut TextRendererCore) {
+        let [x, y] = self.position;
+        let line_height = self.size * 1.25;
+        let style = TextStyle::new(self.size, [1.0, 1.0, 1.0, 1.0])
+            .with_shadow([1.0, 1.0], [0.0, 0.0, 0.0, 0.8])
+            .with_layer(self.layer);
+        let mut lines = vec![format!(
+            "{:.0} fps  {:.2} ms frame  {:.2} ms cpu",
+            self.fps(),
+            average(&self.intervals),
+            average(&self.cpu_times)
+        )];
+        if self.memory_stats {
+            let stats = draw_text.cache_stats();
+            lines.push(format!(
+                "atlas {} pages {:.0}% used, {} glyphs, {} evicted",
+                stats.pages, stats.used_area_pct, stats.glyphs_resident, stats.evictions_last_frame
+            ));
+            lines.push(format!(
+                "{} KiB uploaded",
+                stats.uploads_bytes_last_frame / 1024
+            ));
+        }
+        for (i, line) in lines.iter().enumerate() {
+            let baseline = y + self.size + i as f32 * line_height;
+            draw_text.queue_text_styled(x, baseline, &style, line);
+        }
+
+        // a bar per frame, as tall as the time between frame starts with 15 fps filling
+        // the graph, and a line at 60 fps
+        let bar_width = 2.0;
+        let height = self.size * 3.0;
+        let top = y + lines.len() as f32 * line_height + self.size * 0.5;
+        let bottom = top + height;
+        let graph = Rect {
+            min: point(x, top),
+            max: point(x + HISTORY as f32 * bar_width, bottom),
+        };
+        draw_text.queue_rects(vec![graph], [0.0, 0.0, 0.0, 0.6], false, self.layer);
+        let mut bars = [(GREEN, vec![]), (YELLOW, vec![]), (RED, vec![])];
+        for (i, &interval) in self.intervals.iter().enumerate() {
+            let bar_height = (interval / (2.0 * SLOW_FRAME)).min(1.0) * height;
+            let left = x + i as f32 * bar_width;
+            let bar = Rect {
+                min: point(left, bottom - bar_height),
+                max: point(left + bar_width, bottom),
+            };
+            let speed = if interval <= GOOD_FRAME {
+                0
+            } else if interval <= SLOW_FRAME {
+                1
+            } else {
+                2
+            };
+            bars[speed].1.push(bar);
+        }
+        for (color, rects) in bars {
+            if !rects.is_empty() {
+                draw_text.queue_rects(rects, color, false, self.layer);
+            }
+        }
+        let target = bottom - GOOD_FRAME / (2.0 * SLOW_FRAME) * height;
+        let target_line = Rect {
+            min: point(graph.min.x, target),
+            max: point(graph.max.x, target + 1.0),
+        };
+        draw_text.queue_rects(vec![target_line], [1.0, 1.0, 1.0, 0.5], false, self.layer);
+    }
+}
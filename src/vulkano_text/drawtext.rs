@@ -1,40 +1,192 @@
-use crate::*;
+use super::anchor::{Anchor, CoordinateOrigin, Offset, TextAnchor};
+use super::ansi;
+use super::backend::{default_backend, FontError, GlyphBackend, GlyphId, PathSegment};
+use super::cache::{
+    self, AtlasDefragment, CacheStats, Glyph, GlyphCache, GlyphMove, CACHE_HEIGHT, CACHE_WIDTH,
+};
+use super::color::{AlphaMode, Color, ColorOutput, ColorSpace};
+use super::effect::{EffectError, EffectParams, TextEffectId};
+use super::frames::{
+    upload_vertices, FrameSlot, MemoryBudget, MemoryStats, OverBudget, OverBudgetError,
+    TargetFrames, UploadedVertices, VertexMemory,
+};
+use super::gpu::compat::{
+    self, Filter, Image, ImageView, Sampler, SamplerAddressMode, SamplerCreateInfo,
+    SamplerMipmapMode, SecondaryCommandBuffer,
+};
+use super::gpu_backend::{AtlasPixels, DirtyRect, TextGpuBackend};
+#[cfg(feature = "gpu_raster")]
+use super::gpu_raster::GpuRasterizer;
+use super::indirect::{draw_culled, indirect_pipeline, GpuCulling};
+use super::inline::{self, InlineObject};
+use super::layout::{TextLayout, OBJECT_CHAR};
+use super::layout_cache::{self, LayoutCache};
+use super::layouter::TextLayouter;
+use super::markdown::{self, MarkdownLink};
+use super::mesh::{self, Tessellation};
+use super::missing::MissingGlyphPolicy;
+use super::msdf::MsdfFont;
+use super::msdf_atlas::{DistanceFieldFont, MsdfAtlas, DISTANCE_RANGE};
+use super::offscreen::{copy_image_to_host, submit_and_wait};
+use super::partial::{changed_bounds, visible_bounds, PartialRedraw};
+use super::quads::{rotate, GlyphQuad, Vertex, NO_FADE};
+use super::quality::Quality;
+use super::retained::{LayoutKey, Placement, RetainedText, RetainedTextId};
+#[cfg(feature = "normalization")]
+use super::sanitize;
+use super::sanitize::ControlChars;
+#[cfg(feature = "screenshot")]
+use super::screenshot::Screenshot;
+use super::script::{FontId, Script};
+#[cfg(feature = "shader_reload")]
+use super::shader_reload;
+#[cfg(feature = "shader_reload")]
+pub(crate) use super::shader_reload::ShaderReload;
+use super::style::{
+    Decorations, GlyphRendering, Gradient, Overflow, Palette, ProgressStyle, TextStyle, TinyText,
+};
+use super::target::{
+    CompositionMode, LayerTargets, NoImagesError, TargetId, TargetKind, TextTarget,
+};
+#[cfg(feature = "gpu_timing")]
+use super::timing::GpuTimer;
+use super::units::{scale_factor_for_dpi, Units};
+use super::variations::{NamedInstance, VariationAxis};
 
-use rusttype::gpu_cache::Cache;
-use rusttype::{point, Font, PositionedGlyph, Rect, Scale};
+use rusttype::{point, Point, Rect};
 
-use vulkano::buffer::{BufferUsage, CpuAccessibleBuffer, CpuBufferPool, TypedBufferAccess};
+use vulkano::command_buffer::allocator::StandardCommandBufferAllocator;
 use vulkano::command_buffer::{
-    AutoCommandBufferBuilder, CopyBufferToImageInfo, PrimaryAutoCommandBuffer, RenderPassBeginInfo,
-    SubpassContents,
+    AutoCommandBufferBuilder, ClearColorImageInfo, CommandBufferInheritanceInfo,
+    CommandBufferUsage, PrimaryAutoCommandBuffer,
 };
 use vulkano::descriptor_set::allocator::StandardDescriptorSetAllocator;
 use vulkano::descriptor_set::{PersistentDescriptorSet, WriteDescriptorSet};
-use vulkano::device::{Device, Queue};
-use vulkano::format::{ClearValue, Format};
-use vulkano::image::view::ImageView;
-use vulkano::image::ImageAccess;
-use vulkano::image::{
-    ImageCreateFlags, ImageDimensions, ImageLayout, ImageUsage, ImmutableImage, SwapchainImage,
-};
-use vulkano::pipeline::{GraphicsPipeline, Pipeline, PipelineBindPoint};
-use vulkano::render_pass::{Framebuffer, Subpass};
-use vulkano::sampler::{Filter, Sampler, SamplerAddressMode, SamplerCreateInfo, SamplerMipmapMode};
-use vulkano::swapchain::{self, AcquireError, Swapchain};
+use vulkano::device::{Device, DeviceOwned, Queue};
+use vulkano::format::{ClearColorValue, Format};
+use vulkano::memory::allocator::StandardMemoryAllocator;
+use vulkano::pipeline::graphics::color_blend::AttachmentBlend;
+use vulkano::pipeline::{GraphicsPipeline, Pipeline};
+use vulkano::render_pass::Subpass;
+use vulkano::shader::ShaderModule;
 
 use bytemuck::{Pod, Zeroable};
 
-use std::iter;
-use std::sync::Arc;
+use std::borrow::Cow;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Debug;
+use std::hash::{Hash, Hasher};
+use std::mem;
+use std::ops::{Deref, DerefMut, Range, RangeInclusive};
+#[cfg(any(feature = "screenshot", feature = "shader_reload"))]
+use std::path::PathBuf;
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant};
+
+// vertices of a text with a fill texture, drawn with the textured pipeline variant
+pub(crate) struct TexturedRange {
+    pub(crate) vertices: Range<u32>,
+    pub(crate) fill: RangeFill,
+}
+
+#[derive(Clone)]
+pub(crate) enum RangeFill {
+    Texture(Arc<ImageView>),
+    // the atlas and distance range of an msdf font, drawn with the msdf pipeline variant
+    Msdf(Arc<ImageView>, f32),
+    // drawn with the effect's pipeline variant, see queue_text_with_effect
+    Effect(TextEffectId, EffectParams),
+}
+
+impl RangeFill {
+    // ranges that follow each other are drawn at once when this holds
+    fn same(&self, other: &RangeFill) -> bool {
+        match (self, other) {
+            (RangeFill::Texture(a), RangeFill::Texture(b)) => Arc::ptr_eq(a, b),
+            (RangeFill::Msdf(a, x), RangeFill::Msdf(b, y)) => Arc::ptr_eq(a, b) && x == y,
+            (RangeFill::Effect(a, x), RangeFill::Effect(b, y)) => a == b && x == y,
+            _ => false,
+        }
+    }
+}
+
+pub(crate) struct TextVertices {
+    pub(crate) vertices: Vec<Vertex>,
+    // in drawing order
+    pub(crate) textured: Vec<TexturedRange>,
+}
+
+// the fill quads of a text with a fill texture, by index into its layer's fill pass
+struct TexturedRun {
+    instances: Range<usize>,
+    fill: RangeFill,
+    ink: Rect<f32>,
+}
+
+// an inline object's quad, drawn right after its pass of the layer with the texture
+// region the object picked. glyphs of msdf fonts are drawn the same way
+struct ObjectQuad {
+    pass: Pass,
+    instance: GlyphInstance,
+    texture: Arc<ImageView>,
+    uv_rect: [f32; 4],
+    msdf: Option<f32>,
+}
 
+// a whole glyph quad, corners already in NDC. text_vertices turns it into the two
+// triangles of the vertex buffer, the gpu-driven path stores it as is, see Glyph in
+// cull.glsl
 #[repr(C)]
 #[derive(Default, Debug, Clone, Copy, Zeroable, Pod)]
-struct Vertex {
-    position: [f32; 2],
-    tex_position: [f32; 2],
-    color: [f32; 4],
+pub(crate) struct GlyphInstance {
+    // bottom left, top left, top right, bottom right
+    corners: [[f32; 2]; 4],
+    uv_rect: [f32; 4],
+    // one per corner for gradients
+    colors: [[f32; 4]; 4],
+    fade: [f32; 3],
+    page: f32,
+}
+
+impl GlyphInstance {
+    fn vertices(&self) -> [Vertex; 6] {
+        let [min_x, min_y, max_x, max_y] = self.uv_rect;
+        let uvs = [
+            [min_x, max_y],
+            [min_x, min_y],
+            [max_x, min_y],
+            [max_x, max_y],
+        ];
+        // bottom left, top left, top right, top right, bottom right, bottom left
+        [0, 1, 2, 2, 3, 0].map(|corner| Vertex {
+            position: self.corners[corner],
+            tex_position: [uvs[corner][0], uvs[corner][1], self.page],
+            color: self.colors[corner],
+            fade: self.fade,
+            fill_uv: [0.0, 0.0],
+        })
+    }
+
+    fn quad(&self, screen: [f32; 2]) -> GlyphQuad {
+        let [min_x, min_y, max_x, max_y] = self.uv_rect;
+        GlyphQuad {
+            corners: self
+                .corners
+                .map(|[x, y]| [(x + 1.0) / 2.0 * screen[0], (y + 1.0) / 2.0 * screen[1]]),
+            uvs: [
+                [min_x, max_y],
+                [min_x, min_y],
+                [max_x, min_y],
+                [max_x, max_y],
+            ],
+            page: self.page as u32,
+            colors: self.colors,
+            fade: self.fade,
+            solid: min_x == SOLID_UV,
+        }
+    }
 }
-vulkano::impl_vertex!(Vertex, position, tex_position, color);
 
 mod vs {
     vulkano_shaders::shader! {
@@ -43,345 +195,4286 @@ mod vs {
     }
 }
 
-mod fs {
+pub(crate) mod fs {
     vulkano_shaders::shader! {
         ty: "fragment",
         path: "src/shaders/fragment.glsl",
     }
 }
 
-struct TextData {
-    glyphs: Vec<PositionedGlyph<'static>>,
-    color: [f32; 4],
+mod textured_fs {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        path: "src/shaders/textured_fragment.glsl",
+    }
 }
 
-pub struct DrawText {
+mod msdf_fs {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        path: "src/shaders/msdf_fragment.glsl",
+    }
+}
+
+// what a text is to a screen reader, see AccessKitAdapter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TextRole {
+    #[default]
+    Text,
+    Heading,
+    Input,
+}
+
+#[derive(Clone)]
+pub(crate) struct TextData {
+    pub(crate) glyphs: Vec<(FontId, Glyph)>,
+    // same order as glyphs, sideways glyphs of vertical text
+    pub(crate) rotated: Vec<bool>,
+    pub(crate) style: TextStyle,
+    // underline, strikethrough and overline in pixels
+    pub(crate) lines: Vec<Rect<f32>>,
+    // padded line boxes behind the glyphs
+    pub(crate) background: Vec<Rect<f32>>,
+    // where the whole atlas is shown, see queue_atlas_overlay
+    pub(crate) atlas: Option<Rect<f32>>,
+    // see Vertex
+    pub(crate) fade: [f32; 3],
+    // covers position, style and string
+    pub(crate) hash: u64,
+    // hash before anchors moved or a palette recolored the text when it was drawn,
+    // content_hash compares it so the same queue next frame hashes the same
+    pub(crate) queued_hash: Option<u64>,
+    // around glyphs and decorations as laid out, before culling, for gradients
+    pub(crate) ink: Option<Rect<f32>>,
+    // palette style of queue_text_themed, and the palette_version its colors are from
+    pub(crate) theme: Option<(String, u64)>,
+    // sampled inside the glyphs, see queue_text_textured
+    pub(crate) texture: Option<Arc<ImageView>>,
+    // see queue_text_with_effect, drawn in place of the texture
+    pub(crate) effect: Option<(TextEffectId, EffectParams)>,
+    // in pixels, see queue_text_inline
+    pub(crate) objects: Vec<(Rect<f32>, InlineObject)>,
+    // drawn under the glyphs of the layer like backgrounds, for panels
+    pub(crate) objects_behind: bool,
+    // see queue_text_anchored
+    pub(crate) anchor: Option<TextAnchor>,
+    // queued by queue_retained, see add_retained
+    pub(crate) retained: bool,
+    // the string as queued, kept once there are screen readers to tell
+    pub(crate) label: Option<(String, TextRole)>,
+}
+
+impl TextData {
+    // pixels touched when drawn, shadow, outline and faux styles included
+    pub(crate) fn bounds(&self) -> Option<Rect<f32>> {
+        let bounds = self.ink_bounds().map(|bounds| self.with_effects(bounds));
+        // the background gets neither shadow nor outline, and is there even for
+        // text without any ink like a run of spaces
+        self.background
+            .iter()
+            .copied()
+            .chain(bounds)
+            .chain(self.atlas)
+            .chain(self.objects.iter().map(|(rect, _)| *rect))
+            .reduce(union)
+    }
+
+    // glyphs and decorations without shadow and outline
+    fn ink_bounds(&self) -> Option<Rect<f32>> {
+        let glyphs = self
+            .glyphs
+            .iter()
+            .zip(&self.rotated)
+            .filter_map(|((_, g), &rotated)| self.glyph_rect(g, rotated));
+        self.lines.iter().copied().chain(glyphs).reduce(union)
+    }
+
+    // the glyph's pixels with faux italic and bold, None for glyphs without ink
+    fn glyph_rect(&self, g: &Glyph, rotated: bool) -> Option<Rect<f32>> {
+        let bb = g.bounds?;
+        let skew = self.style.skew();
+        let baseline = g.position.y;
+        let top = skew * (baseline - bb.min.y as f32);
+        let bottom = skew * (baseline - bb.max.y as f32);
+        let bold = self.style.emboldening();
+        let glyph = Rect {
+            min: point(bb.min.x as f32 + top.min(bottom), bb.min.y as f32),
+            max: point(bb.max.x as f32 + top.max(bottom) + bold, bb.max.y as f32),
+        };
+        Some(if rotated {
+            rotate_rect(glyph, g.position)
+        } else {
+            glyph
+        })
+    }
+
+    // grown by the outline and shifted out by the shadow
+    fn with_effects(&self, mut rect: Rect<f32>) -> Rect<f32> {
+        let stroke = self.style.outline.map_or(0.0, |outline| outline.width);
+        rect.min = point(rect.min.x - stroke, rect.min.y - stroke);
+        rect.max = point(rect.max.x + stroke, rect.max.y + stroke);
+        if let Some(shadow) = self.style.shadow {
+            let [dx, dy] = shadow.offset;
+            rect.min = point(rect.min.x + dx.min(0.0), rect.min.y + dy.min(0.0));
+            rect.max = point(rect.max.x + dx.max(0.0), rect.max.y + dy.max(0.0));
+        }
+        rect
+    }
+
+    // tessellated texts draw their glyphs without the atlas
+    fn in_atlas(&self) -> bool {
+        self.style.rendering == GlyphRendering::Atlas
+    }
+
+    // whether anything of the glyph, shadow and outline included, lands in rect
+    fn glyph_visible(&self, g: &Glyph, rotated: bool, rect: &Rect<f32>) -> bool {
+        self.glyph_rect(g, rotated)
+            .map_or(false, |glyph| intersects(&self.with_effects(glyph), rect))
+    }
+
+    // the colors of style, the glyphs stay where they are
+    fn recolor(&mut self, style: &TextStyle) {
+        self.style = self.style.clone().recolored(style);
+        self.queued_hash.get_or_insert(self.hash);
+        let mut hasher = DefaultHasher::new();
+        self.hash.hash(&mut hasher);
+        self.style.hash_into(&mut hasher);
+        self.hash = hasher.finish();
+    }
+
+    // moves everything by whole pixels, so glyphs keep their cache entries
+    fn translate(&mut self, [dx, dy]: [f32; 2]) {
+        let shift = |rect: &mut Rect<f32>| {
+            rect.min = point(rect.min.x + dx, rect.min.y + dy);
+            rect.max = point(rect.max.x + dx, rect.max.y + dy);
+        };
+        for (_, glyph) in &mut self.glyphs {
+            glyph.position = point(glyph.position.x + dx, glyph.position.y + dy);
+            if let Some(bounds) = &mut glyph.bounds {
+                let (dx, dy) = (dx as i32, dy as i32);
+                bounds.min = point(bounds.min.x + dx, bounds.min.y + dy);
+                bounds.max = point(bounds.max.x + dx, bounds.max.y + dy);
+            }
+        }
+        self.lines.iter_mut().for_each(shift);
+        self.background.iter_mut().for_each(shift);
+        self.atlas.iter_mut().for_each(shift);
+        self.ink.iter_mut().for_each(shift);
+        self.objects.iter_mut().for_each(|(rect, _)| shift(rect));
+        if self.fade != NO_FADE {
+            let d = if self.fade[2] == 0.0 { dx } else { dy };
+            self.fade[0] += d;
+            self.fade[1] += d;
+        }
+        self.queued_hash.get_or_insert(self.hash);
+        let mut hasher = DefaultHasher::new();
+        self.hash.hash(&mut hasher);
+        dx.to_bits().hash(&mut hasher);
+        dy.to_bits().hash(&mut hasher);
+        self.hash = hasher.finish();
+    }
+}
+
+pub(crate) fn union(a: Rect<f32>, b: Rect<f32>) -> Rect<f32> {
+    Rect {
+        min: point(a.min.x.min(b.min.x), a.min.y.min(b.min.y)),
+        max: point(a.max.x.max(b.max.x), a.max.y.max(b.max.y)),
+    }
+}
+
+pub(crate) fn intersects(a: &Rect<f32>, b: &Rect<f32>) -> bool {
+    a.min.x < b.max.x && b.min.x < a.max.x && a.min.y < b.max.y && b.min.y < a.max.y
+}
+
+// what of a style a layout depends on, see TextStyle::hash_layout_into
+fn layout_hash(style: &TextStyle) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    style.hash_layout_into(&mut hasher);
+    hasher.finish()
+}
+
+fn rotate_rect(rect: Rect<f32>, pivot: Point<f32>) -> Rect<f32> {
+    let a = rotate(rect.min, pivot);
+    let b = rotate(rect.max, pivot);
+    Rect {
+        min: point(a.x.min(b.x), a.y.min(b.y)),
+        max: point(a.x.max(b.x), a.y.max(b.y)),
+    }
+}
+
+// paint order inside a frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Pass {
+    Background = 0,
+    Shadow = 1,
+    Outline = 2,
+    Fill = 3,
+}
+
+// one glyph's place on screen in pixels and in the atlas
+#[derive(Debug, Clone, Copy)]
+struct PlacedGlyph {
+    screen_rect: Rect<f32>,
+    uv_rect: Rect<f32>,
+    page: u32,
+    baseline: f32,
+    // faux italic, x moves right by skew * height above the baseline
+    skew: f32,
+    // turned around this point after the skew, see rotate
+    pivot: Option<Point<f32>>,
+    fade: [f32; 3],
+}
+
+// tex_position below zero makes the fragment shader skip the atlas and
+// cover the whole quad, used for decoration lines
+const SOLID_UV: f32 = -1.0;
+
+impl PlacedGlyph {
+    fn solid(screen_rect: Rect<f32>, fade: [f32; 3]) -> PlacedGlyph {
+        PlacedGlyph {
+            screen_rect,
+            uv_rect: Rect {
+                min: point(SOLID_UV, SOLID_UV),
+                max: point(SOLID_UV, SOLID_UV),
+            },
+            page: 0,
+            baseline: 0.0,
+            skew: 0.0,
+            pivot: None,
+            fade,
+        }
+    }
+}
+
+// what a quad gets filled with, gradients are spread over the rect in pixels
+#[derive(Debug, Clone, Copy)]
+enum Paint {
+    Solid([f32; 4]),
+    Gradient(Gradient, Rect<f32>),
+}
+
+impl From<[f32; 4]> for Paint {
+    fn from(color: [f32; 4]) -> Paint {
+        Paint::Solid(color)
+    }
+}
+
+impl Paint {
+    fn color_at(&self, p: Point<f32>) -> [f32; 4] {
+        match self {
+            Paint::Solid(color) => *color,
+            Paint::Gradient(gradient, area) => gradient.color_at(area, p),
+        }
+    }
+}
+
+// the quad gets sheared, turned, then moved by offset before being mapped to NDC
+fn push_glyph(
+    instances: &mut Vec<GlyphInstance>,
+    screen: [f32; 2],
+    quad: &PlacedGlyph,
+    offset: [f32; 2],
+    paint: impl Into<Paint>,
+) {
+    let rect = quad.screen_rect;
+    let corners = [
+        point(rect.min.x, rect.max.y),
+        point(rect.min.x, rect.min.y),
+        point(rect.max.x, rect.min.y),
+        point(rect.max.x, rect.max.y),
+    ];
+    push_shape(instances, screen, quad, corners, [1.0; 4], offset, paint);
+}
+
+// any four corners in pixels, in push_glyph's order, with the uvs, skew and turn of
+// quad. coverage multiplies the alpha at every corner, triangles repeat their last one
+fn push_shape(
+    instances: &mut Vec<GlyphInstance>,
+    screen: [f32; 2],
+    quad: &PlacedGlyph,
+    corners: [Point<f32>; 4],
+    coverage: [f32; 4],
+    offset: [f32; 2],
+    paint: impl Into<Paint>,
+) {
+    let PlacedGlyph {
+        uv_rect,
+        page,
+        baseline,
+        skew,
+        pivot,
+        fade,
+        ..
+    } = *quad;
+    let paint = paint.into();
+    let to_screen = |p: Point<f32>| {
+        let mut p = point(p.x + skew * (baseline - p.y), p.y);
+        if let Some(pivot) = pivot {
+            p = rotate(p, pivot);
+        }
+        point(p.x + offset[0], p.y + offset[1])
+    };
+    let corners = corners.map(to_screen);
+    let mut colors = corners.map(|p| paint.color_at(p));
+    for (color, coverage) in colors.iter_mut().zip(coverage) {
+        color[3] *= coverage;
+    }
+    instances.push(GlyphInstance {
+        corners: corners.map(|p| [(p.x / screen[0] - 0.5) * 2.0, (p.y / screen[1] - 0.5) * 2.0]),
+        uv_rect: [uv_rect.min.x, uv_rect.min.y, uv_rect.max.x, uv_rect.max.y],
+        colors,
+        fade,
+        page: page as f32,
+    });
+}
+
+// the triangles of a tessellated glyph moved to origin, then unless the target is
+// multisampled a pixel wide fringe fading out around its contours
+#[allow(clippy::too_many_arguments)]
+fn push_tessellated(
+    instances: &mut Vec<GlyphInstance>,
+    screen: [f32; 2],
+    quad: &PlacedGlyph,
+    tessellation: &Tessellation,
+    origin: Point<f32>,
+    feather: bool,
+    offset: [f32; 2],
+    paint: Paint,
+) {
+    let at = |[x, y]: [f32; 2]| point(origin.x + x, origin.y + y);
+    for &[a, b, c] in &tessellation.triangles {
+        let corners = [a, b, c, c].map(at);
+        push_shape(instances, screen, quad, corners, [1.0; 4], offset, paint);
+    }
+    if !feather {
+        return;
+    }
+    let normal = |a: [f32; 2], b: [f32; 2]| {
+        let [dx, dy] = [b[0] - a[0], b[1] - a[1]];
+        let length = dx.hypot(dy).max(f32::EPSILON);
+        [dy / length, -dx / length]
+    };
+    for contour in &tessellation.contours {
+        let len = contour.len();
+        // every point moved a pixel out, halfway between the normals of its edges
+        let outside: Vec<Point<f32>> = (0..len)
+            .map(|i| {
+                let [previous, p, next] = [
+                    contour[(i + len - 1) % len],
+                    contour[i],
+                    contour[(i + 1) % len],
+                ];
+                let [a, b] = [normal(previous, p), normal(p, next)];
+                let [x, y] = [a[0] + b[0], a[1] + b[1]];
+                let length = x.hypot(y).max(f32::EPSILON);
+                at([p[0] + x / length, p[1] + y / length])
+            })
+            .collect();
+        for i in 0..len {
+            let j = (i + 1) % len;
+            let corners = [at(contour[i]), outside[i], outside[j], at(contour[j])];
+            push_shape(
+                instances,
+                screen,
+                quad,
+                corners,
+                [1.0, 0.0, 0.0, 1.0],
+                offset,
+                paint,
+            );
+        }
+    }
+}
+
+// counted while a frame is recorded, for CacheStats and MemoryStats
+#[derive(Debug, Clone, Copy, Default)]
+struct FrameCounters {
+    evictions: usize,
+    upload_bytes: usize,
+    dropped_glyphs: usize,
+    clipped_chars: usize,
+    vertex_bytes: u64,
+    staging_bytes: u64,
+    buffers: usize,
+    over_budget: bool,
+    budget_error: Option<OverBudgetError>,
+}
+
+// the atlas uploaded by prepare_text, for the draw_text_in_subpass in the same
+// command buffer
+pub struct PreparedText {
+    subpass: Subpass,
+    pipeline: Arc<GraphicsPipeline>,
+    set: Arc<PersistentDescriptorSet>,
+}
+
+// everything DrawText can be configured with up front, most of it can also be
+// changed later through the setters
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DrawTextCreateInfo {
+    pub quality: Quality,
+    // a transparent pixel around every glyph in the atlas, so filtering never picks
+    // up a neighbouring glyph
+    pub pad_glyphs: bool,
+    // shrinks glyph uvs by half a texel for the same reason, pixel-art fonts sampled
+    // with Nearest want both of these off
+    pub uv_inset: bool,
+    pub address_mode: SamplerAddressMode,
+    pub frames_in_flight: usize,
+    pub vertex_memory: VertexMemory,
+    // the atlas grows by a 1000x1000 page whenever it's full, until it has this many.
+    // CJK heavy applications want more than the default, every page costs a megabyte
+    // per frame in flight
+    pub max_atlas_pages: u32,
+    // see DrawText::set_async_rasterization
+    pub async_rasterization: bool,
+    // see DrawText::set_gpu_driven
+    pub gpu_driven: bool,
+    // laid out strings kept for queueing them again, see set_layout_cache_capacity
+    pub layout_cache_capacity: usize,
+    // see DrawText::set_max_glyphs_per_call
+    pub max_glyphs_per_call: Option<usize>,
+    // see DrawText::set_full_atlas_upload
+    pub full_atlas_upload: bool,
+    // see DrawText::set_atlas_defragment
+    pub atlas_defragment: Option<AtlasDefragment>,
+    // what the colors handed to DrawText are meant as
+    pub color_space: ColorSpace,
+    // for all of DrawText's own pipelines, fixed once it's created
+    pub alpha_mode: AlphaMode,
+    pub coordinate_origin: CoordinateOrigin,
+    // see DrawText::set_units
+    pub units: Units,
+    // see DrawText::set_ui_scale
+    pub ui_scale: f64,
+    // see DrawText::set_memory_budget
+    pub memory_budget: Option<MemoryBudget>,
+}
+
+impl Default for DrawTextCreateInfo {
+    fn default() -> DrawTextCreateInfo {
+        DrawTextCreateInfo {
+            quality: Quality::default(),
+            pad_glyphs: true,
+            uv_inset: true,
+            address_mode: SamplerAddressMode::ClampToEdge,
+            frames_in_flight: FRAMES_IN_FLIGHT,
+            vertex_memory: VertexMemory::default(),
+            max_atlas_pages: 4,
+            async_rasterization: false,
+            gpu_driven: false,
+            layout_cache_capacity: 1024,
+            max_glyphs_per_call: Some(MAX_GLYPHS_PER_CALL),
+            full_atlas_upload: false,
+            atlas_defragment: None,
+            color_space: ColorSpace::default(),
+            alpha_mode: AlphaMode::default(),
+            coordinate_origin: CoordinateOrigin::default(),
+            units: Units::default(),
+            ui_scale: 1.0,
+            memory_budget: None,
+        }
+    }
+}
+
+// fonts, the glyph atlas and its copies, pipelines and the text queue, everything a
+// renderer shares between the targets it draws into. it has no window of its own,
+// draw_into draws into any TextTarget, see create_target. DrawText is one of these
+// together with a swapchain
+pub struct TextRendererCore {
     device: Arc<Device>,
     queue: Arc<Queue>,
-    font: Font<'static>,
-    cache: Cache<'static>,
+    // the fonts and everything text is laid out by
+    layouter: TextLayouter,
+    // for inline code in queue_markdown
+    code_font: Option<FontId>,
+    // fonts added with add_msdf_font, drawn from their own atlas
+    msdf_fonts: HashMap<FontId, MsdfFont>,
+    // glyphs of fonts added with add_font_msdf
+    msdf_atlas: MsdfAtlas,
+    // outlines of tessellated glyphs around their origin, by font, glyph, size and
+    // instance. only those of the queued texts are kept
+    tessellations: HashMap<(FontId, GlyphId, u32, u64), Tessellation>,
+    palette: Palette,
+    // bumped by set_palette, themed texts of an older one get recolored when drawn
+    palette_version: u64,
+    cache: GlyphCache,
     cache_pixel_buffer: Vec<u8>,
-    pipeline: Arc<GraphicsPipeline>,
-    framebuffers: Vec<Arc<Framebuffer>>,
+    layout_cache: LayoutCache,
+    max_glyphs_per_call: Option<usize>,
+    #[cfg(feature = "normalization")]
+    normalize: bool,
+    // shrink glyph uvs by half a texel
+    uv_inset: bool,
+    // the size of the swapchain of the DrawText this is part of, texts are culled to it
+    // when queued. None when it has none, texts can be drawn into targets of any size
+    extent: Option<[u32; 2]>,
+    texture_target: Option<TextTarget>,
+    // one per format and kind, only their render pass and pipeline get reused
+    shared_targets: HashMap<(Format, TargetKind), TextTarget>,
+    // by render pass and subpass index, with the frame they were last drawn with
+    subpass_pipelines: HashMap<(usize, u32), (Arc<GraphicsPipeline>, usize)>,
+    // bumped when everything drawn has to be drawn again, like after set_color_space.
+    // PartialRedraw throws its overlay away when it changed
+    pub(crate) invalidated: u64,
+    frames_in_flight: usize,
+    vertex_memory: VertexMemory,
+    memory_budget: Option<MemoryBudget>,
+    gpu_driven: bool,
+    // built on first use, like the indirect pipelines
+    culling: Option<GpuCulling>,
+    // fragment shaders of add_effect by id
+    effects: Vec<Arc<ShaderModule>>,
+    // keyed by the address of the pipeline they were built from, which the weak
+    // reference keeps from being reused. dropped once that pipeline is
+    variant_pipelines:
+        HashMap<(usize, PipelineVariant), (Weak<GraphicsPipeline>, Arc<GraphicsPipeline>)>,
+    fill_sampler: Arc<Sampler>,
+    // by the target drawn into, None for the caller's own subpasses and secondary
+    // command buffers
+    frames: HashMap<Option<TargetId>, TargetFrames>,
+    // the atlas generate_quads handed out last
+    quads_atlas: Option<(FrameSlot, Arc<ImageView>)>,
+    descriptor_set_allocator: StandardDescriptorSetAllocator,
+    sampler: Arc<Sampler>,
+    filter: Filter,
+    address_mode: SamplerAddressMode,
+    // counts finished frames, for telling idle pipelines and targets
+    frame: usize,
+    #[cfg(feature = "gpu_timing")]
+    gpu_timer: Option<GpuTimer>,
+    #[cfg(feature = "shader_reload")]
+    shader_reload: Option<ShaderReload>,
+    // bumped whenever glyphs are rasterized into cache_pixel_buffer
+    atlas_version: u64,
+    // the rects rasterized into since atlas_dirty_since, the oldest version a frame slot
+    // holds or frames in flight versions back when there are none
+    atlas_dirty: Vec<DirtyRect>,
+    atlas_dirty_since: u64,
+    full_atlas_upload: bool,
+    atlas_defragment: Option<AtlasDefragment>,
+    color_space: ColorSpace,
+    alpha_mode: AlphaMode,
+    coordinate_origin: CoordinateOrigin,
+    // set while a queue call places what it queues, see from_origin
+    placing: bool,
+    // BottomLeft: moves everything the current queue call queues
+    flip: Option<TextAnchor>,
+    // see with_role
+    role: TextRole,
+    // texts keep their strings, and the ones drawn last are in drawn_labels in
+    // physical pixels
+    labels: bool,
+    drawn_labels: Vec<(String, TextRole, Rect<f32>)>,
+    recording: FrameCounters,
+    last_frame: FrameCounters,
+    layer_targets: HashMap<i32, LayerTargets>,
     texts: Vec<TextData>,
+    retained: Vec<(RetainedTextId, RetainedText)>,
+    next_retained_id: u64,
+    // bumped whenever retained texts change, stands in for them in content_hash
+    retained_version: u64,
+    // whether the retained texts were appended to texts this frame
+    retained_queued: bool,
+    drawn_hash: Option<u64>,
+    // hash and bounds of the texts last drawn, for take_damage
+    drawn_bounds: Vec<(u64, Rect<f32>)>,
+    damage: Option<Rect<f32>>,
+    // some glyphs were still being rasterized, their texts change once they are in
+    damage_all: bool,
+    redraw_at: Option<Instant>,
+    // see set_view_scale, the layouter's is the one glyphs are rasterized at
+    view_scale: f32,
+    // set_view_scale was called since the last frame
+    zooming: bool,
 }
 
-const CACHE_WIDTH: usize = 1000;
-const CACHE_HEIGHT: usize = 1000;
+// a TextRendererCore drawing into a swapchain of its own with draw_text. everything
+// but the swapchain is the core's, which it derefs to
+pub struct DrawText {
+    core: TextRendererCore,
+    target: Option<Arc<TextTarget>>,
+    // the swapchain target between on_surface_lost and on_surface_recreated, without
+    // its images
+    lost_target: Option<TextTarget>,
+    composition: CompositionMode,
+    overlay: Option<PartialRedraw>,
+    // where the next presented frame goes, see capture_next_frame
+    #[cfg(feature = "screenshot")]
+    capture_path: Option<PathBuf>,
+    // copied and waiting for the gpu
+    #[cfg(feature = "screenshot")]
+    screenshot: Option<Screenshot>,
+}
 
-impl DrawText {
-    pub fn new(
+impl Deref for DrawText {
+    type Target = TextRendererCore;
+
+    fn deref(&self) -> &TextRendererCore {
+        &self.core
+    }
+}
+
+impl DerefMut for DrawText {
+    fn deref_mut(&mut self) -> &mut TextRendererCore {
+        &mut self.core
+    }
+}
+
+const LINK_COLOR: [f32; 3] = [0.35, 0.6, 1.0];
+const CODE_BACKGROUND: [f32; 4] = [0.5, 0.5, 0.5, 0.25];
+// faux bold weight for markdown's bold text and headings
+const BOLD_WEIGHT: u16 = 700;
+// see DrawText::set_atlas_defragment
+const DEFRAGMENT_USED_PCT: f32 = 50.0;
+const TEXTURE_FORMAT: Format = Format::R8G8B8A8_UNORM;
+// enough for a swapchain with three images
+const FRAMES_IN_FLIGHT: usize = 3;
+// how far the view scale may get from the one glyphs were rasterized at, either way,
+// before they are rasterized again
+const VIEW_SCALE_STEP: f32 = 1.25;
+// a few screens full of small text
+const MAX_GLYPHS_PER_CALL: usize = 65536;
+// a subpass pipeline not drawn with for this many frames is dropped with the next one
+// built
+const SUBPASS_PIPELINE_IDLE_FRAMES: usize = 120;
+// the same for the frame slots of a target
+const TARGET_IDLE_FRAMES: usize = 120;
+
+// what a pipeline in TextRendererCore::variant_pipelines was built from another for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum PipelineVariant {
+    // the blend of the alpha mode, or shaders that were reloaded
+    Premultiplied,
+    // masking a fill texture
+    Textured,
+    // drawing glyphs of an msdf font
+    Msdf,
+    Effect(TextEffectId),
+    // reading the glyphs GpuCulling left
+    Indirect,
+}
+
+// without the feature there is never one, pipelines get None for it
+#[cfg(not(feature = "shader_reload"))]
+pub(crate) enum ShaderReload {}
+
+// the shader of file in src/shaders as compiled in, or as last reloaded from disk by
+// reload, see TextRendererCore::watch_shaders
+#[cfg(feature = "shader_reload")]
+pub(crate) fn load_shader<E: Debug>(
+    device: Arc<Device>,
+    reload: Option<&ShaderReload>,
+    file: &str,
+    compiled: fn(Arc<Device>) -> Result<Arc<ShaderModule>, E>,
+) -> Arc<ShaderModule> {
+    shader_reload::load(device, reload, file, compiled)
+}
+
+#[cfg(not(feature = "shader_reload"))]
+pub(crate) fn load_shader<E: Debug>(
+    device: Arc<Device>,
+    _reload: Option<&ShaderReload>,
+    _file: &str,
+    compiled: fn(Arc<Device>) -> Result<Arc<ShaderModule>, E>,
+) -> Arc<ShaderModule> {
+    compiled(device).unwrap()
+}
+
+// pipeline drawing glyph quads into the given subpass
+pub(crate) fn text_pipeline(
+    device: Arc<Device>,
+    subpass: Subpass,
+    blend: AttachmentBlend,
+    reload: Option<&ShaderReload>,
+) -> Arc<GraphicsPipeline> {
+    let vs = load_shader(device.clone(), reload, "vertex.glsl", vs::load);
+    let fs = load_shader(device, reload, "fragment.glsl", fs::load);
+    let pipeline = compat::graphics_pipeline(vs, fs, true, subpass, blend);
+    compat::set_name(&*pipeline, "font-render text pipeline");
+    pipeline
+}
+
+// the same pipeline, with the fragment shader masking a fill texture in set 1
+fn textured_pipeline(
+    base: &GraphicsPipeline,
+    reload: Option<&ShaderReload>,
+) -> Arc<GraphicsPipeline> {
+    let device = base.device().clone();
+    let (subpass, blend) = compat::pipeline_target(base);
+    let vs = load_shader(device.clone(), reload, "vertex.glsl", vs::load);
+    let fs = load_shader(device, reload, "textured_fragment.glsl", textured_fs::load);
+    let pipeline = compat::graphics_pipeline(vs, fs, true, subpass, blend);
+    compat::set_name(&*pipeline, "font-render textured text pipeline");
+    pipeline
+}
+
+// the same pipeline, with the fragment shader drawing glyphs from an msdf atlas in set 1
+fn msdf_pipeline(base: &GraphicsPipeline, reload: Option<&ShaderReload>) -> Arc<GraphicsPipeline> {
+    let device = base.device().clone();
+    let (subpass, blend) = compat::pipeline_target(base);
+    let vs = load_shader(device.clone(), reload, "vertex.glsl", vs::load);
+    let fs = load_shader(device, reload, "msdf_fragment.glsl", msdf_fs::load);
+    let pipeline = compat::graphics_pipeline(vs, fs, true, subpass, blend);
+    compat::set_name(&*pipeline, "font-render msdf text pipeline");
+    pipeline
+}
+
+// the same pipeline, with a fragment shader of add_effect
+fn effect_pipeline(
+    base: &GraphicsPipeline,
+    fs: Arc<ShaderModule>,
+    reload: Option<&ShaderReload>,
+) -> Result<Arc<GraphicsPipeline>, EffectError> {
+    let device = base.device().clone();
+    let (subpass, blend) = compat::pipeline_target(base);
+    let vs = load_shader(device, reload, "vertex.glsl", vs::load);
+    let pipeline =
+        compat::try_graphics_pipeline(vs, fs, true, subpass, blend).map_err(EffectError)?;
+    compat::set_name(&*pipeline, "font-render effect text pipeline");
+    Ok(pipeline)
+}
+
+fn fill_sampler(device: Arc<Device>) -> Arc<Sampler> {
+    Sampler::new(
+        device,
+        SamplerCreateInfo {
+            mag_filter: Filter::Linear,
+            min_filter: Filter::Linear,
+            address_mode: [SamplerAddressMode::ClampToEdge; 3],
+            ..Default::default()
+        },
+    )
+    .unwrap()
+}
+
+pub(crate) fn atlas_sampler(
+    device: Arc<Device>,
+    filter: Filter,
+    address_mode: SamplerAddressMode,
+) -> Arc<Sampler> {
+    Sampler::new(
+        device,
+        SamplerCreateInfo {
+            mag_filter: filter,
+            min_filter: filter,
+            mipmap_mode: SamplerMipmapMode::Nearest,
+            address_mode: [address_mode; 3],
+            ..Default::default()
+        },
+    )
+    .unwrap()
+}
+
+impl TextRendererCore {
+    // no window, text is drawn into targets from create_target with draw_into
+    pub fn new(device: Arc<Device>, queue: Arc<Queue>) -> TextRendererCore {
+        TextRendererCore::with_create_info(device, queue, DrawTextCreateInfo::default())
+    }
+
+    // a target for a window or image set sharing this renderer's fonts and glyph
+    // cache, draw into it with draw_into. targets of the same format share their
+    // render pass and pipeline
+    pub fn create_target(
+        &mut self,
+        kind: TargetKind,
+        views: Vec<Arc<ImageView>>,
+    ) -> Result<TextTarget, NoImagesError> {
+        let format = compat::view_format(views.first().ok_or(NoImagesError)?);
+        let device = &self.device;
+        Ok(self
+            .shared_targets
+            .entry((format, kind))
+            .or_insert_with(|| TextTarget::new(device.clone(), kind, format, vec![]))
+            .sibling(views))
+    }
+
+    pub fn with_create_info(
         device: Arc<Device>,
         queue: Arc<Queue>,
-        swapchain: Arc<Swapchain>,
-        images: &[Arc<SwapchainImage>],
-    ) -> DrawText {
-        let font_data = include_bytes!("../font/DejaVuSans.ttf");
-        let font = Font::from_bytes(font_data as &[u8]).unwrap();
+        create_info: DrawTextCreateInfo,
+    ) -> TextRendererCore {
+        let DrawTextCreateInfo {
+            quality,
+            pad_glyphs,
+            uv_inset,
+            address_mode,
+            frames_in_flight,
+            vertex_memory,
+            max_atlas_pages,
+            async_rasterization,
+            gpu_driven,
+            layout_cache_capacity,
+            max_glyphs_per_call,
+            full_atlas_upload,
+            atlas_defragment,
+            color_space,
+            alpha_mode,
+            coordinate_origin,
+            units,
+            ui_scale,
+            memory_budget,
+        } = create_info;
+        assert!(ui_scale > 0.0, "ui scale has to be positive");
+        assert!(
+            frames_in_flight > 0,
+            "at least one frame has to be in flight"
+        );
 
-        let vs = vs::load(device.clone()).unwrap();
-        let fs = fs::load(device.clone()).unwrap();
+        let mut layouter = TextLayouter::new().with_quality(quality);
+        layouter.set_units(units);
+        layouter.set_ui_scale(ui_scale);
 
-        let cache = Cache::builder()
-            .dimensions(CACHE_WIDTH as u32, CACHE_HEIGHT as u32)
-            .build();
+        let settings = quality.settings();
+        let mut cache = GlyphCache::new(
+            CACHE_WIDTH as u32,
+            CACHE_HEIGHT as u32,
+            max_atlas_pages,
+            settings.scale_tolerance,
+            settings.position_tolerance,
+            pad_glyphs,
+        );
+        cache.set_async(async_rasterization);
         let cache_pixel_buffer = vec![0; CACHE_WIDTH * CACHE_HEIGHT];
+        let descriptor_set_allocator = compat::descriptor_set_allocator(device.clone());
+        let sampler = atlas_sampler(device.clone(), settings.filter, address_mode);
+        let fill_sampler = fill_sampler(device.clone());
 
-        let render_pass = vulkano::single_pass_renderpass!(device.clone(),
-            attachments: {
-                color: {
-                    load: Load,
-                    store: Store,
-                    format: swapchain.image_format(),
-                    samples: 1,
-                }
-            },
-            pass: {
-                color: [color],
-                depth_stencil: {}
-            }
-        )
-        .unwrap();
-
-        let framebuffers = images
-            .iter()
-            .map(|image| {
-                let view = ImageView::new_default(image.clone()).unwrap();
-                Framebuffer::new(
-                    render_pass.clone(),
-                    FramebufferCreateInfo {
-                        attachments: vec![view],
-                        ..Default::default()
-                    },
-                )
-                .unwrap()
-            })
-            .collect::<Vec<_>>();
-
-        let pipeline = GraphicsPipeline::start()
-            .vertex_input_single_buffer::<Vertex>()
-            .vertex_shader(vs.entry_point("main").unwrap(), ())
-            .triangle_list()
-            .viewports(iter::once(Viewport {
-                origin: [0.0, 0.0],
-                depth_range: 0.0..1.0,
-                dimensions: [
-                    images[0].dimensions().width_height()[0] as f32,
-                    images[0].dimensions().width_height()[1] as f32,
-                ],
-            }))
-            .fragment_shader(fs.entry_point("main").unwrap(), ())
-            .blend_alpha_blending()
-            .render_pass(Subpass::from(render_pass.clone(), 0).unwrap())
-            .build(device.clone())
-            .unwrap();
-
-        DrawText {
+        TextRendererCore {
             device,
             queue,
-            font,
+            layouter,
+            code_font: None,
+            msdf_fonts: HashMap::new(),
+            msdf_atlas: MsdfAtlas::new(),
+            tessellations: HashMap::new(),
+            palette: Palette::new(),
+            palette_version: 0,
             cache,
             cache_pixel_buffer,
-            pipeline,
-            framebuffers,
+            layout_cache: LayoutCache::new(layout_cache_capacity),
+            max_glyphs_per_call,
+            #[cfg(feature = "normalization")]
+            normalize: false,
+            uv_inset,
+            extent: None,
+            texture_target: None,
+            shared_targets: HashMap::new(),
+            subpass_pipelines: HashMap::new(),
+            invalidated: 0,
+            frames_in_flight,
+            vertex_memory,
+            memory_budget,
+            gpu_driven,
+            culling: None,
+            effects: vec![],
+            variant_pipelines: HashMap::new(),
+            fill_sampler,
+            frames: HashMap::new(),
+            quads_atlas: None,
+            descriptor_set_allocator,
+            sampler,
+            filter: settings.filter,
+            address_mode,
+            frame: 0,
+            #[cfg(feature = "gpu_timing")]
+            gpu_timer: None,
+            #[cfg(feature = "shader_reload")]
+            shader_reload: None,
+            atlas_version: 0,
+            atlas_dirty: vec![],
+            atlas_dirty_since: 0,
+            full_atlas_upload,
+            atlas_defragment,
+            color_space,
+            alpha_mode,
+            coordinate_origin,
+            placing: false,
+            flip: None,
+            role: TextRole::Text,
+            labels: false,
+            drawn_labels: vec![],
+            recording: FrameCounters::default(),
+            last_frame: FrameCounters::default(),
+            layer_targets: HashMap::new(),
             texts: vec![],
+            retained: vec![],
+            next_retained_id: 0,
+            retained_version: 0,
+            retained_queued: false,
+            drawn_hash: None,
+            drawn_bounds: vec![],
+            damage: None,
+            damage_all: false,
+            redraw_at: None,
+            view_scale: 1.0,
+            zooming: false,
         }
     }
 
-    pub fn queue_text(&mut self, x: f32, y: f32, size: f32, color: [f32; 4], text: &str) {
-        let glyphs: Vec<PositionedGlyph> = self
-            .font
-            .layout(text, Scale::uniform(size), point(x, y))
-            .map(|x| x.standalone())
-            .collect();
-        for glyph in &glyphs {
-            self.cache.queue_glyph(0, glyph.clone());
-        }
-        self.texts.push(TextData {
-            glyphs: glyphs.clone(),
-            color,
-        });
+    pub fn device(&self) -> &Arc<Device> {
+        &self.device
     }
 
-    pub fn draw_text<'a>(
-        &mut self,
-        command_buffer: &'a mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>,
-        memory_allocator: &Arc<StandardMemoryAllocator>,
-        image_num: usize,
-    ) -> &'a mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer> {
-        let screen_width = self.framebuffers[image_num].extent()[0];
-        let screen_height = self.framebuffers[image_num].extent()[1];
-        let cache_pixel_buffer = &mut self.cache_pixel_buffer;
-        let cache = &mut self.cache;
+    pub fn queue(&self) -> &Arc<Queue> {
+        &self.queue
+    }
 
-        // update texture cache
-        cache
-            .cache_queued(|rect, src_data| {
-                let width = (rect.max.x - rect.min.x) as usize;
-                let height = (rect.max.y - rect.min.y) as usize;
-                let mut dst_index = rect.min.y as usize * CACHE_WIDTH + rect.min.x as usize;
-                let mut src_index = 0;
-
-                for _ in 0..height {
-                    let dst_slice = &mut cache_pixel_buffer[dst_index..dst_index + width];
-                    let src_slice = &src_data[src_index..src_index + width];
-                    dst_slice.copy_from_slice(src_slice);
-
-                    dst_index += CACHE_WIDTH;
-                    src_index += width;
-                }
-            })
-            .unwrap();
+    // for measuring text on threads that have no DrawText, a clone of it lays out
+    // with the same fonts and settings
+    pub fn layouter(&self) -> &TextLayouter {
+        &self.layouter
+    }
 
-        let buffer = CpuAccessibleBuffer::<[u8]>::from_iter(
-            memory_allocator,
-            BufferUsage {
-                transfer_src: true,
-                transfer_dst: true,
-                uniform_texel_buffer: true,
-                storage_texel_buffer: true,
-                uniform_buffer: true,
-                storage_buffer: true,
-                index_buffer: true,
-                vertex_buffer: true,
-                indirect_buffer: true,
-                shader_device_address: true,
-                ..Default::default()
-            },
-            false,
-            cache_pixel_buffer.iter().cloned(),
-        )
-        .unwrap();
+    // what was drawn into the old images, the next frame draws everything. the queue
+    // stays, text queued before the surface went away is drawn into the new images
+    fn forget_drawn_frames(&mut self) {
+        self.subpass_pipelines.clear();
+        self.drawn_hash = None;
+        self.damage_all = true;
+    }
 
-        let (cache_texture, cache_texture_write) = ImmutableImage::uninitialized(
-            memory_allocator,
-            ImageDimensions::Dim2d {
-                width: CACHE_WIDTH as u32,
-                height: CACHE_HEIGHT as u32,
-                array_layers: 1,
-            },
-            Format::R8_UNORM,
-            1,
-            ImageUsage {
-                sampled: true,
-                transfer_dst: true,
-                ..ImageUsage::empty()
-            },
-            ImageCreateFlags::empty(),
-            ImageLayout::General,
-            Some(self.queue.queue_family_index()),
-        )
-        .unwrap();
+    // loaded with rusttype, or ab_glyph or swash when their feature is on. data the
+    // backend can't read is a FontError and adds nothing
+    pub fn add_font(&mut self, data: Vec<u8>) -> Result<FontId, FontError> {
+        Ok(self.add_font_backend(default_backend(data)?))
+    }
 
-        let sampler = Sampler::new(
-            self.device.clone(),
-            SamplerCreateInfo {
-                mag_filter: Filter::Linear,
-                min_filter: Filter::Linear,
-                mipmap_mode: SamplerMipmapMode::Nearest,
-                address_mode: [SamplerAddressMode::Repeat; 3],
-                ..Default::default()
-            },
-        )
-        .unwrap();
+    // a font loaded by any glyph backend, fonts of different backends can be mixed
+    pub fn add_font_backend(&mut self, font: Box<dyn GlyphBackend>) -> FontId {
+        let id = self.layouter.add_font_backend(font);
+        self.layout_cache.clear();
+        id
+    }
 
-        let cache_texture_view = ImageView::new_default(cache_texture).unwrap();
-        let descriptor_set_allocator = StandardDescriptorSetAllocator::new(self.device.clone());
+    // a font pre-baked by msdf-atlas-gen, see MsdfFont. its glyphs are drawn by
+    // draw_text and friends but not by generate_quads or the gpu-driven path
+    pub fn add_msdf_font(&mut self, font: MsdfFont) -> FontId {
+        let id = self.add_font_backend(Box::new(font.clone()));
+        self.msdf_fonts.insert(id, font);
+        id
+    }
 
-        let set = PersistentDescriptorSet::new(
-            &descriptor_set_allocator,
-            // self.pipeline.layout().set_layouts().get(0).unwrap().clone(),
-            self.pipeline.layout().set_layouts()[0].clone(),
-            [WriteDescriptorSet::image_view_sampler(
-                0,
-                cache_texture_view,
-                sampler,
-            )],
-        )
-        .unwrap();
+    // like add_font, with glyphs drawn from distance fields generated at runtime, see
+    // MsdfAtlas
+    pub fn add_font_msdf(&mut self, data: Vec<u8>) -> Result<FontId, FontError> {
+        Ok(self.add_msdf_backend(default_backend(data)?))
+    }
 
-        let mut clear_values = vec![Some(0f32.into())];
-        let mut command_buffer = command_buffer
-            .copy_buffer_to_image(CopyBufferToImageInfo::buffer_image(
-                buffer,
-                cache_texture_write,
-            ))
-            .unwrap()
-            .begin_render_pass(
-                RenderPassBeginInfo {
-                    clear_values,
-                    ..RenderPassBeginInfo::framebuffer(
-                        self.framebuffers[image_num as usize].clone(),
-                    )
-                },
-                SubpassContents::Inline,
-            )
-            .unwrap();
+    // backends without outlines draw nothing
+    pub fn add_msdf_backend(&mut self, font: Box<dyn GlyphBackend>) -> FontId {
+        let font: Arc<dyn GlyphBackend> = Arc::from(font);
+        let id = self.add_font_backend(Box::new(DistanceFieldFont(font.clone())));
+        self.msdf_atlas.add_font(id, font);
+        id
+    }
 
-        // draw
-        for text in &mut self.texts.drain(..) {
-            let vertices: Vec<Vertex> = text
-                .glyphs
-                .iter()
-                .flat_map(|g| {
-                    if let Ok(Some((uv_rect, screen_rect))) = cache.rect_for(0, g) {
-                        let gl_rect = Rect {
-                            min: point(
-                                (screen_rect.min.x as f32 / screen_width as f32 - 0.5) * 2.0,
-                                (screen_rect.min.y as f32 / screen_height as f32 - 0.5) * 2.0,
-                            ),
-                            max: point(
-                                (screen_rect.max.x as f32 / screen_width as f32 - 0.5) * 2.0,
-                                (screen_rect.max.y as f32 / screen_height as f32 - 0.5) * 2.0,
-                            ),
-                        };
-                        vec![
-                            // vertex 2
-                            // bottom left
-                            Vertex {
-                                position: [gl_rect.min.x, gl_rect.max.y],
-                                tex_position: [uv_rect.min.x, uv_rect.max.y],
-                                color: text.color,
-                            },
-                            // top left
-                            Vertex {
-                                position: [gl_rect.min.x, gl_rect.min.y],
-                                tex_position: [uv_rect.min.x, uv_rect.min.y],
-                                color: text.color,
-                            },
-                            // vertice 1
-                            // top right
-                            Vertex {
-                                position: [gl_rect.max.x, gl_rect.min.y],
-                                tex_position: [uv_rect.max.x, uv_rect.min.y],
-                                color: text.color,
-                            },
-                            // vertice 1
-                            // top right
-                            Vertex {
-                                position: [gl_rect.max.x, gl_rect.min.y],
-                                tex_position: [uv_rect.max.x, uv_rect.min.y],
-                                color: text.color,
-                            },
-                            // bottom right
-                            Vertex {
-                                position: [gl_rect.max.x, gl_rect.max.y],
-                                tex_position: [uv_rect.max.x, uv_rect.max.y],
-                                color: text.color,
-                            },
-                            // vertex 2
-                            // bottom left
-                            Vertex {
-                                position: [gl_rect.min.x, gl_rect.max.y],
-                                tex_position: [uv_rect.min.x, uv_rect.max.y],
-                                color: text.color,
-                            },
-                        ]
-                        .into_iter()
-                    } else {
-                        vec![].into_iter()
-                    }
-                })
-                .collect();
+    // the axes a variable font can be set along with TextStyle::with_variation,
+    // empty for other fonts
+    pub fn variation_axes(&self, font: FontId) -> &[VariationAxis] {
+        self.layouter.variation_axes(font)
+    }
 
-            if vertices.is_empty() {
-                break;
-            }
+    // presets like "Bold" or "Condensed Light" for TextStyle::with_named_instance
+    pub fn named_instances(&self, font: FontId) -> &[NamedInstance] {
+        self.layouter.named_instances(font)
+    }
 
-            let vertex_buffer = CpuAccessibleBuffer::from_iter(
-                memory_allocator,
-                BufferUsage {
-                    transfer_src: true,
-                    transfer_dst: true,
-                    uniform_texel_buffer: true,
-                    storage_texel_buffer: true,
-                    uniform_buffer: true,
-                    storage_buffer: true,
-                    index_buffer: true,
-                    vertex_buffer: true,
-                    indirect_buffer: true,
-                    shader_device_address: true,
-                    ..Default::default()
-                },
-                false,
-                vertices.into_iter(),
-            )
-            .unwrap();
+    // bindings win over the default font for every char of the script,
+    // even if the bound font has no glyph for it
+    pub fn bind_script(&mut self, script: Script, font: FontId) {
+        self.layouter.bind_script(script, font);
+        self.layout_cache.clear();
+    }
 
-            command_buffer = command_buffer
-                .bind_pipeline_graphics(self.pipeline.clone())
-                .bind_descriptor_sets(
-                    PipelineBindPoint::Graphics,
-                    self.pipeline.layout().clone(),
-                    0,
-                    set.clone(),
-                )
-                .bind_vertex_buffers(0, vertex_buffer.clone())
-                .draw(vertex_buffer.len() as u32, 1, 0, 0)
-                .unwrap();
-        }
+    pub fn bind_range(&mut self, range: RangeInclusive<char>, font: FontId) {
+        self.layouter.bind_range(range, font);
+        self.layout_cache.clear();
+    }
+
+    pub fn unbind_font(&mut self, font: FontId) {
+        self.layouter.unbind_font(font);
+        self.layout_cache.clear();
+    }
+
+    // a monospace font for inline code in queue_markdown, which otherwise only gets a
+    // background
+    pub fn set_code_font(&mut self, font: Option<FontId>) {
+        self.code_font = font;
+    }
+
+    // rasterizes new glyphs on worker threads instead of while recording the frame, so
+    // a burst of them (opening a CJK document, say) doesn't stall it. glyphs show up in
+    // the first frame after they are done and are left out until then, next_redraw asks
+    // for that frame
+    pub fn set_async_rasterization(&mut self, on: bool) {
+        self.cache.set_async(on);
+    }
+
+    // how many laid out strings are kept, so the same string queued again at the same
+    // size skips the layout. 0 turns it off
+    pub fn set_layout_cache_capacity(&mut self, capacity: usize) {
+        self.layout_cache.set_capacity(capacity);
+    }
+
+    // guards against pathological input like a whole log file queued as one string:
+    // every queue call lays out at most this many chars and drops the rest, counted in
+    // CacheStats::clipped_chars_last_frame. None lays out everything. 65536 by default
+    pub fn set_max_glyphs_per_call(&mut self, max: Option<usize>) {
+        self.max_glyphs_per_call = max;
+    }
+
+    // copies the whole atlas whenever glyphs were added instead of only the rects they
+    // went into, to rule the partial uploads out when glyphs look corrupted
+    pub fn set_full_atlas_upload(&mut self, on: bool) {
+        self.full_atlas_upload = on;
+    }
+
+    pub fn atlas_defragment(&self) -> Option<AtlasDefragment> {
+        self.atlas_defragment
+    }
+
+    // glyphs that aren't drawn anymore keep their room in the atlas until it's full and
+    // everything gets evicted at once. with this set, an atlas more than half full whose
+    // live glyphs take up less than min_utilization_pct of its rows gets repacked:
+    // glyphs that weren't queued for max_idle_frames are dropped, the rest are moved
+    // together with copies on the gpu instead of being rasterized and uploaded again.
+    // None, the default, never repacks
+    pub fn set_atlas_defragment(&mut self, defragment: Option<AtlasDefragment>) {
+        self.atlas_defragment = defragment;
+    }
+
+    // Keep by default. queued text and layout_text both go by it
+    pub fn set_control_chars(&mut self, control_chars: ControlChars) {
+        self.layouter.set_control_chars(control_chars);
+        self.layout_cache.clear();
+    }
+
+    // Skip by default, queued text and layout_text both go by it
+    pub fn set_missing_glyph_policy(&mut self, policy: MissingGlyphPolicy) {
+        self.layouter.set_missing_glyph_policy(policy);
+        self.layout_cache.clear();
+    }
+
+    // queued text is brought into NFC before layout, so decomposed input looks like
+    // the precomposed one. layout_text measures strings as they are, byte offsets of
+    // normalized text may differ from those of the queued string
+    #[cfg(feature = "normalization")]
+    pub fn set_normalization(&mut self, on: bool) {
+        self.normalize = on;
+    }
+
+    // queued text as it gets laid out: cut after max_glyphs_per_call chars and, with
+    // normalization on, in NFC
+    fn input<'t>(&mut self, text: &'t str) -> Cow<'t, str> {
+        let end = self
+            .max_glyphs_per_call
+            .and_then(|max| text.char_indices().nth(max))
+            .map(|(end, _)| end);
+        let text = match end {
+            Some(end) => {
+                self.recording.clipped_chars += text[end..].chars().count();
+                &text[..end]
+            }
+            None => text,
+        };
+        #[cfg(feature = "normalization")]
+        if self.normalize {
+            return sanitize::nfc(text);
+        }
+        Cow::Borrowed(text)
+    }
+
+    // experimental. batches of new glyphs big enough to stall the frame are filled in
+    // with a compute shader, the rest stays on the cpu. the frame doesn't wait for the
+    // gpu, those glyphs show up in a later one like with async rasterization. returns
+    // whether it's on, which it can't be when the queue doesn't run compute work. async
+    // rasterization takes precedence
+    #[cfg(feature = "gpu_raster")]
+    pub fn set_gpu_rasterization(&mut self, on: bool) -> bool {
+        let gpu = if on {
+            GpuRasterizer::new(self.queue.clone())
+        } else {
+            None
+        };
+        let enabled = gpu.is_some();
+        self.cache.set_gpu(gpu);
+        enabled
+    }
+
+    // records timestamps around the atlas copy and the draw of draw_text and
+    // draw_into, last_gpu_time reports how long the gpu took for them. returns
+    // whether it's on, which it can't be when the queue doesn't write timestamps
+    #[cfg(feature = "gpu_timing")]
+    pub fn set_gpu_timing(&mut self, on: bool) -> bool {
+        self.gpu_timer = if on {
+            GpuTimer::new(&self.queue, self.frames_in_flight)
+        } else {
+            None
+        };
+        self.gpu_timer.is_some()
+    }
+
+    // of the most recent frame the gpu finished, which is frames_in_flight frames
+    // behind the one being recorded. None until the first one finished
+    #[cfg(feature = "gpu_timing")]
+    pub fn last_gpu_time(&self) -> Option<Duration> {
+        self.gpu_timer.as_ref().and_then(GpuTimer::last)
+    }
+
+    // for working on the shaders: the text shaders in dir, the crate's src/shaders, are
+    // compiled again whenever one of them is saved and every pipeline is built again
+    // with them, no rebuild of the crate needed. a change shows up in the frame after
+    // the one it was saved during
+    #[cfg(feature = "shader_reload")]
+    pub fn watch_shaders(&mut self, dir: impl Into<PathBuf>) {
+        self.shader_reload = Some(ShaderReload::new(dir.into()));
+    }
+
+    // the compiler's errors for a saved shader, which is drawn with its last version
+    // that compiled until then
+    #[cfg(feature = "shader_reload")]
+    pub fn shader_error(&self) -> Option<&str> {
+        self.shader_reload.as_ref().and_then(ShaderReload::error)
+    }
+
+    #[cfg(feature = "shader_reload")]
+    fn reload_shaders(&mut self) {
+        let reloaded = self
+            .shader_reload
+            .as_mut()
+            .map_or(false, ShaderReload::poll);
+        if !reloaded {
+            return;
+        }
+        self.subpass_pipelines.clear();
+        self.variant_pipelines.clear();
+        self.drawn_hash = None;
+        self.damage_all = true;
+        self.invalidated += 1;
+    }
+
+    #[cfg(feature = "shader_reload")]
+    fn watches_shaders(&self) -> bool {
+        self.shader_reload.is_some()
+    }
+
+    #[cfg(not(feature = "shader_reload"))]
+    fn watches_shaders(&self) -> bool {
+        false
+    }
+
+    #[cfg(feature = "shader_reload")]
+    fn shader_reload(&self) -> Option<&ShaderReload> {
+        self.shader_reload.as_ref()
+    }
+
+    #[cfg(not(feature = "shader_reload"))]
+    fn shader_reload(&self) -> Option<&ShaderReload> {
+        None
+    }
+
+    pub fn with_quality(mut self, quality: Quality) -> TextRendererCore {
+        self.set_quality(quality);
+        self
+    }
+
+    pub fn quality(&self) -> Quality {
+        self.layouter.quality()
+    }
+
+    // takes effect for text queued afterwards, already rasterized glyphs are dropped
+    pub fn set_quality(&mut self, quality: Quality) {
+        if quality == self.layouter.quality() {
+            return;
+        }
+        self.layouter.set_quality(quality);
+        self.layout_cache.clear();
+
+        let settings = quality.settings();
+        self.cache
+            .set_tolerances(settings.scale_tolerance, settings.position_tolerance);
+        self.filter = settings.filter;
+        self.rebuild_sampler();
+        // texts kept around for a capture were already taken off the glyph queue
+        for text in self.texts.iter().filter(|text| text.in_atlas()) {
+            for (font_id, glyph) in &text.glyphs {
+                self.cache
+                    .queue_glyph(*font_id, *glyph, &text.style.variations);
+            }
+        }
+        self.drawn_hash = None;
+    }
+
+    // the window's scale factor, winit's Window::scale_factor. positions, sizes and
+    // widths stay in logical pixels while glyphs get rasterized for the physical ones,
+    // so text stays sharp on hidpi displays. takes effect for text queued afterwards
+    pub fn set_scale_factor(&mut self, scale_factor: f64) {
+        self.layouter.set_scale_factor(scale_factor);
+        self.drawn_hash = None;
+    }
+
+    // the same from the monitor's pixels per inch
+    pub fn set_dpi(&mut self, dpi: f64) {
+        self.set_scale_factor(scale_factor_for_dpi(dpi));
+    }
+
+    // physical pixels per unit, with the units and ui scale applied
+    pub fn scale_factor(&self) -> f64 {
+        self.layouter.scale_factor()
+    }
+
+    pub fn units(&self) -> Units {
+        self.layouter.units()
+    }
+
+    // Dp by default. like the scale factor it takes effect for text queued afterwards,
+    // layouts of the new units have to be taken again
+    pub fn set_units(&mut self, units: Units) {
+        self.layouter.set_units(units);
+        self.drawn_hash = None;
+    }
+
+    pub fn ui_scale(&self) -> f64 {
+        self.layouter.ui_scale()
+    }
+
+    // grows or shrinks everything this DrawText draws, positions included, e.g. for a
+    // text size setting or a hud drawn smaller than the menus
+    pub fn set_ui_scale(&mut self, ui_scale: f64) {
+        self.layouter.set_ui_scale(ui_scale);
+        self.drawn_hash = None;
+    }
+
+    pub fn view_scale(&self) -> f32 {
+        self.view_scale
+    }
+
+    // the zoom of a canvas, e.g. of a node editor or a map: grows or shrinks
+    // everything like the ui scale, for text queued afterwards. while it changes
+    // glyphs are only rasterized again once it's a quarter away from the size they
+    // were, in between their quads are stretched, so a zoom doesn't fill the atlas
+    // with every size it passes. a frame without a change rasterizes them sharp
+    pub fn set_view_scale(&mut self, view_scale: f32) {
+        assert!(view_scale > 0.0, "view scale has to be positive");
+        if view_scale == self.view_scale {
+            return;
+        }
+        self.view_scale = view_scale;
+        self.zooming = true;
+        let stretch = view_scale / self.layouter.view_scale();
+        if !(1.0 / VIEW_SCALE_STEP..=VIEW_SCALE_STEP).contains(&stretch) {
+            self.layouter.set_view_scale(view_scale);
+        }
+        self.drawn_hash = None;
+        self.damage_all = true;
+    }
+
+    // how much the quads of text laid out at the layouter's view scale are stretched
+    pub(crate) fn view_stretch(&self) -> f32 {
+        self.view_scale / self.layouter.view_scale()
+    }
+
+    // the target's size in the pixels text is laid out in
+    pub(crate) fn laid_out_screen(&self, screen: [f32; 2]) -> [f32; 2] {
+        let stretch = self.view_stretch();
+        [screen[0] / stretch, screen[1] / stretch]
+    }
+
+    // a rect of laid out text in the target's pixels
+    pub(crate) fn stretched(&self, rect: Rect<f32>) -> Rect<f32> {
+        let stretch = self.view_stretch();
+        Rect {
+            min: point(rect.min.x * stretch, rect.min.y * stretch),
+            max: point(rect.max.x * stretch, rect.max.y * stretch),
+        }
+    }
+
+    pub fn color_space(&self) -> ColorSpace {
+        self.color_space
+    }
+
+    // Srgb by default. applies to everything drawn from now on, queued text included
+    pub fn set_color_space(&mut self, space: ColorSpace) {
+        self.color_space = space;
+        self.drawn_hash = None;
+        self.damage_all = true;
+        self.invalidated += 1;
+    }
+
+    // color as the functions taking [f32; 4] colors expect it
+    pub fn color(&self, color: Color) -> [f32; 4] {
+        color.to(self.color_space)
+    }
+
+    pub fn alpha_mode(&self) -> AlphaMode {
+        self.alpha_mode
+    }
+
+    pub fn coordinate_origin(&self) -> CoordinateOrigin {
+        self.coordinate_origin
+    }
+
+    // runs f, everything it queues is a role to screen readers. text is Text unless
+    // it's a heading of queue_markdown or a TextEditController's
+    pub fn with_role<R>(
+        &mut self,
+        role: TextRole,
+        f: impl FnOnce(&mut TextRendererCore) -> R,
+    ) -> R {
+        let outer = mem::replace(&mut self.role, role);
+        let result = f(self);
+        self.role = outer;
+        result
+    }
+
+    pub(crate) fn keep_labels(&mut self) {
+        self.labels = true;
+    }
+
+    pub(crate) fn drawn_labels(&self) -> &[(String, TextRole, Rect<f32>)] {
+        &self.drawn_labels
+    }
+
+    // runs f, which lays out what it queues downwards from y as with TopLeft. with
+    // BottomLeft, y is measured from the bottom and all of it is moved there when
+    // drawn. calls nested in f are placed together with it
+    pub(crate) fn from_origin<R>(
+        &mut self,
+        y: f32,
+        f: impl FnOnce(&mut TextRendererCore) -> R,
+    ) -> R {
+        let flip = match self.coordinate_origin {
+            CoordinateOrigin::TopLeft => None,
+            CoordinateOrigin::BottomLeft => {
+                Some(TextAnchor::flip(y, self.layouter.scale_factor as f32))
+            }
+        };
+        self.placing_with(flip, f)
+    }
+
+    // for what is laid out against the screen's edges, the console and the like. y
+    // points down from the top whatever the origin
+    pub(crate) fn on_screen<R>(&mut self, f: impl FnOnce(&mut TextRendererCore) -> R) -> R {
+        self.placing_with(None, f)
+    }
+
+    fn placing_with<R>(
+        &mut self,
+        flip: Option<TextAnchor>,
+        f: impl FnOnce(&mut TextRendererCore) -> R,
+    ) -> R {
+        if self.placing {
+            return f(self);
+        }
+        self.placing = true;
+        self.flip = flip;
+        let result = f(self);
+        self.placing = false;
+        self.flip = None;
+        result
+    }
+
+    // y of the caller's coordinates as laid out inside from_origin(origin, ..), and the
+    // other way around
+    pub(crate) fn flip_y(&self, origin: f32, y: f32) -> f32 {
+        match self.coordinate_origin {
+            CoordinateOrigin::TopLeft => y,
+            CoordinateOrigin::BottomLeft => 2.0 * origin - y,
+        }
+    }
+
+    pub(crate) fn flip_rect(&self, origin: f32, rect: Rect<f32>) -> Rect<f32> {
+        let (a, b) = (
+            self.flip_y(origin, rect.min.y),
+            self.flip_y(origin, rect.max.y),
+        );
+        Rect {
+            min: point(rect.min.x, a.min(b)),
+            max: point(rect.max.x, a.max(b)),
+        }
+    }
+
+    pub(crate) fn color_output(&self, linear: bool) -> ColorOutput {
+        ColorOutput {
+            linear,
+            premultiplied: self.alpha_mode == AlphaMode::Premultiplied,
+        }
+    }
+
+    // of the swapchain in logical pixels, None without one
+    pub fn screen_size(&self) -> Option<[f32; 2]> {
+        let [width, height] = self.extent?;
+        let scale = self.layouter.scale_factor as f32;
+        Some([width as f32 / scale, height as f32 / scale])
+    }
+
+    // the outline of c in the font bound to it, size is the font's descent to ascent
+    // in whatever units the caller wants the path in. empty for chars without one, and
+    // for fonts whose backend doesn't read outlines
+    pub fn glyph_outline(&self, c: char, size: f32) -> Vec<PathSegment> {
+        self.layouter.glyph_outline(c, size)
+    }
+
+    // every glyph's origin and outline as text would be laid out with its top left at
+    // 0, 0, in the style's units. for TextMesh
+    pub(crate) fn glyph_paths(
+        &self,
+        style: &TextStyle,
+        text: &str,
+    ) -> Vec<(Point<f32>, Vec<PathSegment>)> {
+        let (glyphs, _) = self.layout(text, style, point(0.0, 0.0), &[], &[]);
+        glyphs
+            .into_iter()
+            .filter_map(|(font_id, glyph)| {
+                let font = &self.layouter.fonts[font_id];
+                let path = font.path(glyph.id, glyph.size, &style.variations)?;
+                Some((glyph.position, path))
+            })
+            .collect()
+    }
+
+    // where the objects of text end up when laid out from start, in pixels. upright
+    // in columns of vertical text, centered on the column
+    fn place_objects(
+        &self,
+        text: &str,
+        style: &TextStyle,
+        start: Point<f32>,
+        breaks: &[usize],
+        objects: &[Option<InlineObject>],
+    ) -> Vec<(Rect<f32>, InlineObject)> {
+        let line_height = self.layouter.line_height(style.size);
+        let ascent = self.layouter.fonts[0].v_metrics(style.size).ascent;
+        let boxes = self
+            .layouter
+            .place_chars(text, style, start, breaks, &inline::aspects(objects))
+            .into_iter()
+            .map(|(char_box, _)| char_box)
+            .filter(|char_box| text[char_box.byte..].starts_with(OBJECT_CHAR));
+        boxes
+            .zip(objects)
+            .filter_map(|(char_box, object)| {
+                let object = object.as_ref()?;
+                let [width, height] = self.layouter.object_size(style.size, object.aspect);
+                let line = char_box.line as f32 * line_height;
+                let min = if style.is_vertical() {
+                    point(start.x - line - width / 2.0, start.y + char_box.x)
+                } else {
+                    point(start.x + char_box.x, start.y + line - ascent)
+                };
+                let rect = Rect {
+                    min,
+                    max: point(min.x + width, min.y + height),
+                };
+                Some((rect, object.clone()))
+            })
+            .collect()
+    }
+
+    // the glyphs and which of them are rotated
+    fn layout(
+        &self,
+        text: &str,
+        style: &TextStyle,
+        start: Point<f32>,
+        breaks: &[usize],
+        objects: &[Option<InlineObject>],
+    ) -> (Vec<(FontId, Glyph)>, Vec<bool>) {
+        self.layouter
+            .place_chars(text, style, start, breaks, &inline::aspects(objects))
+            .into_iter()
+            .flat_map(|(_, glyphs)| glyphs)
+            .map(|(font_id, glyph, rotated)| ((font_id, glyph), rotated))
+            .unzip()
+    }
+
+    // layout, unless the same string was laid out the same way recently
+    fn cached_layout(
+        &mut self,
+        text: &str,
+        style: &TextStyle,
+        start: Point<f32>,
+        breaks: &[usize],
+        objects: &[Option<InlineObject>],
+    ) -> (Vec<(FontId, Glyph)>, Vec<bool>) {
+        let snaps = self.layouter.snaps(style);
+        let key = layout_cache::key(|hasher| {
+            text.hash(hasher);
+            // only their widths matter to the glyphs
+            for object in objects {
+                object.as_ref().map(|o| o.aspect.to_bits()).hash(hasher);
+            }
+            // the scale factor isn't part of the key, it is right only because the caller
+            // passes style.scaled(scale), whose sizes already are in physical pixels
+            style.hash_layout_into(hasher);
+            snaps.hash(hasher);
+            breaks.hash(hasher);
+            layout_cache::fraction(start).hash(hasher);
+        });
+        if let Some(layout) = self.layout_cache.get(key, text, start) {
+            return layout;
+        }
+        let (glyphs, rotated) = self.layout(text, style, start, breaks, objects);
+        self.layout_cache
+            .insert(key, text, start, &glyphs, &rotated);
+        (glyphs, rotated)
+    }
+
+    // where queue_text_styled would put every grapheme cluster of text, relative to its x, y
+    pub fn layout_text(&self, style: &TextStyle, text: &str) -> TextLayout {
+        self.layouter.layout_text(style, text)
+    }
+
+    // the same for queue_text_wrapped
+    pub fn layout_text_wrapped(&self, style: &TextStyle, text: &str, max_width: f32) -> TextLayout {
+        self.layouter.layout_text_wrapped(style, text, max_width)
+    }
+
+    // draws a caret for layout, queued with queue_text at x, y, in front of byte_index
+    pub fn queue_caret(
+        &mut self,
+        x: f32,
+        y: f32,
+        layout: &TextLayout,
+        byte_index: usize,
+        width: f32,
+        color: [f32; 4],
+    ) {
+        let (caret_x, caret_y, length) = layout.caret_position(byte_index);
+        // across the line, so lying down in columns
+        let [w, h] = if layout.is_vertical() {
+            [length, width]
+        } else {
+            [width, length]
+        };
+        let rect = Rect {
+            min: point(x + caret_x, y + caret_y),
+            max: point(x + caret_x + w, y + caret_y + h),
+        };
+        self.from_origin(y, |draw_text| {
+            draw_text.queue_rects(vec![rect], color, false, 0)
+        });
+    }
+
+    // highlights the chars of layout, queued with queue_text at x, y, that start in
+    // range, with a rect per stretch of them next to each other where right to left
+    // text mixes in. drawn behind all text, so queue it in any order
+    pub fn queue_selection(
+        &mut self,
+        x: f32,
+        y: f32,
+        layout: &TextLayout,
+        range: Range<usize>,
+        color: [f32; 4],
+    ) {
+        let rects = layout
+            .selection_rects(range)
+            .into_iter()
+            .map(|rect| Rect {
+                min: point(x + rect.min.x, y + rect.min.y),
+                max: point(x + rect.max.x, y + rect.max.y),
+            })
+            .collect();
+        self.from_origin(y, |draw_text| draw_text.queue_rects(rects, color, true, 0));
+    }
+
+    // a bar over rect filled up to fraction, which is clamped to 0 to 1, with the
+    // percentage centered on it if the style has a label
+    pub fn queue_progress_bar(&mut self, rect: Rect<f32>, fraction: f32, style: &ProgressStyle) {
+        let percent = format!("{:.0}%", fraction.clamp(0.0, 1.0) * 100.0);
+        self.queue_progress_bar_labeled(rect, fraction, style, &percent);
+    }
+
+    // the same with any label, like "HP 40/100" on a gauge
+    pub fn queue_progress_bar_labeled(
+        &mut self,
+        rect: Rect<f32>,
+        fraction: f32,
+        style: &ProgressStyle,
+        label: &str,
+    ) {
+        let rect = self.flip_rect(rect.max.y, rect);
+        self.from_origin(rect.min.y, |draw_text| {
+            draw_text.queue_bar(rect, fraction, style, label)
+        });
+    }
+
+    fn queue_bar(&mut self, rect: Rect<f32>, fraction: f32, style: &ProgressStyle, label: &str) {
+        let fraction = fraction.clamp(0.0, 1.0);
+        self.queue_rects(vec![rect], style.track, true, style.layer);
+        let filled = if style.vertical {
+            let top = rect.max.y - (rect.max.y - rect.min.y) * fraction;
+            Rect {
+                min: point(rect.min.x, top),
+                max: rect.max,
+            }
+        } else {
+            let right = rect.min.x + (rect.max.x - rect.min.x) * fraction;
+            Rect {
+                min: rect.min,
+                max: point(right, rect.max.y),
+            }
+        };
+        if fraction > 0.0 {
+            self.queue_rects(vec![filled], style.fill, true, style.layer);
+        }
+
+        if let Some(label_style) = &style.label {
+            let label_style = label_style.clone().with_layer(style.layer);
+            let layout = self.layout_text(&label_style, label);
+            let x = (rect.min.x + rect.max.x - layout.width()) / 2.0;
+            let top = (rect.min.y + rect.max.y - layout.height()) / 2.0;
+            self.queue_text_styled(x, top - layout.line_top(0), &label_style, label);
+        }
+    }
+
+    // solid quads in the text passes, drawn as decoration lines or backgrounds of a
+    // text without glyphs
+    pub(crate) fn queue_rects(
+        &mut self,
+        rects: Vec<Rect<f32>>,
+        color: [f32; 4],
+        behind_text: bool,
+        layer: i32,
+    ) {
+        let scale = self.layouter.scale_factor as f32;
+        let rects: Vec<_> = rects
+            .into_iter()
+            .map(|rect| Rect {
+                min: point(rect.min.x * scale, rect.min.y * scale),
+                max: point(rect.max.x * scale, rect.max.y * scale),
+            })
+            .collect();
+        let mut hasher = DefaultHasher::new();
+        for rect in &rects {
+            for value in [rect.min.x, rect.min.y, rect.max.x, rect.max.y] {
+                value.to_bits().hash(&mut hasher);
+            }
+        }
+        let mut style = TextStyle::new(0.0, color).with_layer(layer);
+        if behind_text {
+            style.background = Some(color);
+        }
+        style.hash_into(&mut hasher);
+        let (lines, background) = if behind_text {
+            (vec![], rects)
+        } else {
+            (rects, vec![])
+        };
+        self.texts.push(TextData {
+            glyphs: vec![],
+            rotated: vec![],
+            style,
+            lines,
+            background,
+            atlas: None,
+            fade: NO_FADE,
+            hash: hasher.finish(),
+            queued_hash: None,
+            ink: None,
+            theme: None,
+            texture: None,
+            effect: None,
+            objects: vec![],
+            objects_behind: false,
+            anchor: self.flip,
+            retained: false,
+            label: None,
+        });
+    }
+
+    // textured quads drawn like backgrounds, tinted objects take color. rects are in
+    // logical pixels like queue_rects takes them
+    pub(crate) fn queue_images(
+        &mut self,
+        images: Vec<(Rect<f32>, InlineObject)>,
+        color: [f32; 4],
+        layer: i32,
+    ) {
+        let scale = self.layouter.scale_factor as f32;
+        let images: Vec<_> = images
+            .into_iter()
+            .map(|(rect, image)| {
+                let rect = Rect {
+                    min: point(rect.min.x * scale, rect.min.y * scale),
+                    max: point(rect.max.x * scale, rect.max.y * scale),
+                };
+                (rect, image)
+            })
+            .collect();
+        let mut hasher = DefaultHasher::new();
+        for (rect, image) in &images {
+            for value in [rect.min.x, rect.min.y, rect.max.x, rect.max.y] {
+                value.to_bits().hash(&mut hasher);
+            }
+            (Arc::as_ptr(&image.texture) as *const () as usize).hash(&mut hasher);
+            image.uv_rect.map(f32::to_bits).hash(&mut hasher);
+            image.tinted.hash(&mut hasher);
+        }
+        let style = TextStyle::new(0.0, color).with_layer(layer);
+        style.hash_into(&mut hasher);
+        self.texts.push(TextData {
+            glyphs: vec![],
+            rotated: vec![],
+            style,
+            lines: vec![],
+            background: vec![],
+            atlas: None,
+            fade: NO_FADE,
+            hash: hasher.finish(),
+            queued_hash: None,
+            ink: None,
+            theme: None,
+            texture: None,
+            effect: None,
+            objects: images,
+            objects_behind: true,
+            anchor: self.flip,
+            retained: false,
+            label: None,
+        });
+    }
+
+    // font, horizontal span and baseline of every line of laid out text. the first
+    // glyph's font decides so decorations don't jump around at font fallbacks
+    fn line_extents(
+        &self,
+        glyphs: &[(FontId, Glyph)],
+        style: &TextStyle,
+        snap: bool,
+    ) -> Vec<(FontId, [f32; 2], f32)> {
+        // lines are the runs of glyphs sharing a baseline
+        let mut runs: Vec<&[(FontId, Glyph)]> = vec![];
+        let mut rest = glyphs;
+        while let Some((_, first)) = rest.first() {
+            let len = rest
+                .iter()
+                .position(|(_, g)| g.position.y != first.position.y)
+                .unwrap_or(rest.len());
+            runs.push(&rest[..len]);
+            rest = &rest[len..];
+        }
+
+        runs.into_iter()
+            .map(|run| {
+                let (font_id, first) = &run[0];
+                let (_, last) = &run[run.len() - 1];
+                let mut start = first.position.x;
+                let mut end = last.position.x + last.advance + style.emboldening();
+                if snap {
+                    start = start.round();
+                    end = end.round();
+                }
+                (*font_id, [start, end], first.position.y)
+            })
+            .collect()
+    }
+
+    // underline, strikethrough and overline rects in pixels
+    fn decoration_lines(
+        &self,
+        glyphs: &[(FontId, Glyph)],
+        style: &TextStyle,
+        snap: bool,
+    ) -> Vec<Rect<f32>> {
+        let decorations = style.decorations;
+        if decorations.is_empty() {
+            return vec![];
+        }
+
+        let mut lines = vec![];
+        for (font_id, [start, end], baseline) in self.line_extents(glyphs, style, snap) {
+            let metrics = &self.layouter.line_metrics[font_id];
+            let v_metrics = self.layouter.fonts[font_id].v_metrics_unscaled();
+            // sizes are the pixel height from descent to ascent
+            let scale = style.size / (v_metrics.ascent - v_metrics.descent);
+
+            let line = |position: f32, thickness: f32| {
+                let mut top = baseline - position * scale;
+                // never thinner than a pixel, or it fades away
+                let mut height = (thickness * scale).max(1.0);
+                if snap {
+                    top = top.round();
+                    height = height.round();
+                }
+                Rect {
+                    min: point(start, top),
+                    max: point(end, top + height),
+                }
+            };
+            if decorations.underline {
+                lines.push(line(
+                    metrics.underline_position,
+                    metrics.underline_thickness,
+                ));
+            }
+            if decorations.strikethrough {
+                lines.push(line(metrics.strikeout_position, metrics.strikeout_size));
+            }
+            if decorations.overline {
+                lines.push(line(v_metrics.ascent, metrics.underline_thickness));
+            }
+        }
+        lines
+    }
+
+    // applies the style's TinyText, see there
+    fn shrink_tiny(&self, text: &mut TextData, snap: bool) {
+        let size = text.style.size * self.view_stretch();
+        let greeked = match text.style.tiny_text {
+            TinyText::Greeked(below) if size < below => !text.style.is_vertical(),
+            TinyText::Hidden(below) if size < below => false,
+            _ => return,
+        };
+        if greeked {
+            // about where lowercase letters are
+            let height = (text.style.size * 0.45).max(1.0);
+            let bars = self.line_extents(&text.glyphs, &text.style, snap);
+            text.lines = bars
+                .into_iter()
+                .map(|(_, [start, end], baseline)| Rect {
+                    min: point(start, baseline - height),
+                    max: point(end, baseline),
+                })
+                .collect();
+        } else {
+            text.lines.clear();
+            text.background.clear();
+            text.objects.clear();
+        }
+        text.glyphs.clear();
+        text.rotated.clear();
+    }
+
+    // the line boxes from ascent to descent, grown by the style's padding
+    fn background_rects(
+        &self,
+        glyphs: &[(FontId, Glyph)],
+        style: &TextStyle,
+        snap: bool,
+    ) -> Vec<Rect<f32>> {
+        if style.background.is_none() {
+            return vec![];
+        }
+
+        let [pad_x, pad_y] = style.background_padding;
+        self.line_extents(glyphs, style, snap)
+            .into_iter()
+            .map(|(font_id, [start, end], baseline)| {
+                let v_metrics = self.layouter.fonts[font_id].v_metrics(style.size);
+                let mut rect = Rect {
+                    min: point(start - pad_x, baseline - v_metrics.ascent - pad_y),
+                    max: point(end + pad_x, baseline - v_metrics.descent + pad_y),
+                };
+                if snap {
+                    rect.min = point(rect.min.x.round(), rect.min.y.round());
+                    rect.max = point(rect.max.x.round(), rect.max.y.round());
+                }
+                rect
+            })
+            .collect()
+    }
+
+    pub fn queue_text(&mut self, x: f32, y: f32, size: f32, color: [f32; 4], text: &str) {
+        self.queue_text_styled(x, y, &TextStyle::new(size, color), text);
+    }
+
+    pub fn queue_text_styled(&mut self, x: f32, y: f32, style: &TextStyle, text: &str) {
+        self.from_origin(y, |draw_text| {
+            draw_text.queue_lines(x, y, style, text, vec![])
+        });
+    }
+
+    // text filled with texture where its glyphs cover, e.g. a gold pattern on a title.
+    // the texture is stretched over the glyphs and decorations and multiplied with the
+    // text's color, shadows and outlines stay solid. the gpu-driven path draws the
+    // text in its color only
+    pub fn queue_text_textured(
+        &mut self,
+        x: f32,
+        y: f32,
+        style: &TextStyle,
+        text: &str,
+        texture: Arc<ImageView>,
+    ) {
+        self.queue_text_styled(x, y, style, text);
+        let text = self.texts.last_mut().unwrap();
+        let mut hasher = DefaultHasher::new();
+        text.hash.hash(&mut hasher);
+        (Arc::as_ptr(&texture) as *const () as usize).hash(&mut hasher);
+        text.hash = hasher.finish();
+        text.texture = Some(texture);
+    }
+
+    // registers a fragment shader for queue_text_with_effect, built into a variant of
+    // the text pipeline for every target it draws to. it gets the inputs of
+    // fragment.glsl, with the glyph atlas at set 0 binding 0, and v_fill_uv at
+    // location 3 running 0 to 1 over the text like a fill texture. params come in
+    // as a push constant block of vec4 params[4], which the shader may leave out.
+    // the pipeline is built right away, for the swapchain target on a DrawText that
+    // has one, so a shader that doesn't fit fails here and not on its first draw
+    pub fn add_effect(
+        &mut self,
+        fragment_shader: Arc<ShaderModule>,
+    ) -> Result<TextEffectId, EffectError> {
+        self.add_effect_for(fragment_shader, None)
+    }
+
+    fn add_effect_for(
+        &mut self,
+        fragment_shader: Arc<ShaderModule>,
+        target: Option<&TextTarget>,
+    ) -> Result<TextEffectId, EffectError> {
+        let base = match target {
+            Some(target) => target.pipeline().clone(),
+            None => {
+                let render_pass = compat::color_render_pass(self.device.clone(), TEXTURE_FORMAT);
+                text_pipeline(
+                    self.device.clone(),
+                    Subpass::from(render_pass, 0).unwrap(),
+                    AttachmentBlend::alpha(),
+                    None,
+                )
+            }
+        };
+        let pipeline = effect_pipeline(&base, fragment_shader.clone(), self.shader_reload())?;
+        let effect = TextEffectId(self.effects.len());
+        self.effects.push(fragment_shader);
+        if target.is_some() {
+            self.variant_pipeline(&base, PipelineVariant::Effect(effect), |_| pipeline);
+        }
+        Ok(effect)
+    }
+
+    // text drawn with an effect of add_effect, e.g. a glow or a dissolve. like
+    // queue_text_textured, shadows and outlines stay solid and the gpu-driven path
+    // draws the text in its color only
+    pub fn queue_text_with_effect(
+        &mut self,
+        x: f32,
+        y: f32,
+        style: &TextStyle,
+        text: &str,
+        effect: TextEffectId,
+        params: EffectParams,
+    ) {
+        assert!(effect.0 < self.effects.len(), "unknown text effect");
+        self.queue_text_styled(x, y, style, text);
+        let text = self.texts.last_mut().unwrap();
+        let mut hasher = DefaultHasher::new();
+        text.hash.hash(&mut hasher);
+        effect.hash(&mut hasher);
+        for value in params.iter().flatten() {
+            value.to_bits().hash(&mut hasher);
+        }
+        text.hash = hasher.finish();
+        text.effect = Some((effect, params));
+    }
+
+    // pinned to a point of the screen instead of placed at x, y: anchor picks the
+    // point and the same point of the text's box sits on it, x and y move it away
+    // from the edges. resolved against the target's extent when drawn, so HUD text
+    // stays in place through resizes
+    pub fn queue_text_anchored(
+        &mut self,
+        anchor: Anchor,
+        x: Offset,
+        y: Offset,
+        style: &TextStyle,
+        text: &str,
+    ) {
+        // laid out with the box's anchor point at 0, 0
+        let layout = self.layout_text(style, text);
+        let [fx, fy] = anchor.factors();
+        let origin_x = -fx * layout.width();
+        let origin_y = -layout.line_top(0) - fy * layout.height();
+        let anchor = TextAnchor {
+            anchor,
+            offset: [x, y],
+            applied: [0.0, 0.0],
+        };
+        self.push_lines(origin_x, origin_y, style, text, vec![], Some(anchor), &[]);
+    }
+
+    // a label that is drawn every frame without being queued again, until it's
+    // removed. it's laid out again by itself when the scale factor or the target's
+    // extent change, anchored ones move with the target's size
+    pub fn add_retained(
+        &mut self,
+        placement: Placement,
+        style: &TextStyle,
+        text: &str,
+    ) -> RetainedTextId {
+        let id = RetainedTextId(self.next_retained_id);
+        self.next_retained_id += 1;
+        self.retained
+            .push((id, RetainedText::new(placement, style, text)));
+        self.retained_version += 1;
+        id
+    }
+
+    // like add_retained with the palette's style of that name, or the default style when
+    // there's none. set_palette recolors it without laying it out again
+    pub fn add_retained_themed(
+        &mut self,
+        placement: Placement,
+        name: &str,
+        text: &str,
+    ) -> RetainedTextId {
+        let style = self.palette.style(name).cloned().unwrap_or_default();
+        let id = self.add_retained(placement, &style, text);
+        self.update_retained(id, |retained| retained.theme = Some(name.to_owned()));
+        id
+    }
+
+    pub fn remove_retained(&mut self, id: RetainedTextId) {
+        self.retained.retain(|(other, _)| *other != id);
+        self.retained_version += 1;
+    }
+
+    pub fn clear_retained(&mut self) {
+        self.retained.clear();
+        self.retained_version += 1;
+    }
+
+    pub fn set_retained_text(&mut self, id: RetainedTextId, text: &str) {
+        self.update_retained(id, |retained| retained.text = text.to_owned());
+    }
+
+    // a themed text keeps this style from now on, whatever the palette
+    pub fn set_retained_style(&mut self, id: RetainedTextId, style: &TextStyle) {
+        self.update_retained(id, |retained| {
+            retained.style = style.clone();
+            retained.theme = None;
+        });
+    }
+
+    pub fn set_retained_placement(&mut self, id: RetainedTextId, placement: Placement) {
+        self.update_retained(id, |retained| retained.placement = placement);
+    }
+
+    fn update_retained(&mut self, id: RetainedTextId, update: impl FnOnce(&mut RetainedText)) {
+        let (_, retained) = self
+            .retained
+            .iter_mut()
+            .find(|(other, _)| *other == id)
+            .expect("no retained text with this id");
+        update(retained);
+        retained.laid_out = None;
+        self.retained_version += 1;
+    }
+
+    // appends the retained texts to the queue once per frame, reusing their layouts
+    // when nothing they depend on changed. themed texts get the palette's colors
+    pub(crate) fn queue_retained(&mut self) {
+        if !self.retained_queued {
+            self.queue_retained_texts();
+            self.retained_queued = true;
+        }
+        self.resolve_themes();
+    }
+
+    fn queue_retained_texts(&mut self) {
+        let key = LayoutKey {
+            scale_factor: self.layouter.scale_factor,
+            extent: self.extent,
+        };
+        let version = self.palette_version;
+        let mut retained = mem::take(&mut self.retained);
+        for (_, text) in &mut retained {
+            // a new palette only recolors the kept layout, unless the style it has for
+            // the text is laid out differently
+            if let Some(name) = &text.theme {
+                let style = self.palette.style(name).cloned().unwrap_or_default();
+                let same_layout = layout_hash(&style) == layout_hash(&text.style);
+                if let Some((_, data)) = &mut text.laid_out {
+                    if !matches!(&data.theme, Some((_, resolved)) if *resolved == version) {
+                        if same_layout {
+                            data.recolor(&style);
+                            data.theme = Some((name.clone(), version));
+                        } else {
+                            text.laid_out = None;
+                        }
+                    }
+                }
+                text.style = style;
+            }
+            match &text.laid_out {
+                Some((laid_out, data)) if *laid_out == key => {
+                    if data.in_atlas() {
+                        for (font_id, glyph) in &data.glyphs {
+                            self.cache
+                                .queue_glyph(*font_id, *glyph, &data.style.variations);
+                        }
+                    }
+                    self.texts.push(data.clone());
+                }
+                _ => {
+                    match text.placement {
+                        Placement::At(x, y) => {
+                            self.queue_text_styled(x, y, &text.style, &text.text)
+                        }
+                        Placement::Anchored(anchor, x, y) => {
+                            self.queue_text_anchored(anchor, x, y, &text.style, &text.text)
+                        }
+                    }
+                    let data = self.texts.last_mut().unwrap();
+                    data.retained = true;
+                    data.theme = text.theme.clone().map(|name| (name, version));
+                    text.laid_out = Some((key, data.clone()));
+                }
+            }
+        }
+        self.retained = retained;
+    }
+
+    // moves anchored texts to where their anchors land on a target of this extent
+    pub(crate) fn resolve_anchors(&mut self, extent: [u32; 2]) {
+        let screen = self.laid_out_screen([extent[0] as f32, extent[1] as f32]);
+        let scale = self.layouter.scale_factor as f32;
+        for text in &mut self.texts {
+            let anchor = match text.anchor {
+                Some(anchor) => anchor,
+                None => continue,
+            };
+            let position = anchor.position(screen, scale);
+            if position == anchor.applied {
+                continue;
+            }
+            text.translate([
+                position[0] - anchor.applied[0],
+                position[1] - anchor.applied[1],
+            ]);
+            text.anchor = Some(TextAnchor {
+                applied: position,
+                ..anchor
+            });
+        }
+    }
+
+    // with the palette's style of that name, or the default style when there's none.
+    // a palette set later that has it recolors the text
+    pub fn queue_text_themed(&mut self, name: &str, x: f32, y: f32, text: &str) {
+        let style = self.palette.style(name).cloned().unwrap_or_default();
+        self.queue_text_styled(x, y, &style, text);
+        self.texts.last_mut().unwrap().theme = Some((name.to_owned(), self.palette_version));
+    }
+
+    pub fn palette(&self) -> &Palette {
+        &self.palette
+    }
+
+    // texts queued with queue_text_themed so far take the colors of their new styles
+    // when they are drawn, without being laid out again. sizes and fonts only change
+    // for texts queued later. retained texts of add_retained_themed are recolored the
+    // same way, and laid out again when their new style's size or font differ
+    pub fn set_palette(&mut self, palette: Palette) {
+        self.palette = palette;
+        self.palette_version += 1;
+    }
+
+    // recolors the themed texts queued with an older palette
+    fn resolve_themes(&mut self) {
+        let version = self.palette_version;
+        for text in &mut self.texts {
+            let style = match &mut text.theme {
+                Some((name, resolved)) if *resolved != version => {
+                    *resolved = version;
+                    self.palette.style(name).cloned()
+                }
+                _ => continue,
+            };
+            if let Some(style) = style {
+                text.recolor(&style);
+            }
+        }
+    }
+
+    // texts are drawn lowest layer first, so tooltips and modals on a higher layer end
+    // up above everything queued below it, whatever the queueing order
+    pub fn queue_text_at_layer(
+        &mut self,
+        layer: i32,
+        x: f32,
+        y: f32,
+        size: f32,
+        color: [f32; 4],
+        text: &str,
+    ) {
+        self.queue_text_styled_at_layer(layer, x, y, &TextStyle::new(size, color), text);
+    }
+
+    pub fn queue_text_styled_at_layer(
+        &mut self,
+        layer: i32,
+        x: f32,
+        y: f32,
+        style: &TextStyle,
+        text: &str,
+    ) {
+        self.queue_text_styled(x, y, &style.clone().with_layer(layer), text);
+    }
+
+    // breaks text into lines no wider than max_width, see wrap
+    pub fn queue_text_wrapped(
+        &mut self,
+        x: f32,
+        y: f32,
+        max_width: f32,
+        style: &TextStyle,
+        text: &str,
+    ) {
+        // before wrapping, which measures all of it
+        let input = self.input(text);
+        let breaks = self.layouter.wrap(style, &input, max_width, &[]);
+        self.from_origin(y, |draw_text| {
+            draw_text.queue_lines(x, y, style, &input, breaks)
+        });
+    }
+
+    // text with icons in it, e.g. "press {key:E} to open" or "{icon:heart} 3". object
+    // gets the name between the braces of every placeholder and returns what to draw
+    // in its place, placeholders it returns None for stay as they are. objects take
+    // room like glyphs and wrap with the text when max_width is set. the gpu-driven
+    // path and generate_quads leave them out
+    pub fn queue_text_inline(
+        &mut self,
+        x: f32,
+        y: f32,
+        max_width: Option<f32>,
+        style: &TextStyle,
+        text: &str,
+        object: impl FnMut(&str) -> Option<InlineObject>,
+    ) {
+        let (text, objects) = inline::resolve(text, object);
+        let input = self.input(&text);
+        let breaks = match max_width {
+            Some(max_width) => {
+                self.layouter
+                    .wrap(style, &input, max_width, &inline::aspects(&objects))
+            }
+            None => vec![],
+        };
+        self.from_origin(y, |draw_text| {
+            draw_text.push_lines(x, y, style, &input, breaks, None, &objects)
+        });
+    }
+
+    // a little markdown for help screens and tooltips: **bold**, *italic*, `code`,
+    // # headings and [links](url), one line per source line and no wrapping. the
+    // style's max_width applies to every line. returns where the links ended up, for
+    // hit testing clicks
+    pub fn queue_markdown(
+        &mut self,
+        x: f32,
+        y: f32,
+        base_style: &TextStyle,
+        source: &str,
+    ) -> Vec<MarkdownLink> {
+        let links = self.from_origin(y, |draw_text| {
+            draw_text.queue_markdown_lines(x, y, base_style, source)
+        });
+        links
+            .into_iter()
+            .map(|link| MarkdownLink {
+                rect: self.flip_rect(y, link.rect),
+                ..link
+            })
+            .collect()
+    }
+
+    fn queue_markdown_lines(
+        &mut self,
+        x: f32,
+        y: f32,
+        base_style: &TextStyle,
+        source: &str,
+    ) -> Vec<MarkdownLink> {
+        let mut links = vec![];
+        let mut baseline = y;
+        let mut last_size = None;
+        for line in markdown::parse(source) {
+            let size = base_style.size * line.scale();
+            // far enough apart for the bigger of two neighbouring lines
+            if let Some(last_size) = last_size {
+                baseline += self.layouter.line_height(size.max(last_size));
+            }
+            last_size = Some(size);
+
+            let mut line_style = base_style.clone();
+            line_style.size = size;
+            if line.heading > 0 {
+                line_style.weight = BOLD_WEIGHT;
+            }
+            let mut urls = vec![];
+            let mut spans = vec![];
+            for span in line.spans {
+                let mut style = line_style.clone();
+                if span.bold {
+                    style.weight = BOLD_WEIGHT;
+                }
+                style.italic |= span.italic;
+                if span.code {
+                    style.font = self.code_font.or(style.font);
+                    style.background = Some(CODE_BACKGROUND);
+                }
+                if span.link.is_some() {
+                    let [r, g, b] = LINK_COLOR;
+                    style.color = [r, g, b, style.color[3]];
+                    style.decorations = Decorations {
+                        underline: true,
+                        ..style.decorations
+                    };
+                }
+                urls.push(span.link);
+                spans.push((style, span.text));
+            }
+            let role = if line.heading > 0 {
+                TextRole::Heading
+            } else {
+                self.role
+            };
+            let rects = self.with_role(role, |draw_text| {
+                draw_text.queue_spans(x, baseline, base_style.max_width, &spans)
+            });
+            for (url, rect) in urls.into_iter().zip(rects) {
+                if let Some(url) = url {
+                    links.push(MarkdownLink { url, rect });
+                }
+            }
+        }
+        links
+    }
+
+    // text with SGR escape sequences, colors, bold, italic, underline and the like, as
+    // terminals and loggers print it. styles carry over from line to line, and the
+    // style's max_width applies to every line
+    pub fn queue_ansi(&mut self, x: f32, y: f32, style: &TextStyle, text: &str) {
+        let line_height = self.layouter.line_height(style.size);
+        self.from_origin(y, |draw_text| {
+            for (i, spans) in ansi::parse(text, style).into_iter().enumerate() {
+                let baseline = y + i as f32 * line_height;
+                draw_text.queue_spans(x, baseline, style.max_width, &spans);
+            }
+        });
+    }
+
+    // differently styled texts one after the other on a line, clipped together at
+    // max_width. returns the line box of every span that got queued
+    fn queue_spans(
+        &mut self,
+        x: f32,
+        y: f32,
+        max_width: Option<f32>,
+        spans: &[(TextStyle, String)],
+    ) -> Vec<Rect<f32>> {
+        let mut rects = vec![];
+        let mut caret = x;
+        for (style, text) in spans {
+            let mut style = style.clone();
+            if let Some(max_width) = max_width {
+                if caret - x >= max_width {
+                    break;
+                }
+                style.max_width = Some(max_width - (caret - x));
+            }
+            let layout = self.layout_text(&style, text);
+            self.queue_text_styled(caret, y, &style, text);
+            let top = y + layout.line_top(0);
+            rects.push(Rect {
+                min: point(caret, top),
+                max: point(caret + layout.width(), top + layout.line_height()),
+            });
+            caret += layout.width();
+        }
+        rects
+    }
+
+    fn queue_lines(&mut self, x: f32, y: f32, style: &TextStyle, text: &str, breaks: Vec<usize>) {
+        self.push_lines(x, y, style, text, breaks, None, &[]);
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn push_lines(
+        &mut self,
+        x: f32,
+        y: f32,
+        style: &TextStyle,
+        text: &str,
+        mut breaks: Vec<usize>,
+        anchor: Option<TextAnchor>,
+        objects: &[Option<InlineObject>],
+    ) {
+        let input = self.input(text);
+        let text = input.as_ref();
+        breaks.retain(|&at| at < text.len());
+        let truncated;
+        // texts with objects get clipped instead, cutting them short would leave
+        // objects without their placeholders
+        let shown = match (style.max_width, style.overflow) {
+            (Some(max_width), Overflow::Ellipsis | Overflow::MiddleEllipsis)
+                if breaks.is_empty() && objects.is_empty() =>
+            {
+                truncated = self.layouter.truncate(style, text, max_width);
+                truncated.as_str()
+            }
+            _ => text,
+        };
+        // physical pixels from here on
+        let scale = self.layouter.scale_factor as f32;
+        let (x, y) = (x * scale, y * scale);
+        let style = &style.scaled(scale);
+        let snap = self.layouter.snaps(style);
+        // ellipsis modes clip too, for labels too narrow for even the ellipsis
+        let (edge, axis) = if style.is_vertical() {
+            (y, 1.0)
+        } else {
+            (x, 0.0)
+        };
+        let fade = match (style.max_width, style.overflow) {
+            (Some(max_width), Overflow::Fade) => {
+                let end = edge + max_width;
+                [end - style.size.min(max_width / 2.0), end, axis]
+            }
+            (Some(max_width), _) => [edge + max_width, edge + max_width, axis],
+            (None, _) => NO_FADE,
+        };
+        let (glyphs, rotated) = self.cached_layout(shown, style, point(x, y), &breaks, objects);
+        let (lines, background) = if style.is_vertical() {
+            (vec![], vec![])
+        } else {
+            (
+                self.decoration_lines(&glyphs, style, snap),
+                self.background_rects(&glyphs, style, snap),
+            )
+        };
+        let mut hasher = DefaultHasher::new();
+        x.to_bits().hash(&mut hasher);
+        y.to_bits().hash(&mut hasher);
+        style.hash_into(&mut hasher);
+        text.hash(&mut hasher);
+        breaks.hash(&mut hasher);
+        let objects = if objects.iter().any(Option::is_some) {
+            self.place_objects(shown, style, point(x, y), &breaks, objects)
+        } else {
+            vec![]
+        };
+        for (_, object) in &objects {
+            (Arc::as_ptr(&object.texture) as *const () as usize).hash(&mut hasher);
+            object.uv_rect.map(f32::to_bits).hash(&mut hasher);
+            object.tinted.hash(&mut hasher);
+        }
+        let mut text = TextData {
+            glyphs,
+            rotated,
+            style: style.clone(),
+            lines,
+            background,
+            atlas: None,
+            fade,
+            hash: hasher.finish(),
+            queued_hash: None,
+            ink: None,
+            theme: None,
+            texture: None,
+            effect: None,
+            objects,
+            objects_behind: false,
+            anchor: anchor.or(self.flip),
+            retained: false,
+            label: self.labels.then(|| (text.to_owned(), self.role)),
+        };
+        self.shrink_tiny(&mut text, snap);
+        text.ink = text.ink_bounds();
+        // glyphs off the target never get rasterized, e.g. the scrolled away lines of
+        // a long wrapped log. decorations and backgrounds were laid out already.
+        // anchored texts only find their place when drawn
+        if let (None, Some(viewport)) = (text.anchor, self.viewport()) {
+            let (glyphs, rotated) = text
+                .glyphs
+                .iter()
+                .zip(&text.rotated)
+                .filter(|((_, g), rotated)| text.glyph_visible(g, **rotated, &viewport))
+                .map(|(glyph, &rotated)| (*glyph, rotated))
+                .unzip();
+            text.glyphs = glyphs;
+            text.rotated = rotated;
+        }
+        if text.in_atlas() {
+            for (font_id, glyph) in &text.glyphs {
+                self.cache.queue_glyph(*font_id, *glyph, &style.variations);
+            }
+        }
+        self.texts.push(text);
+    }
+
+    // the swapchain's extent in physical pixels. without one nothing is culled when
+    // queueing, text can be drawn into targets of any size
+    fn viewport(&self) -> Option<Rect<f32>> {
+        let [width, height] = self.extent?;
+        Some(Rect {
+            min: point(0.0, 0.0),
+            max: point(width as f32, height as f32),
+        })
+    }
+
+    // draws the glyph atlas itself over dark squares at x, y, its pages size wide next
+    // to each other, for tuning the atlas size and spotting cache thrashing. queued like
+    // text, so it only shows up in frames it was queued for
+    pub fn queue_atlas_overlay(&mut self, x: f32, y: f32, size: f32) {
+        let scale = self.layouter.scale_factor as f32;
+        let pages = self.cache.pages() as f32;
+        let rect = Rect {
+            min: point(x * scale, y * scale),
+            max: point((x + size * pages) * scale, (y + size) * scale),
+        };
+        let mut hasher = DefaultHasher::new();
+        for value in [rect.min.x, rect.min.y, rect.max.x, rect.max.y] {
+            value.to_bits().hash(&mut hasher);
+        }
+        self.atlas_version.hash(&mut hasher);
+        let anchor = self.from_origin(y, |draw_text| draw_text.flip);
+        self.texts.push(TextData {
+            glyphs: vec![],
+            rotated: vec![],
+            style: TextStyle::new(0.0, [1.0, 1.0, 1.0, 1.0]),
+            lines: vec![],
+            background: vec![],
+            atlas: Some(rect),
+            fade: NO_FADE,
+            hash: hasher.finish(),
+            queued_hash: None,
+            ink: None,
+            theme: None,
+            texture: None,
+            effect: None,
+            objects: vec![],
+            objects_behind: false,
+            anchor,
+            retained: false,
+            label: None,
+        });
+    }
+
+    // the glyph atlas with what's queued rasterized into it, to be loaded again with
+    // load_atlas. e.g. queue a hud's digits and letters at the sizes they're drawn at
+    // in a build step and ship the file, so they never get rasterized on startup. glyphs
+    // async rasterization hasn't finished yet are left out
+    pub fn save_atlas(&mut self) -> Vec<u8> {
+        self.update_cache();
+        self.cache
+            .save(&self.layouter.fonts, &self.cache_pixel_buffer)
+    }
+
+    // replaces the glyph atlas with a saved one. false, with nothing changed, when data
+    // isn't one or was saved with other fonts, fonts added in another order, another
+    // quality or atlas settings. fonts are still needed for the layout
+    pub fn load_atlas(&mut self, data: &[u8]) -> bool {
+        let pixels = match self.cache.load(&self.layouter.fonts, data) {
+            Some(pixels) => pixels,
+            None => return false,
+        };
+        self.cache_pixel_buffer = pixels;
+        self.atlas_version += 1;
+        // the frames' images get the whole atlas again
+        self.frames.clear();
+        self.atlas_dirty.clear();
+        self.atlas_dirty_since = self.atlas_version;
+        self.drawn_hash = None;
+        true
+    }
+
+    pub fn cache_stats(&self) -> CacheStats {
+        CacheStats {
+            used_area_pct: self.cache.used_area_pct(),
+            pages: self.cache.pages(),
+            glyphs_resident: self.cache.glyphs_resident(),
+            evictions_last_frame: self.last_frame.evictions,
+            uploads_bytes_last_frame: self.last_frame.upload_bytes,
+            dropped_glyphs_last_frame: self.last_frame.dropped_glyphs,
+            clipped_chars_last_frame: self.last_frame.clipped_chars,
+            utilization_pct: self
+                .cache
+                .utilization_pct(self.atlas_defragment.unwrap_or_default().max_idle_frames),
+        }
+    }
+
+    // hash of everything queued so far this frame
+    pub fn content_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        for text in self.texts.iter().filter(|text| !text.retained) {
+            text.queued_hash.unwrap_or(text.hash).hash(&mut hasher);
+        }
+        self.retained_version.hash(&mut hasher);
+        self.palette_version.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    // whether the queued text differs from what was last drawn, applications
+    // can skip the frame (and call clear) when it doesn't
+    pub fn is_dirty(&self) -> bool {
+        self.drawn_hash != Some(self.content_hash())
+    }
+
+    // for animated text (blinking, tickers, ...) that needs a frame at a certain
+    // time even when nothing else changes, the earliest request wins
+    pub fn schedule_redraw(&mut self, at: Instant) {
+        self.redraw_at = Some(self.redraw_at.map_or(at, |current| current.min(at)));
+    }
+
+    pub fn schedule_redraw_in(&mut self, delay: Duration) {
+        self.schedule_redraw(Instant::now() + delay);
+    }
+
+    // when the next animation frame is due, None if nothing is animating. applications
+    // using ControlFlow::Wait can hand this to ControlFlow::WaitUntil
+    pub fn next_redraw(&self) -> Option<Instant> {
+        self.redraw_at
+    }
+
+    // the rect around everything on screen that changed in the frames drawn since the
+    // last call, in physical pixels: texts that appeared, moved, changed or went away.
+    // for partial presentation, e.g. the rects of VK_KHR_incremental_present. None
+    // when nothing changed
+    pub fn take_damage(&mut self) -> Option<Rect<f32>> {
+        self.damage.take()
+    }
+
+    // whether rendering a frame now would change anything on screen
+    pub fn needs_redraw(&self, now: Instant) -> bool {
+        self.is_dirty() || self.redraw_at.map_or(false, |at| at <= now)
+    }
+
+    pub fn clear(&mut self) {
+        self.texts.clear();
+        self.retained_queued = false;
+    }
+
+    // drawing inside the caller's own subpass instead of a render pass of DrawText's:
+    // uploads the glyph atlas, has to be recorded before the caller begins the render
+    // pass containing subpass
+    pub fn prepare_text(
+        &mut self,
+        command_buffer: &mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>,
+        memory_allocator: &Arc<StandardMemoryAllocator>,
+        subpass: &Subpass,
+    ) -> PreparedText {
+        let pipeline = self.subpass_pipeline(subpass);
+        self.queue_retained();
+        let set = self.upload_atlas(command_buffer, memory_allocator, &pipeline, None);
+        PreparedText {
+            subpass: subpass.clone(),
+            pipeline,
+            set,
+        }
+    }
+
+    // draws the queue inside the subpass prepared was made for, extent is the size of
+    // the framebuffer. the caller's viewport and scissor are overwritten
+    pub fn draw_text_in_subpass<'a>(
+        &mut self,
+        command_buffer: &'a mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>,
+        memory_allocator: &Arc<StandardMemoryAllocator>,
+        prepared: PreparedText,
+        extent: [u32; 2],
+    ) -> &'a mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer> {
+        let PreparedText {
+            subpass,
+            pipeline,
+            set,
+        } = prepared;
+        self.resolve_anchors(extent);
+        let vertices = self.text_vertices(
+            self.visible_texts(TargetKind::Present),
+            [extent[0] as f32, extent[1] as f32],
+            self.color_output(compat::linear_output(&subpass)),
+        );
+        // no copies inside the caller's render pass
+        let vertices = self.upload_text_vertices(None, memory_allocator, vertices);
+        compat::begin_label(command_buffer, "font-render text");
+        compat::set_viewport_scissor(command_buffer, extent, None);
+        self.draw_vertices(command_buffer, &pipeline, &set, vertices);
+        compat::end_label(command_buffer);
+        self.finish_frame();
+        command_buffer
+    }
+
+    pub fn draw_into<'a>(
+        &mut self,
+        command_buffer: &'a mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>,
+        memory_allocator: &Arc<StandardMemoryAllocator>,
+        target: &TextTarget,
+        image_num: usize,
+    ) -> &'a mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer> {
+        let command_buffer = self.record_text(command_buffer, memory_allocator, target, image_num);
+        self.finish_frame();
+        command_buffer
+    }
+
+    // for engines recording their frame into secondary command buffers, possibly on
+    // several threads. the atlas upload is recorded into upload_buffer, which has to
+    // run outside of the render pass before the returned buffer is executed in subpass
+    pub fn draw_text_secondary(
+        &mut self,
+        upload_buffer: &mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>,
+        command_buffer_allocator: &StandardCommandBufferAllocator,
+        memory_allocator: &Arc<StandardMemoryAllocator>,
+        subpass: Subpass,
+        extent: [u32; 2],
+    ) -> SecondaryCommandBuffer {
+        let pipeline = self.subpass_pipeline(&subpass);
+        self.queue_retained();
+        let set = self.upload_atlas(upload_buffer, memory_allocator, &pipeline, None);
+        self.resolve_anchors(extent);
+        let vertices = self.text_vertices(
+            self.visible_texts(TargetKind::Present),
+            [extent[0] as f32, extent[1] as f32],
+            self.color_output(compat::linear_output(&subpass)),
+        );
+        let vertices = self.upload_text_vertices(Some(upload_buffer), memory_allocator, vertices);
+
+        let mut builder = AutoCommandBufferBuilder::secondary(
+            command_buffer_allocator,
+            self.queue.queue_family_index(),
+            CommandBufferUsage::OneTimeSubmit,
+            CommandBufferInheritanceInfo {
+                render_pass: Some(subpass.into()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        compat::begin_label(&mut builder, "font-render text");
+        compat::set_viewport_scissor(&mut builder, extent, None);
+        self.draw_vertices(&mut builder, &pipeline, &set, vertices);
+        compat::end_label(&mut builder);
+
+        self.finish_frame();
+        builder.build().unwrap()
+    }
+
+    // the pipeline holds on to the render pass, so its address can't be reused while
+    // it is cached
+    fn subpass_pipeline(&mut self, subpass: &Subpass) -> Arc<GraphicsPipeline> {
+        let key = (Arc::as_ptr(subpass.render_pass()) as usize, subpass.index());
+        let frame = self.frame;
+        if let Some((pipeline, used)) = self.subpass_pipelines.get_mut(&key) {
+            *used = frame;
+            return pipeline.clone();
+        }
+
+        let pipeline = text_pipeline(
+            self.device.clone(),
+            subpass.clone(),
+            AttachmentBlend::alpha(),
+            self.shader_reload(),
+        );
+        // render passes of the caller's that weren't drawn in for a while are most
+        // likely gone, and only their pipelines would keep them alive
+        self.subpass_pipelines
+            .retain(|_, (_, used)| frame - *used < SUBPASS_PIPELINE_IDLE_FRAMES);
+        self.subpass_pipelines
+            .insert(key, (pipeline.clone(), frame));
+        pipeline
+    }
+
+    // same as draw_into but keeps the queue, so a capture can be taken
+    // before the queued text is presented
+    pub fn capture_text_into<'a>(
+        &mut self,
+        command_buffer: &'a mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>,
+        memory_allocator: &Arc<StandardMemoryAllocator>,
+        target: &TextTarget,
+        image_num: usize,
+    ) -> &'a mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer> {
+        self.record_text(command_buffer, memory_allocator, target, image_num)
+    }
+
+    // draws the queue into a new transparent RGBA texture of the given size, for
+    // sampling in the caller's own pipelines (ui panels, decals on meshes, ...).
+    // the color is premultiplied by alpha, and the texture is only ready once the
+    // command buffer has executed
+    pub fn render_to_texture(
+        &mut self,
+        command_buffer: &mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>,
+        memory_allocator: &Arc<StandardMemoryAllocator>,
+        extent: [u32; 2],
+    ) -> Arc<ImageView> {
+        self.render_texture(command_buffer, memory_allocator, extent)
+            .1
+    }
+
+    // draws the queue into a new image like render_to_texture and reads it back, for
+    // tools without a present loop: video overlays, screenshots for documentation.
+    // tightly packed RGBA rows with straight alpha, the gpu is waited for here
+    pub fn render_to_rgba_bytes(
+        &mut self,
+        memory_allocator: &Arc<StandardMemoryAllocator>,
+        command_buffer_allocator: &StandardCommandBufferAllocator,
+        extent: [u32; 2],
+    ) -> Vec<u8> {
+        let mut builder = AutoCommandBufferBuilder::primary(
+            command_buffer_allocator,
+            self.queue.queue_family_index(),
+            CommandBufferUsage::OneTimeSubmit,
+        )
+        .unwrap();
+        let (image, _) = self.render_texture(&mut builder, memory_allocator, extent);
+        let buffer = copy_image_to_host(&mut builder, memory_allocator, image);
+        submit_and_wait(&self.queue, builder);
+
+        let mut pixels = buffer.read().unwrap().to_vec();
+        for pixel in pixels.chunks_exact_mut(4) {
+            let alpha = pixel[3] as u32;
+            if alpha > 0 {
+                for channel in &mut pixel[..3] {
+                    *channel = (*channel as u32 * 255 / alpha).min(255) as u8;
+                }
+            }
+        }
+        pixels
+    }
+
+    fn render_texture(
+        &mut self,
+        command_buffer: &mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>,
+        memory_allocator: &Arc<StandardMemoryAllocator>,
+        extent: [u32; 2],
+    ) -> (Arc<Image>, Arc<ImageView>) {
+        let image = compat::render_image(memory_allocator, TEXTURE_FORMAT, extent);
+        compat::name_image(&image, "font-render texture");
+        let view = compat::view(image.clone());
+        let views = vec![view.clone()];
+
+        // render pass and pipeline only depend on the format, so they are kept around
+        let target = match &self.texture_target {
+            Some(target) => target.retarget(views),
+            None => TextTarget::with_blend(
+                self.device.clone(),
+                TargetKind::Present,
+                TEXTURE_FORMAT,
+                views,
+                compat::premultiplied_blend(),
+            ),
+        };
+
+        command_buffer
+            .clear_color_image(ClearColorImageInfo {
+                clear_value: ClearColorValue::Float([0.0; 4]),
+                ..ClearColorImageInfo::image(image.clone())
+            })
+            .unwrap();
+        self.draw_into(command_buffer, memory_allocator, &target, 0);
+        self.texture_target = Some(target);
+        (image, view)
+    }
+
+    // the queue as plain quads in drawing order, for drawing text with your own
+    // pipeline, together with the atlas they sample: an R8 array image with one layer
+    // per page. nothing is recorded into your command buffers, the atlas is uploaded
+    // and waited for here. when glyphs were added since the last call it's a new
+    // image, a copy of the one returned before with the new glyphs uploaded into it,
+    // and the old one stays as it was. fill textures are left out
+    pub fn generate_quads(
+        &mut self,
+        memory_allocator: &Arc<StandardMemoryAllocator>,
+        command_buffer_allocator: &StandardCommandBufferAllocator,
+        image_extent: [u32; 2],
+    ) -> (Vec<GlyphQuad>, Arc<ImageView>) {
+        self.queue_retained();
+        self.update_cache();
+        if self.defragment_atlas().is_some() {
+            self.frames.clear();
+        }
+        let atlas = match &self.quads_atlas {
+            Some((slot, atlas)) if slot.version() == Some(self.atlas_version) => atlas.clone(),
+            _ => {
+                let pages = self.cache.pages();
+                let mut builder = AutoCommandBufferBuilder::primary(
+                    command_buffer_allocator,
+                    self.queue.queue_family_index(),
+                    CommandBufferUsage::OneTimeSubmit,
+                )
+                .unwrap();
+                // the image handed out before stays as it was, the new one starts as a
+                // copy of it and only gets the rects rasterized since
+                let dirty_since = self.dirty_since();
+                let mut slot = match &self.quads_atlas {
+                    Some((slot, _)) if slot.version() >= Some(dirty_since) => slot.grown(
+                        &mut builder,
+                        memory_allocator,
+                        &self.cache_pixel_buffer,
+                        pages,
+                    ),
+                    _ => FrameSlot::new(
+                        memory_allocator,
+                        [CACHE_WIDTH as u32, CACHE_HEIGHT as u32],
+                        pages,
+                    ),
+                };
+                self.recording.upload_bytes += slot.upload(
+                    &mut builder,
+                    &AtlasPixels {
+                        pixels: &self.cache_pixel_buffer,
+                        extent: [CACHE_WIDTH as u32, CACHE_HEIGHT as u32],
+                        pages,
+                        version: self.atlas_version,
+                        dirty: &self.atlas_dirty,
+                        dirty_since,
+                    },
+                );
+                submit_and_wait(&self.queue, builder);
+
+                let atlas = compat::array_view(slot.atlas());
+                self.quads_atlas = Some((slot, atlas.clone()));
+                self.trim_atlas_dirty();
+                atlas
+            }
+        };
+
+        let quads = self.quads(image_extent);
+        self.finish_frame();
+        (quads, atlas)
+    }
+
+    // draws the queue with a backend other than this renderer's own pipelines, see
+    // TextGpuBackend. call the backend's draw inside your render pass afterwards
+    pub fn upload_to<B: TextGpuBackend>(
+        &mut self,
+        backend: &mut B,
+        upload: &mut B::Upload<'_>,
+        image_extent: [u32; 2],
+    ) {
+        self.queue_retained();
+        self.update_cache();
+        if self.defragment_atlas().is_some() {
+            self.frames.clear();
+        }
+        backend.upload_atlas(
+            upload,
+            &AtlasPixels {
+                pixels: &self.cache_pixel_buffer,
+                extent: [CACHE_WIDTH as u32, CACHE_HEIGHT as u32],
+                pages: self.cache.pages(),
+                version: self.atlas_version,
+                dirty: &self.atlas_dirty,
+                dirty_since: self.dirty_since(),
+            },
+        );
+        self.trim_atlas_dirty();
+        let quads = self.quads(image_extent);
+        backend.upload_quads(upload, &quads, image_extent);
+        self.finish_frame();
+    }
+
+    fn quads(&mut self, image_extent: [u32; 2]) -> Vec<GlyphQuad> {
+        self.resolve_anchors(image_extent);
+        let screen = [image_extent[0] as f32, image_extent[1] as f32];
+        self.text_instances(
+            self.visible_texts(TargetKind::Present),
+            screen,
+            true,
+            // other backends blend as they like
+            ColorOutput {
+                linear: false,
+                premultiplied: false,
+            },
+        )
+        .into_iter()
+        .flat_map(|(passes, _, _)| passes)
+        .flatten()
+        .map(|instance| instance.quad(screen))
+        .collect()
+    }
+
+    // remembers what was drawn for is_dirty and empties the queue
+    pub(crate) fn finish_frame(&mut self) {
+        let bounds = visible_bounds(self);
+        let mut changed = changed_bounds(&self.drawn_bounds, &bounds);
+        if self.damage_all {
+            changed.extend(bounds.iter().map(|(_, bounds)| *bounds));
+        }
+        self.damage = changed.into_iter().chain(self.damage).reduce(union);
+        self.drawn_bounds = bounds;
+        if self.labels {
+            self.drawn_labels = self
+                .visible_texts(TargetKind::Present)
+                .filter_map(|text| {
+                    let (string, role) = text.label.clone()?;
+                    Some((string, role, self.stretched(text.bounds()?)))
+                })
+                .collect();
+        }
+        let pending = self.cache.pending() + self.msdf_atlas.pending();
+        self.damage_all = pending > 0;
+        self.drawn_hash = Some(self.content_hash());
+        // stretched text stays dirty until a frame after the zoom stopped lays it out
+        // sharp
+        let zooming = mem::take(&mut self.zooming);
+        if self.view_stretch() != 1.0 {
+            if !zooming {
+                self.layouter.set_view_scale(self.view_scale);
+                self.damage_all = true;
+            }
+            self.drawn_hash = None;
+        }
+        self.texts.clear();
+        self.retained_queued = false;
+        self.frame += 1;
+        self.last_frame = mem::take(&mut self.recording);
+        // animations schedule their next frame again while they are running
+        if self.redraw_at.map_or(false, |at| at <= Instant::now()) {
+            self.redraw_at = None;
+        }
+        // glyphs still on the rasterizer threads, the gpu or the msdf generator are
+        // missing from this frame
+        if pending > 0 {
+            self.drawn_hash = None;
+            self.schedule_redraw(Instant::now());
+        }
+        #[cfg(feature = "shader_reload")]
+        self.reload_shaders();
+    }
+
+    // how many frames the application records ahead of the gpu for each target, each
+    // one gets its own copy of the glyph atlas. more frames in flight than this in any
+    // target make submitting fail
+    pub fn set_frames_in_flight(&mut self, count: usize) {
+        assert!(count > 0, "at least one frame has to be in flight");
+        self.frames_in_flight = count;
+        self.frames.clear();
+        #[cfg(feature = "gpu_timing")]
+        if self.gpu_timer.is_some() {
+            self.gpu_timer = GpuTimer::new(&self.queue, count);
+        }
+    }
+
+    pub fn frames_in_flight(&self) -> usize {
+        self.frames_in_flight
+    }
+
+    // overrides the quality preset's filter until the next set_quality,
+    // e.g. Nearest for pixel fonts
+    pub fn set_filter(&mut self, filter: Filter) {
+        self.filter = filter;
+        self.rebuild_sampler();
+    }
+
+    // ClampToEdge by default, Repeat lets linear filtering pull in the opposite
+    // edge of the atlas
+    pub fn set_address_mode(&mut self, address_mode: SamplerAddressMode) {
+        self.address_mode = address_mode;
+        self.rebuild_sampler();
+    }
+
+    fn rebuild_sampler(&mut self) {
+        self.sampler = atlas_sampler(self.device.clone(), self.filter, self.address_mode);
+        for slot in self
+            .frames
+            .values_mut()
+            .flat_map(|frames| &mut frames.slots)
+        {
+            slot.descriptor_set = None;
+        }
+    }
+
+    pub fn vertex_memory(&self) -> VertexMemory {
+        self.vertex_memory
+    }
+
+    pub fn set_vertex_memory(&mut self, memory: VertexMemory) {
+        self.vertex_memory = memory;
+    }
+
+    pub fn memory_budget(&self) -> Option<MemoryBudget> {
+        self.memory_budget
+    }
+
+    // limits the vertex and staging buffers allocated while a frame is recorded, for
+    // devices short on memory. None allocates whatever the text on screen needs
+    pub fn set_memory_budget(&mut self, budget: Option<MemoryBudget>) {
+        self.memory_budget = budget;
+    }
+
+    // the last frame went over its budget with OverBudget::Fail and some of its text
+    // wasn't drawn
+    pub fn budget_error(&self) -> Option<OverBudgetError> {
+        self.last_frame.budget_error
+    }
+
+    pub fn memory_stats(&self) -> MemoryStats {
+        MemoryStats {
+            vertex_bytes_last_frame: self.last_frame.vertex_bytes,
+            staging_bytes_last_frame: self.last_frame.staging_bytes,
+            buffers_last_frame: self.last_frame.buffers,
+            over_budget_last_frame: self.last_frame.over_budget,
+            atlas_bytes: self
+                .frames
+                .values()
+                .flat_map(|frames| &frames.slots)
+                .map(FrameSlot::bytes)
+                .sum(),
+        }
+    }
+
+    // whether bytes more fit into what's left of the frame's budget
+    pub(crate) fn fits_budget(&self, bytes: u64) -> bool {
+        let allocated = self.recording.vertex_bytes + self.recording.staging_bytes;
+        self.memory_budget
+            .map_or(true, |budget| allocated + bytes <= budget.bytes_per_frame)
+    }
+
+    // upload_vertices with the buffers counted against the memory budget
+    pub(crate) fn upload_text_vertices(
+        &mut self,
+        command_buffer: Option<&mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>>,
+        memory_allocator: &Arc<StandardMemoryAllocator>,
+        vertices: TextVertices,
+    ) -> Option<UploadedVertices> {
+        if vertices.vertices.is_empty() {
+            return None;
+        }
+        let bytes = (vertices.vertices.len() * mem::size_of::<Vertex>()) as u64;
+        let mut memory = self.vertex_memory;
+        let mut staging = match (memory, &command_buffer) {
+            (VertexMemory::DeviceLocal, Some(_)) => bytes,
+            _ => 0,
+        };
+        if !self.fits_budget(bytes + staging) {
+            let budget = self.memory_budget.unwrap();
+            self.recording.over_budget = true;
+            if budget.over_budget == OverBudget::Fail {
+                let allocated = self.recording.vertex_bytes + self.recording.staging_bytes;
+                self.recording.budget_error = Some(OverBudgetError {
+                    bytes_per_frame: budget.bytes_per_frame,
+                    needed: allocated + bytes,
+                });
+                return None;
+            }
+            memory = VertexMemory::HostVisible;
+            staging = 0;
+        }
+        self.recording.vertex_bytes += bytes;
+        self.recording.staging_bytes += staging;
+        self.recording.buffers += if staging > 0 { 2 } else { 1 };
+        upload_vertices(command_buffer, memory_allocator, memory, vertices)
+    }
+
+    pub fn gpu_driven(&self) -> bool {
+        self.gpu_driven
+    }
+
+    // for thousands of queued texts: glyphs go into a storage buffer as they are, a
+    // compute pass throws out the ones off screen and writes the counts of indirect
+    // draws, so the cpu does nothing per glyph beyond writing it. glyphs overlapping
+    // within the same pass may paint in any order. only draw_into and what
+    // builds on it draw this way, subpass and secondary drawing keep vertex buffers
+    pub fn set_gpu_driven(&mut self, on: bool) {
+        self.gpu_driven = on;
+    }
+
+    // layers without an entry go to every target
+    pub fn set_layer_targets(&mut self, layer: i32, targets: LayerTargets) {
+        self.layer_targets.insert(layer, targets);
+    }
+
+    fn record_text<'a>(
+        &mut self,
+        command_buffer: &'a mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>,
+        memory_allocator: &Arc<StandardMemoryAllocator>,
+        target: &TextTarget,
+        image_num: usize,
+    ) -> &'a mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer> {
+        let extent = target.extent(image_num);
+        let screen = [extent[0] as f32, extent[1] as f32];
+        self.queue_retained();
+        self.resolve_anchors(extent);
+        compat::begin_label(command_buffer, "font-render text");
+        #[cfg(feature = "gpu_timing")]
+        self.begin_timing(command_buffer);
+        let set = self.upload_atlas(
+            command_buffer,
+            memory_allocator,
+            target.pipeline(),
+            Some(target.id()),
+        );
+        // culled before the render pass begins, drawn inside it
+        let mut vertices = None;
+        let mut culled = None;
+        if self.gpu_driven {
+            // glyphs are culled on the gpu
+            let texts = self.visible_texts(target.kind());
+            let passes = self.text_instances(
+                texts,
+                screen,
+                false,
+                self.color_output(target.linear_output()),
+            );
+            let culling = self
+                .culling
+                .get_or_insert_with(|| GpuCulling::new(self.device.clone()));
+            let passes: Vec<&[GlyphInstance]> = passes
+                .iter()
+                .flat_map(|(passes, _, _)| passes.iter().map(Vec::as_slice))
+                .collect();
+            culled = culling.cull(
+                command_buffer,
+                memory_allocator,
+                &self.descriptor_set_allocator,
+                target.id(),
+                self.frames[&Some(target.id())].slot(self.frames_in_flight),
+                self.frames_in_flight,
+                &passes,
+            );
+        } else {
+            let texts = self.visible_texts(target.kind());
+            let text_vertices =
+                self.text_vertices(texts, screen, self.color_output(target.linear_output()));
+            vertices = self.upload_text_vertices(
+                Some(&mut *command_buffer),
+                memory_allocator,
+                text_vertices,
+            );
+        }
+
+        let clear_values = vec![Some(0f32.into())];
+        compat::begin_render_pass(
+            command_buffer,
+            target.framebuffer(image_num).clone(),
+            clear_values,
+        );
+        compat::set_viewport_scissor(command_buffer, extent, None);
+        if self.gpu_driven {
+            let pipeline = self.indirect_pipeline(target);
+            draw_culled(
+                command_buffer,
+                &pipeline,
+                &set,
+                &self.descriptor_set_allocator,
+                culled,
+            );
+        } else {
+            self.draw_vertices(command_buffer, target.pipeline(), &set, vertices);
+        }
+        compat::end_render_pass(command_buffer);
+        #[cfg(feature = "gpu_timing")]
+        self.end_timing(command_buffer);
+        compat::end_label(command_buffer);
+        command_buffer
+    }
+
+    #[cfg(feature = "gpu_timing")]
+    pub(crate) fn begin_timing(
+        &mut self,
+        command_buffer: &mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>,
+    ) {
+        let slot = self.frame % self.frames_in_flight;
+        if let Some(timer) = &mut self.gpu_timer {
+            timer.begin(command_buffer, slot);
+        }
+    }
+
+    #[cfg(feature = "gpu_timing")]
+    pub(crate) fn end_timing(
+        &mut self,
+        command_buffer: &mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>,
+    ) {
+        let slot = self.frame % self.frames_in_flight;
+        if let Some(timer) = &mut self.gpu_timer {
+            timer.end(command_buffer, slot);
+        }
+    }
+
+    // textured ranges are drawn with a variant of pipeline, in between the rest
+    pub(crate) fn draw_vertices<L>(
+        &mut self,
+        command_buffer: &mut AutoCommandBufferBuilder<L>,
+        pipeline: &Arc<GraphicsPipeline>,
+        set: &Arc<PersistentDescriptorSet>,
+        vertices: Option<UploadedVertices>,
+    ) {
+        let vertices = match vertices {
+            Some(vertices) => vertices,
+            None => return,
+        };
+        let pipeline = &self.premultiplied_pipeline(pipeline);
+
+        compat::bind_vertex_buffer(command_buffer, &vertices.buffer);
+        // the plain pipeline between and after the textured ranges
+        let draw_plain = |command_buffer: &mut AutoCommandBufferBuilder<L>, range: Range<u32>| {
+            if range.is_empty() {
+                return;
+            }
+            compat::bind_graphics(command_buffer, pipeline, set.clone());
+            command_buffer
+                .draw(range.end - range.start, 1, range.start, 0)
+                .unwrap();
+        };
+        let mut drawn = 0;
+        for range in vertices.textured {
+            draw_plain(command_buffer, drawn..range.vertices.start);
+            match range.fill {
+                RangeFill::Texture(texture) => {
+                    let textured = self.textured_pipeline(pipeline);
+                    self.bind_fill(command_buffer, &textured, set, texture);
+                }
+                RangeFill::Msdf(texture, distance_range) => {
+                    let msdf = self.msdf_pipeline(pipeline);
+                    self.bind_fill(command_buffer, &msdf, set, texture);
+                    compat::push_constants(command_buffer, &msdf, distance_range);
+                }
+                RangeFill::Effect(effect, params) => {
+                    let effect = self.effect_pipeline(pipeline, effect);
+                    compat::bind_graphics(command_buffer, &effect, set.clone());
+                    if !effect.layout().push_constant_ranges().is_empty() {
+                        compat::push_constants(command_buffer, &effect, params);
+                    }
+                }
+            }
+            command_buffer
+                .draw(
+                    range.vertices.end - range.vertices.start,
+                    1,
+                    range.vertices.start,
+                    0,
+                )
+                .unwrap();
+            drawn = range.vertices.end;
+        }
+        draw_plain(command_buffer, drawn..vertices.len);
+    }
+
+    // binds the atlas set and texture as the fill set of a textured or msdf pipeline
+    fn bind_fill<L>(
+        &self,
+        command_buffer: &mut AutoCommandBufferBuilder<L>,
+        pipeline: &Arc<GraphicsPipeline>,
+        set: &Arc<PersistentDescriptorSet>,
+        texture: Arc<ImageView>,
+    ) {
+        let fill_set = compat::descriptor_set(
+            &self.descriptor_set_allocator,
+            pipeline.layout().set_layouts()[1].clone(),
+            [WriteDescriptorSet::image_view_sampler(
+                0,
+                texture,
+                self.fill_sampler.clone(),
+            )],
+        );
+        compat::bind_graphics(command_buffer, pipeline, (set.clone(), fill_set));
+    }
+
+    // the variant of base, built the first time it's asked for
+    fn variant_pipeline(
+        &mut self,
+        base: &Arc<GraphicsPipeline>,
+        variant: PipelineVariant,
+        build: impl FnOnce(&TextRendererCore) -> Arc<GraphicsPipeline>,
+    ) -> Arc<GraphicsPipeline> {
+        let key = (Arc::as_ptr(base) as usize, variant);
+        if let Some((_, pipeline)) = self.variant_pipelines.get(&key) {
+            return pipeline.clone();
+        }
+
+        let pipeline = build(self);
+        self.variant_pipelines
+            .retain(|_, (plain, _)| plain.strong_count() > 0);
+        self.variant_pipelines
+            .insert(key, (Arc::downgrade(base), pipeline.clone()));
+        pipeline
+    }
+
+    fn textured_pipeline(&mut self, base: &Arc<GraphicsPipeline>) -> Arc<GraphicsPipeline> {
+        self.variant_pipeline(base, PipelineVariant::Textured, |draw_text| {
+            textured_pipeline(base, draw_text.shader_reload())
+        })
+    }
+
+    fn msdf_pipeline(&mut self, base: &Arc<GraphicsPipeline>) -> Arc<GraphicsPipeline> {
+        self.variant_pipeline(base, PipelineVariant::Msdf, |draw_text| {
+            msdf_pipeline(base, draw_text.shader_reload())
+        })
+    }
+
+    fn effect_pipeline(
+        &mut self,
+        base: &Arc<GraphicsPipeline>,
+        effect: TextEffectId,
+    ) -> Arc<GraphicsPipeline> {
+        self.variant_pipeline(base, PipelineVariant::Effect(effect), |draw_text| {
+            // add_effect built it once already, only a target of another format can
+            // still fail
+            effect_pipeline(
+                base,
+                draw_text.effects[effect.0].clone(),
+                draw_text.shader_reload(),
+            )
+            .unwrap()
+        })
+    }
+
+    // the blend every pipeline of DrawText's draws with in place of base
+    fn blend(&self, base: AttachmentBlend) -> AttachmentBlend {
+        match self.alpha_mode {
+            AlphaMode::Straight => base,
+            AlphaMode::Premultiplied => compat::premultiplied_over(),
+        }
+    }
+
+    // while shaders are watched, pipelines of straight alpha are built again too, from
+    // the shaders last reloaded
+    fn premultiplied_pipeline(&mut self, base: &Arc<GraphicsPipeline>) -> Arc<GraphicsPipeline> {
+        if self.alpha_mode == AlphaMode::Straight && !self.watches_shaders() {
+            return base.clone();
+        }
+        self.variant_pipeline(base, PipelineVariant::Premultiplied, |draw_text| {
+            let (subpass, blend) = compat::pipeline_target(base);
+            text_pipeline(
+                draw_text.device.clone(),
+                subpass,
+                draw_text.blend(blend),
+                draw_text.shader_reload(),
+            )
+        })
+    }
+
+    // kept as a variant of the target's pipeline, so it goes when the target does
+    fn indirect_pipeline(&mut self, target: &TextTarget) -> Arc<GraphicsPipeline> {
+        self.variant_pipeline(target.pipeline(), PipelineVariant::Indirect, |draw_text| {
+            indirect_pipeline(
+                draw_text.device.clone(),
+                Subpass::from(target.render_pass().clone(), 0).unwrap(),
+                draw_text.blend(target.blend()),
+                draw_text.shader_reload(),
+            )
+        })
+    }
+
+    pub(crate) fn texts(&self) -> &[TextData] {
+        &self.texts
+    }
+
+    // texts whose layer is routed to targets of this kind
+    pub(crate) fn visible_texts(&self, kind: TargetKind) -> impl Iterator<Item = &TextData> {
+        let layer_targets = &self.layer_targets;
+        self.texts.iter().filter(move |text| {
+            layer_targets
+                .get(&text.style.layer)
+                .map_or(true, |targets| targets.contains(kind))
+        })
+    }
+
+    // rasterizes the glyphs queued since the last frame into the cpu side atlas, its
+    // pages one after the other
+    fn update_cache(&mut self) {
+        let cache_pixel_buffer = &mut self.cache_pixel_buffer;
+        let cache = &mut self.cache;
+        let atlas_dirty = &mut self.atlas_dirty;
+        let version = self.atlas_version + 1;
+        let mut changed = false;
+
+        let evicted = cache.cache_queued(&self.layouter.fonts, |page, rect, src_data| {
+            cache::blit(cache_pixel_buffer, page, rect, src_data);
+            atlas_dirty.push(DirtyRect {
+                version,
+                page,
+                rect,
+            });
+            changed = true;
+        });
+        self.recording.evictions += evicted;
+        self.recording.dropped_glyphs += self.cache.take_dropped();
+        if changed {
+            self.atlas_version += 1;
+        }
+
+        let glyphs = self.texts.iter().flat_map(|text| &text.glyphs);
+        self.recording.dropped_glyphs += self
+            .msdf_atlas
+            .generate(glyphs.map(|(font, glyph)| (*font, glyph.id)));
+        self.update_tessellations();
+    }
+
+    // cuts the outlines of tessellated glyphs not seen last frame into triangles, and
+    // forgets the ones no text uses anymore
+    fn update_tessellations(&mut self) {
+        let mut used = HashSet::new();
+        for text in &self.texts {
+            if text.in_atlas() {
+                continue;
+            }
+            let instance = cache::instance_hash(&text.style.variations);
+            for (font_id, g) in &text.glyphs {
+                if self.msdf_fonts.contains_key(font_id) || self.msdf_atlas.has_font(*font_id) {
+                    continue;
+                }
+                let key = (*font_id, g.id, g.size.to_bits(), instance);
+                if used.insert(key) && !self.tessellations.contains_key(&key) {
+                    let font = &self.layouter.fonts[*font_id];
+                    if let Some(path) = font.path(g.id, g.size, &text.style.variations) {
+                        self.tessellations
+                            .insert(key, mesh::tessellate(&path, |p| p));
+                    }
+                }
+            }
+        }
+        self.tessellations.retain(|key, _| used.contains(key));
+    }
+
+    // repacks the atlas when set_atlas_defragment says so and moves the cpu side pixels
+    // along, returns the moves for the frames' images. None when it wasn't repacked
+    fn defragment_atlas(&mut self) -> Option<Vec<GlyphMove>> {
+        let policy = self.atlas_defragment?;
+        // an atlas with room left isn't worth repacking
+        if self.cache.used_area_pct() < DEFRAGMENT_USED_PCT
+            || self.cache.utilization_pct(policy.max_idle_frames) >= policy.min_utilization_pct
+        {
+            return None;
+        }
+
+        let moves = self.cache.defragment(policy.max_idle_frames);
+        let mut pixels = vec![0; self.cache_pixel_buffer.len()];
+        let offset = |page: u32, x: u32, y: u32| {
+            page as usize * CACHE_WIDTH * CACHE_HEIGHT + y as usize * CACHE_WIDTH + x as usize
+        };
+        for glyph in &moves {
+            let width = glyph.rect.width() as usize;
+            for y in 0..glyph.rect.height() {
+                let from = offset(glyph.page, glyph.rect.min.x, glyph.rect.min.y + y);
+                let to = offset(glyph.to_page, glyph.to[0], glyph.to[1] + y);
+                pixels[to..to + width]
+                    .copy_from_slice(&self.cache_pixel_buffer[from..from + width]);
+            }
+        }
+        self.cache_pixel_buffer = pixels;
+        self.atlas_version += 1;
+        // every frame's image is rebuilt from the new layout
+        self.atlas_dirty.clear();
+        self.atlas_dirty_since = self.atlas_version;
+        Some(moves)
+    }
+
+    // the version atlas_dirty has every rect since, with set_full_atlas_upload the
+    // current one so every copy gets the whole atlas
+    fn dirty_since(&self) -> u64 {
+        if self.full_atlas_upload {
+            self.atlas_version
+        } else {
+            self.atlas_dirty_since
+        }
+    }
+
+    // forgets the rects every frame slot has already got. without slots, i.e. when
+    // only generate_quads or upload_to copy the atlas, the rects of the last frames in
+    // flight versions are kept. copies further behind get the whole atlas
+    fn trim_atlas_dirty(&mut self) {
+        let behind = self
+            .atlas_version
+            .saturating_sub(self.frames_in_flight as u64);
+        let uploaded = self
+            .frames
+            .values()
+            .flat_map(|frames| &frames.slots)
+            .filter_map(FrameSlot::version)
+            .min()
+            .unwrap_or(behind)
+            .max(self.atlas_dirty_since);
+        self.atlas_dirty.retain(|rect| rect.version > uploaded);
+        self.atlas_dirty_since = uploaded;
+    }
+
+    // records the atlas upload, which has to happen outside of a render pass,
+    // and returns the descriptor set sampling the atlas for the given pipeline. the
+    // upload goes into the next of target's frame slots
+    pub(crate) fn upload_atlas(
+        &mut self,
+        command_buffer: &mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>,
+        memory_allocator: &Arc<StandardMemoryAllocator>,
+        pipeline: &Arc<GraphicsPipeline>,
+        target: Option<TargetId>,
+    ) -> Arc<PersistentDescriptorSet> {
+        self.update_cache();
+
+        // targets that weren't drawn into for a while are most likely gone, like the
+        // render passes of subpass pipelines
+        let frame = self.frame;
+        if !self.frames.contains_key(&target) {
+            self.frames
+                .retain(|_, frames| frame - frames.drawn < TARGET_IDLE_FRAMES);
+            let frames = &self.frames;
+            if let Some(culling) = &mut self.culling {
+                culling.retain_targets(|id| frames.contains_key(&Some(*id)));
+            }
+            self.msdf_atlas.retain_targets(|id| frames.contains_key(id));
+        }
+        let frames_in_flight = self.frames_in_flight;
+        let dirty_since = self.dirty_since();
+        let frames = self.frames.entry(target).or_default();
+        frames.drawn = frame;
+        let slot = frames.next_slot(frames_in_flight);
+
+        // a new page means new images, the old ones can't grow. the pages of this frame's
+        // slot are copied over on the gpu, the other slots may still be in use
+        let pages = self.cache.pages();
+        if frames.slots.len() == frames_in_flight
+            && frames.slots.iter().any(|slot| slot.pages() < pages)
+        {
+            let current = &frames.slots[slot];
+            let grown: Vec<_> = (0..frames_in_flight)
+                .map(|_| {
+                    current.grown(
+                        command_buffer,
+                        memory_allocator,
+                        &self.cache_pixel_buffer,
+                        pages,
+                    )
+                })
+                .collect();
+            self.recording.buffers += grown.len();
+            frames.slots = grown;
+        } else if frames.slots.len() != frames_in_flight {
+            frames.slots = (0..frames_in_flight)
+                .map(|_| {
+                    FrameSlot::new(
+                        memory_allocator,
+                        [CACHE_WIDTH as u32, CACHE_HEIGHT as u32],
+                        pages,
+                    )
+                })
+                .collect();
+        }
+        compat::begin_label(command_buffer, "font-render atlas upload");
+        self.recording.upload_bytes += frames.slots[slot].upload(
+            command_buffer,
+            &AtlasPixels {
+                pixels: &self.cache_pixel_buffer,
+                extent: [CACHE_WIDTH as u32, CACHE_HEIGHT as u32],
+                pages,
+                version: self.atlas_version,
+                dirty: &self.atlas_dirty,
+                dirty_since,
+            },
+        );
+        let msdf_bytes = self.msdf_atlas.upload(
+            command_buffer,
+            memory_allocator,
+            target,
+            slot,
+            frames_in_flight,
+        );
+        self.recording.upload_bytes += msdf_bytes;
+        if msdf_bytes > 0 {
+            self.recording.staging_bytes += msdf_bytes as u64;
+            self.recording.buffers += 1;
+        }
+        // the slot was just brought up to date, every slot of the target gets its
+        // glyphs where the repack put them. other targets' slots are older than the
+        // repack and get the whole atlas on their next turn
+        if let Some(moves) = self.defragment_atlas() {
+            let frames = self.frames.get_mut(&target).unwrap();
+            let current = &frames.slots[slot];
+            let moved: Vec<_> = (0..frames_in_flight)
+                .map(|_| {
+                    current.moved(command_buffer, memory_allocator, &moves, self.atlas_version)
+                })
+                .collect();
+            self.recording.buffers += moved.len();
+            frames.slots = moved;
+        }
+        compat::end_label(command_buffer);
+        self.trim_atlas_dirty();
+        let slot = &mut self.frames.get_mut(&target).unwrap().slots[slot];
+
+        // the set only changes together with the slot's atlas or the sampler
+        if slot.descriptor_set.is_none() {
+            // an array view even for a single page, the shader samples a sampler2DArray
+            let cache_texture_view = compat::array_view(slot.atlas());
+
+            slot.descriptor_set = Some(compat::descriptor_set(
+                &self.descriptor_set_allocator,
+                pipeline.layout().set_layouts()[0].clone(),
+                [WriteDescriptorSet::image_view_sampler(
+                    0,
+                    cache_texture_view,
+                    self.sampler.clone(),
+                )],
+            ));
+        }
+        slot.descriptor_set.clone().unwrap()
+    }
+
+    pub(crate) fn text_vertices<'t>(
+        &self,
+        texts: impl IntoIterator<Item = &'t TextData>,
+        screen: [f32; 2],
+        output: ColorOutput,
+    ) -> TextVertices {
+        // the passes are laid out back to back in one vertex buffer, so a shadow can
+        // never end up on top of another text's fill
+        let mut vertices = vec![];
+        let mut textured = vec![];
+        // inks are in the pixels the text was laid out in
+        let laid_out = self.laid_out_screen(screen);
+        for (passes, runs, objects) in self.text_instances(texts, screen, true, output) {
+            for (pass, instances) in passes.iter().enumerate() {
+                let start = vertices.len();
+                vertices.extend(instances.iter().flat_map(GlyphInstance::vertices));
+                // the texture is stretched over the text's ink
+                let runs = if pass == Pass::Fill as usize {
+                    &runs[..]
+                } else {
+                    &[]
+                };
+                for run in runs.iter().filter(|run| !run.instances.is_empty()) {
+                    let range = start + run.instances.start * 6..start + run.instances.end * 6;
+                    let size = [
+                        (run.ink.max.x - run.ink.min.x).max(1.0),
+                        (run.ink.max.y - run.ink.min.y).max(1.0),
+                    ];
+                    for vertex in &mut vertices[range.clone()] {
+                        let [x, y] = vertex.position;
+                        let pixel = [(x + 1.0) / 2.0 * laid_out[0], (y + 1.0) / 2.0 * laid_out[1]];
+                        vertex.fill_uv = [
+                            (pixel[0] - run.ink.min.x) / size[0],
+                            (pixel[1] - run.ink.min.y) / size[1],
+                        ];
+                    }
+                    textured.push(TexturedRange {
+                        vertices: range.start as u32..range.end as u32,
+                        fill: run.fill.clone(),
+                    });
+                }
+                // the texture's region is mapped onto the quad's corners
+                for object in objects.iter().filter(|object| object.pass as usize == pass) {
+                    let start = vertices.len() as u32;
+                    let [min_x, min_y, max_x, max_y] = object.uv_rect;
+                    let uvs = [
+                        [min_x, max_y],
+                        [min_x, min_y],
+                        [max_x, min_y],
+                        [max_x, max_y],
+                    ];
+                    let corners = [0, 1, 2, 2, 3, 0];
+                    vertices.extend(object.instance.vertices().into_iter().zip(corners).map(
+                        |(vertex, corner)| Vertex {
+                            fill_uv: uvs[corner],
+                            ..vertex
+                        },
+                    ));
+                    // the glyphs of an msdf font follow each other, one draw for them all
+                    let end = vertices.len() as u32;
+                    let fill = match object.msdf {
+                        Some(distance_range) => {
+                            RangeFill::Msdf(object.texture.clone(), distance_range)
+                        }
+                        None => RangeFill::Texture(object.texture.clone()),
+                    };
+                    match textured.last_mut() {
+                        Some(last) if last.vertices.end == start && last.fill.same(&fill) => {
+                            last.vertices.end = end;
+                        }
+                        _ => textured.push(TexturedRange {
+                            vertices: start..end,
+                            fill,
+                        }),
+                    }
+                }
+            }
+        }
+        TextVertices { vertices, textured }
+    }
+
+    // one list per pass for every layer, lowest layer first, and every text contributes
+    // to each pass of its layer. texts entirely off screen are skipped, and with
+    // cull_glyphs every glyph that is. the fill quads of textured texts are noted per
+    // layer, the quads of inline objects are kept apart from the passes
+    #[allow(clippy::type_complexity)]
+    fn text_instances<'t>(
+        &self,
+        texts: impl IntoIterator<Item = &'t TextData>,
+        screen: [f32; 2],
+        cull_glyphs: bool,
+        output: ColorOutput,
+    ) -> Vec<([Vec<GlyphInstance>; 4], Vec<TexturedRun>, Vec<ObjectQuad>)> {
+        // everything is placed in the pixels it was laid out in, the quads come out
+        // stretched by the view scale
+        let screen = self.laid_out_screen(screen);
+        let viewport = Rect {
+            min: point(0.0, 0.0),
+            max: point(screen[0], screen[1]),
+        };
+        let mut texts: Vec<_> = texts.into_iter().collect();
+        // stable, so texts of a layer stay in the order they were queued
+        texts.sort_by_key(|text| text.style.layer);
+        let mut layers: Vec<(
+            i32,
+            [Vec<GlyphInstance>; 4],
+            Vec<TexturedRun>,
+            Vec<ObjectQuad>,
+        )> = vec![];
+        for text in texts {
+            if !text.bounds().map_or(false, |b| intersects(&b, &viewport)) {
+                continue;
+            }
+            if layers.last().map(|(layer, _, _, _)| *layer) != Some(text.style.layer) {
+                layers.push((text.style.layer, Default::default(), vec![], vec![]));
+            }
+            let (_, passes, runs, objects) = layers.last_mut().unwrap();
+            let fill_start = passes[Pass::Fill as usize].len();
+            let style = &text.style;
+            if let Some(color) = style.background {
+                for rect in &text.background {
+                    let pass = &mut passes[Pass::Background as usize];
+                    let quad = PlacedGlyph::solid(*rect, text.fade);
+                    push_glyph(pass, screen, &quad, [0.0, 0.0], color);
+                }
+            }
+            // solid dark squares, then the atlas pages over them
+            if let Some(rect) = text.atlas {
+                let pass = &mut passes[Pass::Fill as usize];
+                let pages = self.cache.pages();
+                let width = (rect.max.x - rect.min.x) / pages as f32;
+                for page in 0..pages {
+                    let left = rect.min.x + page as f32 * width;
+                    let square = PlacedGlyph::solid(
+                        Rect {
+                            min: point(left, rect.min.y),
+                            max: point(left + width, rect.max.y),
+                        },
+                        NO_FADE,
+                    );
+                    push_glyph(pass, screen, &square, [0.0, 0.0], [0.0, 0.0, 0.0, 0.8]);
+                    let atlas = PlacedGlyph {
+                        uv_rect: Rect {
+                            min: point(0.0, 0.0),
+                            max: point(1.0, 1.0),
+                        },
+                        page,
+                        ..square
+                    };
+                    push_glyph(pass, screen, &atlas, [0.0, 0.0], text.style.color);
+                }
+            }
+            let fill = match (style.gradient, text.ink) {
+                (Some(gradient), Some(ink)) => Paint::Gradient(gradient, ink),
+                _ => Paint::Solid(style.color),
+            };
+            let outline_offsets = style.outline.map(|outline| outline.offsets());
+            let smear = style.smear_offsets();
+            let instance = cache::instance_hash(&style.variations);
+            for ((font_id, g), &rotated) in text.glyphs.iter().zip(&text.rotated) {
+                if cull_glyphs && !text.glyph_visible(g, rotated, &viewport) {
+                    continue;
+                }
+                // glyphs of msdf fonts aren't in the atlas, they are solid quads drawn
+                // with their font's texture like inline objects
+                let msdf = if let Some(font) = self.msdf_fonts.get(font_id) {
+                    match font.quad(g.id, g.size, g.position) {
+                        Some((screen_rect, uv_rect)) => {
+                            Some((&font.texture, font.distance_range, screen_rect, uv_rect))
+                        }
+                        None => continue,
+                    }
+                } else if self.msdf_atlas.has_font(*font_id) {
+                    match self.msdf_atlas.quad(*font_id, g) {
+                        Some((texture, screen_rect, uv_rect)) => {
+                            Some((texture, DISTANCE_RANGE, screen_rect, uv_rect))
+                        }
+                        None => continue,
+                    }
+                } else {
+                    None
+                };
+                // outlines cut into triangles, and whether they get a fringe
+                let tessellation = match style.rendering {
+                    GlyphRendering::Atlas => None,
+                    _ if msdf.is_some() => None,
+                    rendering => {
+                        let key = (*font_id, g.id, g.size.to_bits(), instance);
+                        let tessellation = match self.tessellations.get(&key) {
+                            Some(tessellation) => tessellation,
+                            None => continue,
+                        };
+                        Some((tessellation, rendering == GlyphRendering::Tessellated))
+                    }
+                };
+                let quad = match (msdf, &tessellation) {
+                    (Some((_, _, screen_rect, _)), _) => PlacedGlyph {
+                        baseline: g.position.y,
+                        skew: style.skew(),
+                        pivot: if rotated { Some(g.position) } else { None },
+                        ..PlacedGlyph::solid(screen_rect, text.fade)
+                    },
+                    (None, Some(_)) => PlacedGlyph {
+                        baseline: g.position.y,
+                        skew: style.skew(),
+                        pivot: if rotated { Some(g.position) } else { None },
+                        ..PlacedGlyph::solid(
+                            Rect {
+                                min: g.position,
+                                max: g.position,
+                            },
+                            text.fade,
+                        )
+                    },
+                    (None, None) => {
+                        let (mut uv_rect, page, screen_rect) =
+                            match self.cache.rect_for(*font_id, g) {
+                                Some(rects) => rects,
+                                None => continue,
+                            };
+                        if self.uv_inset {
+                            let texel = [0.5 / CACHE_WIDTH as f32, 0.5 / CACHE_HEIGHT as f32];
+                            uv_rect.min = point(uv_rect.min.x + texel[0], uv_rect.min.y + texel[1]);
+                            uv_rect.max = point(uv_rect.max.x - texel[0], uv_rect.max.y - texel[1]);
+                        }
+                        PlacedGlyph {
+                            screen_rect: Rect {
+                                min: point(screen_rect.min.x as f32, screen_rect.min.y as f32),
+                                max: point(screen_rect.max.x as f32, screen_rect.max.y as f32),
+                            },
+                            uv_rect,
+                            page,
+                            baseline: g.position.y,
+                            skew: style.skew(),
+                            pivot: if rotated { Some(g.position) } else { None },
+                            fade: text.fade,
+                        }
+                    }
+                };
+                // faux bold draws every layer once per smear offset
+                let mut push = |pass: Pass, offset: [f32; 2], paint: Paint| {
+                    for s in &smear {
+                        let offset = [offset[0] + s[0], offset[1] + s[1]];
+                        match (msdf, &tessellation) {
+                            (Some((texture, distance_range, _, uv_rect)), _) => {
+                                let mut instance = vec![];
+                                push_glyph(&mut instance, screen, &quad, offset, paint);
+                                objects.push(ObjectQuad {
+                                    pass,
+                                    instance: instance[0],
+                                    texture: texture.clone(),
+                                    uv_rect,
+                                    msdf: Some(distance_range),
+                                });
+                            }
+                            (None, Some((tessellation, feather))) => push_tessellated(
+                                &mut passes[pass as usize],
+                                screen,
+                                &quad,
+                                tessellation,
+                                g.position,
+                                *feather,
+                                offset,
+                                paint,
+                            ),
+                            (None, None) => {
+                                push_glyph(&mut passes[pass as usize], screen, &quad, offset, paint)
+                            }
+                        }
+                    }
+                };
+
+                if let Some(shadow) = style.shadow {
+                    // the shadow has to cover the outline too
+                    push(Pass::Shadow, shadow.offset, shadow.color.into());
+                    for offset in outline_offsets.iter().flatten() {
+                        push(
+                            Pass::Shadow,
+                            [shadow.offset[0] + offset[0], shadow.offset[1] + offset[1]],
+                            shadow.color.into(),
+                        );
+                    }
+                }
+                if let (Some(outline), Some(offsets)) = (style.outline, outline_offsets) {
+                    for offset in offsets {
+                        push(Pass::Outline, offset, outline.color.into());
+                    }
+                }
+                push(Pass::Fill, [0.0, 0.0], fill);
+            }
+
+            // lines get a solid stroke instead of the offset copies glyphs use
+            let stroke = style.outline.map_or(0.0, |outline| outline.width);
+            for line in &text.lines {
+                let stroked = PlacedGlyph::solid(
+                    Rect {
+                        min: point(line.min.x - stroke, line.min.y - stroke),
+                        max: point(line.max.x + stroke, line.max.y + stroke),
+                    },
+                    text.fade,
+                );
+                if let Some(shadow) = style.shadow {
+                    let pass = &mut passes[Pass::Shadow as usize];
+                    push_glyph(pass, screen, &stroked, shadow.offset, shadow.color);
+                }
+                if let Some(outline) = style.outline {
+                    let pass = &mut passes[Pass::Outline as usize];
+                    push_glyph(pass, screen, &stroked, [0.0, 0.0], outline.color);
+                }
+                let pass = &mut passes[Pass::Fill as usize];
+                push_glyph(
+                    pass,
+                    screen,
+                    &PlacedGlyph::solid(*line, text.fade),
+                    [0.0, 0.0],
+                    fill,
+                );
+            }
+
+            let fill = match (text.effect, &text.texture) {
+                (Some((effect, params)), _) => Some(RangeFill::Effect(effect, params)),
+                (None, Some(texture)) => Some(RangeFill::Texture(texture.clone())),
+                (None, None) => None,
+            };
+            if let (Some(fill), Some(ink)) = (fill, text.ink) {
+                runs.push(TexturedRun {
+                    instances: fill_start..passes[Pass::Fill as usize].len(),
+                    fill,
+                    ink,
+                });
+            }
+
+            let [r, g, b, a] = style.color;
+            for (rect, object) in &text.objects {
+                if cull_glyphs && !intersects(rect, &viewport) {
+                    continue;
+                }
+                let color = if object.tinted {
+                    [r, g, b, a]
+                } else {
+                    [1.0, 1.0, 1.0, a]
+                };
+                let mut instance = vec![];
+                let quad = PlacedGlyph::solid(*rect, text.fade);
+                push_glyph(&mut instance, screen, &quad, [0.0, 0.0], color);
+                objects.push(ObjectQuad {
+                    pass: if text.objects_behind {
+                        Pass::Background
+                    } else {
+                        Pass::Fill
+                    },
+                    instance: instance[0],
+                    texture: object.texture.clone(),
+                    uv_rect: object.uv_rect,
+                    msdf: None,
+                });
+            }
+        }
+
+        // converted once everything is placed, gradients are interpolated in the
+        // target's space
+        let stretch = self.view_stretch();
+        let convert = |instance: &mut GlyphInstance| {
+            for color in &mut instance.colors {
+                *color = self.color_space.output(*color, output);
+            }
+            // fades are in the target's pixels
+            if instance.fade != NO_FADE {
+                instance.fade[0] *= stretch;
+                instance.fade[1] *= stretch;
+            }
+        };
+        layers
+            .into_iter()
+            .map(|(_, mut passes, runs, mut objects)| {
+                passes.iter_mut().flatten().for_each(convert);
+                objects
+                    .iter_mut()
+                    .for_each(|object| convert(&mut object.instance));
+                (passes, runs, objects)
+            })
+            .collect()
+    }
+}
+
+impl DrawText {
+    // draw_text renders into these images, usually the swapchain's
+    pub fn new(
+        device: Arc<Device>,
+        queue: Arc<Queue>,
+        images: &[Arc<Image>],
+    ) -> Result<DrawText, NoImagesError> {
+        let target = TextTarget::from_images(device.clone(), TargetKind::Present, images)?;
+        Ok(DrawText::with_target(device, queue, Some(Arc::new(target))))
+    }
+
+    pub fn with_views(
+        device: Arc<Device>,
+        queue: Arc<Queue>,
+        views: Vec<Arc<ImageView>>,
+    ) -> Result<DrawText, NoImagesError> {
+        let target = TextTarget::from_views(device.clone(), TargetKind::Present, views)?;
+        Ok(DrawText::with_target(device, queue, Some(Arc::new(target))))
+    }
+
+    // no swapchain until on_surface_recreated, text can only be drawn with draw_into
+    // meanwhile. without a swapchain ever, TextRendererCore::new is the same
+    pub fn headless(device: Arc<Device>, queue: Arc<Queue>) -> DrawText {
+        DrawText::with_target(device, queue, None)
+    }
+
+    fn with_target(
+        device: Arc<Device>,
+        queue: Arc<Queue>,
+        target: Option<Arc<TextTarget>>,
+    ) -> DrawText {
+        DrawText::with_create_info(device, queue, target, DrawTextCreateInfo::default())
+    }
+
+    // target is drawn into by draw_text, None for a headless renderer
+    pub fn with_create_info(
+        device: Arc<Device>,
+        queue: Arc<Queue>,
+        target: Option<Arc<TextTarget>>,
+        create_info: DrawTextCreateInfo,
+    ) -> DrawText {
+        let mut draw_text = DrawText {
+            core: TextRendererCore::with_create_info(device, queue, create_info),
+            target: None,
+            lost_target: None,
+            composition: CompositionMode::default(),
+            overlay: None,
+            #[cfg(feature = "screenshot")]
+            capture_path: None,
+            #[cfg(feature = "screenshot")]
+            screenshot: None,
+        };
+        draw_text.set_target(target);
+        draw_text
+    }
+
+    pub fn with_quality(mut self, quality: Quality) -> DrawText {
+        self.set_quality(quality);
+        self
+    }
+
+    // the core without the swapchain, for drawing into other windows' targets after
+    // all
+    pub fn into_core(self) -> TextRendererCore {
+        self.core
+    }
+
+    // the swapchain target, None when headless or while the surface is lost
+    pub fn target(&self) -> Option<&Arc<TextTarget>> {
+        self.target.as_ref()
+    }
+
+    // texts queued from now on are culled to the swapchain's size
+    fn set_target(&mut self, target: Option<Arc<TextTarget>>) {
+        self.core.extent = target.as_ref().map(|target| target.extent(0));
+        self.target = target;
+    }
+
+    // the swapchain is gone, like when an android app is suspended. drops everything
+    // tied to its images, fonts, the glyph atlas and retained texts stay. draw_text
+    // can't be called until on_surface_recreated, texts queued meanwhile are laid
+    // out like on a headless renderer
+    pub fn on_surface_lost(&mut self) {
+        if let Some(target) = self.target.take() {
+            self.lost_target = Some(target.retarget(vec![]));
+        }
+        self.set_target(None);
+        self.forget_drawn_frames();
+    }
+
+    // the new swapchain images after on_surface_lost or a resize. the render pass
+    // and pipeline are kept if the format stayed the same
+    pub fn on_surface_recreated(&mut self, images: &[Arc<Image>]) -> Result<(), NoImagesError> {
+        let views: Vec<_> = images
+            .iter()
+            .map(|image| compat::view(image.clone()))
+            .collect();
+        let format = compat::view_format(views.first().ok_or(NoImagesError)?);
+        let previous = self.target.as_deref().or(self.lost_target.as_ref());
+        let target = match previous {
+            Some(previous) if previous.format() == format => previous.retarget(views),
+            _ => TextTarget::new(self.device.clone(), TargetKind::Present, format, views),
+        };
+        self.set_target(Some(Arc::new(target)));
+        self.lost_target = None;
+        self.forget_drawn_frames();
+        Ok(())
+    }
+
+    fn forget_drawn_frames(&mut self) {
+        self.overlay = None;
+        self.core.forget_drawn_frames();
+    }
+
+    // like TextRendererCore::add_effect, with the pipeline built for the swapchain
+    pub fn add_effect(
+        &mut self,
+        fragment_shader: Arc<ShaderModule>,
+    ) -> Result<TextEffectId, EffectError> {
+        let target = self.target.clone();
+        self.core.add_effect_for(fragment_shader, target.as_deref())
+    }
+
+    // copies the swapchain image the next draw_text draws into, right after the text,
+    // and writes it to path as a png. the file shows up once the gpu finished that
+    // frame, during a later draw_text. the swapchain needs transfer_src usage, 8 and
+    // 10 bit rgba and bgra formats work, other formats and failed writes are skipped
+    // with a log warning
+    #[cfg(feature = "screenshot")]
+    pub fn capture_next_frame(&mut self, path: impl Into<PathBuf>) {
+        self.capture_path = Some(path.into());
+    }
+
+    pub fn draw_text<'a>(
+        &mut self,
+        command_buffer: &'a mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>,
+        memory_allocator: &Arc<StandardMemoryAllocator>,
+        image_num: usize,
+    ) -> &'a mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer> {
+        let target = self
+            .target
+            .clone()
+            .expect("DrawText has no swapchain, it was created headless or its surface was lost");
+        #[cfg(feature = "screenshot")]
+        if self
+            .screenshot
+            .as_ref()
+            .map_or(false, Screenshot::try_write)
+        {
+            self.screenshot = None;
+        }
+        let command_buffer = match self.composition {
+            CompositionMode::OwnPass => {
+                self.core
+                    .draw_into(command_buffer, memory_allocator, &target, image_num)
+            }
+            CompositionMode::Overlay => {
+                let mut overlay = self
+                    .overlay
+                    .take()
+                    .unwrap_or_else(|| PartialRedraw::new(self, memory_allocator));
+                let command_buffer =
+                    overlay.draw(self, command_buffer, memory_allocator, image_num);
+                self.overlay = Some(overlay);
+                command_buffer
+            }
+        };
+        #[cfg(feature = "screenshot")]
+        if let Some(path) = self.capture_path.take() {
+            self.screenshot = Screenshot::record(
+                command_buffer,
+                memory_allocator,
+                compat::framebuffer_image(target.framebuffer(image_num)),
+                target.format(),
+                path,
+            );
+        }
+        command_buffer
+    }
+
+    pub fn composition_mode(&self) -> CompositionMode {
+        self.composition
+    }
 
-        command_buffer.end_render_pass().unwrap()
+    pub fn set_composition_mode(&mut self, mode: CompositionMode) {
+        self.composition = mode;
+        self.overlay = None;
     }
 }
 
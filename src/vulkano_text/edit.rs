@@ -0,0 +1,309 @@
+use super::drawtext::{TextRendererCore, TextRole};
+use super::input::{InputEvent, Key, Modifiers};
+use super::layout::TextLayout;
+use super::style::{Decorations, TextStyle};
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use std::ops::Range;
+use std::time::{Duration, Instant};
+
+// the usual caret blink rate on most desktops
+const BLINK_INTERVAL: Duration = Duration::from_millis(530);
+
+// a single line text field driven by InputEvents. it only keeps the
+// editing state, drawing goes through DrawText like any other text
+pub struct TextEditController {
+    text: String,
+    // byte index into text, always on a grapheme cluster boundary
+    caret: usize,
+    // the fixed end of the selection, the caret is the end that moves
+    anchor: Option<usize>,
+    // ime composition shown at the caret until it gets committed
+    preedit: String,
+    // caret inside the composition, None hides the caret
+    preedit_caret: Option<usize>,
+    modifiers: Modifiers,
+    // blinking restarts whenever the caret moves
+    blink_start: Instant,
+    pub selection_color: [f32; 4],
+}
+
+impl Default for TextEditController {
+    fn default() -> TextEditController {
+        TextEditController {
+            text: String::new(),
+            caret: 0,
+            anchor: None,
+            preedit: String::new(),
+            preedit_caret: None,
+            modifiers: Modifiers::default(),
+            blink_start: Instant::now(),
+            selection_color: [0.2, 0.4, 0.9, 0.5],
+        }
+    }
+}
+
+impl TextEditController {
+    pub fn new(text: &str) -> TextEditController {
+        TextEditController {
+            text: text.to_owned(),
+            caret: text.len(),
+            ..Default::default()
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn set_text(&mut self, text: &str) {
+        self.text = text.to_owned();
+        self.anchor = None;
+        self.move_caret(self.text.len(), false);
+    }
+
+    pub fn caret(&self) -> usize {
+        self.caret
+    }
+
+    // selected bytes, None when nothing is selected
+    pub fn selection(&self) -> Option<Range<usize>> {
+        let anchor = self.anchor?;
+        let range = anchor.min(self.caret)..anchor.max(self.caret);
+        if range.is_empty() {
+            None
+        } else {
+            Some(range)
+        }
+    }
+
+    pub fn preedit(&self) -> &str {
+        &self.preedit
+    }
+
+    // returns whether the field changed and has to be redrawn
+    pub fn handle_event(&mut self, event: &InputEvent) -> bool {
+        match event {
+            InputEvent::Modifiers(modifiers) => {
+                self.modifiers = *modifiers;
+                false
+            }
+            // backspace, enter and friends arrive here as control chars too,
+            // they are handled as key presses instead
+            InputEvent::Char(c) if !c.is_control() => {
+                self.insert(&c.to_string());
+                true
+            }
+            InputEvent::Preedit(text, cursor) => {
+                self.preedit = text.clone();
+                self.preedit_caret = *cursor;
+                self.blink_start = Instant::now();
+                true
+            }
+            InputEvent::Commit(text) => {
+                self.preedit.clear();
+                self.insert(text);
+                true
+            }
+            InputEvent::ImeDisabled => {
+                self.preedit.clear();
+                true
+            }
+            // while composing, keys belong to the input method
+            InputEvent::KeyPressed(key) if self.preedit.is_empty() => self.key(*key),
+            _ => false,
+        }
+    }
+
+    fn key(&mut self, key: Key) -> bool {
+        let extend = self.modifiers.shift;
+        match key {
+            Key::Left => match self.selection() {
+                Some(selection) if !extend => self.move_caret(selection.start, false),
+                _ => self.move_caret(self.prev_boundary(self.caret), extend),
+            },
+            Key::Right => match self.selection() {
+                Some(selection) if !extend => self.move_caret(selection.end, false),
+                _ => self.move_caret(self.next_boundary(self.caret), extend),
+            },
+            Key::Home => self.move_caret(0, extend),
+            Key::End => self.move_caret(self.text.len(), extend),
+            Key::Character('a') if self.modifiers.ctrl => {
+                self.anchor = Some(0);
+                self.move_caret(self.text.len(), true);
+            }
+            Key::Backspace => {
+                if !self.delete_selection() {
+                    let start = self.prev_boundary(self.caret);
+                    self.delete(start..self.caret);
+                }
+            }
+            Key::Delete => {
+                if !self.delete_selection() {
+                    let end = self.next_boundary(self.caret);
+                    self.delete(self.caret..end);
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    // arrows and deletion step over whole grapheme clusters, so a family emoji or
+    // a letter with combining accents goes away in one piece
+    fn prev_boundary(&self, index: usize) -> usize {
+        self.text[..index]
+            .grapheme_indices(true)
+            .next_back()
+            .map_or(0, |(i, _)| i)
+    }
+
+    fn next_boundary(&self, index: usize) -> usize {
+        self.text[index..]
+            .graphemes(true)
+            .next()
+            .map_or(index, |g| index + g.len())
+    }
+
+    // extend keeps the anchor where it is, or drops it where the caret was
+    fn move_caret(&mut self, to: usize, extend: bool) {
+        if extend {
+            self.anchor.get_or_insert(self.caret);
+        } else {
+            self.anchor = None;
+        }
+        self.caret = to;
+        self.blink_start = Instant::now();
+    }
+
+    fn delete(&mut self, range: Range<usize>) {
+        let start = range.start;
+        self.text.replace_range(range, "");
+        self.move_caret(start, false);
+    }
+
+    fn delete_selection(&mut self) -> bool {
+        match self.selection() {
+            Some(selection) => {
+                self.delete(selection);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn insert(&mut self, text: &str) {
+        self.delete_selection();
+        self.text.insert_str(self.caret, text);
+        self.move_caret(self.caret + text.len(), false);
+    }
+
+    // the text as shown, with the composition spliced in at the caret
+    fn display_text(&self) -> String {
+        let mut text = self.text.clone();
+        text.insert_str(self.caret, &self.preedit);
+        text
+    }
+
+    // layout of the text as shown, for clicks and placing the ime window
+    pub fn layout(&self, draw_text: &TextRendererCore, style: &TextStyle) -> TextLayout {
+        draw_text.layout_text(style, &self.display_text())
+    }
+
+    // moves the caret to a click at x, y relative to where the field is queued
+    pub fn click(&mut self, layout: &TextLayout, x: f32, y: f32, extend: bool) {
+        // the layout includes the composition, whose bytes aren't in text
+        if self.preedit.is_empty() {
+            self.move_caret(layout.hit_test(x, y), extend);
+        }
+    }
+
+    // where the input method should open its candidate window, just below the caret,
+    // or right of it for vertical text
+    pub fn ime_position(&self, layout: &TextLayout, x: f32, y: f32) -> [f32; 2] {
+        let (caret_x, caret_y, length) = layout.caret_position(self.caret);
+        if layout.is_vertical() {
+            [x + caret_x + length, y + caret_y]
+        } else {
+            [x + caret_x, y + caret_y + length]
+        }
+    }
+
+    fn caret_visible(&self, now: Instant) -> bool {
+        (now.saturating_duration_since(self.blink_start).as_millis() / BLINK_INTERVAL.as_millis())
+            % 2
+            == 0
+    }
+
+    fn next_blink(&self, now: Instant) -> Instant {
+        let blinks = now.saturating_duration_since(self.blink_start).as_millis()
+            / BLINK_INTERVAL.as_millis();
+        self.blink_start + BLINK_INTERVAL * (blinks as u32 + 1)
+    }
+
+    // queues the field with its baseline at x, y: selection, text, an underlined
+    // composition and the blinking caret. schedules the next blink on draw_text
+    pub fn queue(
+        &self,
+        draw_text: &mut TextRendererCore,
+        x: f32,
+        y: f32,
+        style: &TextStyle,
+    ) -> TextLayout {
+        draw_text.from_origin(y, |draw_text| {
+            draw_text.with_role(TextRole::Input, |draw_text| {
+                self.queue_field(draw_text, x, y, style)
+            })
+        })
+    }
+
+    fn queue_field(
+        &self,
+        draw_text: &mut TextRendererCore,
+        x: f32,
+        y: f32,
+        style: &TextStyle,
+    ) -> TextLayout {
+        let now = Instant::now();
+        let text = self.display_text();
+        let layout = draw_text.layout_text(style, &text);
+
+        if let Some(selection) = self.selection() {
+            draw_text.queue_selection(x, y, &layout, selection, self.selection_color);
+        }
+
+        let composing = self.caret..self.caret + self.preedit.len();
+        let underlined = style.clone().with_decorations(Decorations::UNDERLINE);
+        let pieces = [
+            (0..composing.start, style),
+            (composing.clone(), &underlined),
+            (composing.end..text.len(), style),
+        ];
+        // split at the composition, each piece starts where the whole line put it
+        for (range, style) in pieces {
+            if !range.is_empty() {
+                let (caret_x, caret_y, _) = layout.caret_position(range.start);
+                if layout.is_vertical() {
+                    draw_text.queue_text_styled(x, y + caret_y, style, &text[range]);
+                } else {
+                    draw_text.queue_text_styled(x + caret_x, y, style, &text[range]);
+                }
+            }
+        }
+
+        let caret = if self.preedit.is_empty() {
+            Some(self.caret)
+        } else {
+            self.preedit_caret.map(|caret| composing.start + caret)
+        };
+        if let Some(caret) = caret {
+            if self.caret_visible(now) {
+                let width = (style.size / 16.0).max(1.0);
+                draw_text.queue_caret(x, y, &layout, caret, width, style.color);
+            }
+        }
+        draw_text.schedule_redraw(self.next_blink(now));
+        layout
+    }
+}
@@ -0,0 +1,26 @@
+use std::error::Error;
+use std::fmt;
+
+// a fragment shader added with DrawText::add_effect
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TextEffectId(pub(crate) usize);
+
+// the user block of an effect, pushed as vec4 params[4] before its texts are drawn
+pub type EffectParams = [[f32; 4]; 4];
+
+// the fragment shader given to add_effect doesn't fit the text pipeline, e.g. its inputs
+// don't match what vertex.glsl writes or it has no main. holds vulkano's reason
+#[derive(Debug)]
+pub struct EffectError(pub(crate) String);
+
+impl fmt::Display for EffectError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "the effect shader doesn't fit the text pipeline: {}",
+            self.0
+        )
+    }
+}
+
+impl Error for EffectError {}
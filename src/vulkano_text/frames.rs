@@ -0,0 +1,354 @@
+use super::cache::GlyphMove;
+use super::drawtext::{TextVertices, TexturedRange};
+use super::gpu::compat::{self, HostBuffer, Image, VertexBuffer};
+use super::gpu_backend::AtlasPixels;
+
+use vulkano::command_buffer::{AutoCommandBufferBuilder, PrimaryAutoCommandBuffer};
+use vulkano::descriptor_set::PersistentDescriptorSet;
+use vulkano::memory::allocator::StandardMemoryAllocator;
+
+use rusttype::{point, Rect};
+
+use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
+
+// where glyph quads live while the gpu draws them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum VertexMemory {
+    // written into a staging buffer and copied to device local memory before the
+    // render pass begins
+    #[default]
+    DeviceLocal,
+    // written straight into host visible memory. on ReBAR and unified memory systems
+    // that memory is device local too, so the copy is pure overhead there
+    HostVisible,
+}
+
+// a limit on the buffers DrawText allocates for a frame, see DrawText::set_memory_budget.
+// the atlas isn't counted, it grows by pages as the glyphs on screen need them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MemoryBudget {
+    // vertex and staging buffers together
+    pub bytes_per_frame: u64,
+    pub over_budget: OverBudget,
+}
+
+// what happens to a frame whose buffers would go over the budget
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum OverBudget {
+    // vertices skip the staging copy and go straight into host visible memory,
+    // partial redraws upload all their regions as one. the frame is drawn whole, so it
+    // can still end up over the budget
+    #[default]
+    Economize,
+    // vertices that don't fit aren't uploaded and their text is missing from the frame,
+    // DrawText::budget_error tells. to catch text piling up during development
+    Fail,
+}
+
+// vertices of a frame that didn't fit its budget with OverBudget::Fail
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OverBudgetError {
+    pub bytes_per_frame: u64,
+    // what the frame's buffers would have taken
+    pub needed: u64,
+}
+
+impl fmt::Display for OverBudgetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "the frame's buffers take {} bytes, more than the memory budget of {}",
+            self.needed, self.bytes_per_frame
+        )
+    }
+}
+
+impl Error for OverBudgetError {}
+
+// the buffers allocated for the last frame, see DrawText::memory_stats
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MemoryStats {
+    pub vertex_bytes_last_frame: u64,
+    // vertices and atlas pixels on their way to device local memory
+    pub staging_bytes_last_frame: u64,
+    pub buffers_last_frame: usize,
+    // whether the budget had to step in
+    pub over_budget_last_frame: bool,
+    // the atlas images and their staging buffers of every frame in flight, kept from
+    // frame to frame
+    pub atlas_bytes: u64,
+}
+
+pub(crate) struct UploadedVertices {
+    pub(crate) buffer: VertexBuffer,
+    pub(crate) len: u32,
+    pub(crate) textured: Vec<TexturedRange>,
+}
+
+// the copy into device local memory can't be recorded inside a render pass, pass
+// None for command_buffer there to fall back to host visible memory
+pub(crate) fn upload_vertices(
+    command_buffer: Option<&mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>>,
+    memory_allocator: &Arc<StandardMemoryAllocator>,
+    memory: VertexMemory,
+    vertices: TextVertices,
+) -> Option<UploadedVertices> {
+    let TextVertices { vertices, textured } = vertices;
+    if vertices.is_empty() {
+        return None;
+    }
+
+    let len = vertices.len() as u32;
+    let command_buffer = match memory {
+        VertexMemory::DeviceLocal => command_buffer,
+        VertexMemory::HostVisible => None,
+    };
+    let buffer = compat::vertex_buffer(command_buffer, memory_allocator, vertices);
+    compat::name_buffer(&buffer, "font-render vertices");
+    Some(UploadedVertices {
+        buffer,
+        len,
+        textured,
+    })
+}
+
+// the frame slots of one target. frames drawn into different targets, like two
+// windows, are in flight independently of each other, so each target takes turns
+// among slots of its own
+#[derive(Default)]
+pub(crate) struct TargetFrames {
+    pub(crate) slots: Vec<FrameSlot>,
+    // atlas uploads for the target so far, the next one goes into the next slot
+    uploads: usize,
+    // DrawText's frame the target was last drawn in
+    pub(crate) drawn: usize,
+}
+
+impl TargetFrames {
+    // the slot the next upload goes into, the one whose turn it is
+    pub(crate) fn next_slot(&mut self, frames_in_flight: usize) -> usize {
+        self.uploads += 1;
+        (self.uploads - 1) % frames_in_flight
+    }
+
+    // the slot of the last upload
+    pub(crate) fn slot(&self, frames_in_flight: usize) -> usize {
+        self.uploads.saturating_sub(1) % frames_in_flight
+    }
+}
+
+// one frame's copy of the glyph atlas. the gpu may still sample the atlas of the
+// previous frames while the next one is recorded, so each frame in flight gets its
+// own image and staging buffer and they are only touched again once their turn comes.
+// every page of the atlas is a layer of the image
+pub(crate) struct FrameSlot {
+    atlas: Arc<Image>,
+    extent: [u32; 2],
+    pages: u32,
+    staging: HostBuffer<u8>,
+    // atlas version last uploaded into this slot
+    version: Option<u64>,
+    // samples the atlas, built by DrawText on first use
+    pub(crate) descriptor_set: Option<Arc<PersistentDescriptorSet>>,
+}
+
+impl FrameSlot {
+    pub(crate) fn new(
+        memory_allocator: &Arc<StandardMemoryAllocator>,
+        extent: [u32; 2],
+        pages: u32,
+    ) -> FrameSlot {
+        let atlas = compat::atlas_image(memory_allocator, extent, pages);
+        let staging = compat::staging_buffer(
+            memory_allocator,
+            extent[0] as u64 * extent[1] as u64 * pages as u64,
+        );
+        compat::name_image(&atlas, "font-render atlas");
+        compat::name_buffer(&staging, "font-render atlas staging");
+
+        FrameSlot {
+            atlas,
+            extent,
+            pages,
+            staging,
+            version: None,
+            descriptor_set: None,
+        }
+    }
+
+    // a slot with more pages that keeps this one's, copied on the gpu, and its version,
+    // so this slot has to be one the gpu is done with. only the new pages come from
+    // pixels, growing costs a page's upload instead of the whole atlas's. glyphs stay
+    // on their page, their uvs don't change. with as many pages it's a plain copy
+    pub(crate) fn grown(
+        &self,
+        command_buffer: &mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>,
+        memory_allocator: &Arc<StandardMemoryAllocator>,
+        pixels: &[u8],
+        pages: u32,
+    ) -> FrameSlot {
+        let mut slot = FrameSlot::new(memory_allocator, self.extent, pages);
+        if self.version.is_none() {
+            return slot;
+        }
+        compat::copy_image(command_buffer, &self.atlas, &slot.atlas);
+
+        let page_bytes = self.extent[0] as usize * self.extent[1] as usize;
+        let new_pages = self.pages as usize * page_bytes..pages as usize * page_bytes;
+        slot.staging.write().unwrap()[new_pages.clone()].copy_from_slice(&pixels[new_pages]);
+        let regions: Vec<_> = (self.pages..pages)
+            .map(|page| (page, [0, 0], self.extent))
+            .collect();
+        if !regions.is_empty() {
+            compat::copy_buffer_regions_to_image(
+                command_buffer,
+                &slot.staging,
+                &slot.atlas,
+                self.extent,
+                &regions,
+            );
+        }
+        slot.version = self.version;
+        slot
+    }
+
+    // a slot with the glyphs of this one moved where a repack put them, copied on the
+    // gpu. like grown, this slot has to be one the gpu is done with and up to date with
+    // the atlas before the repack. version is the atlas's after it
+    pub(crate) fn moved(
+        &self,
+        command_buffer: &mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>,
+        memory_allocator: &Arc<StandardMemoryAllocator>,
+        moves: &[GlyphMove],
+        version: u64,
+    ) -> FrameSlot {
+        let mut slot = FrameSlot::new(memory_allocator, self.extent, self.pages);
+        let regions: Vec<_> = moves
+            .iter()
+            .map(|glyph| {
+                (
+                    glyph.page,
+                    [glyph.rect.min.x, glyph.rect.min.y],
+                    glyph.to_page,
+                    glyph.to,
+                    [glyph.rect.width(), glyph.rect.height()],
+                )
+            })
+            .collect();
+        if !regions.is_empty() {
+            compat::copy_image_regions(command_buffer, &self.atlas, &slot.atlas, &regions);
+        }
+        slot.version = Some(version);
+        slot
+    }
+
+    pub(crate) fn atlas(&self) -> &Arc<Image> {
+        &self.atlas
+    }
+
+    pub(crate) fn pages(&self) -> u32 {
+        self.pages
+    }
+
+    // the image and its staging buffer, a byte per pixel each
+    pub(crate) fn bytes(&self) -> u64 {
+        2 * self.extent[0] as u64 * self.extent[1] as u64 * self.pages as u64
+    }
+
+    pub(crate) fn version(&self) -> Option<u64> {
+        self.version
+    }
+
+    // copies the atlas's pixels unless this slot already holds that version, returns
+    // the bytes copied. only the rects changed since the slot's last upload are copied
+    // when the atlas still has them all, a slot that never had one gets everything
+    pub(crate) fn upload(
+        &mut self,
+        command_buffer: &mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>,
+        atlas: &AtlasPixels,
+    ) -> usize {
+        let (pixels, version) = (atlas.pixels, atlas.version);
+        if self.version == Some(version) {
+            return 0;
+        }
+
+        let regions = match self.version {
+            Some(uploaded) if uploaded >= atlas.dirty_since => coalesce(
+                atlas
+                    .dirty
+                    .iter()
+                    .filter(|rect| rect.version > uploaded)
+                    .map(|rect| (rect.page, rect.rect)),
+            ),
+            _ => vec![],
+        };
+        self.version = Some(version);
+        if regions.is_empty() || regions.len() > MAX_REGIONS {
+            self.staging.write().unwrap().copy_from_slice(pixels);
+            compat::copy_buffer_to_image(command_buffer, &self.staging, &self.atlas);
+            return pixels.len();
+        }
+
+        // same offsets as in pixels, so the copies can address the staging buffer
+        // like the whole atlas
+        let [width, height] = self.extent;
+        let mut staging = self.staging.write().unwrap();
+        let mut bytes = 0;
+        let regions: Vec<_> = regions
+            .into_iter()
+            .map(|(page, rect)| {
+                let page_start = page as usize * width as usize * height as usize;
+                for y in rect.min.y..rect.max.y {
+                    let start = page_start + y as usize * width as usize + rect.min.x as usize;
+                    let end = start + rect.width() as usize;
+                    staging[start..end].copy_from_slice(&pixels[start..end]);
+                }
+                bytes += rect.width() as usize * rect.height() as usize;
+                (
+                    page,
+                    [rect.min.x, rect.min.y],
+                    [rect.width(), rect.height()],
+                )
+            })
+            .collect();
+        drop(staging);
+        compat::copy_buffer_regions_to_image(
+            command_buffer,
+            &self.staging,
+            &self.atlas,
+            self.extent,
+            &regions,
+        );
+        bytes
+    }
+}
+
+// more than this many copies and the whole atlas goes in one
+const MAX_REGIONS: usize = 256;
+
+// merges rects on the same page that overlap or touch into their bounding box. glyphs
+// are packed in rows, so a frame's new glyphs mostly end up as a few strips. whatever
+// the boxes cover besides them is copied again unchanged
+fn coalesce(rects: impl Iterator<Item = (u32, Rect<u32>)>) -> Vec<(u32, Rect<u32>)> {
+    let mut merged: Vec<(u32, Rect<u32>)> = vec![];
+    for (page, mut rect) in rects {
+        // a grown box may now reach boxes it didn't before
+        while let Some(i) = merged.iter().position(|&(other_page, other)| {
+            other_page == page
+                && other.min.x <= rect.max.x
+                && rect.min.x <= other.max.x
+                && other.min.y <= rect.max.y
+                && rect.min.y <= other.max.y
+        }) {
+            let (_, other) = merged.swap_remove(i);
+            rect = Rect {
+                min: point(rect.min.x.min(other.min.x), rect.min.y.min(other.min.y)),
+                max: point(rect.max.x.max(other.max.x), rect.max.y.max(other.max.y)),
+            };
+        }
+        merged.push((page, rect));
+    }
+    merged
+}
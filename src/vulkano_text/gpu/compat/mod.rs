@@ -0,0 +1,11 @@
+// everything that differs between the vulkano versions the vulkano-032 and
+// vulkano-034 features pick, both modules have the same items
+#[cfg(feature = "vulkano-032")]
+mod v032;
+#[cfg(feature = "vulkano-034")]
+mod v034;
+
+#[cfg(feature = "vulkano-032")]
+pub use v032::*;
+#[cfg(feature = "vulkano-034")]
+pub use v034::*;
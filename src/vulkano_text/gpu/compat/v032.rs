@@ -0,0 +1,804 @@
+use crate::vulkano_text::mesh::MeshVertex;
+use crate::vulkano_text::quads::Vertex;
+
+use vulkano::buffer::{
+    BufferAccess, BufferContents, BufferUsage, CpuAccessibleBuffer, DeviceLocalBuffer,
+};
+use vulkano::command_buffer::{
+    AutoCommandBufferBuilder, BufferImageCopy, ClearAttachment, ClearRect, CopyBufferToImageInfo,
+    CopyImageInfo, CopyImageToBufferInfo, DrawIndirectCommand, ImageCopy, PrimaryAutoCommandBuffer,
+    RenderPassBeginInfo, SecondaryAutoCommandBuffer, SubpassContents,
+};
+use vulkano::descriptor_set::allocator::StandardDescriptorSetAllocator;
+use vulkano::descriptor_set::layout::DescriptorSetLayout;
+use vulkano::descriptor_set::{
+    DescriptorSetsCollection, PersistentDescriptorSet, WriteDescriptorSet,
+};
+use vulkano::device::{Device, DeviceOwned, Queue};
+use vulkano::format::{ClearColorValue, ClearValue, Format, NumericType};
+use vulkano::image::view::{ImageViewAbstract, ImageViewCreateInfo, ImageViewType};
+use vulkano::image::{
+    AttachmentImage, ImageAccess, ImageAspect, ImageCreateFlags, ImageDimensions,
+    ImageSubresourceLayers, ImageUsage, SampleCount, StorageImage,
+};
+use vulkano::instance::debug::DebugUtilsLabel;
+use vulkano::memory::allocator::StandardMemoryAllocator;
+use vulkano::pipeline::graphics::color_blend::{
+    AttachmentBlend, BlendFactor, BlendOp, ColorBlendState,
+};
+use vulkano::pipeline::graphics::multisample::MultisampleState;
+use vulkano::pipeline::graphics::render_pass::PipelineRenderPassType;
+use vulkano::pipeline::graphics::vertex_input::{
+    BuffersDefinition, VertexDefinition, VertexInputState,
+};
+use vulkano::pipeline::graphics::viewport::{Scissor, Viewport, ViewportState};
+use vulkano::pipeline::{ComputePipeline, GraphicsPipeline, Pipeline, PipelineBindPoint};
+#[cfg(feature = "gpu_timing")]
+use vulkano::query::{QueryPool, QueryPoolCreateInfo, QueryResultFlags, QueryType};
+use vulkano::render_pass::{Framebuffer, RenderPass, Subpass};
+use vulkano::shader::ShaderModule;
+#[cfg(feature = "gpu_timing")]
+use vulkano::sync::PipelineStage;
+use vulkano::{DeviceSize, VulkanObject};
+
+#[cfg(feature = "gpu_timing")]
+use std::ops::Range;
+use std::sync::Arc;
+
+pub use vulkano::sampler::{
+    Filter, Sampler, SamplerAddressMode, SamplerCreateInfo, SamplerMipmapMode,
+};
+
+// 0.32 images are generic over their kind, the library only deals in trait objects
+pub type Image = dyn ImageAccess;
+pub type ImageView = dyn ImageViewAbstract;
+pub type SecondaryCommandBuffer = SecondaryAutoCommandBuffer;
+// the vertex and index buffers of a TextMesh
+pub type MeshBuffer<T> = Arc<CpuAccessibleBuffer<[T]>>;
+pub(crate) type HostBuffer<T> = Arc<CpuAccessibleBuffer<[T]>>;
+pub(crate) type DeviceBuffer<T> = Arc<DeviceLocalBuffer<[T]>>;
+pub(crate) type VertexBuffer = Arc<dyn BufferAccess>;
+
+type Builder = AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>;
+
+vulkano::impl_vertex!(Vertex, position, tex_position, color, fade, fill_uv);
+vulkano::impl_vertex!(MeshVertex, position, normal);
+
+// an image text can be drawn into, sampled, cleared and read back
+pub(crate) fn render_image(
+    memory_allocator: &Arc<StandardMemoryAllocator>,
+    format: Format,
+    extent: [u32; 2],
+) -> Arc<Image> {
+    AttachmentImage::with_usage(
+        memory_allocator.as_ref(),
+        extent,
+        format,
+        ImageUsage {
+            sampled: true,
+            transfer_src: true,
+            transfer_dst: true,
+            ..ImageUsage::empty()
+        },
+    )
+    .unwrap()
+}
+
+// a texture filled by copies, like the msdf atlas
+pub(crate) fn texture_image(
+    memory_allocator: &Arc<StandardMemoryAllocator>,
+    format: Format,
+    extent: [u32; 2],
+) -> Arc<Image> {
+    StorageImage::with_usage(
+        memory_allocator.as_ref(),
+        ImageDimensions::Dim2d {
+            width: extent[0],
+            height: extent[1],
+            array_layers: 1,
+        },
+        format,
+        ImageUsage {
+            sampled: true,
+            transfer_dst: true,
+            ..ImageUsage::empty()
+        },
+        ImageCreateFlags::empty(),
+        [],
+    )
+    .unwrap()
+}
+
+pub(crate) fn atlas_image(
+    memory_allocator: &Arc<StandardMemoryAllocator>,
+    extent: [u32; 2],
+    pages: u32,
+) -> Arc<Image> {
+    StorageImage::with_usage(
+        memory_allocator.as_ref(),
+        ImageDimensions::Dim2d {
+            width: extent[0],
+            height: extent[1],
+            array_layers: pages,
+        },
+        Format::R8_UNORM,
+        // a source too, for growing the atlas
+        ImageUsage {
+            sampled: true,
+            transfer_src: true,
+            transfer_dst: true,
+            ..ImageUsage::empty()
+        },
+        ImageCreateFlags::empty(),
+        [],
+    )
+    .unwrap()
+}
+
+pub(crate) fn view(image: Arc<Image>) -> Arc<ImageView> {
+    vulkano::image::view::ImageView::new_default(image).unwrap()
+}
+
+// every layer of the image, for sampling the atlas pages
+pub(crate) fn array_view(image: &Arc<Image>) -> Arc<ImageView> {
+    vulkano::image::view::ImageView::new(
+        image.clone(),
+        ImageViewCreateInfo {
+            view_type: ImageViewType::Dim2dArray,
+            ..ImageViewCreateInfo::from_image(image.as_ref())
+        },
+    )
+    .unwrap()
+}
+
+pub(crate) fn image_extent(image: &Image) -> [u32; 2] {
+    image.dimensions().width_height()
+}
+
+// bytes of the first layer, tightly packed
+pub(crate) fn image_bytes(image: &Image) -> DeviceSize {
+    let [width, height] = image_extent(image);
+    width as DeviceSize * height as DeviceSize * image.format().block_size().unwrap()
+}
+
+pub(crate) fn view_format(view: &ImageView) -> Format {
+    view.format().unwrap()
+}
+
+// srgb and float formats take linear values from the shader and encode them
+pub(crate) fn linear_format(format: Format) -> bool {
+    matches!(
+        format.type_color(),
+        Some(NumericType::SRGB | NumericType::SFLOAT | NumericType::UFLOAT)
+    )
+}
+
+// for the first color attachment of subpass
+pub(crate) fn linear_output(subpass: &Subpass) -> bool {
+    subpass
+        .subpass_desc()
+        .color_attachments
+        .iter()
+        .flatten()
+        .next()
+        .and_then(|reference| {
+            subpass.render_pass().attachments()[reference.attachment as usize].format
+        })
+        .map_or(false, linear_format)
+}
+
+// what the framebuffer's only attachment draws into
+pub(crate) fn framebuffer_image(framebuffer: &Framebuffer) -> Arc<Image> {
+    framebuffer.attachments()[0].image()
+}
+
+pub(crate) fn is_color_attachment(view: &ImageView) -> bool {
+    view.usage().color_attachment
+}
+
+// in device local memory when command_buffer is given to record the copy into
+pub(crate) fn vertex_buffer(
+    command_buffer: Option<&mut Builder>,
+    memory_allocator: &Arc<StandardMemoryAllocator>,
+    vertices: Vec<Vertex>,
+) -> VertexBuffer {
+    let usage = BufferUsage {
+        vertex_buffer: true,
+        ..BufferUsage::empty()
+    };
+    let allocator = memory_allocator.as_ref();
+    match command_buffer {
+        Some(command_buffer) => {
+            DeviceLocalBuffer::from_iter(allocator, vertices, usage, command_buffer).unwrap()
+        }
+        None => CpuAccessibleBuffer::from_iter(allocator, usage, false, vertices).unwrap(),
+    }
+}
+
+pub(crate) fn mesh_buffers(
+    memory_allocator: &Arc<StandardMemoryAllocator>,
+    vertices: Vec<MeshVertex>,
+    indices: Vec<u32>,
+) -> (MeshBuffer<MeshVertex>, MeshBuffer<u32>) {
+    let allocator = memory_allocator.as_ref();
+    let usage = |vertex_buffer, index_buffer| BufferUsage {
+        vertex_buffer,
+        index_buffer,
+        ..BufferUsage::empty()
+    };
+    (
+        CpuAccessibleBuffer::from_iter(allocator, usage(true, false), false, vertices).unwrap(),
+        CpuAccessibleBuffer::from_iter(allocator, usage(false, true), false, indices).unwrap(),
+    )
+}
+
+pub(crate) fn staging_buffer(
+    memory_allocator: &Arc<StandardMemoryAllocator>,
+    len: DeviceSize,
+) -> HostBuffer<u8> {
+    let usage = BufferUsage {
+        transfer_src: true,
+        ..BufferUsage::empty()
+    };
+    let data = (0..len).map(|_| 0u8);
+    CpuAccessibleBuffer::from_iter(memory_allocator.as_ref(), usage, false, data).unwrap()
+}
+
+pub(crate) fn readback_buffer(
+    memory_allocator: &Arc<StandardMemoryAllocator>,
+    len: DeviceSize,
+) -> HostBuffer<u8> {
+    let usage = BufferUsage {
+        transfer_dst: true,
+        ..BufferUsage::empty()
+    };
+    let data = (0..len).map(|_| 0u8);
+    CpuAccessibleBuffer::from_iter(memory_allocator.as_ref(), usage, true, data).unwrap()
+}
+
+// read_back for buffers the shader writes and the cpu reads afterwards
+pub(crate) fn storage_buffer<T, I>(
+    memory_allocator: &Arc<StandardMemoryAllocator>,
+    data: I,
+    read_back: bool,
+) -> HostBuffer<T>
+where
+    [T]: BufferContents,
+    I: IntoIterator<Item = T>,
+    I::IntoIter: ExactSizeIterator,
+{
+    let usage = BufferUsage {
+        storage_buffer: true,
+        ..BufferUsage::empty()
+    };
+    CpuAccessibleBuffer::from_iter(memory_allocator.as_ref(), usage, read_back, data).unwrap()
+}
+
+pub(crate) fn device_storage_buffer<T>(
+    memory_allocator: &Arc<StandardMemoryAllocator>,
+    len: DeviceSize,
+) -> DeviceBuffer<T>
+where
+    [T]: BufferContents,
+{
+    let usage = BufferUsage {
+        storage_buffer: true,
+        ..BufferUsage::empty()
+    };
+    DeviceLocalBuffer::array(memory_allocator.as_ref(), len, usage, []).unwrap()
+}
+
+// also bound as a storage buffer, so shaders can fill in the counts
+pub(crate) fn indirect_buffer(
+    memory_allocator: &Arc<StandardMemoryAllocator>,
+    commands: Vec<DrawIndirectCommand>,
+) -> HostBuffer<DrawIndirectCommand> {
+    let usage = BufferUsage {
+        storage_buffer: true,
+        indirect_buffer: true,
+        ..BufferUsage::empty()
+    };
+    CpuAccessibleBuffer::from_iter(memory_allocator.as_ref(), usage, false, commands).unwrap()
+}
+
+pub(crate) fn copy_buffer_to_image(
+    command_buffer: &mut Builder,
+    buffer: &HostBuffer<u8>,
+    image: &Arc<Image>,
+) {
+    command_buffer
+        .copy_buffer_to_image(CopyBufferToImageInfo::buffer_image(
+            buffer.clone(),
+            image.clone(),
+        ))
+        .unwrap();
+}
+
+// copies only the given (page, offset, extent) rects. buffer is laid out like the
+// image, rows of row_length texels and its layers one after the other
+pub(crate) fn copy_buffer_regions_to_image(
+    command_buffer: &mut Builder,
+    buffer: &HostBuffer<u8>,
+    image: &Arc<Image>,
+    image_extent: [u32; 2],
+    regions: &[(u32, [u32; 2], [u32; 2])],
+) {
+    let [width, height] = image_extent;
+    let regions = regions
+        .iter()
+        .map(|&(page, [x, y], [w, h])| BufferImageCopy {
+            buffer_offset: (page as DeviceSize * height as DeviceSize + y as DeviceSize)
+                * width as DeviceSize
+                + x as DeviceSize,
+            buffer_row_length: width,
+            buffer_image_height: height,
+            image_subresource: ImageSubresourceLayers {
+                aspects: ImageAspect::Color.into(),
+                mip_level: 0,
+                array_layers: page..page + 1,
+            },
+            image_offset: [x, y, 0],
+            image_extent: [w, h, 1],
+            ..Default::default()
+        })
+        .collect();
+    command_buffer
+        .copy_buffer_to_image(CopyBufferToImageInfo {
+            regions,
+            ..CopyBufferToImageInfo::buffer_image(buffer.clone(), image.clone())
+        })
+        .unwrap();
+}
+
+// copies (offset, extent) rects of a single layer image out of a buffer holding just
+// their texels, tightly packed one rect after the other
+pub(crate) fn copy_packed_regions_to_image(
+    command_buffer: &mut Builder,
+    buffer: &HostBuffer<u8>,
+    image: &Arc<Image>,
+    texel_bytes: u32,
+    regions: &[([u32; 2], [u32; 2])],
+) {
+    let mut buffer_offset = 0;
+    let regions = regions
+        .iter()
+        .map(|&([x, y], [w, h])| {
+            let region = BufferImageCopy {
+                buffer_offset,
+                image_subresource: ImageSubresourceLayers {
+                    aspects: ImageAspect::Color.into(),
+                    mip_level: 0,
+                    array_layers: 0..1,
+                },
+                image_offset: [x, y, 0],
+                image_extent: [w, h, 1],
+                ..Default::default()
+            };
+            buffer_offset += w as DeviceSize * h as DeviceSize * texel_bytes as DeviceSize;
+            region
+        })
+        .collect();
+    command_buffer
+        .copy_buffer_to_image(CopyBufferToImageInfo {
+            regions,
+            ..CopyBufferToImageInfo::buffer_image(buffer.clone(), image.clone())
+        })
+        .unwrap();
+}
+
+// every layer the two images have in common, they have the same extent
+pub(crate) fn copy_image(command_buffer: &mut Builder, src: &Arc<Image>, dst: &Arc<Image>) {
+    command_buffer
+        .copy_image(CopyImageInfo::images(src.clone(), dst.clone()))
+        .unwrap();
+}
+
+// copies only the given (src page, src offset, dst page, dst offset, extent) rects
+pub(crate) fn copy_image_regions(
+    command_buffer: &mut Builder,
+    src: &Arc<Image>,
+    dst: &Arc<Image>,
+    regions: &[(u32, [u32; 2], u32, [u32; 2], [u32; 2])],
+) {
+    let layer = |page: u32| ImageSubresourceLayers {
+        aspects: ImageAspect::Color.into(),
+        mip_level: 0,
+        array_layers: page..page + 1,
+    };
+    let regions = regions
+        .iter()
+        .map(
+            |&(src_page, [sx, sy], dst_page, [dx, dy], [w, h])| ImageCopy {
+                src_subresource: layer(src_page),
+                src_offset: [sx, sy, 0],
+                dst_subresource: layer(dst_page),
+                dst_offset: [dx, dy, 0],
+                extent: [w, h, 1],
+                ..Default::default()
+            },
+        )
+        .collect();
+    command_buffer
+        .copy_image(CopyImageInfo {
+            regions,
+            ..CopyImageInfo::images(src.clone(), dst.clone())
+        })
+        .unwrap();
+}
+
+pub(crate) fn copy_image_to_buffer(
+    command_buffer: &mut Builder,
+    image: &Arc<Image>,
+    buffer: &HostBuffer<u8>,
+) {
+    command_buffer
+        .copy_image_to_buffer(CopyImageToBufferInfo::image_buffer(
+            image.clone(),
+            buffer.clone(),
+        ))
+        .unwrap();
+}
+
+// inline contents, nothing in this crate records secondary buffers into its own passes
+pub(crate) fn begin_render_pass(
+    command_buffer: &mut Builder,
+    framebuffer: Arc<Framebuffer>,
+    clear_values: Vec<Option<ClearValue>>,
+) {
+    command_buffer
+        .begin_render_pass(
+            RenderPassBeginInfo {
+                clear_values,
+                ..RenderPassBeginInfo::framebuffer(framebuffer)
+            },
+            SubpassContents::Inline,
+        )
+        .unwrap();
+}
+
+pub(crate) fn end_render_pass(command_buffer: &mut Builder) {
+    command_buffer.end_render_pass().unwrap();
+}
+
+pub(crate) fn bind_graphics<L>(
+    command_buffer: &mut AutoCommandBufferBuilder<L>,
+    pipeline: &Arc<GraphicsPipeline>,
+    sets: impl DescriptorSetsCollection,
+) {
+    command_buffer
+        .bind_pipeline_graphics(pipeline.clone())
+        .bind_descriptor_sets(
+            PipelineBindPoint::Graphics,
+            pipeline.layout().clone(),
+            0,
+            sets,
+        );
+}
+
+pub(crate) fn push_constants<L>(
+    command_buffer: &mut AutoCommandBufferBuilder<L>,
+    pipeline: &Arc<impl Pipeline>,
+    data: impl BufferContents,
+) {
+    command_buffer.push_constants(pipeline.layout().clone(), 0, data);
+}
+
+pub(crate) fn bind_compute<L>(
+    command_buffer: &mut AutoCommandBufferBuilder<L>,
+    pipeline: &Arc<ComputePipeline>,
+    sets: impl DescriptorSetsCollection,
+) {
+    command_buffer
+        .bind_pipeline_compute(pipeline.clone())
+        .bind_descriptor_sets(
+            PipelineBindPoint::Compute,
+            pipeline.layout().clone(),
+            0,
+            sets,
+        );
+}
+
+pub(crate) fn bind_vertex_buffer<L>(
+    command_buffer: &mut AutoCommandBufferBuilder<L>,
+    buffer: &VertexBuffer,
+) {
+    command_buffer.bind_vertex_buffers(0, buffer.clone());
+}
+
+// draws with the command at index
+pub(crate) fn draw_indirect<L>(
+    command_buffer: &mut AutoCommandBufferBuilder<L>,
+    commands: &HostBuffer<DrawIndirectCommand>,
+    index: DeviceSize,
+) {
+    let command = commands.slice(index..index + 1).unwrap();
+    command_buffer.draw_indirect(command).unwrap();
+}
+
+pub(crate) fn scissor(offset: [u32; 2], extent: [u32; 2]) -> Scissor {
+    Scissor {
+        origin: offset,
+        dimensions: extent,
+    }
+}
+
+// scissor is in pixels and defaults to the whole extent
+pub(crate) fn set_viewport_scissor<L>(
+    command_buffer: &mut AutoCommandBufferBuilder<L>,
+    extent: [u32; 2],
+    scissor: Option<Scissor>,
+) {
+    command_buffer
+        .set_viewport(
+            0,
+            [Viewport {
+                origin: [0.0, 0.0],
+                dimensions: [extent[0] as f32, extent[1] as f32],
+                depth_range: 0.0..1.0,
+            }],
+        )
+        .set_scissor(
+            0,
+            [scissor.unwrap_or_else(|| self::scissor([0, 0], extent))],
+        );
+}
+
+// clears the first color attachment to transparent inside scissor
+pub(crate) fn clear_region(command_buffer: &mut Builder, scissor: &Scissor) {
+    command_buffer
+        .clear_attachments(
+            [ClearAttachment::Color {
+                color_attachment: 0,
+                clear_value: ClearColorValue::Float([0.0; 4]),
+            }],
+            [ClearRect {
+                offset: scissor.origin,
+                extent: scissor.dimensions,
+                array_layers: 0..1,
+            }],
+        )
+        .unwrap();
+}
+
+pub(crate) fn descriptor_set_allocator(device: Arc<Device>) -> StandardDescriptorSetAllocator {
+    StandardDescriptorSetAllocator::new(device)
+}
+
+pub(crate) fn descriptor_set(
+    allocator: &StandardDescriptorSetAllocator,
+    layout: Arc<DescriptorSetLayout>,
+    writes: impl IntoIterator<Item = WriteDescriptorSet>,
+) -> Arc<PersistentDescriptorSet> {
+    PersistentDescriptorSet::new(allocator, layout, writes).unwrap()
+}
+
+// a single subpass drawing over whatever the color attachment holds
+pub(crate) fn color_render_pass(device: Arc<Device>, format: Format) -> Arc<RenderPass> {
+    vulkano::single_pass_renderpass!(device,
+        attachments: {
+            color: {
+                load: Load,
+                store: Store,
+                format: format,
+                samples: 1,
+            }
+        },
+        pass: {
+            color: [color],
+            depth_stencil: {}
+        }
+    )
+    .unwrap()
+}
+
+// a triangle list pipeline drawing into subpass, viewport and scissor are set when
+// drawing. without vertex_input the vertex shader fetches its vertices itself,
+// otherwise it reads quads' Vertex
+pub(crate) fn graphics_pipeline(
+    vs: Arc<ShaderModule>,
+    fs: Arc<ShaderModule>,
+    vertex_input: bool,
+    subpass: Subpass,
+    blend: AttachmentBlend,
+) -> Arc<GraphicsPipeline> {
+    try_graphics_pipeline(vs, fs, vertex_input, subpass, blend).unwrap()
+}
+
+// vulkano's reason as the error, for shaders of the caller's
+pub(crate) fn try_graphics_pipeline(
+    vs: Arc<ShaderModule>,
+    fs: Arc<ShaderModule>,
+    vertex_input: bool,
+    subpass: Subpass,
+    blend: AttachmentBlend,
+) -> Result<Arc<GraphicsPipeline>, String> {
+    let device = subpass.render_pass().device().clone();
+    // drawing into a multisampled subpass needs a pipeline with its sample count
+    let rasterization_samples = subpass.num_samples().unwrap_or(SampleCount::Sample1);
+    let no_main = || "no main entry point".to_string();
+    let vs = vs.entry_point("main").ok_or_else(no_main)?;
+    let fs = fs.entry_point("main").ok_or_else(no_main)?;
+    let vertex_input_state = match vertex_input {
+        true => BuffersDefinition::new()
+            .vertex::<Vertex>()
+            .definition(vs.input_interface())
+            .map_err(|error| error.to_string())?,
+        false => VertexInputState::new(),
+    };
+
+    GraphicsPipeline::start()
+        .vertex_input_state(vertex_input_state)
+        .vertex_shader(vs, ())
+        .triangle_list()
+        .viewport_state(ViewportState::viewport_dynamic_scissor_dynamic(1))
+        .fragment_shader(fs, ())
+        .multisample_state(MultisampleState {
+            rasterization_samples,
+            ..MultisampleState::new()
+        })
+        .color_blend_state(ColorBlendState::new(subpass.num_color_attachments()).blend(blend))
+        .render_pass(subpass)
+        .build(device)
+        .map_err(|error| error.to_string())
+}
+
+pub(crate) fn compute_pipeline(cs: Arc<ShaderModule>) -> Arc<ComputePipeline> {
+    ComputePipeline::new(
+        cs.device().clone(),
+        cs.entry_point("main").unwrap(),
+        &(),
+        None,
+        |_| {},
+    )
+    .unwrap()
+}
+
+// spir-v compiled at runtime, see shader_reload
+#[cfg(feature = "shader_reload")]
+pub(crate) fn shader_module(device: Arc<Device>, words: &[u32]) -> Arc<ShaderModule> {
+    unsafe { ShaderModule::from_words(device, words) }.unwrap()
+}
+
+// the subpass and blend a pipeline of graphics_pipeline was built with
+pub(crate) fn pipeline_target(pipeline: &GraphicsPipeline) -> (Subpass, AttachmentBlend) {
+    let subpass = match pipeline.render_pass() {
+        PipelineRenderPassType::BeginRenderPass(subpass) => subpass.clone(),
+        PipelineRenderPassType::BeginRendering(_) => unreachable!(),
+    };
+    let blend = pipeline
+        .color_blend_state()
+        .and_then(|state| state.attachments[0].blend)
+        .unwrap();
+    (subpass, blend)
+}
+
+// for targets that get composited later: the text blend would multiply alpha into
+// the color a second time when compositing, so keep the color premultiplied and
+// accumulate alpha instead
+pub(crate) fn premultiplied_blend() -> AttachmentBlend {
+    AttachmentBlend {
+        alpha_source: BlendFactor::One,
+        ..AttachmentBlend::alpha()
+    }
+}
+
+// draws a premultiplied image over the target
+pub(crate) fn premultiplied_over() -> AttachmentBlend {
+    AttachmentBlend {
+        color_op: BlendOp::Add,
+        color_source: BlendFactor::One,
+        color_destination: BlendFactor::OneMinusSrcAlpha,
+        alpha_op: BlendOp::Add,
+        alpha_source: BlendFactor::One,
+        alpha_destination: BlendFactor::OneMinusSrcAlpha,
+    }
+}
+
+pub(crate) fn supports_compute(queue: &Queue) -> bool {
+    let properties = queue.device().physical_device().queue_family_properties();
+    properties[queue.queue_family_index() as usize]
+        .queue_flags
+        .compute
+}
+
+// objects and debug regions only get names when the application enabled
+// VK_EXT_debug_utils on its instance, for validation messages and RenderDoc captures
+fn debug_utils(device: &Device) -> bool {
+    device.instance().enabled_extensions().ext_debug_utils
+}
+
+pub(crate) fn set_name<T: VulkanObject + DeviceOwned>(object: &T, name: &str) {
+    if debug_utils(object.device()) {
+        object
+            .device()
+            .set_debug_utils_object_name(object, Some(name))
+            .unwrap();
+    }
+}
+
+pub(crate) fn name_image(image: &Image, name: &str) {
+    set_name(&**image.inner().image, name);
+}
+
+pub(crate) fn name_buffer<B: BufferAccess + ?Sized>(buffer: &B, name: &str) {
+    set_name(&**buffer.inner().buffer, name);
+}
+
+// a region of the command buffer named in captures, closed by end_label
+pub(crate) fn begin_label<L>(command_buffer: &mut AutoCommandBufferBuilder<L>, name: &str) {
+    if debug_utils(command_buffer.device()) {
+        command_buffer
+            .begin_debug_utils_label(DebugUtilsLabel {
+                label_name: name.to_owned(),
+                ..Default::default()
+            })
+            .unwrap();
+    }
+}
+
+pub(crate) fn end_label<L>(command_buffer: &mut AutoCommandBufferBuilder<L>) {
+    if debug_utils(command_buffer.device()) {
+        unsafe {
+            command_buffer.end_debug_utils_label().unwrap();
+        }
+    }
+}
+
+// how many bits of a timestamp count, None when the queue can't write them
+#[cfg(feature = "gpu_timing")]
+pub(crate) fn timestamp_bits(queue: &Queue) -> Option<u32> {
+    let properties = queue.device().physical_device().queue_family_properties();
+    properties[queue.queue_family_index() as usize].timestamp_valid_bits
+}
+
+#[cfg(feature = "gpu_timing")]
+pub(crate) fn timestamp_pool(device: Arc<Device>, query_count: u32) -> Arc<QueryPool> {
+    QueryPool::new(
+        device,
+        QueryPoolCreateInfo {
+            query_count,
+            ..QueryPoolCreateInfo::query_type(QueryType::Timestamp)
+        },
+    )
+    .unwrap()
+}
+
+// resets the queries and writes the first of them once the gpu gets to this point
+#[cfg(feature = "gpu_timing")]
+pub(crate) fn begin_timestamps(
+    command_buffer: &mut Builder,
+    query_pool: &Arc<QueryPool>,
+    queries: Range<u32>,
+) {
+    unsafe {
+        command_buffer
+            .reset_query_pool(query_pool.clone(), queries.clone())
+            .unwrap()
+            .write_timestamp(query_pool.clone(), queries.start, PipelineStage::TopOfPipe)
+            .unwrap();
+    }
+}
+
+// once everything recorded before is done
+#[cfg(feature = "gpu_timing")]
+pub(crate) fn end_timestamp(command_buffer: &mut Builder, query_pool: &Arc<QueryPool>, query: u32) {
+    unsafe {
+        command_buffer
+            .write_timestamp(query_pool.clone(), query, PipelineStage::BottomOfPipe)
+            .unwrap();
+    }
+}
+
+// doesn't wait, false while the gpu hasn't written all of them yet
+#[cfg(feature = "gpu_timing")]
+pub(crate) fn query_results(
+    query_pool: &QueryPool,
+    queries: Range<u32>,
+    results: &mut [u64],
+) -> bool {
+    query_pool
+        .queries_range(queries)
+        .unwrap()
+        .get_results(results, QueryResultFlags::empty())
+        .unwrap()
+}
@@ -0,0 +1,979 @@
+use crate::vulkano_text::mesh::MeshVertex;
+use crate::vulkano_text::quads::Vertex;
+
+use vulkano::buffer::{Buffer, BufferContents, BufferCreateInfo, BufferUsage, Subbuffer};
+use vulkano::command_buffer::{
+    AutoCommandBufferBuilder, BufferImageCopy, ClearAttachment, ClearRect, CopyBufferInfo,
+    CopyBufferToImageInfo, CopyImageInfo, CopyImageToBufferInfo, DrawIndirectCommand, ImageCopy,
+    PrimaryAutoCommandBuffer, RenderPassBeginInfo, SecondaryAutoCommandBuffer, SubpassBeginInfo,
+    SubpassEndInfo,
+};
+use vulkano::descriptor_set::allocator::StandardDescriptorSetAllocator;
+use vulkano::descriptor_set::layout::DescriptorSetLayout;
+use vulkano::descriptor_set::{
+    DescriptorSetsCollection, PersistentDescriptorSet, WriteDescriptorSet,
+};
+use vulkano::device::{Device, DeviceOwned, Queue, QueueFlags};
+use vulkano::format::{ClearColorValue, ClearValue, Format, NumericFormat};
+use vulkano::image::view::{ImageViewCreateInfo, ImageViewType};
+use vulkano::image::{
+    ImageAspects, ImageCreateInfo, ImageSubresourceLayers, ImageType, ImageUsage, SampleCount,
+};
+use vulkano::instance::debug::DebugUtilsLabel;
+use vulkano::memory::allocator::{AllocationCreateInfo, MemoryTypeFilter, StandardMemoryAllocator};
+use vulkano::pipeline::compute::ComputePipelineCreateInfo;
+use vulkano::pipeline::graphics::color_blend::{
+    AttachmentBlend, BlendFactor, BlendOp, ColorBlendAttachmentState, ColorBlendState,
+};
+use vulkano::pipeline::graphics::input_assembly::InputAssemblyState;
+use vulkano::pipeline::graphics::multisample::MultisampleState;
+use vulkano::pipeline::graphics::rasterization::RasterizationState;
+use vulkano::pipeline::graphics::subpass::PipelineSubpassType;
+use vulkano::pipeline::graphics::vertex_input::{
+    VertexBufferDescription, VertexDefinition, VertexInputRate, VertexInputState, VertexMemberInfo,
+};
+use vulkano::pipeline::graphics::viewport::{Scissor, Viewport, ViewportState};
+use vulkano::pipeline::graphics::GraphicsPipelineCreateInfo;
+use vulkano::pipeline::layout::PipelineDescriptorSetLayoutCreateInfo;
+use vulkano::pipeline::{
+    ComputePipeline, DynamicState, GraphicsPipeline, Pipeline, PipelineBindPoint, PipelineLayout,
+    PipelineShaderStageCreateInfo,
+};
+#[cfg(feature = "gpu_timing")]
+use vulkano::query::{QueryPool, QueryPoolCreateInfo, QueryResultFlags, QueryType};
+use vulkano::render_pass::{Framebuffer, RenderPass, Subpass};
+use vulkano::shader::ShaderModule;
+#[cfg(feature = "shader_reload")]
+use vulkano::shader::ShaderModuleCreateInfo;
+#[cfg(feature = "gpu_timing")]
+use vulkano::sync::PipelineStage;
+use vulkano::{DeviceSize, VulkanObject};
+
+use std::mem;
+#[cfg(feature = "gpu_timing")]
+use std::ops::Range;
+use std::sync::Arc;
+
+pub use vulkano::image::sampler::{
+    Filter, Sampler, SamplerAddressMode, SamplerCreateInfo, SamplerMipmapMode,
+};
+pub use vulkano::image::view::ImageView;
+pub use vulkano::image::Image;
+
+pub type SecondaryCommandBuffer = Arc<SecondaryAutoCommandBuffer>;
+// the vertex and index buffers of a TextMesh
+pub type MeshBuffer<T> = Subbuffer<[T]>;
+pub(crate) type HostBuffer<T> = Subbuffer<[T]>;
+pub(crate) type DeviceBuffer<T> = Subbuffer<[T]>;
+pub(crate) type VertexBuffer = Subbuffer<[Vertex]>;
+
+type Builder = AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>;
+
+const HOST_WRITE: MemoryTypeFilter =
+    MemoryTypeFilter::PREFER_DEVICE.union(MemoryTypeFilter::HOST_SEQUENTIAL_WRITE);
+const HOST_READ: MemoryTypeFilter =
+    MemoryTypeFilter::PREFER_HOST.union(MemoryTypeFilter::HOST_RANDOM_ACCESS);
+
+fn image(
+    memory_allocator: &Arc<StandardMemoryAllocator>,
+    format: Format,
+    extent: [u32; 2],
+    array_layers: u32,
+    usage: ImageUsage,
+) -> Arc<Image> {
+    Image::new(
+        memory_allocator.clone(),
+        ImageCreateInfo {
+            image_type: ImageType::Dim2d,
+            format,
+            extent: [extent[0], extent[1], 1],
+            array_layers,
+            usage,
+            ..Default::default()
+        },
+        AllocationCreateInfo::default(),
+    )
+    .unwrap()
+}
+
+// an image text can be drawn into, sampled, cleared and read back
+pub(crate) fn render_image(
+    memory_allocator: &Arc<StandardMemoryAllocator>,
+    format: Format,
+    extent: [u32; 2],
+) -> Arc<Image> {
+    let usage = ImageUsage::COLOR_ATTACHMENT
+        | ImageUsage::SAMPLED
+        | ImageUsage::TRANSFER_SRC
+        | ImageUsage::TRANSFER_DST;
+    image(memory_allocator, format, extent, 1, usage)
+}
+
+pub(crate) fn atlas_image(
+    memory_allocator: &Arc<StandardMemoryAllocator>,
+    extent: [u32; 2],
+    pages: u32,
+) -> Arc<Image> {
+    // a source too, for growing the atlas
+    let usage = ImageUsage::SAMPLED | ImageUsage::TRANSFER_SRC | ImageUsage::TRANSFER_DST;
+    image(memory_allocator, Format::R8_UNORM, extent, pages, usage)
+}
+
+// a texture filled by copies, like the msdf atlas
+pub(crate) fn texture_image(
+    memory_allocator: &Arc<StandardMemoryAllocator>,
+    format: Format,
+    extent: [u32; 2],
+) -> Arc<Image> {
+    let usage = ImageUsage::SAMPLED | ImageUsage::TRANSFER_DST;
+    image(memory_allocator, format, extent, 1, usage)
+}
+
+pub(crate) fn view(image: Arc<Image>) -> Arc<ImageView> {
+    ImageView::new_default(image).unwrap()
+}
+
+// every layer of the image, for sampling the atlas pages
+pub(crate) fn array_view(image: &Arc<Image>) -> Arc<ImageView> {
+    ImageView::new(
+        image.clone(),
+        ImageViewCreateInfo {
+            view_type: ImageViewType::Dim2dArray,
+            ..ImageViewCreateInfo::from_image(image)
+        },
+    )
+    .unwrap()
+}
+
+pub(crate) fn image_extent(image: &Image) -> [u32; 2] {
+    let [width, height, _] = image.extent();
+    [width, height]
+}
+
+// bytes of the first layer, tightly packed
+pub(crate) fn image_bytes(image: &Image) -> DeviceSize {
+    let [width, height] = image_extent(image);
+    width as DeviceSize * height as DeviceSize * image.format().block_size()
+}
+
+pub(crate) fn view_format(view: &ImageView) -> Format {
+    view.format()
+}
+
+// srgb and float formats take linear values from the shader and encode them
+pub(crate) fn linear_format(format: Format) -> bool {
+    matches!(
+        format.numeric_format_color(),
+        Some(NumericFormat::SRGB | NumericFormat::SFLOAT | NumericFormat::UFLOAT)
+    )
+}
+
+// for the first color attachment of subpass
+pub(crate) fn linear_output(subpass: &Subpass) -> bool {
+    subpass
+        .subpass_desc()
+        .color_attachments
+        .iter()
+        .flatten()
+        .next()
+        .map(|reference| subpass.render_pass().attachments()[reference.attachment as usize].format)
+        .map_or(false, linear_format)
+}
+
+// what the framebuffer's only attachment draws into
+pub(crate) fn framebuffer_image(framebuffer: &Framebuffer) -> Arc<Image> {
+    framebuffer.attachments()[0].image().clone()
+}
+
+pub(crate) fn is_color_attachment(view: &ImageView) -> bool {
+    view.usage().intersects(ImageUsage::COLOR_ATTACHMENT)
+}
+
+// in device local memory when command_buffer is given to record the copy into
+pub(crate) fn vertex_buffer(
+    command_buffer: Option<&mut Builder>,
+    memory_allocator: &Arc<StandardMemoryAllocator>,
+    vertices: Vec<Vertex>,
+) -> VertexBuffer {
+    let host_visible = |usage| {
+        Buffer::from_iter(
+            memory_allocator.clone(),
+            BufferCreateInfo {
+                usage,
+                ..Default::default()
+            },
+            AllocationCreateInfo {
+                memory_type_filter: HOST_WRITE,
+                ..Default::default()
+            },
+            vertices.iter().copied(),
+        )
+        .unwrap()
+    };
+    match command_buffer {
+        Some(command_buffer) => {
+            let staging = host_visible(BufferUsage::TRANSFER_SRC);
+            let buffer = Buffer::new_slice::<Vertex>(
+                memory_allocator.clone(),
+                BufferCreateInfo {
+                    usage: BufferUsage::VERTEX_BUFFER | BufferUsage::TRANSFER_DST,
+                    ..Default::default()
+                },
+                AllocationCreateInfo {
+                    memory_type_filter: MemoryTypeFilter::PREFER_DEVICE,
+                    ..Default::default()
+                },
+                vertices.len() as DeviceSize,
+            )
+            .unwrap();
+            command_buffer
+                .copy_buffer(CopyBufferInfo::buffers(staging, buffer.clone()))
+                .unwrap();
+            buffer
+        }
+        None => host_visible(BufferUsage::VERTEX_BUFFER),
+    }
+}
+
+fn host_buffer<T, I>(
+    memory_allocator: &Arc<StandardMemoryAllocator>,
+    usage: BufferUsage,
+    memory_type_filter: MemoryTypeFilter,
+    data: I,
+) -> HostBuffer<T>
+where
+    T: BufferContents,
+    I: IntoIterator<Item = T>,
+    I::IntoIter: ExactSizeIterator,
+{
+    Buffer::from_iter(
+        memory_allocator.clone(),
+        BufferCreateInfo {
+            usage,
+            ..Default::default()
+        },
+        AllocationCreateInfo {
+            memory_type_filter,
+            ..Default::default()
+        },
+        data,
+    )
+    .unwrap()
+}
+
+pub(crate) fn staging_buffer(
+    memory_allocator: &Arc<StandardMemoryAllocator>,
+    len: DeviceSize,
+) -> HostBuffer<u8> {
+    host_buffer(
+        memory_allocator,
+        BufferUsage::TRANSFER_SRC,
+        MemoryTypeFilter::PREFER_HOST | MemoryTypeFilter::HOST_SEQUENTIAL_WRITE,
+        (0..len).map(|_| 0u8),
+    )
+}
+
+pub(crate) fn readback_buffer(
+    memory_allocator: &Arc<StandardMemoryAllocator>,
+    len: DeviceSize,
+) -> HostBuffer<u8> {
+    host_buffer(
+        memory_allocator,
+        BufferUsage::TRANSFER_DST,
+        HOST_READ,
+        (0..len).map(|_| 0u8),
+    )
+}
+
+// read_back for buffers the shader writes and the cpu reads afterwards
+pub(crate) fn storage_buffer<T, I>(
+    memory_allocator: &Arc<StandardMemoryAllocator>,
+    data: I,
+    read_back: bool,
+) -> HostBuffer<T>
+where
+    T: BufferContents,
+    I: IntoIterator<Item = T>,
+    I::IntoIter: ExactSizeIterator,
+{
+    let memory_type_filter = if read_back { HOST_READ } else { HOST_WRITE };
+    host_buffer(
+        memory_allocator,
+        BufferUsage::STORAGE_BUFFER,
+        memory_type_filter,
+        data,
+    )
+}
+
+pub(crate) fn device_storage_buffer<T: BufferContents>(
+    memory_allocator: &Arc<StandardMemoryAllocator>,
+    len: DeviceSize,
+) -> DeviceBuffer<T> {
+    Buffer::new_slice(
+        memory_allocator.clone(),
+        BufferCreateInfo {
+            usage: BufferUsage::STORAGE_BUFFER,
+            ..Default::default()
+        },
+        AllocationCreateInfo {
+            memory_type_filter: MemoryTypeFilter::PREFER_DEVICE,
+            ..Default::default()
+        },
+        len,
+    )
+    .unwrap()
+}
+
+pub(crate) fn mesh_buffers(
+    memory_allocator: &Arc<StandardMemoryAllocator>,
+    vertices: Vec<MeshVertex>,
+    indices: Vec<u32>,
+) -> (MeshBuffer<MeshVertex>, MeshBuffer<u32>) {
+    let vertices = host_buffer(
+        memory_allocator,
+        BufferUsage::VERTEX_BUFFER,
+        HOST_WRITE,
+        vertices,
+    );
+    let indices = host_buffer(
+        memory_allocator,
+        BufferUsage::INDEX_BUFFER,
+        HOST_WRITE,
+        indices,
+    );
+    (vertices, indices)
+}
+
+// also bound as a storage buffer, so shaders can fill in the counts
+pub(crate) fn indirect_buffer(
+    memory_allocator: &Arc<StandardMemoryAllocator>,
+    commands: Vec<DrawIndirectCommand>,
+) -> HostBuffer<DrawIndirectCommand> {
+    host_buffer(
+        memory_allocator,
+        BufferUsage::STORAGE_BUFFER | BufferUsage::INDIRECT_BUFFER,
+        HOST_WRITE,
+        commands,
+    )
+}
+
+pub(crate) fn copy_buffer_to_image(
+    command_buffer: &mut Builder,
+    buffer: &HostBuffer<u8>,
+    image: &Arc<Image>,
+) {
+    command_buffer
+        .copy_buffer_to_image(CopyBufferToImageInfo::buffer_image(
+            buffer.clone(),
+            image.clone(),
+        ))
+        .unwrap();
+}
+
+// copies only the given (page, offset, extent) rects. buffer is laid out like the
+// image, rows of row_length texels and its layers one after the other
+pub(crate) fn copy_buffer_regions_to_image(
+    command_buffer: &mut Builder,
+    buffer: &HostBuffer<u8>,
+    image: &Arc<Image>,
+    image_extent: [u32; 2],
+    regions: &[(u32, [u32; 2], [u32; 2])],
+) {
+    let [width, height] = image_extent;
+    let regions = regions
+        .iter()
+        .map(|&(page, [x, y], [w, h])| BufferImageCopy {
+            buffer_offset: (page as DeviceSize * height as DeviceSize + y as DeviceSize)
+                * width as DeviceSize
+                + x as DeviceSize,
+            buffer_row_length: width,
+            buffer_image_height: height,
+            image_subresource: ImageSubresourceLayers {
+                aspects: ImageAspects::COLOR,
+                mip_level: 0,
+                array_layers: page..page + 1,
+            },
+            image_offset: [x, y, 0],
+            image_extent: [w, h, 1],
+            ..Default::default()
+        })
+        .collect();
+    command_buffer
+        .copy_buffer_to_image(CopyBufferToImageInfo {
+            regions,
+            ..CopyBufferToImageInfo::buffer_image(buffer.clone(), image.clone())
+        })
+        .unwrap();
+}
+
+// copies (offset, extent) rects of a single layer image out of a buffer holding just
+// their texels, tightly packed one rect after the other
+pub(crate) fn copy_packed_regions_to_image(
+    command_buffer: &mut Builder,
+    buffer: &HostBuffer<u8>,
+    image: &Arc<Image>,
+    texel_bytes: u32,
+    regions: &[([u32; 2], [u32; 2])],
+) {
+    let mut buffer_offset = 0;
+    let regions = regions
+        .iter()
+        .map(|&([x, y], [w, h])| {
+            let region = BufferImageCopy {
+                buffer_offset,
+                image_subresource: ImageSubresourceLayers {
+                    aspects: ImageAspects::COLOR,
+                    mip_level: 0,
+                    array_layers: 0..1,
+                },
+                image_offset: [x, y, 0],
+                image_extent: [w, h, 1],
+                ..Default::default()
+            };
+            buffer_offset += w as DeviceSize * h as DeviceSize * texel_bytes as DeviceSize;
+            region
+        })
+        .collect();
+    command_buffer
+        .copy_buffer_to_image(CopyBufferToImageInfo {
+            regions,
+            ..CopyBufferToImageInfo::buffer_image(buffer.clone(), image.clone())
+        })
+        .unwrap();
+}
+
+// every layer the two images have in common, they have the same extent
+pub(crate) fn copy_image(command_buffer: &mut Builder, src: &Arc<Image>, dst: &Arc<Image>) {
+    command_buffer
+        .copy_image(CopyImageInfo::images(src.clone(), dst.clone()))
+        .unwrap();
+}
+
+// copies only the given (src page, src offset, dst page, dst offset, extent) rects
+pub(crate) fn copy_image_regions(
+    command_buffer: &mut Builder,
+    src: &Arc<Image>,
+    dst: &Arc<Image>,
+    regions: &[(u32, [u32; 2], u32, [u32; 2], [u32; 2])],
+) {
+    let layer = |page: u32| ImageSubresourceLayers {
+        aspects: ImageAspects::COLOR,
+        mip_level: 0,
+        array_layers: page..page + 1,
+    };
+    let regions = regions
+        .iter()
+        .map(
+            |&(src_page, [sx, sy], dst_page, [dx, dy], [w, h])| ImageCopy {
+                src_subresource: layer(src_page),
+                src_offset: [sx, sy, 0],
+                dst_subresource: layer(dst_page),
+                dst_offset: [dx, dy, 0],
+                extent: [w, h, 1],
+                ..Default::default()
+            },
+        )
+        .collect();
+    command_buffer
+        .copy_image(CopyImageInfo {
+            regions,
+            ..CopyImageInfo::images(src.clone(), dst.clone())
+        })
+        .unwrap();
+}
+
+pub(crate) fn copy_image_to_buffer(
+    command_buffer: &mut Builder,
+    image: &Arc<Image>,
+    buffer: &HostBuffer<u8>,
+) {
+    command_buffer
+        .copy_image_to_buffer(CopyImageToBufferInfo::image_buffer(
+            image.clone(),
+            buffer.clone(),
+        ))
+        .unwrap();
+}
+
+// inline contents, nothing in this crate records secondary buffers into its own passes
+pub(crate) fn begin_render_pass(
+    command_buffer: &mut Builder,
+    framebuffer: Arc<Framebuffer>,
+    clear_values: Vec<Option<ClearValue>>,
+) {
+    command_buffer
+        .begin_render_pass(
+            RenderPassBeginInfo {
+                clear_values,
+                ..RenderPassBeginInfo::framebuffer(framebuffer)
+            },
+            SubpassBeginInfo::default(),
+        )
+        .unwrap();
+}
+
+pub(crate) fn end_render_pass(command_buffer: &mut Builder) {
+    command_buffer
+        .end_render_pass(SubpassEndInfo::default())
+        .unwrap();
+}
+
+pub(crate) fn bind_graphics<L>(
+    command_buffer: &mut AutoCommandBufferBuilder<L>,
+    pipeline: &Arc<GraphicsPipeline>,
+    sets: impl DescriptorSetsCollection,
+) {
+    command_buffer
+        .bind_pipeline_graphics(pipeline.clone())
+        .unwrap()
+        .bind_descriptor_sets(
+            PipelineBindPoint::Graphics,
+            pipeline.layout().clone(),
+            0,
+            sets,
+        )
+        .unwrap();
+}
+
+pub(crate) fn push_constants<L>(
+    command_buffer: &mut AutoCommandBufferBuilder<L>,
+    pipeline: &Arc<impl Pipeline>,
+    data: impl BufferContents,
+) {
+    command_buffer
+        .push_constants(pipeline.layout().clone(), 0, data)
+        .unwrap();
+}
+
+pub(crate) fn bind_compute<L>(
+    command_buffer: &mut AutoCommandBufferBuilder<L>,
+    pipeline: &Arc<ComputePipeline>,
+    sets: impl DescriptorSetsCollection,
+) {
+    command_buffer
+        .bind_pipeline_compute(pipeline.clone())
+        .unwrap()
+        .bind_descriptor_sets(
+            PipelineBindPoint::Compute,
+            pipeline.layout().clone(),
+            0,
+            sets,
+        )
+        .unwrap();
+}
+
+pub(crate) fn bind_vertex_buffer<L>(
+    command_buffer: &mut AutoCommandBufferBuilder<L>,
+    buffer: &VertexBuffer,
+) {
+    command_buffer
+        .bind_vertex_buffers(0, buffer.clone())
+        .unwrap();
+}
+
+// draws with the command at index
+pub(crate) fn draw_indirect<L>(
+    command_buffer: &mut AutoCommandBufferBuilder<L>,
+    commands: &HostBuffer<DrawIndirectCommand>,
+    index: DeviceSize,
+) {
+    command_buffer
+        .draw_indirect(commands.clone().slice(index..index + 1))
+        .unwrap();
+}
+
+pub(crate) fn scissor(offset: [u32; 2], extent: [u32; 2]) -> Scissor {
+    Scissor { offset, extent }
+}
+
+// scissor is in pixels and defaults to the whole extent
+pub(crate) fn set_viewport_scissor<L>(
+    command_buffer: &mut AutoCommandBufferBuilder<L>,
+    extent: [u32; 2],
+    scissor: Option<Scissor>,
+) {
+    let viewport = Viewport {
+        offset: [0.0, 0.0],
+        extent: [extent[0] as f32, extent[1] as f32],
+        depth_range: 0.0..=1.0,
+    };
+    let scissor = scissor.unwrap_or(Scissor {
+        offset: [0, 0],
+        extent,
+    });
+    command_buffer
+        .set_viewport(0, [viewport].into_iter().collect())
+        .unwrap()
+        .set_scissor(0, [scissor].into_iter().collect())
+        .unwrap();
+}
+
+// clears the first color attachment to transparent inside scissor
+pub(crate) fn clear_region(command_buffer: &mut Builder, scissor: &Scissor) {
+    command_buffer
+        .clear_attachments(
+            [ClearAttachment::Color {
+                color_attachment: 0,
+                clear_value: ClearColorValue::Float([0.0; 4]),
+            }]
+            .into_iter()
+            .collect(),
+            [ClearRect {
+                offset: scissor.offset,
+                extent: scissor.extent,
+                array_layers: 0..1,
+            }]
+            .into_iter()
+            .collect(),
+        )
+        .unwrap();
+}
+
+pub(crate) fn descriptor_set_allocator(device: Arc<Device>) -> StandardDescriptorSetAllocator {
+    StandardDescriptorSetAllocator::new(device, Default::default())
+}
+
+pub(crate) fn descriptor_set(
+    allocator: &StandardDescriptorSetAllocator,
+    layout: Arc<DescriptorSetLayout>,
+    writes: impl IntoIterator<Item = WriteDescriptorSet>,
+) -> Arc<PersistentDescriptorSet> {
+    PersistentDescriptorSet::new(allocator, layout, writes, []).unwrap()
+}
+
+// a single subpass drawing over whatever the color attachment holds
+pub(crate) fn color_render_pass(device: Arc<Device>, format: Format) -> Arc<RenderPass> {
+    vulkano::single_pass_renderpass!(device,
+        attachments: {
+            color: {
+                format: format,
+                samples: 1,
+                load_op: Load,
+                store_op: Store,
+            },
+        },
+        pass: {
+            color: [color],
+            depth_stencil: {}
+        }
+    )
+    .unwrap()
+}
+
+// the layout of a TextMesh's vertices, for the caller's own pipelines
+unsafe impl vulkano::pipeline::graphics::vertex_input::Vertex for MeshVertex {
+    fn per_vertex() -> VertexBufferDescription {
+        let member = |offset| VertexMemberInfo {
+            offset,
+            format: Format::R32G32B32_SFLOAT,
+            num_elements: 1,
+        };
+        let members = [
+            ("position", member(mem::offset_of!(MeshVertex, position))),
+            ("normal", member(mem::offset_of!(MeshVertex, normal))),
+        ];
+        VertexBufferDescription {
+            members: members
+                .into_iter()
+                .map(|(name, member)| (name.to_owned(), member))
+                .collect(),
+            stride: mem::size_of::<MeshVertex>() as u32,
+            input_rate: VertexInputRate::Vertex,
+        }
+    }
+
+    fn per_instance() -> VertexBufferDescription {
+        Self::per_vertex().per_instance()
+    }
+
+    fn per_instance_with_divisor(divisor: u32) -> VertexBufferDescription {
+        Self::per_vertex().per_instance_with_divisor(divisor)
+    }
+}
+
+// the layout of quads' Vertex
+fn vertex_input(vs: &ShaderModule) -> VertexInputState {
+    let member = |offset, format| VertexMemberInfo {
+        offset,
+        format,
+        num_elements: 1,
+    };
+    let members = [
+        (
+            "position",
+            member(mem::offset_of!(Vertex, position), Format::R32G32_SFLOAT),
+        ),
+        (
+            "tex_position",
+            member(
+                mem::offset_of!(Vertex, tex_position),
+                Format::R32G32B32_SFLOAT,
+            ),
+        ),
+        (
+            "color",
+            member(mem::offset_of!(Vertex, color), Format::R32G32B32A32_SFLOAT),
+        ),
+        (
+            "fade",
+            member(mem::offset_of!(Vertex, fade), Format::R32G32B32_SFLOAT),
+        ),
+        (
+            "fill_uv",
+            member(mem::offset_of!(Vertex, fill_uv), Format::R32G32_SFLOAT),
+        ),
+    ];
+    let description = VertexBufferDescription {
+        members: members
+            .into_iter()
+            .map(|(name, member)| (name.to_owned(), member))
+            .collect(),
+        stride: mem::size_of::<Vertex>() as u32,
+        input_rate: VertexInputRate::Vertex,
+    };
+    let entry_point = vs.entry_point("main").unwrap();
+    description
+        .definition(&entry_point.info().input_interface)
+        .unwrap()
+}
+
+// a triangle list pipeline drawing into subpass, viewport and scissor are set when
+// drawing. without vertex_input the vertex shader fetches its vertices itself,
+// otherwise it reads quads' Vertex
+pub(crate) fn graphics_pipeline(
+    vs: Arc<ShaderModule>,
+    fs: Arc<ShaderModule>,
+    vertex_input: bool,
+    subpass: Subpass,
+    blend: AttachmentBlend,
+) -> Arc<GraphicsPipeline> {
+    try_graphics_pipeline(vs, fs, vertex_input, subpass, blend).unwrap()
+}
+
+// vulkano's reason as the error, for shaders of the caller's
+pub(crate) fn try_graphics_pipeline(
+    vs: Arc<ShaderModule>,
+    fs: Arc<ShaderModule>,
+    vertex_input: bool,
+    subpass: Subpass,
+    blend: AttachmentBlend,
+) -> Result<Arc<GraphicsPipeline>, String> {
+    let device = subpass.render_pass().device().clone();
+    // drawing into a multisampled subpass needs a pipeline with its sample count
+    let rasterization_samples = subpass.num_samples().unwrap_or(SampleCount::Sample1);
+    let vertex_input_state = match vertex_input {
+        true => self::vertex_input(&vs),
+        false => VertexInputState::new(),
+    };
+    let entry_point = |module: &Arc<ShaderModule>| {
+        module
+            .entry_point("main")
+            .ok_or_else(|| "no main entry point".to_string())
+    };
+    let stages = [
+        PipelineShaderStageCreateInfo::new(entry_point(&vs)?),
+        PipelineShaderStageCreateInfo::new(entry_point(&fs)?),
+    ];
+    let layout = PipelineLayout::new(
+        device.clone(),
+        PipelineDescriptorSetLayoutCreateInfo::from_stages(&stages)
+            .into_pipeline_layout_create_info(device.clone())
+            .map_err(|error| error.to_string())?,
+    )
+    .map_err(|error| error.to_string())?;
+
+    GraphicsPipeline::new(
+        device,
+        None,
+        GraphicsPipelineCreateInfo {
+            stages: stages.into_iter().collect(),
+            vertex_input_state: Some(vertex_input_state),
+            input_assembly_state: Some(InputAssemblyState::default()),
+            viewport_state: Some(ViewportState::default()),
+            rasterization_state: Some(RasterizationState::default()),
+            multisample_state: Some(MultisampleState {
+                rasterization_samples,
+                ..Default::default()
+            }),
+            color_blend_state: Some(ColorBlendState::with_attachment_states(
+                subpass.num_color_attachments(),
+                ColorBlendAttachmentState {
+                    blend: Some(blend),
+                    ..Default::default()
+                },
+            )),
+            dynamic_state: [DynamicState::Viewport, DynamicState::Scissor]
+                .into_iter()
+                .collect(),
+            subpass: Some(subpass.into()),
+            ..GraphicsPipelineCreateInfo::layout(layout)
+        },
+    )
+    .map_err(|error| error.to_string())
+}
+
+pub(crate) fn compute_pipeline(cs: Arc<ShaderModule>) -> Arc<ComputePipeline> {
+    let device = cs.device().clone();
+    let stage = PipelineShaderStageCreateInfo::new(cs.entry_point("main").unwrap());
+    let layout = PipelineLayout::new(
+        device.clone(),
+        PipelineDescriptorSetLayoutCreateInfo::from_stages([&stage])
+            .into_pipeline_layout_create_info(device.clone())
+            .unwrap(),
+    )
+    .unwrap();
+    ComputePipeline::new(
+        device,
+        None,
+        ComputePipelineCreateInfo::stage_layout(stage, layout),
+    )
+    .unwrap()
+}
+
+// spir-v compiled at runtime, see shader_reload
+#[cfg(feature = "shader_reload")]
+pub(crate) fn shader_module(device: Arc<Device>, words: &[u32]) -> Arc<ShaderModule> {
+    unsafe { ShaderModule::new(device, ShaderModuleCreateInfo::new(words)) }.unwrap()
+}
+
+// the subpass and blend a pipeline of graphics_pipeline was built with
+pub(crate) fn pipeline_target(pipeline: &GraphicsPipeline) -> (Subpass, AttachmentBlend) {
+    let subpass = match pipeline.subpass() {
+        PipelineSubpassType::BeginRenderPass(subpass) => subpass.clone(),
+        PipelineSubpassType::BeginRendering(_) => unreachable!(),
+    };
+    let blend = pipeline
+        .color_blend_state()
+        .and_then(|state| state.attachments[0].blend)
+        .unwrap();
+    (subpass, blend)
+}
+
+// for targets that get composited later: the text blend would multiply alpha into
+// the color a second time when compositing, so keep the color premultiplied and
+// accumulate alpha instead
+pub(crate) fn premultiplied_blend() -> AttachmentBlend {
+    AttachmentBlend {
+        src_alpha_blend_factor: BlendFactor::One,
+        ..AttachmentBlend::alpha()
+    }
+}
+
+// draws a premultiplied image over the target
+pub(crate) fn premultiplied_over() -> AttachmentBlend {
+    AttachmentBlend {
+        src_color_blend_factor: BlendFactor::One,
+        dst_color_blend_factor: BlendFactor::OneMinusSrcAlpha,
+        color_blend_op: BlendOp::Add,
+        src_alpha_blend_factor: BlendFactor::One,
+        dst_alpha_blend_factor: BlendFactor::OneMinusSrcAlpha,
+        alpha_blend_op: BlendOp::Add,
+    }
+}
+
+pub(crate) fn supports_compute(queue: &Queue) -> bool {
+    let properties = queue.device().physical_device().queue_family_properties();
+    properties[queue.queue_family_index() as usize]
+        .queue_flags
+        .intersects(QueueFlags::COMPUTE)
+}
+
+// objects and debug regions only get names when the application enabled
+// VK_EXT_debug_utils on its instance, for validation messages and RenderDoc captures
+fn debug_utils(device: &Device) -> bool {
+    device.instance().enabled_extensions().ext_debug_utils
+}
+
+pub(crate) fn set_name<T: VulkanObject + DeviceOwned>(object: &T, name: &str) {
+    if debug_utils(object.device()) {
+        object
+            .device()
+            .set_debug_utils_object_name(object, Some(name))
+            .unwrap();
+    }
+}
+
+pub(crate) fn name_image(image: &Image, name: &str) {
+    set_name(image, name);
+}
+
+pub(crate) fn name_buffer<T: ?Sized>(buffer: &Subbuffer<T>, name: &str) {
+    set_name(&**buffer.buffer(), name);
+}
+
+// a region of the command buffer named in captures, closed by end_label
+pub(crate) fn begin_label<L>(command_buffer: &mut AutoCommandBufferBuilder<L>, name: &str) {
+    if debug_utils(command_buffer.device()) {
+        command_buffer
+            .begin_debug_utils_label(DebugUtilsLabel {
+                label_name: name.to_owned(),
+                ..Default::default()
+            })
+            .unwrap();
+    }
+}
+
+pub(crate) fn end_label<L>(command_buffer: &mut AutoCommandBufferBuilder<L>) {
+    if debug_utils(command_buffer.device()) {
+        unsafe {
+            command_buffer.end_debug_utils_label().unwrap();
+        }
+    }
+}
+
+// how many bits of a timestamp count, None when the queue can't write them
+#[cfg(feature = "gpu_timing")]
+pub(crate) fn timestamp_bits(queue: &Queue) -> Option<u32> {
+    let properties = queue.device().physical_device().queue_family_properties();
+    properties[queue.queue_family_index() as usize].timestamp_valid_bits
+}
+
+#[cfg(feature = "gpu_timing")]
+pub(crate) fn timestamp_pool(device: Arc<Device>, query_count: u32) -> Arc<QueryPool> {
+    QueryPool::new(
+        device,
+        QueryPoolCreateInfo {
+            query_count,
+            ..QueryPoolCreateInfo::query_type(QueryType::Timestamp)
+        },
+    )
+    .unwrap()
+}
+
+// resets the queries and writes the first of them once the gpu gets to this point
+#[cfg(feature = "gpu_timing")]
+pub(crate) fn begin_timestamps(
+    command_buffer: &mut Builder,
+    query_pool: &Arc<QueryPool>,
+    queries: Range<u32>,
+) {
+    unsafe {
+        command_buffer
+            .reset_query_pool(query_pool.clone(), queries.clone())
+            .unwrap()
+            .write_timestamp(query_pool.clone(), queries.start, PipelineStage::TopOfPipe)
+            .unwrap();
+    }
+}
+
+// once everything recorded before is done
+#[cfg(feature = "gpu_timing")]
+pub(crate) fn end_timestamp(command_buffer: &mut Builder, query_pool: &Arc<QueryPool>, query: u32) {
+    unsafe {
+        command_buffer
+            .write_timestamp(query_pool.clone(), query, PipelineStage::BottomOfPipe)
+            .unwrap();
+    }
+}
+
+// doesn't wait, false while the gpu hasn't written all of them yet
+#[cfg(feature = "gpu_timing")]
+pub(crate) fn query_results(
+    query_pool: &QueryPool,
+    queries: Range<u32>,
+    results: &mut [u64],
+) -> bool {
+    query_pool
+        .get_results(queries, results, QueryResultFlags::empty())
+        .unwrap()
+}
@@ -0,0 +1,55 @@
+use super::quads::GlyphQuad;
+
+use rusttype::Rect;
+
+// the cpu side atlas, an R8 image per page one after the other
+pub struct AtlasPixels<'a> {
+    pub pixels: &'a [u8],
+    pub extent: [u32; 2],
+    pub pages: u32,
+    // bumped whenever pixels changed, the same version holds the same pixels
+    pub version: u64,
+    // every rect rasterized into after version dirty_since, so a copy of that version or
+    // a later one only needs these. older copies need all of pixels
+    #[cfg_attr(not(feature = "gpu"), allow(dead_code))]
+    pub(crate) dirty: &'a [DirtyRect],
+    #[cfg_attr(not(feature = "gpu"), allow(dead_code))]
+    pub(crate) dirty_since: u64,
+}
+
+// a rect of the atlas rasterized into for the given version
+#[cfg_attr(not(feature = "gpu"), allow(dead_code))]
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct DirtyRect {
+    pub(crate) version: u64,
+    pub(crate) page: u32,
+    pub(crate) rect: Rect<u32>,
+}
+
+// the gpu side of drawing text: the atlas texture, the vertex upload, the pipeline and
+// recording the draw. DrawText::upload_to and TextQuads::upload_to hand a backend the
+// atlas and the glyph quads of the queue, and the backend's draw is recorded inside
+// the caller's render pass afterwards. VulkanoBackend (feature gpu) draws like
+// DrawText's own pipelines do, WgpuBackend (feature wgpu) draws with wgpu and needs no
+// vulkano. fill textures aren't drawn by either
+pub trait TextGpuBackend {
+    // what the uploads are recorded into, outside of a render pass
+    type Upload<'a>;
+    // what the draw is recorded into, inside the render pass
+    type Pass<'a>
+    where
+        Self: 'a;
+
+    // called every frame, backends compare the version to skip unchanged atlases
+    fn upload_atlas(&mut self, upload: &mut Self::Upload<'_>, atlas: &AtlasPixels);
+
+    // corners of the quads are in pixels of extent
+    fn upload_quads(
+        &mut self,
+        upload: &mut Self::Upload<'_>,
+        quads: &[GlyphQuad],
+        extent: [u32; 2],
+    );
+
+    fn draw<'a>(&'a self, pass: &mut Self::Pass<'a>);
+}
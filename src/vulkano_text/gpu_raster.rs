@@ -0,0 +1,165 @@
+use super::backend::{GlyphBitmap, GlyphOutline, PixelBounds};
+use super::gpu::compat;
+
+use vulkano::command_buffer::allocator::StandardCommandBufferAllocator;
+use vulkano::command_buffer::{AutoCommandBufferBuilder, CommandBufferUsage};
+use vulkano::descriptor_set::allocator::StandardDescriptorSetAllocator;
+use vulkano::descriptor_set::WriteDescriptorSet;
+use vulkano::device::Queue;
+use vulkano::memory::allocator::StandardMemoryAllocator;
+use vulkano::pipeline::{ComputePipeline, Pipeline};
+use vulkano::sync::future::FenceSignalFuture;
+use vulkano::sync::{self, GpuFuture};
+
+use std::sync::Arc;
+
+mod cs {
+    vulkano_shaders::shader! {
+        ty: "compute",
+        path: "src/shaders/raster.glsl",
+    }
+}
+
+// see raster.glsl
+const GROUP_SIZE: u32 = 8;
+
+// fills glyph outlines on the gpu, for batches of new glyphs big enough to stall the
+// cpu. outlines are flattened to lines by the backends, and since the atlas lives on
+// the cpu the coverage is read back once the batch's fence is signaled
+pub(crate) struct GpuRasterizer {
+    queue: Arc<Queue>,
+    pipeline: Arc<ComputePipeline>,
+    memory_allocator: Arc<StandardMemoryAllocator>,
+    command_buffer_allocator: StandardCommandBufferAllocator,
+    descriptor_set_allocator: StandardDescriptorSetAllocator,
+}
+
+impl GpuRasterizer {
+    // None when the queue can't run compute shaders
+    pub(crate) fn new(queue: Arc<Queue>) -> Option<GpuRasterizer> {
+        if !compat::supports_compute(&queue) {
+            return None;
+        }
+
+        let device = queue.device().clone();
+        let shader = cs::load(device.clone()).unwrap();
+        let pipeline = compat::compute_pipeline(shader);
+        compat::set_name(&*pipeline, "font-render rasterization pipeline");
+        Some(GpuRasterizer {
+            queue,
+            pipeline,
+            memory_allocator: Arc::new(StandardMemoryAllocator::new_default(device.clone())),
+            command_buffer_allocator: StandardCommandBufferAllocator::new(
+                device.clone(),
+                Default::default(),
+            ),
+            descriptor_set_allocator: compat::descriptor_set_allocator(device),
+        })
+    }
+
+    // submits the outlines without waiting for them, see GpuBatch::take
+    pub(crate) fn rasterize(&self, outlines: &[GlyphOutline]) -> GpuBatch {
+        let mut lines = vec![];
+        let mut glyphs = vec![];
+        let mut pixels = 0;
+        let mut max = [1, 1];
+        for outline in outlines {
+            let width = outline.bounds.width() as u32;
+            let height = outline.bounds.height() as u32;
+            glyphs.push([
+                lines.len() as u32,
+                outline.lines.len() as u32,
+                width | height << 16,
+                pixels,
+            ]);
+            lines.extend_from_slice(&outline.lines);
+            pixels += width * height;
+            max = [max[0].max(width), max[1].max(height)];
+        }
+        // empty buffers can't be bound
+        if lines.is_empty() {
+            lines.push([0.0; 4]);
+        }
+
+        let allocator = &self.memory_allocator;
+        let lines = compat::storage_buffer(allocator, lines, false);
+        let glyph_buffer = compat::storage_buffer(allocator, glyphs, false);
+        let coverage = compat::storage_buffer(allocator, (0..pixels.max(1)).map(|_| 0u32), true);
+
+        let set = compat::descriptor_set(
+            &self.descriptor_set_allocator,
+            self.pipeline.layout().set_layouts()[0].clone(),
+            [
+                WriteDescriptorSet::buffer(0, lines),
+                WriteDescriptorSet::buffer(1, glyph_buffer),
+                WriteDescriptorSet::buffer(2, coverage.clone()),
+            ],
+        );
+
+        let mut builder = AutoCommandBufferBuilder::primary(
+            &self.command_buffer_allocator,
+            self.queue.queue_family_index(),
+            CommandBufferUsage::OneTimeSubmit,
+        )
+        .unwrap();
+        compat::bind_compute(&mut builder, &self.pipeline, set);
+        builder
+            .dispatch([
+                (max[0] + GROUP_SIZE - 1) / GROUP_SIZE,
+                (max[1] + GROUP_SIZE - 1) / GROUP_SIZE,
+                outlines.len() as u32,
+            ])
+            .unwrap();
+        let command_buffer = builder.build().unwrap();
+        let fence = sync::now(self.queue.device().clone())
+            .then_execute(self.queue.clone(), command_buffer)
+            .unwrap()
+            .boxed()
+            .then_signal_fence_and_flush()
+            .unwrap();
+        GpuBatch {
+            fence,
+            coverage,
+            bounds: outlines.iter().map(|outline| outline.bounds).collect(),
+        }
+    }
+}
+
+// outlines submitted by GpuRasterizer::rasterize, dropping it waits for the gpu
+pub(crate) struct GpuBatch {
+    fence: FenceSignalFuture<Box<dyn GpuFuture>>,
+    coverage: compat::HostBuffer<u32>,
+    bounds: Vec<PixelBounds>,
+}
+
+impl GpuBatch {
+    // one bitmap per outline in the order they were submitted, None while the gpu is
+    // still at it
+    pub(crate) fn take(&self) -> Option<Vec<GlyphBitmap>> {
+        if !self.fence.is_signaled().unwrap() {
+            return None;
+        }
+        // returns right away, and lets go of the coverage buffer
+        self.fence.wait(None).unwrap();
+
+        let coverage = self.coverage.read().unwrap();
+        let mut start = 0;
+        let bitmaps = self
+            .bounds
+            .iter()
+            .map(|&bounds| {
+                let len = (bounds.width() * bounds.height()) as usize;
+                let bitmap = GlyphBitmap {
+                    bounds,
+                    coverage: coverage[start..start + len]
+                        .iter()
+                        .map(|&v| v as u8)
+                        .collect(),
+                };
+                start += len;
+                bitmap
+            })
+            .collect();
+        Some(bitmaps)
+    }
+}
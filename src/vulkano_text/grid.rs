@@ -0,0 +1,239 @@
+use super::drawtext::TextRendererCore;
+use super::style::TextStyle;
+
+use rusttype::{point, Rect};
+
+use std::ops::Range;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cell {
+    pub ch: char,
+    pub fg: [f32; 4],
+    // fully transparent backgrounds aren't drawn
+    pub bg: [f32; 4],
+}
+
+impl Default for Cell {
+    fn default() -> Cell {
+        Cell {
+            ch: ' ',
+            fg: [1.0, 1.0, 1.0, 1.0],
+            bg: [0.0, 0.0, 0.0, 0.0],
+        }
+    }
+}
+
+// what a row looks like queued: text runs of one color over a column range, and the
+// background fills of every color
+#[derive(Debug, Clone, Default)]
+struct RowRuns {
+    texts: Vec<(Range<usize>, [f32; 4], String)>,
+    backgrounds: Vec<([f32; 4], Vec<Range<usize>>)>,
+}
+
+// a fixed grid of monospace cells, the backend of a terminal emulator. cells are as
+// wide as the style's font advances and as tall as its lines, so the style should
+// pick a monospace font. only rows with changed cells are turned into runs again, and
+// rows that didn't change queue the same texts as before, which PartialRedraw leaves
+// alone. chars wider than a cell spill into the next one
+pub struct TextGrid {
+    cols: usize,
+    rows: usize,
+    cells: Vec<Cell>,
+    // changed columns of every row since the last queue
+    damage: Vec<Option<Range<usize>>>,
+    runs: Vec<RowRuns>,
+    style: TextStyle,
+    cell_size: [f32; 2],
+    // from the top of a cell to its baseline
+    ascent: f32,
+}
+
+impl TextGrid {
+    pub fn new(
+        draw_text: &TextRendererCore,
+        cols: usize,
+        rows: usize,
+        style: &TextStyle,
+    ) -> TextGrid {
+        let layout = draw_text.layout_text(style, "M");
+        TextGrid {
+            cols,
+            rows,
+            cells: vec![Cell::default(); cols * rows],
+            damage: vec![Some(0..cols); rows],
+            runs: vec![RowRuns::default(); rows],
+            style: style.clone(),
+            cell_size: [layout.width(), layout.line_height()],
+            ascent: -layout.line_top(0),
+        }
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    // in logical pixels
+    pub fn cell_size(&self) -> [f32; 2] {
+        self.cell_size
+    }
+
+    pub fn cell(&self, col: usize, row: usize) -> Option<&Cell> {
+        if col < self.cols && row < self.rows {
+            Some(&self.cells[row * self.cols + col])
+        } else {
+            None
+        }
+    }
+
+    // cells outside the grid are ignored
+    pub fn set_cell(&mut self, col: usize, row: usize, ch: char, fg: [f32; 4], bg: [f32; 4]) {
+        if col >= self.cols || row >= self.rows {
+            return;
+        }
+        let cell = Cell { ch, fg, bg };
+        if self.cells[row * self.cols + col] != cell {
+            self.cells[row * self.cols + col] = cell;
+            self.damage_cells(row, col..col + 1);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.fill(Cell::default());
+        self.damage_all();
+    }
+
+    // keeps the cells that still fit, new ones are blank
+    pub fn resize(&mut self, cols: usize, rows: usize) {
+        let mut cells = vec![Cell::default(); cols * rows];
+        for row in 0..rows.min(self.rows) {
+            let kept = cols.min(self.cols);
+            cells[row * cols..row * cols + kept]
+                .copy_from_slice(&self.cells[row * self.cols..row * self.cols + kept]);
+        }
+        self.cells = cells;
+        self.cols = cols;
+        self.rows = rows;
+        self.runs = vec![RowRuns::default(); rows];
+        self.damage_all();
+    }
+
+    // moves every row up by lines, blank rows come in at the bottom
+    pub fn scroll_up(&mut self, lines: usize) {
+        let lines = lines.min(self.rows);
+        self.cells.drain(..lines * self.cols);
+        self.cells
+            .extend(std::iter::repeat(Cell::default()).take(lines * self.cols));
+        self.damage_all();
+    }
+
+    fn damage_cells(&mut self, row: usize, cols: Range<usize>) {
+        let damage = &mut self.damage[row];
+        *damage = Some(match damage.take() {
+            Some(d) => d.start.min(cols.start)..d.end.max(cols.end),
+            None => cols,
+        });
+    }
+
+    fn damage_all(&mut self) {
+        self.damage = vec![Some(0..self.cols); self.rows];
+    }
+
+    // what changed since the last queue, relative to the grid's top left corner
+    pub fn damage(&self) -> Vec<Rect<f32>> {
+        let [width, height] = self.cell_size;
+        self.damage
+            .iter()
+            .enumerate()
+            .filter_map(|(row, cols)| {
+                let cols = cols.as_ref()?;
+                Some(Rect {
+                    min: point(cols.start as f32 * width, row as f32 * height),
+                    max: point(cols.end as f32 * width, (row + 1) as f32 * height),
+                })
+            })
+            .collect()
+    }
+
+    pub fn is_damaged(&self) -> bool {
+        self.damage.iter().any(Option::is_some)
+    }
+
+    // x, y is the top left corner of the grid. clears the damage
+    pub fn queue(&mut self, draw_text: &mut TextRendererCore, x: f32, y: f32) {
+        draw_text.from_origin(y, |draw_text| self.queue_rows(draw_text, x, y));
+    }
+
+    fn queue_rows(&mut self, draw_text: &mut TextRendererCore, x: f32, y: f32) {
+        for row in 0..self.rows {
+            if self.damage[row].take().is_some() {
+                self.runs[row] = self.row_runs(row);
+            }
+        }
+
+        // every row queued on its own, so rows that didn't change stay the same texts
+        let [width, height] = self.cell_size;
+        let mut style = self.style.clone();
+        for (row, runs) in self.runs.iter().enumerate() {
+            let top = y + row as f32 * height;
+            let rect = |cols: &Range<usize>| Rect {
+                min: point(x + cols.start as f32 * width, top),
+                max: point(x + cols.end as f32 * width, top + height),
+            };
+            for (color, ranges) in &runs.backgrounds {
+                let rects = ranges.iter().map(rect).collect();
+                draw_text.queue_rects(rects, *color, true, self.style.layer);
+            }
+            for (cols, color, text) in &runs.texts {
+                style.color = *color;
+                draw_text.queue_text_styled(rect(cols).min.x, top + self.ascent, &style, text);
+            }
+        }
+    }
+
+    fn row_runs(&self, row: usize) -> RowRuns {
+        let cells = &self.cells[row * self.cols..(row + 1) * self.cols];
+        let mut runs = RowRuns::default();
+        for (col, cell) in cells.iter().enumerate() {
+            if cell.bg[3] > 0.0 {
+                match runs
+                    .backgrounds
+                    .iter_mut()
+                    .find(|(color, _)| *color == cell.bg)
+                {
+                    Some((_, ranges)) => match ranges.last_mut() {
+                        Some(cols) if cols.end == col => cols.end += 1,
+                        _ => ranges.push(col..col + 1),
+                    },
+                    None => runs.backgrounds.push((cell.bg, vec![col..col + 1])),
+                }
+            }
+            // blanks end no run, they take the color of the one they're in
+            let last = runs.texts.last_mut().filter(|(cols, _, _)| cols.end == col);
+            match last {
+                Some((cols, _, text)) if cell.ch.is_whitespace() => {
+                    cols.end += 1;
+                    text.push(' ');
+                }
+                Some((cols, color, text)) if *color == cell.fg => {
+                    cols.end += 1;
+                    text.push(cell.ch);
+                }
+                _ if cell.ch.is_whitespace() => {}
+                _ => runs
+                    .texts
+                    .push((col..col + 1, cell.fg, cell.ch.to_string())),
+            }
+        }
+        for (cols, _, text) in &mut runs.texts {
+            let trimmed = text.trim_end().len();
+            cols.end -= text.len() - trimmed;
+            text.truncate(trimmed);
+        }
+        runs
+    }
+}
@@ -0,0 +1,229 @@
+use super::drawtext::{fs, load_shader, GlyphInstance, ShaderReload};
+use super::gpu::compat::{self, DeviceBuffer, HostBuffer};
+use super::target::TargetId;
+
+use vulkano::command_buffer::{
+    AutoCommandBufferBuilder, DrawIndirectCommand, PrimaryAutoCommandBuffer,
+};
+use vulkano::descriptor_set::allocator::StandardDescriptorSetAllocator;
+use vulkano::descriptor_set::{PersistentDescriptorSet, WriteDescriptorSet};
+use vulkano::device::Device;
+use vulkano::memory::allocator::StandardMemoryAllocator;
+use vulkano::pipeline::graphics::color_blend::AttachmentBlend;
+use vulkano::pipeline::{ComputePipeline, GraphicsPipeline, Pipeline};
+use vulkano::render_pass::Subpass;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+mod cs {
+    vulkano_shaders::shader! {
+        ty: "compute",
+        path: "src/shaders/cull.glsl",
+    }
+}
+
+mod vs {
+    vulkano_shaders::shader! {
+        ty: "vertex",
+        path: "src/shaders/instanced.glsl",
+    }
+}
+
+// see cull.glsl
+const GROUP_SIZE: u32 = 64;
+// glyphs and passes the buffers of a frame slot start out with room for
+const MIN_GLYPHS: usize = 1024;
+const MIN_PASSES: usize = 16;
+
+// text_pipeline's counterpart reading glyphs from the storage buffer cull.glsl wrote,
+// without any vertex input
+pub(crate) fn indirect_pipeline(
+    device: Arc<Device>,
+    subpass: Subpass,
+    blend: AttachmentBlend,
+    reload: Option<&ShaderReload>,
+) -> Arc<GraphicsPipeline> {
+    let vs = load_shader(device.clone(), reload, "instanced.glsl", vs::load);
+    let fs = load_shader(device, reload, "fragment.glsl", fs::load);
+    let pipeline = compat::graphics_pipeline(vs, fs, false, subpass, blend);
+    compat::set_name(&*pipeline, "font-render indirect text pipeline");
+    pipeline
+}
+
+// the glyphs of one frame after culling, drawn with one indirect draw per pass of every
+// layer
+pub(crate) struct CulledGlyphs {
+    visible: DeviceBuffer<GlyphInstance>,
+    commands: HostBuffer<DrawIndirectCommand>,
+    passes: u64,
+}
+
+// the buffers of a frame in flight, written again every time the slot comes around.
+// they only get replaced by bigger ones, cull.glsl is told how much of them is used
+struct CullBuffers {
+    queued: HostBuffer<GlyphInstance>,
+    visible: DeviceBuffer<GlyphInstance>,
+    commands: HostBuffer<DrawIndirectCommand>,
+    glyphs: usize,
+    passes: usize,
+}
+
+impl CullBuffers {
+    fn new(
+        memory_allocator: &Arc<StandardMemoryAllocator>,
+        glyphs: usize,
+        passes: usize,
+    ) -> CullBuffers {
+        let glyphs = glyphs.max(MIN_GLYPHS).next_power_of_two();
+        let passes = passes.max(MIN_PASSES).next_power_of_two();
+        let queued = compat::storage_buffer(
+            memory_allocator,
+            (0..glyphs).map(|_| GlyphInstance::default()),
+            false,
+        );
+        let visible = compat::device_storage_buffer(memory_allocator, glyphs as u64);
+        let commands = compat::indirect_buffer(
+            memory_allocator,
+            vec![
+                DrawIndirectCommand {
+                    vertex_count: 0,
+                    instance_count: 1,
+                    first_vertex: 0,
+                    first_instance: 0,
+                };
+                passes
+            ],
+        );
+        compat::name_buffer(&queued, "font-render queued glyphs");
+        compat::name_buffer(&visible, "font-render visible glyphs");
+        compat::name_buffer(&commands, "font-render indirect draws");
+        CullBuffers {
+            queued,
+            visible,
+            commands,
+            glyphs,
+            passes,
+        }
+    }
+}
+
+// throws out glyphs that are entirely off screen on the gpu, so the cpu only writes
+// the glyphs once and never counts what gets drawn. the order of overlapping glyphs
+// within a pass isn't kept, passes still paint in order
+pub(crate) struct GpuCulling {
+    pipeline: Arc<ComputePipeline>,
+    // one per frame in flight of every target, see TargetFrames
+    slots: HashMap<TargetId, Vec<Option<CullBuffers>>>,
+}
+
+impl GpuCulling {
+    pub(crate) fn new(device: Arc<Device>) -> GpuCulling {
+        let shader = cs::load(device).unwrap();
+        let pipeline = compat::compute_pipeline(shader);
+        compat::set_name(&*pipeline, "font-render culling pipeline");
+        GpuCulling {
+            pipeline,
+            slots: HashMap::new(),
+        }
+    }
+
+    // drops the buffers of targets DrawText forgot
+    pub(crate) fn retain_targets(&mut self, keep: impl Fn(&TargetId) -> bool) {
+        self.slots.retain(|target, _| keep(target));
+    }
+
+    // records the culling dispatch, which has to happen outside of a render pass.
+    // passes are in paint order, they are written straight into the buffers of the
+    // target's slot
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn cull(
+        &mut self,
+        command_buffer: &mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>,
+        memory_allocator: &Arc<StandardMemoryAllocator>,
+        descriptor_set_allocator: &StandardDescriptorSetAllocator,
+        target: TargetId,
+        slot: usize,
+        frames_in_flight: usize,
+        passes: &[&[GlyphInstance]],
+    ) -> Option<CulledGlyphs> {
+        let len: usize = passes.iter().map(|pass| pass.len()).sum();
+        if len == 0 {
+            return None;
+        }
+        // a slot may still be read by the gpu while slots are counted differently
+        let slots = self.slots.entry(target).or_default();
+        if slots.len() != frames_in_flight {
+            *slots = (0..frames_in_flight).map(|_| None).collect();
+        }
+        let slot = &mut slots[slot];
+        if slot.as_ref().map_or(true, |buffers| {
+            buffers.glyphs < len || buffers.passes < passes.len()
+        }) {
+            *slot = Some(CullBuffers::new(memory_allocator, len, passes.len()));
+        }
+        let buffers = slot.as_ref().unwrap();
+
+        {
+            let mut queued = buffers.queued.write().unwrap();
+            let mut commands = buffers.commands.write().unwrap();
+            let mut start = 0;
+            for (pass, glyphs) in passes.iter().enumerate() {
+                queued[start..start + glyphs.len()].copy_from_slice(glyphs);
+                commands[pass].vertex_count = 0;
+                commands[pass].first_vertex = start as u32 * 6;
+                start += glyphs.len();
+            }
+        }
+
+        let layout = self.pipeline.layout();
+        let set = compat::descriptor_set(
+            descriptor_set_allocator,
+            layout.set_layouts()[0].clone(),
+            [
+                WriteDescriptorSet::buffer(0, buffers.queued.clone()),
+                WriteDescriptorSet::buffer(1, buffers.visible.clone()),
+                WriteDescriptorSet::buffer(2, buffers.commands.clone()),
+            ],
+        );
+        compat::bind_compute(command_buffer, &self.pipeline, set);
+        compat::push_constants(
+            command_buffer,
+            &self.pipeline,
+            [len as u32, passes.len() as u32],
+        );
+        command_buffer
+            .dispatch([(len as u32 + GROUP_SIZE - 1) / GROUP_SIZE, 1, 1])
+            .unwrap();
+        Some(CulledGlyphs {
+            visible: buffers.visible.clone(),
+            commands: buffers.commands.clone(),
+            passes: passes.len() as u64,
+        })
+    }
+}
+
+// atlas_set is the one upload_atlas returns, the glyphs are bound as set 1. a draw per
+// pass instead of a single one, drawing several at once needs multi_draw_indirect
+pub(crate) fn draw_culled<L>(
+    command_buffer: &mut AutoCommandBufferBuilder<L>,
+    pipeline: &Arc<GraphicsPipeline>,
+    atlas_set: &Arc<PersistentDescriptorSet>,
+    descriptor_set_allocator: &StandardDescriptorSetAllocator,
+    culled: Option<CulledGlyphs>,
+) {
+    let culled = match culled {
+        Some(culled) => culled,
+        None => return,
+    };
+
+    let glyph_set = compat::descriptor_set(
+        descriptor_set_allocator,
+        pipeline.layout().set_layouts()[1].clone(),
+        [WriteDescriptorSet::buffer(0, culled.visible)],
+    );
+    compat::bind_graphics(command_buffer, pipeline, (atlas_set.clone(), glyph_set));
+    for pass in 0..culled.passes {
+        compat::draw_indirect(command_buffer, &culled.commands, pass);
+    }
+}
@@ -0,0 +1,94 @@
+use super::gpu::compat::ImageView;
+use super::layout::OBJECT_CHAR;
+
+use std::sync::Arc;
+
+// an icon drawn inline with text in place of a placeholder, see queue_text_inline.
+// it stands on the line like a glyph, as tall as the font's ascent to descent
+#[derive(Clone)]
+pub struct InlineObject {
+    pub texture: Arc<ImageView>,
+    // min x, min y, max x, max y of the region drawn, in uvs of the texture
+    pub uv_rect: [f32; 4],
+    // width over height
+    pub aspect: f32,
+    // multiplied with the text's color, for monochrome icons. otherwise only the
+    // text's opacity applies
+    pub tinted: bool,
+}
+
+impl InlineObject {
+    // the whole texture, square
+    pub fn new(texture: Arc<ImageView>) -> InlineObject {
+        InlineObject {
+            texture,
+            uv_rect: [0.0, 0.0, 1.0, 1.0],
+            aspect: 1.0,
+            tinted: false,
+        }
+    }
+
+    // a region of an icon atlas
+    pub fn with_region(mut self, uv_rect: [f32; 4]) -> InlineObject {
+        self.uv_rect = uv_rect;
+        self
+    }
+
+    pub fn with_aspect(mut self, aspect: f32) -> InlineObject {
+        self.aspect = aspect;
+        self
+    }
+
+    pub fn with_tint(mut self, tinted: bool) -> InlineObject {
+        self.tinted = tinted;
+        self
+    }
+}
+
+// text with every {name} object returns an object for replaced by OBJECT_CHAR, and
+// one entry per OBJECT_CHAR in it. ones that were in text already get None and are
+// drawn like any other char, unresolved placeholders stay as they are
+pub(crate) fn resolve(
+    text: &str,
+    mut object: impl FnMut(&str) -> Option<InlineObject>,
+) -> (String, Vec<Option<InlineObject>>) {
+    let mut resolved = String::with_capacity(text.len());
+    let mut objects = vec![];
+    let mut rest = text;
+    while let Some(open) = rest.find('{') {
+        literal(&mut resolved, &mut objects, &rest[..open]);
+        let after = &rest[open + 1..];
+        let close = match after.find(['{', '}', '\n']) {
+            Some(close) if after[close..].starts_with('}') => close,
+            // a brace that doesn't open a placeholder
+            _ => {
+                literal(&mut resolved, &mut objects, "{");
+                rest = after;
+                continue;
+            }
+        };
+        match object(&after[..close]) {
+            Some(found) => {
+                resolved.push(OBJECT_CHAR);
+                objects.push(Some(found));
+            }
+            None => literal(&mut resolved, &mut objects, &rest[open..open + close + 2]),
+        }
+        rest = &after[close + 1..];
+    }
+    literal(&mut resolved, &mut objects, rest);
+    (resolved, objects)
+}
+
+fn literal(resolved: &mut String, objects: &mut Vec<Option<InlineObject>>, s: &str) {
+    resolved.push_str(s);
+    objects.extend(s.matches(OBJECT_CHAR).map(|_| None));
+}
+
+// the width over height of each object, as TextLayouter makes room for them
+pub(crate) fn aspects(objects: &[Option<InlineObject>]) -> Vec<Option<f32>> {
+    objects
+        .iter()
+        .map(|object| object.as_ref().map(|object| object.aspect))
+        .collect()
+}
@@ -0,0 +1,39 @@
+// keyboard and ime input for TextEditController and Console, independent of any
+// windowing library. with the winit feature InputEvent::from_winit translates
+// winit's window events, other event loops (SDL2, custom surfaces) build them directly
+#[derive(Debug, Clone, PartialEq)]
+pub enum InputEvent {
+    Modifiers(Modifiers),
+    // typed text, control chars are ignored
+    Char(char),
+    // ime composition and the caret inside it, None hides the caret
+    Preedit(String, Option<usize>),
+    Commit(String),
+    ImeDisabled,
+    KeyPressed(Key),
+    // mouse wheel in lines, positive scrolls up
+    Scroll(f32),
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Modifiers {
+    pub shift: bool,
+    pub ctrl: bool,
+}
+
+// the keys text fields and the console react to, and the letters for shortcuts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Key {
+    Left,
+    Right,
+    Home,
+    End,
+    Backspace,
+    Delete,
+    Enter,
+    PageUp,
+    PageDown,
+    // letter and digit keys, lowercase whatever the modifiers, for shortcuts. ctrl+a
+    // selects everything, typed text comes as InputEvent::Char
+    Character(char),
+}
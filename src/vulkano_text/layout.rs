@@ -0,0 +1,479 @@
+use rusttype::{point, Rect};
+
+#[cfg(feature = "unicode-linebreak")]
+use unicode_segmentation::GraphemeCursor;
+use unicode_segmentation::UnicodeSegmentation;
+
+use std::fmt;
+use std::iter;
+use std::ops::Range;
+
+// what placeholders are laid out as, the char unicode set aside for inline objects
+pub(crate) const OBJECT_CHAR: char = '\u{fffc}';
+
+// where a grapheme cluster of the text ended up, relative to the layout's origin on the
+// first baseline. x and advance run along the line, so they are vertical for columns
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct CharBox {
+    pub(crate) byte: usize,
+    pub(crate) line: usize,
+    pub(crate) x: f32,
+    // where x was in text order, before right to left runs were mirrored
+    pub(crate) pen: f32,
+    pub(crate) advance: f32,
+    pub(crate) newline: bool,
+    // in a right to left run, its start is on the right
+    pub(crate) rtl: bool,
+}
+
+// positions of every grapheme cluster of a text as queue_text would draw it, for placing a
+// caret and mapping clicks back to the text. coordinates are relative to the
+// x, y passed to queue_text, so they stay valid when the text moves
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextLayout {
+    chars: Vec<CharBox>,
+    len: usize,
+    ascent: f32,
+    descent: f32,
+    line_height: f32,
+    // columns instead of lines
+    vertical: bool,
+}
+
+impl TextLayout {
+    // chars has one box per char of text, they get merged into clusters so a caret
+    // never ends up between an emoji and its modifier or a letter and its accent
+    pub(crate) fn new(
+        text: &str,
+        chars: Vec<CharBox>,
+        ascent: f32,
+        descent: f32,
+        line_height: f32,
+        vertical: bool,
+    ) -> TextLayout {
+        let mut chars = chars.into_iter().peekable();
+        let mut clusters = vec![];
+        for (start, cluster) in text.grapheme_indices(true) {
+            let end = start + cluster.len();
+            let mut members = iter::from_fn(|| chars.next_if(|c| c.byte < end));
+            let mut merged = match members.next() {
+                Some(first) => first,
+                None => continue,
+            };
+            let mut right = merged.x + merged.advance;
+            for c in members {
+                right = right.max(c.x + c.advance);
+                merged.newline |= c.newline;
+            }
+            merged.advance = right - merged.x;
+            clusters.push(merged);
+        }
+
+        TextLayout {
+            chars: clusters,
+            len: text.len(),
+            ascent,
+            descent,
+            line_height,
+            vertical,
+        }
+    }
+
+    // laid out in physical pixels, handed out in logical ones
+    pub(crate) fn scaled(mut self, factor: f32) -> TextLayout {
+        for c in &mut self.chars {
+            c.x *= factor;
+            c.pen *= factor;
+            c.advance *= factor;
+        }
+        self.ascent *= factor;
+        self.descent *= factor;
+        self.line_height *= factor;
+        self
+    }
+
+    pub fn is_vertical(&self) -> bool {
+        self.vertical
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.chars
+            .last()
+            .map_or(1, |c| c.line + c.newline as usize + 1)
+    }
+
+    pub fn line_height(&self) -> f32 {
+        self.line_height
+    }
+
+    // longest line
+    pub(crate) fn line_length(&self) -> f32 {
+        self.chars
+            .iter()
+            .filter(|c| !c.newline)
+            .map(|c| c.x + c.advance)
+            .fold(0.0, f32::max)
+    }
+
+    // from the first line's box to the last's
+    fn lines_extent(&self) -> f32 {
+        (self.line_count() - 1) as f32 * self.line_height + self.caret_height()
+    }
+
+    // in pixels
+    pub fn width(&self) -> f32 {
+        if self.vertical {
+            self.lines_extent()
+        } else {
+            self.line_length()
+        }
+    }
+
+    pub fn height(&self) -> f32 {
+        if self.vertical {
+            self.line_length()
+        } else {
+            self.lines_extent()
+        }
+    }
+
+    // top of a line relative to the first baseline
+    pub(crate) fn line_top(&self, line: usize) -> f32 {
+        line as f32 * self.line_height - self.ascent
+    }
+
+    // the line's box across the text, [top, bottom] for lines and [left, right] for
+    // columns, which go to the left of the first one's center
+    fn line_span(&self, line: usize) -> [f32; 2] {
+        if self.vertical {
+            let center = -(line as f32) * self.line_height;
+            [
+                center - self.line_height / 2.0,
+                center + self.line_height / 2.0,
+            ]
+        } else {
+            let top = self.line_top(line);
+            [top, top + self.line_height]
+        }
+    }
+
+    // along and across the line to x, y
+    fn rect(&self, along: [f32; 2], across: [f32; 2]) -> Rect<f32> {
+        if self.vertical {
+            Rect {
+                min: point(across[0], along[0]),
+                max: point(across[1], along[1]),
+            }
+        } else {
+            Rect {
+                min: point(along[0], across[0]),
+                max: point(along[1], across[1]),
+            }
+        }
+    }
+
+    pub(crate) fn caret_height(&self) -> f32 {
+        self.ascent - self.descent
+    }
+
+    // bytes of every line, line breaks left out
+    pub(crate) fn line_ranges(&self) -> Vec<Range<usize>> {
+        let mut ranges: Vec<Range<usize>> = vec![];
+        for (i, c) in self.chars.iter().enumerate() {
+            let end = self.chars.get(i + 1).map_or(self.len, |next| next.byte);
+            while ranges.len() <= c.line {
+                ranges.push(c.byte..c.byte);
+            }
+            if !c.newline {
+                ranges[c.line].end = end;
+            }
+        }
+        while ranges.len() < self.line_count() {
+            ranges.push(self.len..self.len);
+        }
+        ranges
+    }
+
+    #[cfg(feature = "gpu")]
+    pub(crate) fn cluster_starts(&self) -> impl Iterator<Item = usize> + '_ {
+        self.chars.iter().map(|c| c.byte)
+    }
+
+    // how far into its line the cluster starting at byte is in text order, or the end
+    // of the text. the same as where it's drawn unless right to left text moved it
+    pub(crate) fn x_at(&self, byte: usize) -> f32 {
+        let i = self.chars.partition_point(|c| c.byte < byte);
+        match (self.chars.get(i), self.chars.last()) {
+            (Some(c), _) => c.pen,
+            (None, Some(last)) if !last.newline => last.pen + last.advance,
+            _ => 0.0,
+        }
+    }
+
+    // the caret positions on the left and right side of the i-th cluster, which are
+    // after and before it in right to left runs
+    fn sides(&self, i: usize) -> (usize, usize) {
+        let c = &self.chars[i];
+        let after = self.chars.get(i + 1).map_or(self.len, |next| next.byte);
+        if c.rtl {
+            (after, c.byte)
+        } else {
+            (c.byte, after)
+        }
+    }
+
+    // byte index of the caret position closest to x, y. clicks on the first half of
+    // a char land before it, on the second half after it, where the first half of
+    // right to left text is the right one
+    pub fn hit_test(&self, x: f32, y: f32) -> usize {
+        let (line, along) = if self.vertical {
+            ((0.5 - x / self.line_height).floor(), y)
+        } else {
+            (((y - self.line_top(0)) / self.line_height).floor(), x)
+        };
+        let line = (line.max(0.0) as usize).min(self.line_count() - 1);
+        // left to right as drawn
+        let mut shown: Vec<usize> = (0..self.chars.len())
+            .filter(|&i| self.chars[i].line == line && !self.chars[i].newline)
+            .collect();
+        shown.sort_by(|&a, &b| self.chars[a].x.partial_cmp(&self.chars[b].x).unwrap());
+        let hit = shown.iter().find(|&&i| {
+            let c = &self.chars[i];
+            along < c.x + c.advance / 2.0
+        });
+        match (hit, shown.last()) {
+            (Some(&i), _) => self.sides(i).0,
+            (None, Some(&last)) => self.sides(last).1,
+            // an empty line, in front of its line break if it has one
+            (None, None) => self
+                .chars
+                .iter()
+                .find(|c| c.line == line)
+                .map_or(self.len, |c| c.byte),
+        }
+    }
+
+    // top left of the caret in front of byte_index and its length, down for
+    // horizontal text and to the right for vertical text. indices inside a char go
+    // to its start, indices past the end to the end of the text. in front of a char
+    // of a right to left run is on its right
+    pub fn caret_position(&self, byte_index: usize) -> (f32, f32, f32) {
+        let (line, x) = match self.chars.iter().rev().find(|c| c.byte <= byte_index) {
+            Some(c) if c.byte == byte_index || byte_index < self.len => {
+                (c.line, if c.rtl { c.x + c.advance } else { c.x })
+            }
+            Some(c) if c.newline => (c.line + 1, 0.0),
+            Some(c) => (c.line, if c.rtl { c.x } else { c.x + c.advance }),
+            None => (0, 0.0),
+        };
+        if self.vertical {
+            let center = -(line as f32) * self.line_height;
+            (center - self.caret_height() / 2.0, x, self.caret_height())
+        } else {
+            (x, self.line_top(line), self.caret_height())
+        }
+    }
+
+    // highlight rects covering the chars whose first byte is in range, one per run of
+    // chars that sit next to each other on screen. a range running from left to right
+    // into right to left text can take several on the same line
+    pub fn selection_rects(&self, range: Range<usize>) -> Vec<Rect<f32>> {
+        // selected line breaks get a stub so empty lines show up as selected
+        let newline_width = self.caret_height() / 4.0;
+        let mut boxes: Vec<(usize, [f32; 2])> = self
+            .chars
+            .iter()
+            .filter(|c| range.contains(&c.byte))
+            .map(|c| {
+                match (c.newline, c.rtl) {
+                    // line breaks of right to left paragraphs sit at the line's left end
+                    (true, true) => (c.line, [c.x - newline_width, c.x]),
+                    (true, false) => (c.line, [c.x, c.x + newline_width]),
+                    _ => (c.line, [c.x, c.x + c.advance]),
+                }
+            })
+            .collect();
+        boxes.sort_by(|a, b| (a.0, a.1[0]).partial_cmp(&(b.0, b.1[0])).unwrap());
+
+        let mut merged: Vec<(usize, [f32; 2])> = vec![];
+        for (line, [start, end]) in boxes {
+            match merged.last_mut() {
+                // kerning can leave tiny gaps between neighbours
+                Some((last_line, span)) if *last_line == line && start <= span[1] + 0.5 => {
+                    span[1] = span[1].max(end);
+                }
+                _ => merged.push((line, [start, end])),
+            }
+        }
+
+        // full line height, so selections over several lines have no gaps
+        merged
+            .into_iter()
+            .map(|(line, along)| self.rect(along, self.line_span(line)))
+            .collect()
+    }
+
+    // the layout as plain values, see LayoutSnapshot
+    pub fn snapshot(&self) -> LayoutSnapshot {
+        let lines = self
+            .line_ranges()
+            .into_iter()
+            .enumerate()
+            .map(|(line, bytes)| {
+                let end = self
+                    .chars
+                    .iter()
+                    .filter(|c| c.line == line && !c.newline)
+                    .map(|c| c.x + c.advance)
+                    .fold(0.0, f32::max);
+                LineSnapshot {
+                    bytes,
+                    rect: self.rect([0.0, end], self.line_span(line)),
+                }
+            })
+            .collect();
+        let clusters = self
+            .chars
+            .iter()
+            .map(|c| ClusterSnapshot {
+                byte: c.byte,
+                line: c.line,
+                x: c.x,
+                advance: c.advance,
+                newline: c.newline,
+            })
+            .collect();
+        LayoutSnapshot {
+            vertical: self.vertical,
+            lines,
+            clusters,
+        }
+    }
+}
+
+// where a layout put its lines and grapheme clusters, for comparing layouts between
+// platforms and versions without drawing anything. to_string gives a stable text
+// form with every position rounded to 1/64 pixel, so float noise from another libm
+// or compiler doesn't show up as a difference
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayoutSnapshot {
+    pub vertical: bool,
+    pub lines: Vec<LineSnapshot>,
+    pub clusters: Vec<ClusterSnapshot>,
+}
+
+// a line's bytes, line break left out, and its box from the origin to its end at full
+// line height
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineSnapshot {
+    pub bytes: Range<usize>,
+    pub rect: Rect<f32>,
+}
+
+// relative to the layout's origin on the first baseline, along the line
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClusterSnapshot {
+    pub byte: usize,
+    pub line: usize,
+    pub x: f32,
+    pub advance: f32,
+    pub newline: bool,
+}
+
+impl fmt::Display for LayoutSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // multiples of 1/64 print exactly, and -0 the same as 0
+        let round = |v: f32| (v * 64.0).round() / 64.0 + 0.0;
+        writeln!(f, "vertical {}", self.vertical)?;
+        for (i, line) in self.lines.iter().enumerate() {
+            writeln!(
+                f,
+                "line {} bytes {}..{} rect {} {} {} {}",
+                i,
+                line.bytes.start,
+                line.bytes.end,
+                round(line.rect.min.x),
+                round(line.rect.min.y),
+                round(line.rect.max.x),
+                round(line.rect.max.y)
+            )?;
+        }
+        for c in &self.clusters {
+            write!(
+                f,
+                "cluster {} line {} x {} advance {}",
+                c.byte,
+                c.line,
+                round(c.x),
+                round(c.advance)
+            )?;
+            if c.newline {
+                write!(f, " newline")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+// spaces that glue words together
+#[cfg(not(feature = "unicode-linebreak"))]
+fn is_breaking_space(cluster: &str) -> bool {
+    cluster
+        .chars()
+        .all(|c| c.is_whitespace() && !matches!(c, '\u{a0}' | '\u{2007}' | '\u{202f}'))
+}
+
+// byte offsets a wrapped line may start at: the start of the text and every word
+// following whitespace. always on grapheme cluster boundaries
+#[cfg(not(feature = "unicode-linebreak"))]
+pub(crate) fn break_opportunities(text: &str) -> Vec<usize> {
+    let mut opportunities = vec![0];
+    let mut after_space = false;
+    for (offset, cluster) in text.grapheme_indices(true) {
+        let space = is_breaking_space(cluster);
+        if after_space && !space {
+            opportunities.push(offset);
+        }
+        after_space = space;
+    }
+    opportunities
+}
+
+// the same following the unicode line breaking algorithm (UAX #14), which also
+// breaks between CJK ideographs and keeps closing punctuation off the start of a line
+#[cfg(feature = "unicode-linebreak")]
+pub(crate) fn break_opportunities(text: &str) -> Vec<usize> {
+    let opportunities = unicode_linebreak::linebreaks(text)
+        .map(|(offset, _)| offset)
+        .filter(|&offset| {
+            offset < text.len()
+                && GraphemeCursor::new(offset, text.len(), true)
+                    .is_boundary(text, 0)
+                    .unwrap()
+        });
+    iter::once(0).chain(opportunities).collect()
+}
+
+// whether a char stays upright in vertical text. a rough take on the Vertical_Orientation
+// property (UAX #50): CJK, kana, hangul and emoji stand, brackets and the long vowel
+// mark that have to turn sideways with the text don't
+pub(crate) fn is_upright(c: char) -> bool {
+    match c {
+        '\u{3008}'..='\u{3011}'
+        | '\u{3014}'..='\u{301f}'
+        | '\u{30fc}'
+        | '\u{ff08}'
+        | '\u{ff09}' => false,
+        '\u{1100}'..='\u{11ff}'
+        | '\u{2e80}'..='\u{a4cf}'
+        | '\u{a960}'..='\u{a97f}'
+        | '\u{ac00}'..='\u{d7ff}'
+        | '\u{f900}'..='\u{faff}'
+        | '\u{fe30}'..='\u{fe4f}'
+        | '\u{ff00}'..='\u{ffef}'
+        | '\u{1f000}'..='\u{1faff}'
+        | '\u{20000}'..='\u{3ffff}' => true,
+        _ => false,
+    }
+}
@@ -0,0 +1,152 @@
+use super::cache::Glyph;
+use super::script::FontId;
+
+use rusttype::{point, Point};
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{Hash, Hasher};
+
+// laid out glyphs of recently queued strings, so labels queued every frame skip the
+// layout. keyed by a 128 bit hash of the string, the layout relevant parts of its
+// style and the fraction of its origin, and the string is compared too. glyphs are
+// kept relative to the origin's whole pixels, the same string moved by whole pixels
+// lays out the same. the least recently used entry goes once there are more than
+// capacity, 0 turns the cache off
+pub(crate) struct LayoutCache {
+    capacity: usize,
+    entries: HashMap<[u64; 2], Entry>,
+    // the keys by when they were last used, oldest first
+    lru: BTreeMap<u64, [u64; 2]>,
+    // bumped on every lookup, entries remember when they were last used
+    tick: u64,
+}
+
+struct Entry {
+    text: String,
+    glyphs: Vec<(FontId, Glyph)>,
+    rotated: Vec<bool>,
+    used: u64,
+}
+
+impl LayoutCache {
+    pub(crate) fn new(capacity: usize) -> LayoutCache {
+        LayoutCache {
+            capacity,
+            entries: HashMap::new(),
+            lru: BTreeMap::new(),
+            tick: 0,
+        }
+    }
+
+    pub(crate) fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.entries.len() > capacity {
+            self.evict();
+        }
+    }
+
+    // fonts or their bindings changed, every layout may be different now
+    pub(crate) fn clear(&mut self) {
+        self.entries.clear();
+        self.lru.clear();
+    }
+
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn get(
+        &mut self,
+        key: [u64; 2],
+        text: &str,
+        origin: Point<f32>,
+    ) -> Option<(Vec<(FontId, Glyph)>, Vec<bool>)> {
+        self.tick += 1;
+        let entry = self
+            .entries
+            .get_mut(&key)
+            .filter(|entry| entry.text == text)?;
+        self.lru.remove(&entry.used);
+        self.lru.insert(self.tick, key);
+        entry.used = self.tick;
+        let glyphs = entry
+            .glyphs
+            .iter()
+            .map(|&(font_id, glyph)| (font_id, moved(glyph, whole(origin), 1.0)))
+            .collect();
+        Some((glyphs, entry.rotated.clone()))
+    }
+
+    pub(crate) fn insert(
+        &mut self,
+        key: [u64; 2],
+        text: &str,
+        origin: Point<f32>,
+        glyphs: &[(FontId, Glyph)],
+        rotated: &[bool],
+    ) {
+        if self.capacity == 0 {
+            return;
+        }
+        self.tick += 1;
+        if self.entries.len() >= self.capacity && !self.entries.contains_key(&key) {
+            self.evict();
+        }
+        let glyphs = glyphs
+            .iter()
+            .map(|&(font_id, glyph)| (font_id, moved(glyph, whole(origin), -1.0)))
+            .collect();
+        let replaced = self.entries.insert(
+            key,
+            Entry {
+                text: text.to_owned(),
+                glyphs,
+                rotated: rotated.to_vec(),
+                used: self.tick,
+            },
+        );
+        if let Some(replaced) = replaced {
+            self.lru.remove(&replaced.used);
+        }
+        self.lru.insert(self.tick, key);
+    }
+
+    fn evict(&mut self) {
+        if let Some((_, key)) = self.lru.pop_first() {
+            self.entries.remove(&key);
+        }
+    }
+}
+
+// hashes what hash writes twice, seeded apart, into a 128 bit key
+pub(crate) fn key(hash: impl Fn(&mut DefaultHasher)) -> [u64; 2] {
+    [0u8, 1].map(|seed| {
+        let mut hasher = DefaultHasher::new();
+        seed.hash(&mut hasher);
+        hash(&mut hasher);
+        hasher.finish()
+    })
+}
+
+// the fraction of the origin, it changes subpixel positions and so the glyphs
+pub(crate) fn fraction(origin: Point<f32>) -> [u32; 2] {
+    let whole = whole(origin);
+    [
+        (origin.x - whole.x).to_bits(),
+        (origin.y - whole.y).to_bits(),
+    ]
+}
+
+fn whole(origin: Point<f32>) -> Point<f32> {
+    point(origin.x.floor(), origin.y.floor())
+}
+
+// by whole pixels, forwards or backwards
+fn moved(mut glyph: Glyph, by: Point<f32>, sign: f32) -> Glyph {
+    let (dx, dy) = (by.x * sign, by.y * sign);
+    glyph.position = point(glyph.position.x + dx, glyph.position.y + dy);
+    if let Some(bounds) = &mut glyph.bounds {
+        let (dx, dy) = (dx as i32, dy as i32);
+        bounds.min = point(bounds.min.x + dx, bounds.min.y + dy);
+        bounds.max = point(bounds.max.x + dx, bounds.max.y + dy);
+    }
+    glyph
+}
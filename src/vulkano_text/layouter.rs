@@ -0,0 +1,753 @@
+use super::backend::{default_backend, FontError, GlyphBackend, GlyphId, PathSegment};
+use super::bidi;
+use super::cache::{instance_hash, Glyph};
+use super::layout::{break_opportunities, is_upright, CharBox, TextLayout, OBJECT_CHAR};
+use super::metrics::LineMetrics;
+use super::missing::MissingGlyphPolicy;
+use super::quality::Quality;
+use super::sanitize::ControlChars;
+use super::script::{FontBindings, FontId, Script};
+#[cfg(feature = "shaping")]
+use super::shaping::shape;
+use super::shaping::ShapedGlyph;
+#[cfg(feature = "gpu")]
+use super::style::Overflow;
+use super::style::{Hinting, TextStyle};
+use super::units::{scale_factor_for_dpi, Units};
+use super::variations::{FontVariations, NamedInstance, VariationAxis};
+
+use rusttype::{point, Point};
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use std::collections::HashMap;
+#[cfg(feature = "gpu")]
+use std::iter;
+use std::ops::{Range, RangeInclusive};
+use std::sync::Arc;
+
+// fonts and how text is laid out with them, without a device. DrawText lays out
+// everything it draws with one, and without the gpu feature it's all the crate
+// builds, for measuring and paginating text where nothing gets drawn
+#[derive(Clone)]
+pub struct TextLayouter {
+    // shared with the rasterizer threads
+    pub(crate) fonts: Vec<Arc<dyn GlyphBackend>>,
+    // same order as fonts
+    pub(crate) line_metrics: Vec<LineMetrics>,
+    variations: Vec<FontVariations>,
+    font_bindings: FontBindings,
+    control_chars: ControlChars,
+    missing_glyphs: MissingGlyphPolicy,
+    quality: Quality,
+    // physical pixels per unit, the product of the four below
+    pub(crate) scale_factor: f64,
+    window_scale_factor: f64,
+    units: Units,
+    ui_scale: f64,
+    view_scale: f64,
+}
+
+#[cfg(feature = "gpu")]
+const ELLIPSIS: &str = "\u{2026}";
+
+impl Default for TextLayouter {
+    fn default() -> TextLayouter {
+        TextLayouter::new()
+    }
+}
+
+impl TextLayouter {
+    // with the font DrawText embeds as font 0
+    pub fn new() -> TextLayouter {
+        let font = default_backend(include_bytes!("../font/DejaVuSans.ttf").to_vec())
+            .expect("the bundled font is readable");
+        let line_metrics = LineMetrics::parse(font.font_data());
+        let variations = FontVariations::parse(font.font_data());
+        TextLayouter {
+            fonts: vec![Arc::from(font)],
+            line_metrics: vec![line_metrics],
+            variations: vec![variations],
+            font_bindings: FontBindings::default(),
+            control_chars: ControlChars::default(),
+            missing_glyphs: MissingGlyphPolicy::default(),
+            quality: Quality::default(),
+            scale_factor: Units::default().dp_per_unit(),
+            window_scale_factor: 1.0,
+            units: Units::default(),
+            ui_scale: 1.0,
+            view_scale: 1.0,
+        }
+    }
+
+    // loaded with rusttype, or ab_glyph or swash when their feature is on. data the
+    // backend can't read is a FontError and adds nothing
+    pub fn add_font(&mut self, data: Vec<u8>) -> Result<FontId, FontError> {
+        Ok(self.add_font_backend(default_backend(data)?))
+    }
+
+    // a font loaded by any glyph backend, fonts of different backends can be mixed
+    pub fn add_font_backend(&mut self, font: Box<dyn GlyphBackend>) -> FontId {
+        if font.is_opentype() {
+            self.line_metrics.push(LineMetrics::parse(font.font_data()));
+            self.variations
+                .push(FontVariations::parse(font.font_data()));
+        } else {
+            let v_metrics = font.v_metrics_unscaled();
+            let height = v_metrics.ascent - v_metrics.descent;
+            self.line_metrics.push(LineMetrics::typical(height));
+            self.variations.push(FontVariations::default());
+        }
+        self.fonts.push(Arc::from(font));
+        self.fonts.len() - 1
+    }
+
+    // the axes a variable font can be set along with TextStyle::with_variation,
+    // empty for other fonts
+    pub fn variation_axes(&self, font: FontId) -> &[VariationAxis] {
+        &self.variations[font].axes
+    }
+
+    // presets like "Bold" or "Condensed Light" for TextStyle::with_named_instance
+    pub fn named_instances(&self, font: FontId) -> &[NamedInstance] {
+        &self.variations[font].instances
+    }
+
+    // bindings win over the default font for every char of the script,
+    // even if the bound font has no glyph for it
+    pub fn bind_script(&mut self, script: Script, font: FontId) {
+        assert!(font < self.fonts.len(), "unknown font id {}", font);
+        self.font_bindings.bind_script(script, font);
+    }
+
+    pub fn bind_range(&mut self, range: RangeInclusive<char>, font: FontId) {
+        assert!(font < self.fonts.len(), "unknown font id {}", font);
+        self.font_bindings.bind_range(range, font);
+    }
+
+    pub fn unbind_font(&mut self, font: FontId) {
+        self.font_bindings.unbind_font(font);
+    }
+
+    // Keep by default. queued text and layout_text both go by it
+    pub fn set_control_chars(&mut self, control_chars: ControlChars) {
+        self.control_chars = control_chars;
+    }
+
+    // Skip by default, queued text and layout_text both go by it
+    pub fn set_missing_glyph_policy(&mut self, policy: MissingGlyphPolicy) {
+        self.missing_glyphs = policy;
+    }
+
+    pub fn with_quality(mut self, quality: Quality) -> TextLayouter {
+        self.set_quality(quality);
+        self
+    }
+
+    pub fn quality(&self) -> Quality {
+        self.quality
+    }
+
+    // whether glyph origins snap to whole pixels
+    pub fn set_quality(&mut self, quality: Quality) {
+        self.quality = quality;
+    }
+
+    // the window's scale factor, winit's Window::scale_factor. positions, sizes and
+    // widths stay in logical pixels while glyphs get rasterized for the physical ones,
+    // so text stays sharp on hidpi displays. takes effect for text queued afterwards
+    pub fn set_scale_factor(&mut self, scale_factor: f64) {
+        assert!(scale_factor > 0.0, "scale factor has to be positive");
+        self.window_scale_factor = scale_factor;
+        self.update_scale_factor();
+    }
+
+    // the same from the monitor's pixels per inch
+    pub fn set_dpi(&mut self, dpi: f64) {
+        self.set_scale_factor(scale_factor_for_dpi(dpi));
+    }
+
+    // physical pixels per unit, with the units and ui scale applied
+    pub fn scale_factor(&self) -> f64 {
+        self.scale_factor
+    }
+
+    pub fn units(&self) -> Units {
+        self.units
+    }
+
+    // Dp by default. like the scale factor it takes effect for text queued afterwards,
+    // layouts of the new units have to be taken again
+    pub fn set_units(&mut self, units: Units) {
+        self.units = units;
+        self.update_scale_factor();
+    }
+
+    pub fn ui_scale(&self) -> f64 {
+        self.ui_scale
+    }
+
+    // grows or shrinks everything laid out, positions included
+    pub fn set_ui_scale(&mut self, ui_scale: f64) {
+        assert!(ui_scale > 0.0, "ui scale has to be positive");
+        self.ui_scale = ui_scale;
+        self.update_scale_factor();
+    }
+
+    pub fn view_scale(&self) -> f32 {
+        self.view_scale as f32
+    }
+
+    // the zoom of a canvas the text is on, scales like the ui scale. see
+    // DrawText::set_view_scale for one that keeps glyphs from being rasterized anew
+    // at every step of a zoom
+    pub fn set_view_scale(&mut self, view_scale: f32) {
+        assert!(view_scale > 0.0, "view scale has to be positive");
+        self.view_scale = view_scale as f64;
+        self.update_scale_factor();
+    }
+
+    fn update_scale_factor(&mut self) {
+        self.scale_factor =
+            self.window_scale_factor * self.units.dp_per_unit() * self.ui_scale * self.view_scale;
+    }
+
+    // the outline of c in the font bound to it, size is the font's descent to ascent
+    // in whatever units the caller wants the path in. empty for chars without one, and
+    // for fonts whose backend doesn't read outlines
+    pub fn glyph_outline(&self, c: char, size: f32) -> Vec<PathSegment> {
+        let font = &self.fonts[self.font_bindings.font_for(c)];
+        let id = font.glyph_id(c);
+        if id == GlyphId(0) {
+            return vec![];
+        }
+        font.path(id, size, &[]).unwrap_or_default()
+    }
+
+    fn font_for(&self, style: &TextStyle, c: char) -> FontId {
+        style.font.unwrap_or_else(|| self.font_bindings.font_for(c))
+    }
+
+    // c when its font has a glyph for it, otherwise what the missing glyph policy
+    // draws instead
+    fn with_glyph(&self, style: &TextStyle, c: char) -> Option<char> {
+        let font = &self.fonts[self.font_for(style, c)];
+        if font.glyph_id(c) == GlyphId(0) {
+            self.missing_glyphs.replace(c)
+        } else {
+            Some(c)
+        }
+    }
+
+    // lines are spaced by the default font, whatever fonts they use
+    pub(crate) fn line_height(&self, size: f32) -> f32 {
+        let v_metrics = self.fonts[0].v_metrics(size);
+        v_metrics.ascent - v_metrics.descent + v_metrics.line_gap
+    }
+
+    // every run of text sharing a font shaped on its own, glyphs keyed by the byte
+    // offset of their cluster. vertical text isn't shaped, it's laid out char by char
+    #[cfg(feature = "shaping")]
+    fn shape_runs(
+        &self,
+        text: &str,
+        style: &TextStyle,
+    ) -> Option<HashMap<usize, Vec<ShapedGlyph>>> {
+        if style.is_vertical() {
+            return None;
+        }
+        let mut runs: Vec<(FontId, Range<usize>)> = vec![];
+        for (byte, c) in text.char_indices().filter(|&(_, c)| c != '\n') {
+            let font_id = self.font_for(style, c);
+            if !self.fonts[font_id].is_opentype() {
+                continue;
+            }
+            let end = byte + c.len_utf8();
+            match runs.last_mut() {
+                Some((font, run)) if *font == font_id && run.end == byte => run.end = end,
+                _ => runs.push((font_id, byte..end)),
+            }
+        }
+
+        let mut glyphs: HashMap<usize, Vec<ShapedGlyph>> = HashMap::new();
+        for (font_id, run) in runs {
+            let v_metrics = self.fonts[font_id].v_metrics_unscaled();
+            let scale = style.size / (v_metrics.ascent - v_metrics.descent);
+            let data = self.fonts[font_id].font_data();
+            for glyph in shape(data, &text[run.clone()], &style.features, scale) {
+                glyphs
+                    .entry(run.start + glyph.cluster)
+                    .or_default()
+                    .push(glyph);
+            }
+        }
+        Some(glyphs)
+    }
+
+    #[cfg(not(feature = "shaping"))]
+    fn shape_runs(
+        &self,
+        _text: &str,
+        _style: &TextStyle,
+    ) -> Option<HashMap<usize, Vec<ShapedGlyph>>> {
+        None
+    }
+
+    // every char with its glyphs and whether they lie on their side. newlines have
+    // none, and with shaping neither have chars merged into a ligature. a new line also
+    // starts at each byte offset in breaks, which has to be sorted. aspects has an
+    // entry per OBJECT_CHAR in text, the ones with an inline object's width over
+    // height make room for it. right to left text ends up in the order it's read, see
+    // reorder_bidi
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn place_chars(
+        &self,
+        text: &str,
+        style: &TextStyle,
+        start: Point<f32>,
+        breaks: &[usize],
+        aspects: &[Option<f32>],
+    ) -> Vec<(CharBox, Vec<(FontId, Glyph, bool)>)> {
+        let size = style.size;
+        // faux bold makes every glyph wider
+        let extra_advance = style.emboldening();
+        let line_height = self.line_height(size);
+        let vertical = style.is_vertical();
+        let mut shaped = self.shape_runs(text, style);
+        let mut aspects = aspects.iter();
+        let mut line_starts = breaks.iter().peekable();
+        let mut caret = 0.0;
+        let mut line = 0;
+        let mut last: Option<(FontId, GlyphId)> = None;
+        let mut placed: Vec<_> = text
+            .char_indices()
+            .map(|(byte, c)| {
+                if line_starts.next_if(|&&at| at <= byte).is_some() {
+                    caret = 0.0;
+                    line += 1;
+                    last = None;
+                }
+                if c == '\n' {
+                    let newline = CharBox {
+                        byte,
+                        line,
+                        x: caret,
+                        pen: caret,
+                        advance: 0.0,
+                        newline: true,
+                        rtl: false,
+                    };
+                    caret = 0.0;
+                    line += 1;
+                    last = None;
+                    return (newline, vec![]);
+                }
+                // the object is drawn later, the line only makes room for it
+                if c == OBJECT_CHAR {
+                    if let Some(&Some(aspect)) = aspects.next() {
+                        let [width, height] = self.object_size(size, aspect);
+                        let char_box = CharBox {
+                            byte,
+                            line,
+                            x: caret,
+                            pen: caret,
+                            advance: if vertical { height } else { width },
+                            newline: false,
+                            rtl: false,
+                        };
+                        caret += char_box.advance;
+                        last = None;
+                        return (char_box, vec![]);
+                    }
+                }
+
+                let original = c;
+                let c = self
+                    .control_chars
+                    .apply(c, shaped.is_some())
+                    .and_then(|c| self.with_glyph(style, c));
+                let c = match c {
+                    Some(c) => c,
+                    None => {
+                        let skipped = CharBox {
+                            byte,
+                            line,
+                            x: caret,
+                            pen: caret,
+                            advance: 0.0,
+                            newline: false,
+                            rtl: false,
+                        };
+                        return (skipped, vec![]);
+                    }
+                };
+
+                let font_id = self.font_for(style, c);
+                let font = &self.fonts[font_id];
+                // the shaper already kerned and positioned the glyphs. replaced control
+                // chars and missing glyphs are laid out on their own
+                if let Some(shaped) = shaped
+                    .as_mut()
+                    .filter(|_| c == original && font.is_opentype())
+                {
+                    let x = caret;
+                    let baseline = start.y + line as f32 * line_height;
+                    let glyphs = shaped
+                        .remove(&byte)
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|g| {
+                            let origin = self.snap_origin(
+                                style,
+                                point(start.x + caret + g.offset[0], baseline + g.offset[1]),
+                            );
+                            caret += g.advance + extra_advance;
+                            let glyph = self.glyph(font_id, g.id, style, origin, g.advance);
+                            (font_id, glyph, false)
+                        })
+                        .collect();
+                    let char_box = CharBox {
+                        byte,
+                        line,
+                        x,
+                        pen: x,
+                        advance: caret - x,
+                        newline: false,
+                        rtl: false,
+                    };
+                    return (char_box, glyphs);
+                }
+
+                let id = font.glyph_id(c);
+                let upright = vertical && is_upright(c);
+                // kerning only makes sense between glyphs of the same font, lying in
+                // a row
+                match last {
+                    Some((last_font, last_id)) if last_font == font_id && !upright => {
+                        caret += font.kerning(last_id, id, size);
+                    }
+                    _ => {}
+                }
+                last = if upright { None } else { Some((font_id, id)) };
+
+                let v_metrics = font.v_metrics(size);
+                let width = font.h_advance(id, size, &style.variations);
+                // columns are centered on their x, upright glyphs take an em each
+                // and sideways ones stand with their baseline left of the center
+                let column = start.x - line as f32 * line_height;
+                let (origin, advance) = if upright {
+                    let origin = point(column - width / 2.0, start.y + caret + v_metrics.ascent);
+                    (origin, v_metrics.ascent - v_metrics.descent + extra_advance)
+                } else if vertical {
+                    let baseline = column - (v_metrics.ascent + v_metrics.descent) / 2.0;
+                    (point(baseline, start.y + caret), width + extra_advance)
+                } else {
+                    let origin = point(start.x + caret, start.y + line as f32 * line_height);
+                    (origin, width + extra_advance)
+                };
+                let origin = self.snap_origin(style, origin);
+                let glyph = self.glyph(font_id, id, style, origin, width);
+                let x = if vertical { caret } else { origin.x - start.x };
+                let char_box = CharBox {
+                    byte,
+                    line,
+                    x,
+                    pen: x,
+                    advance,
+                    newline: false,
+                    rtl: false,
+                };
+                caret += advance;
+                (char_box, vec![(font_id, glyph, vertical && !upright)])
+            })
+            .collect();
+        // columns run top to bottom whatever the script
+        if !vertical {
+            self.reorder_bidi(text, style, breaks, &mut placed);
+        }
+        placed
+    }
+
+    // moves the grapheme clusters of every line to where the bidi algorithm shows them,
+    // runs of right to left text mirrored within the line. their glyphs move along,
+    // shaped and kerned like they were in text order
+    #[allow(clippy::type_complexity)]
+    fn reorder_bidi(
+        &self,
+        text: &str,
+        style: &TextStyle,
+        breaks: &[usize],
+        placed: &mut [(CharBox, Vec<(FontId, Glyph, bool)>)],
+    ) {
+        let levels = match bidi::levels(text, breaks) {
+            Some(levels) => levels,
+            None => return,
+        };
+        // indices into placed of every cluster, which never spans lines
+        let mut clusters: Vec<Range<usize>> = vec![];
+        for (_, cluster) in text.grapheme_indices(true) {
+            let start = clusters.last().map_or(0, |last| last.end);
+            clusters.push(start..start + cluster.chars().count());
+        }
+
+        let mut lines: Vec<&[Range<usize>]> = vec![];
+        let mut rest = &clusters[..];
+        while let Some(first) = rest.first() {
+            let line = placed[first.start].0.line;
+            let len = rest
+                .iter()
+                .take_while(|cluster| placed[cluster.start].0.line == line)
+                .count();
+            let (clusters, next) = rest.split_at(len);
+            lines.push(clusters);
+            rest = next;
+        }
+
+        for line in lines {
+            let line_levels: Vec<u8> = line.iter().map(|cluster| levels[cluster.start]).collect();
+            for (cluster, level) in line.iter().zip(&line_levels) {
+                for (char_box, _) in &mut placed[cluster.clone()] {
+                    char_box.rtl = level % 2 == 1;
+                }
+            }
+            let order = bidi::visual_order(&line_levels);
+            if order.iter().enumerate().all(|(i, &j)| i == j) {
+                continue;
+            }
+
+            // each cluster keeps the room it had up to the next one in text order
+            let left = |cluster: &Range<usize>| placed[cluster.start].0.x;
+            let widths: Vec<f32> = line
+                .iter()
+                .enumerate()
+                .map(|(i, cluster)| match line.get(i + 1) {
+                    Some(next) => left(next) - left(cluster),
+                    None => placed[cluster.clone()]
+                        .iter()
+                        .map(|(char_box, _)| char_box.x + char_box.advance - left(cluster))
+                        .fold(0.0, f32::max),
+                })
+                .collect();
+            let mut x = left(&line[0]);
+            let shifts: Vec<(usize, f32)> = order
+                .into_iter()
+                .map(|i| {
+                    let shift = (i, x - left(&line[i]));
+                    x += widths[i];
+                    shift
+                })
+                .collect();
+            for (i, dx) in shifts {
+                for (char_box, glyphs) in &mut placed[line[i].clone()] {
+                    char_box.x += dx;
+                    for (font_id, glyph, _) in glyphs {
+                        let origin =
+                            self.snap_origin(style, point(glyph.position.x + dx, glyph.position.y));
+                        *glyph = self.glyph(*font_id, glyph.id, style, origin, glyph.advance);
+                    }
+                }
+            }
+        }
+    }
+
+    // an inline object's width and height, it spans the default font's ascent to
+    // descent like line_height does without the gap
+    pub(crate) fn object_size(&self, size: f32, aspect: f32) -> [f32; 2] {
+        let v_metrics = self.fonts[0].v_metrics(size);
+        let height = v_metrics.ascent - v_metrics.descent;
+        [height * aspect, height]
+    }
+
+    // variations and hinting only tell glyphs apart in the cache for backends that
+    // draw them
+    fn glyph(
+        &self,
+        font_id: FontId,
+        id: GlyphId,
+        style: &TextStyle,
+        position: Point<f32>,
+        advance: f32,
+    ) -> Glyph {
+        let font = &self.fonts[font_id];
+        Glyph {
+            id,
+            size: style.size,
+            position,
+            advance,
+            bounds: font
+                .pixel_bounds(
+                    id,
+                    style.size,
+                    [position.x, position.y],
+                    &style.variations,
+                    style.hinting,
+                )
+                .map(Into::into),
+            instance: if font.supports_variations() {
+                instance_hash(&style.variations)
+            } else {
+                0
+            },
+            hinting: if font.supports_hinting() {
+                style.hinting
+            } else {
+                Hinting::None
+            },
+        }
+    }
+
+    pub(crate) fn snaps(&self, style: &TextStyle) -> bool {
+        style.snap_to_pixel || self.quality.settings().snap_to_pixel
+    }
+
+    // hinted outlines only line up with the pixel grid from a whole pixel origin
+    fn snap_origin(&self, style: &TextStyle, origin: Point<f32>) -> Point<f32> {
+        match style.hinting {
+            _ if self.snaps(style) => point(origin.x.round(), origin.y.round()),
+            Hinting::Full => point(origin.x.round(), origin.y.round()),
+            Hinting::Slight => point(origin.x, origin.y.round()),
+            Hinting::None => origin,
+        }
+    }
+
+    // laid out like queue_lines does, in physical pixels
+    fn text_layout(
+        &self,
+        style: &TextStyle,
+        text: &str,
+        breaks: &[usize],
+        aspects: &[Option<f32>],
+    ) -> TextLayout {
+        let scale = self.scale_factor as f32;
+        let style = &style.scaled(scale);
+        let chars = self
+            .place_chars(text, style, point(0.0, 0.0), breaks, aspects)
+            .into_iter()
+            .map(|(char_box, _)| char_box)
+            .collect();
+        let v_metrics = self.fonts[0].v_metrics(style.size);
+        TextLayout::new(
+            text,
+            chars,
+            v_metrics.ascent,
+            v_metrics.descent,
+            self.line_height(style.size),
+            style.is_vertical(),
+        )
+        .scaled(1.0 / scale)
+    }
+
+    // where DrawText::queue_text_styled would put every grapheme cluster of text,
+    // relative to its x, y
+    pub fn layout_text(&self, style: &TextStyle, text: &str) -> TextLayout {
+        self.text_layout(style, text, &[], &[])
+    }
+
+    // the same for queue_text_wrapped
+    pub fn layout_text_wrapped(&self, style: &TextStyle, text: &str, max_width: f32) -> TextLayout {
+        let breaks = self.wrap(style, text, max_width, &[]);
+        self.text_layout(style, text, &breaks, &[])
+    }
+
+    // every line of text cut down to max_width with an ellipsis where it's too long,
+    // at its end or in its middle depending on the style's overflow
+    #[cfg(feature = "gpu")]
+    pub(crate) fn truncate(&self, style: &TextStyle, text: &str, max_width: f32) -> String {
+        text.split('\n')
+            .map(|line| self.truncate_line(style, line, max_width))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[cfg(feature = "gpu")]
+    fn truncate_line(&self, style: &TextStyle, line: &str, max_width: f32) -> String {
+        let layout = self.layout_text(style, line);
+        if layout.line_length() <= max_width {
+            return line.to_owned();
+        }
+
+        let available = max_width - self.layout_text(style, ELLIPSIS).line_length();
+        let starts: Vec<usize> = layout
+            .cluster_starts()
+            .chain(iter::once(line.len()))
+            .collect();
+        // the last cluster boundary at most width into the line
+        let fitting = |width: f32| {
+            starts
+                .iter()
+                .copied()
+                .take_while(|&at| layout.x_at(at) <= width)
+                .last()
+                .unwrap_or(0)
+        };
+        match style.overflow {
+            Overflow::MiddleEllipsis => {
+                let head = fitting(available / 2.0);
+                let tail_width = available - layout.x_at(head);
+                let end = layout.x_at(line.len());
+                let tail = starts
+                    .iter()
+                    .copied()
+                    .find(|&at| at >= head && end - layout.x_at(at) <= tail_width)
+                    .unwrap_or(line.len());
+                format!("{}{}{}", &line[..head], ELLIPSIS, &line[tail..])
+            }
+            _ => format!("{}{}", line[..fitting(available)].trim_end(), ELLIPSIS),
+        }
+    }
+
+    // byte offsets where lines have to break so none is wider than max_width. lines
+    // break after whitespace, words wider than a whole line between grapheme clusters
+    pub(crate) fn wrap(
+        &self,
+        style: &TextStyle,
+        text: &str,
+        max_width: f32,
+        aspects: &[Option<f32>],
+    ) -> Vec<usize> {
+        // measured on one long line, so the width of any stretch is a subtraction
+        let unbroken = self.text_layout(style, text, &[], aspects);
+        let x_at = |byte: usize| unbroken.x_at(byte);
+
+        let mut breaks = vec![];
+        let mut line_start = 0;
+        let opportunities = break_opportunities(text);
+        for (i, &start) in opportunities.iter().enumerate() {
+            let end = opportunities.get(i + 1).copied().unwrap_or(text.len());
+            // trailing whitespace may hang past the edge
+            let ink_end = start + text[start..end].trim_end().len();
+
+            if start > line_start && x_at(ink_end) - x_at(line_start) > max_width {
+                breaks.push(start);
+                line_start = start;
+            }
+            while x_at(ink_end) - x_at(line_start) > max_width {
+                let clusters: Vec<usize> = text[line_start..ink_end]
+                    .grapheme_indices(true)
+                    .map(|(offset, _)| line_start + offset)
+                    .skip(1)
+                    .collect();
+                // at least one cluster per line, even if it alone is too wide
+                let fits = clusters
+                    .iter()
+                    .copied()
+                    .take_while(|&at| x_at(at) - x_at(line_start) <= max_width)
+                    .last()
+                    .or_else(|| clusters.first().copied());
+                match fits {
+                    Some(at) => {
+                        breaks.push(at);
+                        line_start = at;
+                    }
+                    None => break,
+                }
+            }
+
+            // x starts over after a newline
+            if text[start..end].contains('\n') {
+                line_start = end;
+            }
+        }
+        breaks
+    }
+}
@@ -0,0 +1,149 @@
+use rusttype::Rect;
+
+// heading sizes relative to the text's, from # to ######
+const HEADING_SCALES: [f32; 6] = [2.0, 1.6, 1.3, 1.15, 1.0, 0.9];
+
+// where queue_markdown put a link, in logical pixels
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarkdownLink {
+    pub url: String,
+    pub rect: Rect<f32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub(crate) struct MarkdownSpan {
+    pub(crate) text: String,
+    pub(crate) bold: bool,
+    pub(crate) italic: bool,
+    pub(crate) code: bool,
+    pub(crate) link: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct MarkdownLine {
+    // 1 to 6, 0 for plain lines
+    pub(crate) heading: usize,
+    pub(crate) spans: Vec<MarkdownSpan>,
+}
+
+impl MarkdownLine {
+    pub(crate) fn scale(&self) -> f32 {
+        match self.heading {
+            0 => 1.0,
+            level => HEADING_SCALES[level - 1],
+        }
+    }
+}
+
+// one line per source line. markers without a closing one are left as they are, and
+// so are underscores inside words, like in snake_case
+pub(crate) fn parse(source: &str) -> Vec<MarkdownLine> {
+    source
+        .lines()
+        .map(|line| {
+            let hashes = line.chars().take_while(|&c| c == '#').count();
+            match line[hashes..].strip_prefix(' ') {
+                Some(rest) if (1..=6).contains(&hashes) => MarkdownLine {
+                    heading: hashes,
+                    spans: parse_inline(rest.trim()),
+                },
+                _ => MarkdownLine {
+                    heading: 0,
+                    spans: parse_inline(line),
+                },
+            }
+        })
+        .collect()
+}
+
+fn parse_inline(line: &str) -> Vec<MarkdownSpan> {
+    let mut spans = vec![];
+    let mut current = MarkdownSpan::default();
+    let mut prev: Option<char> = None;
+    let mut rest = line;
+    while let Some(c) = rest.chars().next() {
+        let next = rest[c.len_utf8()..].chars().next();
+        let consumed = match c {
+            '\\' if next.map_or(false, |n| n.is_ascii_punctuation()) => {
+                current.text.extend(next);
+                2
+            }
+            // everything up to the closing backtick is taken literally
+            '`' => match rest[1..].find('`') {
+                Some(end) => {
+                    flush(&mut spans, &mut current);
+                    spans.push(MarkdownSpan {
+                        text: rest[1..end + 1].to_owned(),
+                        code: true,
+                        ..current.clone()
+                    });
+                    end + 2
+                }
+                None => 0,
+            },
+            '[' => match link(rest) {
+                Some((text, url, len)) => {
+                    flush(&mut spans, &mut current);
+                    let mut link_spans = parse_inline(text);
+                    for span in &mut link_spans {
+                        span.bold |= current.bold;
+                        span.italic |= current.italic;
+                        span.link = Some(url.to_owned());
+                    }
+                    spans.extend(link_spans);
+                    len
+                }
+                None => 0,
+            },
+            '*' | '_' => {
+                let double = next == Some(c);
+                let marker = if double { &rest[..2] } else { &rest[..1] };
+                let after = rest[marker.len()..].chars().next();
+                let open = if double { current.bold } else { current.italic };
+                let intraword = c == '_'
+                    && prev.map_or(false, char::is_alphanumeric)
+                    && after.map_or(false, char::is_alphanumeric);
+                if !intraword && (open || rest[marker.len()..].contains(marker)) {
+                    flush(&mut spans, &mut current);
+                    if double {
+                        current.bold = !current.bold;
+                    } else {
+                        current.italic = !current.italic;
+                    }
+                    marker.len()
+                } else {
+                    0
+                }
+            }
+            _ => 0,
+        };
+        let consumed = if consumed == 0 {
+            current.text.push(c);
+            c.len_utf8()
+        } else {
+            consumed
+        };
+        prev = rest[..consumed].chars().last();
+        rest = &rest[consumed..];
+    }
+    flush(&mut spans, &mut current);
+    spans
+}
+
+fn flush(spans: &mut Vec<MarkdownSpan>, current: &mut MarkdownSpan) {
+    if !current.text.is_empty() {
+        let text = std::mem::take(&mut current.text);
+        spans.push(MarkdownSpan {
+            text,
+            ..current.clone()
+        });
+    }
+}
+
+// text, url and length in bytes of a [text](url) at the start of s
+fn link(s: &str) -> Option<(&str, &str, usize)> {
+    let close = s.find(']')?;
+    let url = s[close + 1..].strip_prefix('(')?;
+    let end = url.find(')')?;
+    Some((&s[1..close], &url[..end], close + end + 3))
+}
@@ -0,0 +1,404 @@
+use super::backend::PathSegment;
+use super::drawtext::TextRendererCore;
+use super::gpu::compat::{self, MeshBuffer};
+use super::style::TextStyle;
+
+use bytemuck::{Pod, Zeroable};
+
+use vulkano::memory::allocator::StandardMemoryAllocator;
+
+use std::sync::Arc;
+
+// curves are cut into lines about this long, in the path's units
+const CURVE_SEGMENT: f32 = 2.0;
+// side walls are shaded smoothly across joints that bend less than about 30 degrees
+const SMOOTH_COS: f32 = 0.87;
+
+type P = [f32; 2];
+
+// its vertex input layout is declared per vulkano version in gpu::compat, for
+// pipelines of the caller's own
+#[repr(C)]
+#[derive(Default, Debug, Clone, Copy, PartialEq, Zeroable, Pod)]
+pub struct MeshVertex {
+    pub position: [f32; 3],
+    pub normal: [f32; 3],
+}
+
+// text as a solid 3D mesh, for title screens and the like. in the style's units with y
+// going up and the first line's baseline at 0, the front faces +z at z 0 and the back
+// is depth behind it. triangles are counterclockwise seen from outside
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TextMesh {
+    pub vertices: Vec<MeshVertex>,
+    pub indices: Vec<u32>,
+}
+
+impl TextMesh {
+    // laid out like queue_text_styled lays out the text, without the scale factor.
+    // glyphs of fonts whose backend doesn't read outlines are left out
+    pub fn extrude(
+        draw_text: &TextRendererCore,
+        text: &str,
+        style: &TextStyle,
+        depth: f32,
+    ) -> TextMesh {
+        let glyphs = draw_text.glyph_paths(style, text);
+        let baseline = glyphs.first().map_or(0.0, |(origin, _)| origin.y);
+        let mut mesh = TextMesh::default();
+        for (origin, path) in glyphs {
+            let contours = flatten(&path, |[x, y]| [origin.x + x, baseline - (origin.y + y)]);
+            for (outer, holes) in nest(contours) {
+                mesh.push_glyph_part(outer, holes, depth);
+            }
+        }
+        mesh
+    }
+
+    // host visible vertex and index buffers, draw them with draw_indexed
+    pub fn buffers(
+        &self,
+        memory_allocator: &Arc<StandardMemoryAllocator>,
+    ) -> (MeshBuffer<MeshVertex>, MeshBuffer<u32>) {
+        compat::mesh_buffers(
+            memory_allocator,
+            self.vertices.clone(),
+            self.indices.clone(),
+        )
+    }
+
+    // an outer contour with its holes, outer counterclockwise and holes clockwise
+    fn push_glyph_part(&mut self, outer: Vec<P>, holes: Vec<Vec<P>>, depth: f32) {
+        let walls: Vec<Vec<P>> = [outer.clone()].into_iter().chain(holes.clone()).collect();
+        let polygon = bridge_holes(outer, holes);
+        let triangles = ear_clip(&polygon);
+
+        let mut face = |z: f32, normal: [f32; 3], flip: bool| {
+            let start = self.vertices.len() as u32;
+            self.vertices
+                .extend(polygon.iter().map(|&[x, y]| MeshVertex {
+                    position: [x, y, z],
+                    normal,
+                }));
+            for [a, b, c] in &triangles {
+                let [a, b, c] = [*a as u32 + start, *b as u32 + start, *c as u32 + start];
+                if flip {
+                    self.indices.extend([a, c, b]);
+                } else {
+                    self.indices.extend([a, b, c]);
+                }
+            }
+        };
+        face(0.0, [0.0, 0.0, 1.0], false);
+        if depth <= 0.0 {
+            return;
+        }
+        face(-depth, [0.0, 0.0, -1.0], true);
+
+        for contour in walls {
+            self.push_walls(&contour, depth);
+        }
+    }
+
+    // a quad per edge, normals pointing away from the solid side
+    fn push_walls(&mut self, contour: &[P], depth: f32) {
+        let len = contour.len();
+        let normals: Vec<P> = (0..len)
+            .map(|i| {
+                let [a, b] = [contour[i], contour[(i + 1) % len]];
+                let [dx, dy] = [b[0] - a[0], b[1] - a[1]];
+                let length = dx.hypot(dy).max(f32::EPSILON);
+                [dy / length, -dx / length]
+            })
+            .collect();
+        let smoothed = |edge: P, other: P| {
+            if edge[0] * other[0] + edge[1] * other[1] < SMOOTH_COS {
+                return edge;
+            }
+            let [x, y] = [edge[0] + other[0], edge[1] + other[1]];
+            let length = x.hypot(y).max(f32::EPSILON);
+            [x / length, y / length]
+        };
+        for i in 0..len {
+            let [a, b] = [contour[i], contour[(i + 1) % len]];
+            let na = smoothed(normals[i], normals[(i + len - 1) % len]);
+            let nb = smoothed(normals[i], normals[(i + 1) % len]);
+            let start = self.vertices.len() as u32;
+            for (p, n, z) in [(a, na, 0.0), (a, na, -depth), (b, nb, 0.0), (b, nb, -depth)] {
+                self.vertices.push(MeshVertex {
+                    position: [p[0], p[1], z],
+                    normal: [n[0], n[1], 0.0],
+                });
+            }
+            let [front_a, back_a, front_b, back_b] = [start, start + 1, start + 2, start + 3];
+            self.indices
+                .extend([front_a, back_a, front_b, front_b, back_a, back_b]);
+        }
+    }
+}
+
+// a glyph's outline covered with triangles and its contours, outer ones
+// counterclockwise and holes clockwise. for GlyphRendering::Tessellated
+pub(crate) struct Tessellation {
+    pub(crate) triangles: Vec<[P; 3]>,
+    pub(crate) contours: Vec<Vec<P>>,
+}
+
+pub(crate) fn tessellate(path: &[PathSegment], to: impl Fn(P) -> P) -> Tessellation {
+    let mut tessellation = Tessellation {
+        triangles: vec![],
+        contours: vec![],
+    };
+    for (outer, holes) in nest(flatten(path, to)) {
+        tessellation.contours.push(outer.clone());
+        tessellation.contours.extend(holes.iter().cloned());
+        let polygon = bridge_holes(outer, holes);
+        let triangles = ear_clip(&polygon).into_iter();
+        tessellation
+            .triangles
+            .extend(triangles.map(|triangle| triangle.map(|i| polygon[i])));
+    }
+    tessellation
+}
+
+// the path's contours as polygons, curves cut into lines and points put through to
+fn flatten(path: &[PathSegment], to: impl Fn(P) -> P) -> Vec<Vec<P>> {
+    let mut contours: Vec<Vec<P>> = vec![];
+    let mut pen = [0.0, 0.0];
+    let curve = |contours: &mut Vec<Vec<P>>, points: &[P]| {
+        let length: f32 = points
+            .windows(2)
+            .map(|w| (w[1][0] - w[0][0]).hypot(w[1][1] - w[0][1]))
+            .sum();
+        let steps = (length / CURVE_SEGMENT).ceil().clamp(1.0, 64.0) as usize;
+        for step in 1..=steps {
+            let t = step as f32 / steps as f32;
+            // de casteljau
+            let mut points = points.to_vec();
+            while points.len() > 1 {
+                points = points
+                    .windows(2)
+                    .map(|w| {
+                        [
+                            w[0][0] + (w[1][0] - w[0][0]) * t,
+                            w[0][1] + (w[1][1] - w[0][1]) * t,
+                        ]
+                    })
+                    .collect();
+            }
+            if let Some(contour) = contours.last_mut() {
+                contour.push(points[0]);
+            }
+        }
+    };
+    for segment in path {
+        match *segment {
+            PathSegment::MoveTo(p) => {
+                contours.push(vec![p]);
+                pen = p;
+            }
+            PathSegment::LineTo(p) => {
+                if let Some(contour) = contours.last_mut() {
+                    contour.push(p);
+                }
+                pen = p;
+            }
+            PathSegment::QuadTo(c, p) => {
+                curve(&mut contours, &[pen, c, p]);
+                pen = p;
+            }
+            PathSegment::CubicTo(c0, c1, p) => {
+                curve(&mut contours, &[pen, c0, c1, p]);
+                pen = p;
+            }
+        }
+    }
+    contours
+        .into_iter()
+        .map(|contour| {
+            let mut points: Vec<P> = contour.into_iter().map(&to).collect();
+            points.dedup_by(|a, b| (a[0] - b[0]).hypot(a[1] - b[1]) < 1e-5);
+            while points.len() > 1 && {
+                let (first, last) = (points[0], points[points.len() - 1]);
+                (first[0] - last[0]).hypot(first[1] - last[1]) < 1e-5
+            } {
+                points.pop();
+            }
+            points
+        })
+        .filter(|points| points.len() >= 3 && area(points).abs() > 1e-6)
+        .collect()
+}
+
+// twice the signed area, positive for counterclockwise polygons
+fn area(points: &[P]) -> f32 {
+    let len = points.len();
+    (0..len)
+        .map(|i| {
+            let [a, b] = [points[i], points[(i + 1) % len]];
+            a[0] * b[1] - b[0] * a[1]
+        })
+        .sum()
+}
+
+// even-odd
+fn contains(polygon: &[P], p: P) -> bool {
+    let len = polygon.len();
+    let mut inside = false;
+    for i in 0..len {
+        let [a, b] = [polygon[i], polygon[(i + 1) % len]];
+        if (a[1] > p[1]) != (b[1] > p[1])
+            && p[0] < a[0] + (p[1] - a[1]) / (b[1] - a[1]) * (b[0] - a[0])
+        {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+// outer contours with the holes right inside them. contours inside an even number of
+// others are outer ones, whichever way the font runs them
+fn nest(contours: Vec<Vec<P>>) -> Vec<(Vec<P>, Vec<Vec<P>>)> {
+    let depth: Vec<usize> = contours
+        .iter()
+        .enumerate()
+        .map(|(i, contour)| {
+            contours
+                .iter()
+                .enumerate()
+                .filter(|&(j, other)| j != i && contains(other, contour[0]))
+                .count()
+        })
+        .collect();
+    let oriented = |mut contour: Vec<P>, counterclockwise: bool| {
+        if (area(&contour) > 0.0) != counterclockwise {
+            contour.reverse();
+        }
+        contour
+    };
+    let mut parts = vec![];
+    for (i, outer) in contours.iter().enumerate() {
+        if depth[i] % 2 == 1 {
+            continue;
+        }
+        let holes = contours
+            .iter()
+            .enumerate()
+            .filter(|&(j, hole)| depth[j] == depth[i] + 1 && contains(outer, hole[0]))
+            .map(|(_, hole)| oriented(hole.clone(), false))
+            .collect();
+        parts.push((oriented(outer.clone(), true), holes));
+    }
+    parts
+}
+
+fn cross(a: P, b: P, c: P) -> f32 {
+    (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0])
+}
+
+fn in_triangle(p: P, a: P, b: P, c: P) -> bool {
+    let [d0, d1, d2] = [cross(a, b, p), cross(b, c, p), cross(c, a, p)];
+    let negative = d0 < 0.0 || d1 < 0.0 || d2 < 0.0;
+    let positive = d0 > 0.0 || d1 > 0.0 || d2 > 0.0;
+    !(negative && positive)
+}
+
+// one polygon, every hole joined to the outline by a seam going right from its
+// rightmost point to a vertex it can see
+fn bridge_holes(mut polygon: Vec<P>, mut holes: Vec<Vec<P>>) -> Vec<P> {
+    let rightmost = |hole: &[P]| {
+        (0..hole.len())
+            .max_by(|&a, &b| hole[a][0].total_cmp(&hole[b][0]))
+            .unwrap()
+    };
+    holes.sort_by(|a, b| b[rightmost(b)][0].total_cmp(&a[rightmost(a)][0]));
+    for hole in holes {
+        let m_index = rightmost(&hole);
+        let m = hole[m_index];
+        let len = polygon.len();
+        // the closest edge the ray crosses
+        let mut hit: Option<(f32, usize)> = None;
+        for i in 0..len {
+            let [a, b] = [polygon[i], polygon[(i + 1) % len]];
+            if (a[1] > m[1]) == (b[1] > m[1]) {
+                continue;
+            }
+            let x = a[0] + (m[1] - a[1]) / (b[1] - a[1]) * (b[0] - a[0]);
+            if x >= m[0] && hit.map_or(true, |(closest, _)| x < closest) {
+                hit = Some((x, i));
+            }
+        }
+        let p_index = match hit {
+            Some((x, i)) => {
+                let [a, b] = [polygon[i], polygon[(i + 1) % len]];
+                let mut p_index = if a[0] > b[0] { i } else { (i + 1) % len };
+                let p = polygon[p_index];
+                // reflex vertices in the way, the one closest to the ray is visible
+                let mut best = f32::MAX;
+                for j in 0..len {
+                    let v = polygon[j];
+                    let reflex =
+                        cross(polygon[(j + len - 1) % len], v, polygon[(j + 1) % len]) < 0.0;
+                    if j != p_index && reflex && v[0] >= m[0] && in_triangle(v, m, [x, m[1]], p) {
+                        let angle = (v[1] - m[1]).abs().atan2(v[0] - m[0]);
+                        if angle < best {
+                            best = angle;
+                            p_index = j;
+                        }
+                    }
+                }
+                p_index
+            }
+            // a hole that isn't inside after all, joined to the closest vertex
+            None => (0..len)
+                .min_by(|&a, &b| {
+                    let distance = |i: usize| (polygon[i][0] - m[0]).hypot(polygon[i][1] - m[1]);
+                    distance(a).total_cmp(&distance(b))
+                })
+                .unwrap(),
+        };
+        let mut bridged = polygon[..=p_index].to_vec();
+        bridged.extend_from_slice(&hole[m_index..]);
+        bridged.extend_from_slice(&hole[..=m_index]);
+        bridged.extend_from_slice(&polygon[p_index..]);
+        polygon = bridged;
+    }
+    polygon
+}
+
+// triangles of a counterclockwise polygon, by index
+fn ear_clip(polygon: &[P]) -> Vec<[usize; 3]> {
+    let mut remaining: Vec<usize> = (0..polygon.len()).collect();
+    let mut triangles = vec![];
+    let mut i = 0;
+    // checked in a row without finding an ear, bad outlines get clipped anyway
+    let mut misses = 0;
+    while remaining.len() > 3 {
+        let len = remaining.len();
+        let [a, b, c] = [
+            remaining[(i + len - 1) % len],
+            remaining[i],
+            remaining[(i + 1) % len],
+        ];
+        let [pa, pb, pc] = [polygon[a], polygon[b], polygon[c]];
+        // seams repeat points, only other points can be in the way
+        let is_ear = cross(pa, pb, pc) > 0.0
+            && !remaining.iter().any(|&j| {
+                let p = polygon[j];
+                p != pa && p != pb && p != pc && in_triangle(p, pa, pb, pc)
+            });
+        if is_ear || misses >= len {
+            triangles.push([a, b, c]);
+            remaining.remove(i);
+            i %= remaining.len();
+            misses = 0;
+        } else {
+            i = (i + 1) % len;
+            misses += 1;
+        }
+    }
+    if let [a, b, c] = remaining[..] {
+        triangles.push([a, b, c]);
+    }
+    triangles
+}
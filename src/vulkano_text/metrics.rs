@@ -0,0 +1,120 @@
+// decoration metrics rusttype doesn't expose, read straight from the font's sfnt tables.
+// everything is in font units, positions are the top edge of the line above the baseline
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct LineMetrics {
+    pub(crate) underline_position: f32,
+    pub(crate) underline_thickness: f32,
+    pub(crate) strikeout_position: f32,
+    pub(crate) strikeout_size: f32,
+}
+
+pub(crate) fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    let bytes = data.get(offset..offset + 2)?;
+    Some(u16::from_be_bytes([bytes[0], bytes[1]]))
+}
+
+fn read_i16(data: &[u8], offset: usize) -> Option<i16> {
+    read_u16(data, offset).map(|value| value as i16)
+}
+
+pub(crate) fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset + 4)?;
+    Some(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+// start of the table with the given tag, for collections the first font is used
+// like rusttype's Font::from_bytes does
+pub(crate) fn find_table(data: &[u8], tag: &[u8; 4]) -> Option<usize> {
+    let font = if data.get(0..4)? == b"ttcf" {
+        read_u32(data, 12)? as usize
+    } else {
+        0
+    };
+    let tables = read_u16(data, font + 4)? as usize;
+    (0..tables)
+        .map(|i| font + 12 + i * 16)
+        .find(|&record| data.get(record..record + 4) == Some(&tag[..]))
+        .and_then(|record| read_u32(data, record + 8))
+        .map(|offset| offset as usize)
+}
+
+// whether the font's table directory and every table in it lie within data, which a
+// truncated file fails
+pub(crate) fn tables_in_bounds(data: &[u8]) -> bool {
+    let font = match data.get(0..4) {
+        Some(b"ttcf") => match read_u32(data, 12) {
+            Some(font) => font as usize,
+            None => return false,
+        },
+        Some(_) => 0,
+        None => return false,
+    };
+    let tables = match read_u16(data, font + 4) {
+        Some(tables) => tables as usize,
+        None => return false,
+    };
+    (0..tables).all(|i| {
+        let record = font + 12 + i * 16;
+        match (read_u32(data, record + 8), read_u32(data, record + 12)) {
+            (Some(offset), Some(length)) => offset as usize + length as usize <= data.len(),
+            _ => false,
+        }
+    })
+}
+
+// whether the tables rusttype reads when it opens a font are there and big enough.
+// rusttype indexes into data without checking, so it would panic in Font::from_bytes
+// instead. only meaningful for tables_in_bounds fonts
+pub(crate) fn has_rusttype_tables(data: &[u8]) -> bool {
+    let has = |tag: &[u8; 4], min: usize| matches!(find_table(data, tag), Some(offset) if data.len() >= offset + min);
+    let cmap_records = find_table(data, b"cmap")
+        .and_then(|cmap| Some(cmap + 4 + 8 * read_u16(data, cmap + 2)? as usize));
+    has(b"loca", 0)
+        && has(b"glyf", 0)
+        && has(b"hhea", 0)
+        && has(b"hmtx", 0)
+        && has(b"head", 54)
+        && (find_table(data, b"maxp").is_none() || has(b"maxp", 6))
+        && matches!(cmap_records, Some(end) if end <= data.len())
+}
+
+impl LineMetrics {
+    // typical values for fonts lacking the tables
+    pub(crate) fn typical(units_per_em: f32) -> LineMetrics {
+        LineMetrics {
+            underline_position: -0.075 * units_per_em,
+            underline_thickness: 0.05 * units_per_em,
+            strikeout_position: 0.25 * units_per_em,
+            strikeout_size: 0.05 * units_per_em,
+        }
+    }
+
+    pub(crate) fn parse(data: &[u8]) -> LineMetrics {
+        let units_per_em = find_table(data, b"head")
+            .and_then(|head| read_u16(data, head + 18))
+            .unwrap_or(2048) as f32;
+        let mut metrics = LineMetrics::typical(units_per_em);
+
+        if let Some(post) = find_table(data, b"post") {
+            if let (Some(position), Some(thickness)) =
+                (read_i16(data, post + 8), read_i16(data, post + 10))
+            {
+                if thickness > 0 {
+                    metrics.underline_position = position as f32;
+                    metrics.underline_thickness = thickness as f32;
+                }
+            }
+        }
+        if let Some(os2) = find_table(data, b"OS/2") {
+            if let (Some(size), Some(position)) =
+                (read_i16(data, os2 + 26), read_i16(data, os2 + 28))
+            {
+                if size > 0 {
+                    metrics.strikeout_size = size as f32;
+                    metrics.strikeout_position = position as f32;
+                }
+            }
+        }
+        metrics
+    }
+}
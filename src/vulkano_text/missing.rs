@@ -0,0 +1,35 @@
+use std::sync::Arc;
+
+// what gets drawn for a char its font has no glyph for
+#[derive(Clone, Default)]
+pub enum MissingGlyphPolicy {
+    // nothing, the char takes no room
+    #[default]
+    Skip,
+    // the font's .notdef glyph, usually an empty box
+    Tofu,
+    // U+FFFD, the question mark in a diamond
+    ReplacementChar,
+    // called with every missing char whenever a string containing it is laid out, e.g.
+    // to log it during localization QA. returns the char to draw instead, None draws
+    // nothing. chars the fonts lack again show as tofu
+    Callback(Arc<dyn Fn(char) -> Option<char> + Send + Sync>),
+}
+
+impl MissingGlyphPolicy {
+    pub fn callback(
+        f: impl Fn(char) -> Option<char> + Send + Sync + 'static,
+    ) -> MissingGlyphPolicy {
+        MissingGlyphPolicy::Callback(Arc::new(f))
+    }
+
+    // what to draw instead of c, None for nothing
+    pub(crate) fn replace(&self, c: char) -> Option<char> {
+        match self {
+            MissingGlyphPolicy::Skip => None,
+            MissingGlyphPolicy::Tofu => Some(c),
+            MissingGlyphPolicy::ReplacementChar => Some(char::REPLACEMENT_CHARACTER),
+            MissingGlyphPolicy::Callback(f) => f(c),
+        }
+    }
+}
@@ -1,3 +1,165 @@
+#[cfg(feature = "accesskit")]
+mod accessibility;
+#[cfg(feature = "gpu")]
+mod anchor;
+#[cfg(feature = "gpu")]
+mod animate;
+#[cfg(feature = "gpu")]
+mod ansi;
+mod atlas_file;
+#[cfg(feature = "gpu")]
+mod axis;
+mod backend;
+#[cfg(feature = "bevy_vk_font_render")]
+mod bevy_plugin;
+mod bidi;
+mod bmfont;
+mod cache;
+#[cfg(any(feature = "gpu", feature = "wgpu"))]
+mod color;
+#[cfg(feature = "gpu")]
+mod console;
+#[cfg(feature = "gpu")]
+mod debug;
+#[cfg(feature = "gpu")]
 mod drawtext;
+#[cfg(feature = "gpu")]
+mod edit;
+#[cfg(feature = "gpu")]
+mod effect;
+#[cfg(feature = "gpu")]
+mod frames;
+#[cfg(feature = "gpu")]
+mod gpu;
+mod gpu_backend;
+#[cfg(feature = "gpu_raster")]
+mod gpu_raster;
+#[cfg(feature = "gpu")]
+mod grid;
+#[cfg(feature = "gpu")]
+mod indirect;
+#[cfg(feature = "gpu")]
+mod inline;
+mod input;
+mod layout;
+#[cfg(feature = "gpu")]
+mod layout_cache;
+mod layouter;
+#[cfg(feature = "gpu")]
+mod markdown;
+#[cfg(feature = "gpu")]
+mod mesh;
+mod metrics;
+mod missing;
+#[cfg(feature = "gpu")]
+mod msdf;
+#[cfg(feature = "gpu")]
+mod msdf_atlas;
+#[cfg(feature = "gpu")]
+mod offscreen;
+#[cfg(feature = "gpu")]
+mod partial;
+mod quads;
+mod quality;
+#[cfg(feature = "gpu")]
+mod retained;
+mod sanitize;
+#[cfg(feature = "screenshot")]
+mod screenshot;
+mod script;
+#[cfg(feature = "shader_reload")]
+mod shader_reload;
+mod shaping;
+mod style;
+#[cfg(feature = "gpu")]
+mod subtitles;
+#[cfg(feature = "gpu")]
+mod surface;
+#[cfg(feature = "gpu")]
+mod table;
+#[cfg(feature = "gpu")]
+mod target;
+#[cfg(feature = "gpu")]
+mod text_box;
+#[cfg(feature = "gpu_timing")]
+mod timing;
+mod units;
+mod variations;
+#[cfg(feature = "gpu")]
+mod vulkano_backend;
+#[cfg(feature = "wgpu")]
+mod wgpu_backend;
+#[cfg(feature = "winit")]
+mod winit_input;
 
+#[cfg(feature = "accesskit")]
+pub use accessibility::*;
+#[cfg(feature = "gpu")]
+pub use anchor::{Anchor, CoordinateOrigin, Offset};
+#[cfg(feature = "gpu")]
+pub use animate::*;
+#[cfg(feature = "gpu")]
+pub use axis::*;
+pub use backend::*;
+#[cfg(feature = "bevy_vk_font_render")]
+pub use bevy_plugin::*;
+pub use bmfont::*;
+pub use cache::{AtlasDefragment, CacheStats};
+#[cfg(any(feature = "gpu", feature = "wgpu"))]
+pub use color::*;
+#[cfg(feature = "gpu")]
+pub use console::*;
+#[cfg(feature = "gpu")]
+pub use debug::*;
+#[cfg(feature = "gpu")]
 pub use drawtext::*;
+#[cfg(feature = "gpu")]
+pub use edit::*;
+#[cfg(feature = "gpu")]
+pub use effect::*;
+#[cfg(feature = "gpu")]
+pub use frames::*;
+pub use gpu_backend::*;
+#[cfg(feature = "gpu")]
+pub use grid::*;
+#[cfg(feature = "gpu")]
+pub use inline::InlineObject;
+pub use input::*;
+pub use layout::*;
+pub use layouter::*;
+#[cfg(feature = "gpu")]
+pub use markdown::MarkdownLink;
+#[cfg(feature = "gpu")]
+pub use mesh::*;
+pub use missing::MissingGlyphPolicy;
+#[cfg(feature = "msdf")]
+pub use msdf::MsdfError;
+#[cfg(feature = "gpu")]
+pub use msdf::MsdfFont;
+#[cfg(feature = "gpu")]
+pub use offscreen::*;
+#[cfg(feature = "gpu")]
+pub use partial::*;
+pub use quads::{GlyphQuad, TextQuads};
+pub use quality::*;
+#[cfg(feature = "gpu")]
+pub use retained::{Placement, RetainedTextId};
+pub use sanitize::ControlChars;
+pub use script::*;
+pub use style::*;
+#[cfg(feature = "gpu")]
+pub use subtitles::*;
+#[cfg(feature = "gpu")]
+pub use surface::*;
+#[cfg(feature = "gpu")]
+pub use table::*;
+#[cfg(feature = "gpu")]
+pub use target::*;
+#[cfg(feature = "gpu")]
+pub use text_box::*;
+pub use units::*;
+pub use variations::*;
+#[cfg(feature = "gpu")]
+pub use vulkano_backend::*;
+#[cfg(feature = "wgpu")]
+pub use wgpu_backend::*;
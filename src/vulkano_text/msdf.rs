@@ -0,0 +1,292 @@
+use super::backend::{FontMetrics, GlyphBackend, GlyphBitmap, GlyphId, PixelBounds};
+use super::gpu::compat::ImageView;
+use super::style::{FontVariation, Hinting};
+
+use rusttype::{point, Point, Rect};
+
+#[cfg(feature = "msdf")]
+use serde::Deserialize;
+
+use std::collections::HashMap;
+#[cfg(feature = "msdf")]
+use std::error::Error;
+#[cfg(feature = "msdf")]
+use std::fmt;
+use std::sync::Arc;
+
+// the parts of msdf-atlas-gen's json layout that are read
+#[cfg(feature = "msdf")]
+#[derive(Deserialize)]
+struct Layout {
+    atlas: LayoutAtlas,
+    metrics: LayoutMetrics,
+    #[serde(default)]
+    glyphs: Vec<LayoutGlyph>,
+    #[serde(default)]
+    kerning: Vec<LayoutKerning>,
+}
+
+#[cfg(feature = "msdf")]
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LayoutAtlas {
+    #[serde(rename = "type")]
+    kind: String,
+    distance_range: f32,
+    width: f32,
+    height: f32,
+    y_origin: Option<String>,
+}
+
+#[cfg(feature = "msdf")]
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LayoutMetrics {
+    ascender: f32,
+    descender: f32,
+    line_height: Option<f32>,
+}
+
+#[cfg(feature = "msdf")]
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LayoutGlyph {
+    unicode: Option<u32>,
+    #[serde(default)]
+    advance: f32,
+    plane_bounds: Option<LayoutBounds>,
+    atlas_bounds: Option<LayoutBounds>,
+}
+
+#[cfg(feature = "msdf")]
+#[derive(Deserialize)]
+struct LayoutBounds {
+    left: f32,
+    bottom: f32,
+    right: f32,
+    top: f32,
+}
+
+#[cfg(feature = "msdf")]
+impl LayoutBounds {
+    fn sides(&self) -> [f32; 4] {
+        [self.left, self.bottom, self.right, self.top]
+    }
+}
+
+#[cfg(feature = "msdf")]
+#[derive(Deserialize)]
+struct LayoutKerning {
+    unicode1: u32,
+    unicode2: u32,
+    advance: f32,
+}
+
+// why an msdf layout couldn't be read
+#[cfg(feature = "msdf")]
+#[derive(Debug)]
+pub enum MsdfError {
+    // not json, or a field that is missing or of the wrong type
+    Json(serde_json::Error),
+    // the atlas type, only msdf and mtsdf are supported
+    UnsupportedType(String),
+}
+
+#[cfg(feature = "msdf")]
+impl fmt::Display for MsdfError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MsdfError::Json(err) => write!(f, "the msdf layout can't be read: {}", err),
+            MsdfError::UnsupportedType(kind) => write!(
+                f,
+                "only msdf and mtsdf atlases are supported, not {:?}",
+                kind
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "msdf")]
+impl Error for MsdfError {}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct MsdfGlyph {
+    // in ems
+    advance: f32,
+    // left, bottom, right, top of the quad around the origin in ems, and of its
+    // region of the atlas in pixels. None for glyphs without ink
+    plane: Option<[f32; 4]>,
+    atlas: [f32; 4],
+}
+
+// a font pre-baked by msdf-atlas-gen into a multi-channel signed distance field atlas,
+// from its json layout and the atlas image. glyphs are drawn straight from the atlas
+// with a shader of their own, so they stay sharp at any size and keep their corners.
+// the texture has to be a unorm rgb or rgba image, msdf and mtsdf atlases work
+#[derive(Clone)]
+pub struct MsdfFont {
+    layout: Vec<u8>,
+    pub(crate) texture: Arc<ImageView>,
+    // in pixels of the atlas
+    pub(crate) distance_range: f32,
+    atlas_size: [f32; 2],
+    // whether y goes up in the layout, msdf-atlas-gen's default
+    y_up: bool,
+    metrics: FontMetrics,
+    // GlyphId 0 is the missing glyph, the glyphs follow in the order they were listed
+    glyphs: Vec<MsdfGlyph>,
+    ids: HashMap<char, GlyphId>,
+    kerning: HashMap<(GlyphId, GlyphId), f32>,
+}
+
+impl MsdfFont {
+    // layout is msdf-atlas-gen's -json output for a single font, texture its -imageout
+    #[cfg(feature = "msdf")]
+    pub fn new(layout: &str, texture: Arc<ImageView>) -> Result<MsdfFont, MsdfError> {
+        let json: Layout = serde_json::from_str(layout).map_err(MsdfError::Json)?;
+        let atlas = json.atlas;
+        if !matches!(atlas.kind.as_str(), "msdf" | "mtsdf") {
+            return Err(MsdfError::UnsupportedType(atlas.kind));
+        }
+        let metrics = json.metrics;
+        let (ascent, descent) = (metrics.ascender, metrics.descender);
+        let mut font = MsdfFont {
+            layout: layout.as_bytes().to_vec(),
+            texture,
+            distance_range: atlas.distance_range,
+            atlas_size: [atlas.width, atlas.height],
+            y_up: atlas.y_origin.as_deref() != Some("top"),
+            metrics: FontMetrics {
+                ascent,
+                descent,
+                line_gap: metrics
+                    .line_height
+                    .map_or(0.0, |height| height - (ascent - descent)),
+            },
+            glyphs: vec![MsdfGlyph::default()],
+            ids: HashMap::new(),
+            kerning: HashMap::new(),
+        };
+        for glyph in json.glyphs {
+            let c = match glyph.unicode.and_then(char::from_u32) {
+                Some(c) => c,
+                None => continue,
+            };
+            font.ids.insert(c, GlyphId(font.glyphs.len() as u32));
+            font.glyphs.push(MsdfGlyph {
+                advance: glyph.advance,
+                plane: glyph.plane_bounds.as_ref().map(LayoutBounds::sides),
+                atlas: glyph
+                    .atlas_bounds
+                    .as_ref()
+                    .map_or([0.0; 4], LayoutBounds::sides),
+            });
+        }
+        for pair in json.kerning {
+            let id = |c| char::from_u32(c).and_then(|c| font.ids.get(&c).copied());
+            if let (Some(first), Some(second)) = (id(pair.unicode1), id(pair.unicode2)) {
+                font.kerning.insert((first, second), pair.advance);
+            }
+        }
+        Ok(font)
+    }
+
+    // pixels per em
+    fn scale(&self, size: f32) -> f32 {
+        size / (self.metrics.ascent - self.metrics.descent)
+    }
+
+    // where the glyph is drawn and the uvs of its region of the atlas, min x, min y, max
+    // x, max y with y going down. None for glyphs without ink
+    pub(crate) fn quad(
+        &self,
+        id: GlyphId,
+        size: f32,
+        position: Point<f32>,
+    ) -> Option<(Rect<f32>, [f32; 4])> {
+        let glyph = self.glyphs.get(id.0 as usize)?;
+        let [left, bottom, right, top] = glyph.plane?;
+        let scale = self.scale(size);
+        let up = if self.y_up { 1.0 } else { -1.0 };
+        let [y0, y1] = [
+            position.y - up * top * scale,
+            position.y - up * bottom * scale,
+        ];
+        let screen = Rect {
+            min: point(position.x + left * scale, y0.min(y1)),
+            max: point(position.x + right * scale, y0.max(y1)),
+        };
+
+        let [left, bottom, right, top] = glyph.atlas;
+        let [width, height] = self.atlas_size;
+        let v = |y: f32| {
+            if self.y_up {
+                1.0 - y / height
+            } else {
+                y / height
+            }
+        };
+        let [v0, v1] = [v(top), v(bottom)];
+        Some((
+            screen,
+            [left / width, v0.min(v1), right / width, v0.max(v1)],
+        ))
+    }
+}
+
+impl GlyphBackend for MsdfFont {
+    // the json layout, it has none of the tables DrawText reads
+    fn font_data(&self) -> &[u8] {
+        &self.layout
+    }
+
+    fn is_opentype(&self) -> bool {
+        false
+    }
+
+    // in ems
+    fn v_metrics_unscaled(&self) -> FontMetrics {
+        self.metrics
+    }
+
+    fn glyph_id(&self, c: char) -> GlyphId {
+        self.ids.get(&c).copied().unwrap_or(GlyphId(0))
+    }
+
+    fn h_advance(&self, id: GlyphId, size: f32, _variations: &[FontVariation]) -> f32 {
+        let advance = self.glyphs.get(id.0 as usize).map_or(0.0, |g| g.advance);
+        advance * self.scale(size)
+    }
+
+    fn kerning(&self, first: GlyphId, second: GlyphId, size: f32) -> f32 {
+        let advance = self.kerning.get(&(first, second)).copied().unwrap_or(0.0);
+        advance * self.scale(size)
+    }
+
+    fn pixel_bounds(
+        &self,
+        id: GlyphId,
+        size: f32,
+        position: [f32; 2],
+        _variations: &[FontVariation],
+        _hinting: Hinting,
+    ) -> Option<PixelBounds> {
+        let (screen, _) = self.quad(id, size, point(position[0], position[1]))?;
+        Some(PixelBounds {
+            min: [screen.min.x.floor() as i32, screen.min.y.floor() as i32],
+            max: [screen.max.x.ceil() as i32, screen.max.y.ceil() as i32],
+        })
+    }
+
+    // never in the atlas, see DrawText::add_msdf_font
+    fn rasterize(
+        &self,
+        _id: GlyphId,
+        _size: f32,
+        _position: [f32; 2],
+        _variations: &[FontVariation],
+        _hinting: Hinting,
+    ) -> Option<GlyphBitmap> {
+        None
+    }
+}
@@ -0,0 +1,708 @@
+use super::backend::{
+    FontMetrics, GlyphBackend, GlyphBitmap, GlyphId, GlyphOutline, PathSegment, PixelBounds,
+};
+use super::cache::Glyph;
+use super::gpu::compat::{self, Image, ImageView};
+use super::script::FontId;
+use super::style::{FontVariation, Hinting};
+use super::target::TargetId;
+
+use rusttype::{point, Rect};
+
+use vulkano::command_buffer::{AutoCommandBufferBuilder, PrimaryAutoCommandBuffer};
+use vulkano::format::Format;
+use vulkano::memory::allocator::StandardMemoryAllocator;
+
+use std::collections::{HashMap, HashSet};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::Arc;
+use std::thread;
+
+// glyphs are generated once at this size, descent to ascent, and scaled to any other
+const GENERATED_SIZE: f32 = 48.0;
+// how far distances reach, in pixels of the atlas
+pub(crate) const DISTANCE_RANGE: f32 = 4.0;
+// around every glyph, enough for the field to fade out and for linear filtering
+const PADDING: i32 = 3;
+const ATLAS_SIZE: u32 = 1024;
+// turns sharper than about 30 degrees between two lines are corners. flattened curves
+// bend less than that from line to line
+const CORNER_SIN: f32 = 0.5;
+
+// the channels an edge's distance goes into
+const RED: u8 = 1;
+const GREEN: u8 = 2;
+const BLUE: u8 = 4;
+const WHITE: u8 = RED | GREEN | BLUE;
+const COLORS: [u8; 3] = [GREEN | BLUE, RED | BLUE, RED | GREEN];
+
+// a font added with add_font_msdf as DrawText sees it. everything but the glyph
+// images comes from the font, those are generated into the MsdfAtlas instead of the
+// glyph atlas
+pub(crate) struct DistanceFieldFont(pub(crate) Arc<dyn GlyphBackend>);
+
+impl GlyphBackend for DistanceFieldFont {
+    fn font_data(&self) -> &[u8] {
+        self.0.font_data()
+    }
+
+    fn v_metrics_unscaled(&self) -> FontMetrics {
+        self.0.v_metrics_unscaled()
+    }
+
+    fn glyph_id(&self, c: char) -> GlyphId {
+        self.0.glyph_id(c)
+    }
+
+    fn h_advance(&self, id: GlyphId, size: f32, variations: &[FontVariation]) -> f32 {
+        self.0.h_advance(id, size, variations)
+    }
+
+    fn kerning(&self, first: GlyphId, second: GlyphId, size: f32) -> f32 {
+        self.0.kerning(first, second, size)
+    }
+
+    fn pixel_bounds(
+        &self,
+        id: GlyphId,
+        size: f32,
+        position: [f32; 2],
+        variations: &[FontVariation],
+        hinting: Hinting,
+    ) -> Option<PixelBounds> {
+        self.0.pixel_bounds(id, size, position, variations, hinting)
+    }
+
+    fn rasterize(
+        &self,
+        _id: GlyphId,
+        _size: f32,
+        _position: [f32; 2],
+        _variations: &[FontVariation],
+        _hinting: Hinting,
+    ) -> Option<GlyphBitmap> {
+        None
+    }
+
+    fn path(
+        &self,
+        id: GlyphId,
+        size: f32,
+        variations: &[FontVariation],
+    ) -> Option<Vec<PathSegment>> {
+        self.0.path(id, size, variations)
+    }
+
+    fn supports_variations(&self) -> bool {
+        self.0.supports_variations()
+    }
+
+    fn is_opentype(&self) -> bool {
+        self.0.is_opentype()
+    }
+
+    fn supports_hinting(&self) -> bool {
+        self.0.supports_hinting()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct MsdfEntry {
+    // the glyph's padded cell around its origin, in pixels at GENERATED_SIZE
+    plane: Rect<f32>,
+    uv_rect: [f32; 4],
+}
+
+// a glyph's padded cell as the generator left it
+struct Field {
+    bounds: PixelBounds,
+    width: u32,
+    height: u32,
+    // rgba, row by row
+    pixels: Vec<u8>,
+}
+
+// None for glyphs without outlines or ink
+fn generate_field(font: &dyn GlyphBackend, id: GlyphId) -> Option<Field> {
+    let outline = font.outline(id, GENERATED_SIZE, [0.0, 0.0], &[])?;
+    if outline.lines.is_empty() {
+        return None;
+    }
+    let width = (outline.bounds.width() + 2 * PADDING) as u32;
+    let height = (outline.bounds.height() + 2 * PADDING) as u32;
+    Some(Field {
+        bounds: outline.bounds,
+        width,
+        height,
+        pixels: generate_msdf(&outline, width, height),
+    })
+}
+
+// a thread generating fields off the render thread, it stops once this is dropped
+struct Generator {
+    jobs: Sender<(FontId, GlyphId, Arc<dyn GlyphBackend>)>,
+    finished: Receiver<((FontId, GlyphId), Option<Field>)>,
+    // sent to the thread and not back yet
+    pending: HashSet<(FontId, GlyphId)>,
+    // the thread panicked, see take_finished
+    dead: bool,
+}
+
+impl Generator {
+    fn new() -> Generator {
+        let (jobs, waiting) = mpsc::channel::<(FontId, GlyphId, Arc<dyn GlyphBackend>)>();
+        let (done, finished) = mpsc::channel();
+        thread::spawn(move || {
+            for (font_id, id, font) in waiting {
+                let field = generate_field(&*font, id);
+                if done.send(((font_id, id), field)).is_err() {
+                    break;
+                }
+            }
+        });
+        Generator {
+            jobs,
+            finished,
+            pending: HashSet::new(),
+            dead: false,
+        }
+    }
+
+    // a job sent to a thread that is gone stays pending, take_finished fails it
+    fn request(&mut self, font_id: FontId, id: GlyphId, font: Arc<dyn GlyphBackend>) {
+        if self.pending.insert((font_id, id)) {
+            let _ = self.jobs.send((font_id, id, font));
+        }
+    }
+
+    // once the thread is gone, a glyph's outline made it panic, the glyphs still
+    // pending come back without a field. which of them it was isn't known, so they are
+    // all left without ink rather than crashing the next thread too
+    fn take_finished(&mut self) -> Vec<((FontId, GlyphId), Option<Field>)> {
+        let mut finished = vec![];
+        loop {
+            match self.finished.try_recv() {
+                Ok(field) => finished.push(field),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.dead = true;
+                    break;
+                }
+            }
+        }
+        for (key, _) in &finished {
+            self.pending.remove(key);
+        }
+        if self.dead {
+            finished.extend(self.pending.drain().map(|key| (key, None)));
+        }
+        finished
+    }
+}
+
+// one frame's copy of the atlas texture, like FrameSlot for the glyph atlas. the gpu
+// may still sample the copies of the previous frames while the next one is written
+struct MsdfSlot {
+    image: Arc<Image>,
+    texture: Arc<ImageView>,
+    // atlas version last uploaded into this copy
+    version: Option<u64>,
+}
+
+// multi-channel signed distance fields of the glyphs of fonts added with
+// add_font_msdf, generated from their outlines the first time they are drawn. glyphs
+// are generated once for every size, so they stay sharp however large they get.
+// generating happens on a thread of its own, glyphs show up in the frame after it's
+// done. every frame in flight of every target has its own texture, made once, and
+// only the cells added since that texture's last upload are copied into it. when the
+// atlas fills up it starts over with the glyphs just generated, and every texture
+// gets it whole
+pub(crate) struct MsdfAtlas {
+    fonts: HashMap<FontId, Arc<dyn GlyphBackend>>,
+    // rgba, row by row
+    pixels: Vec<u8>,
+    // y, height and the width used of each row
+    rows: Vec<[u32; 3]>,
+    // None for glyphs without outlines or ink
+    entries: HashMap<(FontId, GlyphId), Option<MsdfEntry>>,
+    // bumped by every cell placed and by clear
+    version: u64,
+    // the cells placed and the version they were placed at, kept until every slot has
+    // them
+    dirty: Vec<(u64, Rect<u32>)>,
+    // slots that uploaded a version older than this get the whole atlas
+    dirty_since: u64,
+    // by target like DrawText's frame slots
+    slots: HashMap<Option<TargetId>, Vec<MsdfSlot>>,
+    // the target and slot of this frame, drawn from by quad
+    current: (Option<TargetId>, usize),
+    generator: Option<Generator>,
+}
+
+impl MsdfAtlas {
+    pub(crate) fn new() -> MsdfAtlas {
+        MsdfAtlas {
+            fonts: HashMap::new(),
+            pixels: vec![],
+            rows: vec![],
+            entries: HashMap::new(),
+            version: 0,
+            dirty: vec![],
+            dirty_since: 0,
+            slots: HashMap::new(),
+            current: (None, 0),
+            generator: None,
+        }
+    }
+
+    pub(crate) fn add_font(&mut self, id: FontId, font: Arc<dyn GlyphBackend>) {
+        self.fonts.insert(id, font);
+    }
+
+    pub(crate) fn has_font(&self, id: FontId) -> bool {
+        self.fonts.contains_key(&id)
+    }
+
+    // glyphs the generator is still working on
+    pub(crate) fn pending(&self) -> usize {
+        self.generator
+            .as_ref()
+            .map_or(0, |generator| generator.pending.len())
+    }
+
+    // places the glyphs generated since the last frame and hands the generator the ones
+    // of this frame that are still missing. returns how many didn't fit
+    pub(crate) fn generate(
+        &mut self,
+        glyphs: impl IntoIterator<Item = (FontId, GlyphId)>,
+    ) -> usize {
+        if self.fonts.is_empty() {
+            return 0;
+        }
+        let generator = self.generator.get_or_insert_with(Generator::new);
+        let finished = generator.take_finished();
+        if generator.dead {
+            self.generator = Some(Generator::new());
+        }
+        let mut dropped = 0;
+        if !self.place(&finished) {
+            self.clear();
+            self.place(&finished);
+            dropped = finished
+                .iter()
+                .filter(|(key, _)| !self.entries.contains_key(key))
+                .count();
+        }
+
+        let generator = self.generator.as_mut().unwrap();
+        for (font_id, id) in glyphs {
+            if let Some(font) = self.fonts.get(&font_id) {
+                if !self.entries.contains_key(&(font_id, id)) {
+                    generator.request(font_id, id, font.clone());
+                }
+            }
+        }
+        dropped
+    }
+
+    // false once the atlas is full
+    fn place(&mut self, fields: &[((FontId, GlyphId), Option<Field>)]) -> bool {
+        for (key, field) in fields {
+            if self.entries.contains_key(key) {
+                continue;
+            }
+            let field = match field {
+                Some(field) => field,
+                None => {
+                    self.entries.insert(*key, None);
+                    continue;
+                }
+            };
+            let (width, height) = (field.width, field.height);
+            let [x, y] = match self.allocate(width, height) {
+                Some(origin) => origin,
+                None => return false,
+            };
+            if self.pixels.is_empty() {
+                self.pixels = vec![0; (ATLAS_SIZE * ATLAS_SIZE * 4) as usize];
+            }
+            for row in 0..height {
+                let dst = (((y + row) * ATLAS_SIZE + x) * 4) as usize;
+                let src = (row * width * 4) as usize;
+                self.pixels[dst..dst + width as usize * 4]
+                    .copy_from_slice(&field.pixels[src..src + width as usize * 4]);
+            }
+            self.version += 1;
+            self.dirty.push((
+                self.version,
+                Rect {
+                    min: point(x, y),
+                    max: point(x + width, y + height),
+                },
+            ));
+            let bounds = field.bounds;
+            let size = ATLAS_SIZE as f32;
+            self.entries.insert(
+                *key,
+                Some(MsdfEntry {
+                    plane: Rect {
+                        min: point(
+                            (bounds.min[0] - PADDING) as f32,
+                            (bounds.min[1] - PADDING) as f32,
+                        ),
+                        max: point(
+                            (bounds.max[0] + PADDING) as f32,
+                            (bounds.max[1] + PADDING) as f32,
+                        ),
+                    },
+                    uv_rect: [
+                        x as f32 / size,
+                        y as f32 / size,
+                        (x + width) as f32 / size,
+                        (y + height) as f32 / size,
+                    ],
+                }),
+            );
+        }
+        true
+    }
+
+    // shelf packing, rows are reused by glyphs a little shorter than them
+    fn allocate(&mut self, width: u32, height: u32) -> Option<[u32; 2]> {
+        let fits = |row: &&mut [u32; 3]| {
+            row[1] >= height && row[1] <= height + 8 && row[2] + width <= ATLAS_SIZE
+        };
+        if let Some(row) = self.rows.iter_mut().find(fits) {
+            row[2] += width;
+            return Some([row[2] - width, row[0]]);
+        }
+        let y = self.rows.last().map_or(0, |row| row[0] + row[1]);
+        if y + height > ATLAS_SIZE || width > ATLAS_SIZE {
+            return None;
+        }
+        self.rows.push([y, height, width]);
+        Some([0, y])
+    }
+
+    fn clear(&mut self) {
+        self.rows.clear();
+        self.entries.clear();
+        self.pixels.fill(0);
+        self.dirty.clear();
+        self.version += 1;
+        self.dirty_since = self.version;
+    }
+
+    // drops the textures of targets DrawText forgot
+    pub(crate) fn retain_targets(&mut self, keep: impl Fn(&Option<TargetId>) -> bool) {
+        self.slots.retain(|target, _| keep(target));
+    }
+
+    // records the copy into target's slot, out of frames_in_flight, of the cells it
+    // hasn't got yet, outside of a render pass, and returns the bytes copied. quad draws
+    // from that slot's texture until the next upload. the textures are made by the
+    // first one
+    pub(crate) fn upload(
+        &mut self,
+        command_buffer: &mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>,
+        memory_allocator: &Arc<StandardMemoryAllocator>,
+        target: Option<TargetId>,
+        slot: usize,
+        frames_in_flight: usize,
+    ) -> usize {
+        if self.pixels.is_empty() {
+            return 0;
+        }
+        let slots = self.slots.entry(target).or_default();
+        if slots.len() != frames_in_flight {
+            *slots = (0..frames_in_flight)
+                .map(|_| {
+                    let image: Arc<Image> = compat::texture_image(
+                        memory_allocator,
+                        Format::R8G8B8A8_UNORM,
+                        [ATLAS_SIZE, ATLAS_SIZE],
+                    );
+                    compat::name_image(&image, "font-render msdf atlas");
+                    MsdfSlot {
+                        texture: compat::view(image.clone()),
+                        image,
+                        version: None,
+                    }
+                })
+                .collect();
+        }
+        self.current = (target, slot);
+        let bytes = self.upload_slot(command_buffer, memory_allocator);
+
+        // forget the cells every slot has
+        let uploaded = self.slots.values().flatten().map(|slot| slot.version).min();
+        if let Some(uploaded) = uploaded.flatten() {
+            self.dirty.retain(|(version, _)| *version > uploaded);
+            self.dirty_since = self.dirty_since.max(uploaded);
+        }
+        bytes
+    }
+
+    fn upload_slot(
+        &mut self,
+        command_buffer: &mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>,
+        memory_allocator: &Arc<StandardMemoryAllocator>,
+    ) -> usize {
+        let (target, slot) = self.current;
+        let slot = &mut self.slots.get_mut(&target).unwrap()[slot];
+        if slot.version == Some(self.version) {
+            return 0;
+        }
+        let dirty: Vec<Rect<u32>> = match slot.version {
+            Some(uploaded) if uploaded >= self.dirty_since => self
+                .dirty
+                .iter()
+                .filter(|(version, _)| *version > uploaded)
+                .map(|(_, rect)| *rect)
+                .collect(),
+            // a new texture's cells are undefined, and a cleared atlas's have all changed
+            _ => vec![Rect {
+                min: point(0, 0),
+                max: point(ATLAS_SIZE, ATLAS_SIZE),
+            }],
+        };
+        slot.version = Some(self.version);
+
+        let bytes: usize = dirty
+            .iter()
+            .map(|rect| (rect.width() * rect.height() * 4) as usize)
+            .sum();
+        let staging = compat::staging_buffer(memory_allocator, bytes as u64);
+        let mut written = staging.write().unwrap();
+        let mut offset = 0;
+        for rect in &dirty {
+            let row_bytes = rect.width() as usize * 4;
+            for y in rect.min.y..rect.max.y {
+                let start = ((y * ATLAS_SIZE + rect.min.x) * 4) as usize;
+                written[offset..offset + row_bytes]
+                    .copy_from_slice(&self.pixels[start..start + row_bytes]);
+                offset += row_bytes;
+            }
+        }
+        drop(written);
+        let regions: Vec<_> = dirty
+            .iter()
+            .map(|rect| ([rect.min.x, rect.min.y], [rect.width(), rect.height()]))
+            .collect();
+        compat::copy_packed_regions_to_image(command_buffer, &staging, &slot.image, 4, &regions);
+        bytes
+    }
+
+    // the texture the glyph is drawn from, where on screen and the uvs of its cell. None
+    // for glyphs without ink or that haven't been uploaded yet
+    pub(crate) fn quad(
+        &self,
+        font: FontId,
+        glyph: &Glyph,
+    ) -> Option<(&Arc<ImageView>, Rect<f32>, [f32; 4])> {
+        let entry = (*self.entries.get(&(font, glyph.id))?)?;
+        let (target, slot) = self.current;
+        let texture = &self.slots.get(&target)?.get(slot)?.texture;
+        let scale = glyph.size / GENERATED_SIZE;
+        let screen = Rect {
+            min: point(
+                glyph.position.x + entry.plane.min.x * scale,
+                glyph.position.y + entry.plane.min.y * scale,
+            ),
+            max: point(
+                glyph.position.x + entry.plane.max.x * scale,
+                glyph.position.y + entry.plane.max.y * scale,
+            ),
+        };
+        Some((texture, screen, entry.uv_rect))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Edge {
+    a: [f32; 2],
+    b: [f32; 2],
+    color: u8,
+}
+
+impl Edge {
+    fn direction(&self) -> [f32; 2] {
+        let [dx, dy] = [self.b[0] - self.a[0], self.b[1] - self.a[1]];
+        let length = dx.hypot(dy);
+        [dx / length, dy / length]
+    }
+
+    // the distance to the edge, positive to its left, the pseudo-distance to the line
+    // it lies on, and where along the edge p is closest, 0 to 1 between its ends
+    fn distance(&self, p: [f32; 2]) -> (f32, f32, f32) {
+        let ab = [self.b[0] - self.a[0], self.b[1] - self.a[1]];
+        let ap = [p[0] - self.a[0], p[1] - self.a[1]];
+        let length_sq = ab[0] * ab[0] + ab[1] * ab[1];
+        let t = (ap[0] * ab[0] + ap[1] * ab[1]) / length_sq;
+        let closest = t.clamp(0.0, 1.0);
+        let cross = ab[0] * ap[1] - ab[1] * ap[0];
+        let side = if cross < 0.0 { -1.0 } else { 1.0 };
+        let distance = (ap[0] - ab[0] * closest).hypot(ap[1] - ab[1] * closest);
+        (distance * side, cross / length_sq.sqrt(), t)
+    }
+}
+
+// lines that don't continue where the last one ended start a new contour
+fn contours(outline: &GlyphOutline) -> Vec<Vec<Edge>> {
+    let mut contours: Vec<Vec<Edge>> = vec![];
+    for &[x0, y0, x1, y1] in &outline.lines {
+        if (x1 - x0).hypot(y1 - y0) < 1e-4 {
+            continue;
+        }
+        let edge = Edge {
+            a: [x0, y0],
+            b: [x1, y1],
+            color: WHITE,
+        };
+        match contours.last_mut().and_then(|contour| contour.last()) {
+            Some(last) if (last.b[0] - x0).hypot(last.b[1] - y0) < 1e-3 => {
+                contours.last_mut().unwrap().push(edge)
+            }
+            _ => contours.push(vec![edge]),
+        }
+    }
+    contours
+}
+
+// edges meeting at a corner get different colors, so the corner survives in the
+// channels they don't share. smooth contours stay white
+fn color_edges(contour: &mut [Edge]) {
+    let len = contour.len();
+    let corners: Vec<usize> = (0..len)
+        .filter(|&i| {
+            let a = contour[(i + len - 1) % len].direction();
+            let b = contour[i].direction();
+            let dot = a[0] * b[0] + a[1] * b[1];
+            let cross = a[0] * b[1] - a[1] * b[0];
+            dot <= 0.0 || cross.abs() > CORNER_SIN
+        })
+        .collect();
+    match corners.len() {
+        0 => {}
+        // a teardrop, split in three so the corner still has two colors meeting
+        1 => {
+            let colors = [COLORS[0], WHITE, COLORS[1]];
+            for i in 0..len {
+                let edge = &mut contour[(corners[0] + i) % len];
+                edge.color = colors[(i * 3 / len).min(2)];
+            }
+        }
+        count => {
+            for (n, &corner) in corners.iter().enumerate() {
+                // the last stretch meets the first, which has the first color
+                let color = if n == count - 1 && n % 3 == 0 {
+                    COLORS[1]
+                } else {
+                    COLORS[n % 3]
+                };
+                let end = corners[(n + 1) % count];
+                let mut i = corner;
+                loop {
+                    contour[i].color = color;
+                    i = (i + 1) % len;
+                    if i == end {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+}
+
+// nonzero, by the edges a ray to the right crosses
+fn inside(contours: &[Vec<Edge>], p: [f32; 2]) -> bool {
+    let mut winding = 0;
+    for edge in contours.iter().flatten() {
+        let [a, b] = [edge.a, edge.b];
+        if (a[1] <= p[1]) != (b[1] <= p[1]) {
+            let x = a[0] + (p[1] - a[1]) / (b[1] - a[1]) * (b[0] - a[0]);
+            if x > p[0] {
+                winding += if b[1] > a[1] { 1 } else { -1 };
+            }
+        }
+    }
+    winding != 0
+}
+
+// rgba rows of the padded cell, distances are positive inside and 0.5 on the outline
+fn generate_msdf(outline: &GlyphOutline, width: u32, height: u32) -> Vec<u8> {
+    let mut contours = contours(outline);
+    for contour in &mut contours {
+        color_edges(contour);
+    }
+    // edges have the inside to their left in contours that go around counterclockwise,
+    // which the outer ones do if the whole shape's area is positive
+    let area: f32 = contours
+        .iter()
+        .flatten()
+        .map(|edge| edge.a[0] * edge.b[1] - edge.b[0] * edge.a[1])
+        .sum();
+    let orientation = if area < 0.0 { -1.0 } else { 1.0 };
+
+    let encode =
+        |distance: f32| ((distance / DISTANCE_RANGE + 0.5).clamp(0.0, 1.0) * 255.0).round() as u8;
+    let mut pixels = Vec::with_capacity((width * height * 4) as usize);
+    for y in 0..height {
+        for x in 0..width {
+            let p = [
+                x as f32 + 0.5 - PADDING as f32,
+                y as f32 + 0.5 - PADDING as f32,
+            ];
+            // per channel the closest edge's distance, with ties going to the edge p
+            // is more square to
+            let mut closest = [(f32::MAX, 0.0, 0.0); 3];
+            let mut true_distance = f32::MAX;
+            for edge in contours.iter().flatten() {
+                let (distance, pseudo, t) = edge.distance(p);
+                let (distance, pseudo) = (distance * orientation, pseudo * orientation);
+                let square = pseudo.abs() / distance.abs().max(1e-6);
+                true_distance = true_distance.min(distance.abs());
+                for (channel, best) in closest.iter_mut().enumerate() {
+                    if edge.color & (1 << channel) == 0 {
+                        continue;
+                    }
+                    let closer = distance.abs() < best.0 - 1e-4
+                        || (distance.abs() < best.0 + 1e-4 && square > best.1);
+                    if closer {
+                        // past an end of the edge its line is continued, which keeps
+                        // corners sharp
+                        let value = if (0.0..=1.0).contains(&t) {
+                            distance
+                        } else {
+                            pseudo
+                        };
+                        *best = (distance.abs(), square, value);
+                    }
+                }
+            }
+            let inside = inside(&contours, p);
+            let true_distance = if inside {
+                true_distance
+            } else {
+                -true_distance
+            };
+            let mut channels = closest.map(|(distance, _, value)| {
+                if distance == f32::MAX {
+                    true_distance
+                } else {
+                    value
+                }
+            });
+            // where the channels disagree with the outline the median would show
+            // artifacts, the plain distance is safe there
+            let [r, g, b] = channels;
+            let median = r.min(g).max(r.max(g).min(b));
+            if (median > 0.0) != inside {
+                channels = [true_distance; 3];
+            }
+            pixels.extend(channels.map(encode));
+            pixels.push(255);
+        }
+    }
+    pixels
+}
@@ -0,0 +1,144 @@
+use super::drawtext::TextRendererCore;
+use super::gpu::compat::{self, HostBuffer, Image};
+use super::target::TextTarget;
+
+use vulkano::command_buffer::allocator::StandardCommandBufferAllocator;
+use vulkano::command_buffer::{
+    AutoCommandBufferBuilder, ClearColorImageInfo, CommandBufferUsage, PrimaryAutoCommandBuffer,
+};
+use vulkano::device::{Device, Queue};
+use vulkano::format::{ClearColorValue, Format};
+use vulkano::memory::allocator::StandardMemoryAllocator;
+use vulkano::sync::{self, GpuFuture};
+
+use std::sync::Arc;
+
+// renders queued text into a plain image instead of a swapchain, e.g. for
+// golden-image tests or rasterizing text on a server
+pub struct DrawTextOffscreen {
+    image: Arc<Image>,
+    target: TextTarget,
+}
+
+impl DrawTextOffscreen {
+    pub fn new(device: Arc<Device>, image: Arc<Image>) -> DrawTextOffscreen {
+        let target = TextTarget::image(device, image.clone());
+        DrawTextOffscreen { image, target }
+    }
+
+    // an image with every usage render() needs
+    pub fn create_image(
+        memory_allocator: &Arc<StandardMemoryAllocator>,
+        extent: [u32; 2],
+        format: Format,
+    ) -> Arc<Image> {
+        compat::render_image(memory_allocator, format, extent)
+    }
+
+    pub fn image(&self) -> &Arc<Image> {
+        &self.image
+    }
+
+    pub fn target(&self) -> &TextTarget {
+        &self.target
+    }
+
+    // clears the image, draws everything queued in draw_text and returns the pixels,
+    // tightly packed rows in the image format
+    pub fn render(
+        &self,
+        draw_text: &mut TextRendererCore,
+        memory_allocator: &Arc<StandardMemoryAllocator>,
+        command_buffer_allocator: &StandardCommandBufferAllocator,
+    ) -> Vec<u8> {
+        self.render_impl(draw_text, memory_allocator, command_buffer_allocator, true)
+    }
+
+    // like render, but leaves the queue untouched so the same text can still be
+    // drawn to the swapchain afterwards; layers routed away from captures are skipped
+    pub fn capture(
+        &self,
+        draw_text: &mut TextRendererCore,
+        memory_allocator: &Arc<StandardMemoryAllocator>,
+        command_buffer_allocator: &StandardCommandBufferAllocator,
+    ) -> Vec<u8> {
+        self.render_impl(draw_text, memory_allocator, command_buffer_allocator, false)
+    }
+
+    fn render_impl(
+        &self,
+        draw_text: &mut TextRendererCore,
+        memory_allocator: &Arc<StandardMemoryAllocator>,
+        command_buffer_allocator: &StandardCommandBufferAllocator,
+        consume: bool,
+    ) -> Vec<u8> {
+        let queue = draw_text.queue().clone();
+        let mut builder = AutoCommandBufferBuilder::primary(
+            command_buffer_allocator,
+            queue.queue_family_index(),
+            CommandBufferUsage::OneTimeSubmit,
+        )
+        .unwrap();
+
+        builder
+            .clear_color_image(ClearColorImageInfo {
+                clear_value: ClearColorValue::Float([0.0; 4]),
+                ..ClearColorImageInfo::image(self.image.clone())
+            })
+            .unwrap();
+        if consume {
+            draw_text.draw_into(&mut builder, memory_allocator, &self.target, 0);
+        } else {
+            draw_text.capture_text_into(&mut builder, memory_allocator, &self.target, 0);
+        }
+        let buffer = copy_image_to_host(&mut builder, memory_allocator, self.image.clone());
+
+        submit_and_wait(&queue, builder);
+        let pixels = buffer.read().unwrap().to_vec();
+        pixels
+    }
+}
+
+// reads the whole image back into host memory, blocking until the copy is done
+pub fn read_image(
+    queue: &Arc<Queue>,
+    memory_allocator: &Arc<StandardMemoryAllocator>,
+    command_buffer_allocator: &StandardCommandBufferAllocator,
+    image: Arc<Image>,
+) -> Vec<u8> {
+    let mut builder = AutoCommandBufferBuilder::primary(
+        command_buffer_allocator,
+        queue.queue_family_index(),
+        CommandBufferUsage::OneTimeSubmit,
+    )
+    .unwrap();
+    let buffer = copy_image_to_host(&mut builder, memory_allocator, image);
+
+    submit_and_wait(queue, builder);
+    let pixels = buffer.read().unwrap().to_vec();
+    pixels
+}
+
+pub(crate) fn copy_image_to_host(
+    builder: &mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>,
+    memory_allocator: &Arc<StandardMemoryAllocator>,
+    image: Arc<Image>,
+) -> HostBuffer<u8> {
+    let buffer = compat::readback_buffer(memory_allocator, compat::image_bytes(&image));
+    compat::copy_image_to_buffer(builder, &image, &buffer);
+    buffer
+}
+
+pub(crate) fn submit_and_wait(
+    queue: &Arc<Queue>,
+    builder: AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>,
+) {
+    let command_buffer = builder.build().unwrap();
+    sync::now(queue.device().clone())
+        .then_execute(queue.clone(), command_buffer)
+        .unwrap()
+        .then_signal_fence_and_flush()
+        .unwrap()
+        .wait(None)
+        .unwrap();
+}
@@ -0,0 +1,334 @@
+use super::drawtext::{intersects, union, DrawText, TextRendererCore, TextVertices};
+use super::frames::UploadedVertices;
+use super::gpu::compat::{self, Filter, Image, Sampler, SamplerAddressMode, SamplerCreateInfo};
+use super::quads::Vertex;
+use super::target::{TargetKind, TextTarget};
+
+use rusttype::{point, Rect};
+
+use vulkano::command_buffer::{
+    AutoCommandBufferBuilder, ClearColorImageInfo, PrimaryAutoCommandBuffer,
+};
+use vulkano::descriptor_set::{PersistentDescriptorSet, WriteDescriptorSet};
+use vulkano::device::Device;
+use vulkano::format::{ClearColorValue, Format};
+use vulkano::memory::allocator::StandardMemoryAllocator;
+use vulkano::pipeline::graphics::viewport::Scissor;
+use vulkano::pipeline::{GraphicsPipeline, Pipeline};
+use vulkano::render_pass::{RenderPass, Subpass};
+
+use std::collections::HashMap;
+use std::mem;
+use std::sync::Arc;
+
+mod composite_vs {
+    vulkano_shaders::shader! {
+        ty: "vertex",
+        path: "src/shaders/composite_vertex.glsl",
+    }
+}
+
+mod composite_fs {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        path: "src/shaders/composite_fragment.glsl",
+    }
+}
+
+const OVERLAY_FORMAT: Format = Format::R8G8B8A8_UNORM;
+
+// keeps the text layer in a persistent overlay image and only redraws the parts
+// that changed since the last frame, the overlay is then blended over the swapchain
+// image. worth it when most of the text stays put, e.g. a hud updating one counter
+pub struct PartialRedraw {
+    overlay: Arc<Image>,
+    overlay_target: TextTarget,
+    composite_pipeline: Arc<GraphicsPipeline>,
+    composite_set: Arc<PersistentDescriptorSet>,
+    // hash and bounds of every text currently in the overlay
+    drawn: Vec<(u64, Rect<f32>)>,
+    full_redraw: bool,
+    // the renderer's invalidated when the overlay was drawn
+    invalidated: u64,
+}
+
+impl PartialRedraw {
+    // has to be recreated together with DrawText when the swapchain is
+    pub fn new(
+        draw_text: &DrawText,
+        memory_allocator: &Arc<StandardMemoryAllocator>,
+    ) -> PartialRedraw {
+        let device = draw_text.device().clone();
+        let target = draw_text
+            .target()
+            .expect("partial redraw needs a DrawText with a swapchain");
+
+        let overlay = compat::render_image(memory_allocator, OVERLAY_FORMAT, target.extent(0));
+        compat::name_image(&overlay, "font-render overlay");
+        let overlay_view = compat::view(overlay.clone());
+
+        let overlay_target = TextTarget::with_blend(
+            device.clone(),
+            TargetKind::Present,
+            OVERLAY_FORMAT,
+            vec![overlay_view.clone()],
+            compat::premultiplied_blend(),
+        );
+
+        let composite_pipeline = composite_pipeline(device.clone(), target.render_pass());
+        let sampler = Sampler::new(
+            device.clone(),
+            SamplerCreateInfo {
+                mag_filter: Filter::Nearest,
+                min_filter: Filter::Nearest,
+                address_mode: [SamplerAddressMode::ClampToEdge; 3],
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let descriptor_set_allocator = compat::descriptor_set_allocator(device);
+        let composite_set = compat::descriptor_set(
+            &descriptor_set_allocator,
+            composite_pipeline.layout().set_layouts()[0].clone(),
+            [WriteDescriptorSet::image_view_sampler(
+                0,
+                overlay_view,
+                sampler,
+            )],
+        );
+
+        PartialRedraw {
+            overlay,
+            overlay_target,
+            composite_pipeline,
+            composite_set,
+            drawn: vec![],
+            full_redraw: true,
+            invalidated: draw_text.invalidated,
+        }
+    }
+
+    // throws the overlay away on the next draw, e.g. after fonts were added
+    pub fn invalidate(&mut self) {
+        self.full_redraw = true;
+    }
+
+    // regions of the overlay the next draw would touch, in pixels
+    pub fn damage(&self, draw_text: &DrawText) -> Vec<Rect<f32>> {
+        if self.full_redraw || draw_text.invalidated != self.invalidated {
+            let [width, height] = compat::image_extent(&self.overlay);
+            return vec![Rect {
+                min: point(0.0, 0.0),
+                max: point(width as f32, height as f32),
+            }];
+        }
+
+        merge_rects(changed_bounds(&self.drawn, &visible_bounds(draw_text)))
+    }
+
+    // use instead of DrawText::draw_text, outside of any render pass
+    pub fn draw<'a>(
+        &mut self,
+        draw_text: &mut DrawText,
+        command_buffer: &'a mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>,
+        memory_allocator: &Arc<StandardMemoryAllocator>,
+        image_num: usize,
+    ) -> &'a mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer> {
+        let target = draw_text
+            .target()
+            .expect("partial redraw needs a DrawText with a swapchain")
+            .clone();
+        let extent = compat::image_extent(&self.overlay);
+        let screen = [extent[0] as f32, extent[1] as f32];
+        let output = draw_text.color_output(target.linear_output());
+        draw_text.queue_retained();
+        draw_text.resolve_anchors(extent);
+        let damage = self.damage(draw_text);
+        let full_redraw = self.full_redraw || draw_text.invalidated != self.invalidated;
+
+        compat::begin_label(command_buffer, "font-render partial redraw");
+        #[cfg(feature = "gpu_timing")]
+        draw_text.begin_timing(command_buffer);
+        let set = draw_text.upload_atlas(
+            command_buffer,
+            memory_allocator,
+            self.overlay_target.pipeline(),
+            Some(target.id()),
+        );
+        if full_redraw {
+            command_buffer
+                .clear_color_image(ClearColorImageInfo {
+                    clear_value: ClearColorValue::Float([0.0; 4]),
+                    ..ClearColorImageInfo::image(self.overlay.clone())
+                })
+                .unwrap();
+        }
+
+        // texts overlapping a region get redrawn whole, the scissor keeps them from
+        // touching anything outside of it
+        let region_vertices = |damage: Vec<Rect<f32>>| -> Vec<(Scissor, TextVertices)> {
+            damage
+                .into_iter()
+                .filter_map(|rect| to_scissor(rect, extent).map(|scissor| (rect, scissor)))
+                .map(|(rect, scissor)| {
+                    let texts = draw_text.visible_texts(TargetKind::Present).filter(|text| {
+                        text.bounds()
+                            .map_or(false, |b| intersects(&draw_text.stretched(b), &rect))
+                    });
+                    // the overlay's values end up in the target as they are
+                    (scissor, draw_text.text_vertices(texts, screen, output))
+                })
+                .collect()
+        };
+        let bounds = damage.iter().copied().reduce(union);
+        let mut regions = region_vertices(damage);
+        // texts in several regions would be uploaded once for each, over the memory
+        // budget they are uploaded once for the box around all regions
+        let bytes: usize = regions
+            .iter()
+            .map(|(_, vertices)| vertices.vertices.len() * mem::size_of::<Vertex>())
+            .sum();
+        if regions.len() > 1 && !draw_text.fits_budget(bytes as u64) {
+            regions = region_vertices(bounds.into_iter().collect());
+        }
+        // vertices are uploaded before the render pass
+        let regions: Vec<(Scissor, Option<UploadedVertices>)> = regions
+            .into_iter()
+            .map(|(scissor, vertices)| {
+                let vertices = draw_text.upload_text_vertices(
+                    Some(&mut *command_buffer),
+                    memory_allocator,
+                    vertices,
+                );
+                (scissor, vertices)
+            })
+            .collect();
+        if !regions.is_empty() {
+            compat::begin_render_pass(
+                command_buffer,
+                self.overlay_target.framebuffer(0).clone(),
+                vec![None],
+            );
+            for (scissor, vertices) in regions {
+                compat::clear_region(command_buffer, &scissor);
+                compat::set_viewport_scissor(command_buffer, extent, Some(scissor));
+                draw_text.draw_vertices(
+                    command_buffer,
+                    self.overlay_target.pipeline(),
+                    &set,
+                    vertices,
+                );
+            }
+            compat::end_render_pass(command_buffer);
+        }
+
+        compat::begin_render_pass(
+            command_buffer,
+            target.framebuffer(image_num).clone(),
+            vec![None],
+        );
+        compat::set_viewport_scissor(command_buffer, target.extent(image_num), None);
+        compat::bind_graphics(
+            command_buffer,
+            &self.composite_pipeline,
+            self.composite_set.clone(),
+        );
+        command_buffer.draw(3, 1, 0, 0).unwrap();
+        compat::end_render_pass(command_buffer);
+        #[cfg(feature = "gpu_timing")]
+        draw_text.end_timing(command_buffer);
+        compat::end_label(command_buffer);
+
+        self.drawn = visible_bounds(draw_text);
+        self.full_redraw = false;
+        self.invalidated = draw_text.invalidated;
+        draw_text.finish_frame();
+        command_buffer
+    }
+}
+
+fn composite_pipeline(device: Arc<Device>, render_pass: &Arc<RenderPass>) -> Arc<GraphicsPipeline> {
+    let vs = composite_vs::load(device.clone()).unwrap();
+    let fs = composite_fs::load(device).unwrap();
+
+    let pipeline = compat::graphics_pipeline(
+        vs,
+        fs,
+        false,
+        Subpass::from(render_pass.clone(), 0).unwrap(),
+        // the overlay is premultiplied
+        compat::premultiplied_over(),
+    );
+    compat::set_name(&*pipeline, "font-render composite pipeline");
+    pipeline
+}
+
+// identical hashes mean identical glyphs in the same place, everything else that was
+// drawn or is about to be drawn is damaged
+pub(crate) fn changed_bounds(
+    drawn: &[(u64, Rect<f32>)],
+    queued: &[(u64, Rect<f32>)],
+) -> Vec<Rect<f32>> {
+    let mut unmatched: HashMap<u64, usize> = HashMap::new();
+    for (hash, _) in drawn {
+        *unmatched.entry(*hash).or_default() += 1;
+    }
+    let mut damage = vec![];
+    for (hash, bounds) in queued {
+        match unmatched.get_mut(hash) {
+            Some(count) if *count > 0 => *count -= 1,
+            _ => damage.push(*bounds),
+        }
+    }
+    for (hash, bounds) in drawn.iter().rev() {
+        if let Some(count) = unmatched.get_mut(hash) {
+            if *count > 0 {
+                *count -= 1;
+                damage.push(*bounds);
+            }
+        }
+    }
+    damage
+}
+
+// texts without any visible glyph never touch the overlay
+pub(crate) fn visible_bounds(draw_text: &TextRendererCore) -> Vec<(u64, Rect<f32>)> {
+    draw_text
+        .visible_texts(TargetKind::Present)
+        .filter_map(|text| {
+            text.bounds()
+                .map(|bounds| (text.hash, draw_text.stretched(bounds)))
+        })
+        .collect()
+}
+
+// overlapping regions are merged so no pixel gets cleared and drawn twice
+fn merge_rects(mut rects: Vec<Rect<f32>>) -> Vec<Rect<f32>> {
+    let mut merged: Vec<Rect<f32>> = vec![];
+    while let Some(mut rect) = rects.pop() {
+        while let Some(i) = merged.iter().position(|other| intersects(&rect, other)) {
+            let other = merged.swap_remove(i);
+            rect = Rect {
+                min: point(rect.min.x.min(other.min.x), rect.min.y.min(other.min.y)),
+                max: point(rect.max.x.max(other.max.x), rect.max.y.max(other.max.y)),
+            };
+        }
+        merged.push(rect);
+    }
+    merged
+}
+
+// rounded outwards and clamped to the image, None when nothing is left
+fn to_scissor(rect: Rect<f32>, extent: [u32; 2]) -> Option<Scissor> {
+    let min_x = rect.min.x.floor().max(0.0) as u32;
+    let min_y = rect.min.y.floor().max(0.0) as u32;
+    let max_x = (rect.max.x.ceil().max(0.0) as u32).min(extent[0]);
+    let max_y = (rect.max.y.ceil().max(0.0) as u32).min(extent[1]);
+    if min_x >= max_x || min_y >= max_y {
+        return None;
+    }
+    Some(compat::scissor(
+        [min_x, min_y],
+        [max_x - min_x, max_y - min_y],
+    ))
+}
@@ -0,0 +1,312 @@
+use super::cache::{self, Glyph, GlyphCache, CACHE_HEIGHT, CACHE_WIDTH};
+use super::gpu_backend::{AtlasPixels, DirtyRect, TextGpuBackend};
+use super::layouter::TextLayouter;
+use super::quality::Quality;
+use super::script::FontId;
+use super::style::TextStyle;
+
+use bytemuck::{Pod, Zeroable};
+
+use rusttype::{point, Point};
+
+// its vertex input layout is declared per vulkano version in gpu::compat
+#[repr(C)]
+#[derive(Default, Debug, Clone, Copy, Zeroable, Pod)]
+pub(crate) struct Vertex {
+    pub(crate) position: [f32; 2],
+    // uv and atlas page
+    pub(crate) tex_position: [f32; 3],
+    pub(crate) color: [f32; 4],
+    // framebuffer x where the text starts fading out and where it is gone, the
+    // third value picks y instead for vertical text
+    pub(crate) fade: [f32; 3],
+    // where the text's fill texture is sampled, see queue_text_textured
+    pub(crate) fill_uv: [f32; 2],
+}
+
+// a quad of DrawText::generate_quads and the TextGpuBackends, for drawing text with
+// your own pipeline
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GlyphQuad {
+    // in pixels of the image extent, bottom left, top left, top right, bottom right
+    pub corners: [[f32; 2]; 4],
+    // same order, in the atlas layer page
+    pub uvs: [[f32; 2]; 4],
+    pub page: u32,
+    pub colors: [[f32; 4]; 4],
+    // framebuffer x where the text starts fading out and where it is gone, the third
+    // value picks y instead for vertical text
+    pub fade: [f32; 3],
+    // backgrounds and decoration lines cover the whole quad, their uvs mean nothing
+    pub solid: bool,
+}
+
+impl GlyphQuad {
+    // the two triangles of the quad, like text_vertices writes them
+    #[cfg(any(feature = "gpu", feature = "wgpu"))]
+    pub(crate) fn vertices(&self, screen: [f32; 2]) -> [Vertex; 6] {
+        [0, 1, 2, 2, 3, 0].map(|corner| {
+            let [x, y] = self.corners[corner];
+            let [u, v] = self.uvs[corner];
+            Vertex {
+                position: [(x / screen[0] - 0.5) * 2.0, (y / screen[1] - 0.5) * 2.0],
+                tex_position: [u, v, self.page as f32],
+                color: self.colors[corner],
+                fade: self.fade,
+                fill_uv: [0.0, 0.0],
+            }
+        })
+    }
+}
+
+// fade for text without a max_width
+pub(crate) const NO_FADE: [f32; 3] = [f32::MAX, f32::MAX, 0.0];
+// the atlas pages TextQuads may grow to, DrawText's default
+const MAX_PAGES: u32 = 4;
+
+// a glyph of the queue in physical pixels, with its style's color and faux italic and
+// bold, turned a quarter when it lies on its side in vertical text
+struct QueuedGlyph {
+    font: FontId,
+    glyph: Glyph,
+    rotated: bool,
+    color: [f32; 4],
+    skew: f32,
+    smear: Vec<[f32; 2]>,
+}
+
+// the cpu half of drawing text through a TextGpuBackend, with nothing but a
+// TextLayouter and the glyph atlas: no vulkano device, so WgpuBackend works without
+// the gpu feature. the glyphs are drawn in their style's color, gradients, shadows,
+// outlines, backgrounds and decorations need DrawText::upload_to
+pub struct TextQuads {
+    layouter: TextLayouter,
+    cache: GlyphCache,
+    // the quality the cache's tolerances are from
+    quality: Quality,
+    // the atlas pages one after the other
+    pixels: Vec<u8>,
+    // bumped whenever pixels changed
+    version: u64,
+    // the rects rasterized into after dirty_since, for backends copying only those
+    dirty: Vec<DirtyRect>,
+    dirty_since: u64,
+    queue: Vec<QueuedGlyph>,
+}
+
+// how many versions back the dirty rects go, a backend's copy further behind gets the
+// whole atlas. as many as DrawText's default frames in flight
+const DIRTY_VERSIONS: u64 = 3;
+
+impl Default for TextQuads {
+    fn default() -> TextQuads {
+        TextQuads::new()
+    }
+}
+
+impl TextQuads {
+    // with the bundled font, like TextLayouter::new
+    pub fn new() -> TextQuads {
+        TextQuads::with_layouter(TextLayouter::new())
+    }
+
+    pub fn with_layouter(layouter: TextLayouter) -> TextQuads {
+        let quality = layouter.quality();
+        let settings = quality.settings();
+        TextQuads {
+            cache: GlyphCache::new(
+                CACHE_WIDTH as u32,
+                CACHE_HEIGHT as u32,
+                MAX_PAGES,
+                settings.scale_tolerance,
+                settings.position_tolerance,
+                true,
+            ),
+            layouter,
+            quality,
+            pixels: vec![0; CACHE_WIDTH * CACHE_HEIGHT],
+            version: 0,
+            dirty: vec![],
+            dirty_since: 0,
+            queue: vec![],
+        }
+    }
+
+    pub fn layouter(&self) -> &TextLayouter {
+        &self.layouter
+    }
+
+    // fonts and scale factor changes apply to text queued afterwards
+    pub fn layouter_mut(&mut self) -> &mut TextLayouter {
+        &mut self.layouter
+    }
+
+    // x and y in units of the layouter, y on the first baseline
+    pub fn queue_text_styled(&mut self, x: f32, y: f32, style: &TextStyle, text: &str) {
+        self.queue_lines(x, y, style, text, &[]);
+    }
+
+    // breaks text into lines no wider than max_width, like DrawText::queue_text_wrapped
+    pub fn queue_text_wrapped(
+        &mut self,
+        x: f32,
+        y: f32,
+        max_width: f32,
+        style: &TextStyle,
+        text: &str,
+    ) {
+        let breaks = self.layouter.wrap(style, text, max_width, &[]);
+        self.queue_lines(x, y, style, text, &breaks);
+    }
+
+    fn queue_lines(&mut self, x: f32, y: f32, style: &TextStyle, text: &str, breaks: &[usize]) {
+        let scale = self.layouter.scale_factor as f32;
+        let style = &style.scaled(scale);
+        let start = point(x * scale, y * scale);
+        let smear = style.smear_offsets();
+        for (_, glyphs) in self.layouter.place_chars(text, style, start, breaks, &[]) {
+            for (font, glyph, rotated) in glyphs {
+                self.cache.queue_glyph(font, glyph, &style.variations);
+                self.queue.push(QueuedGlyph {
+                    font,
+                    glyph,
+                    rotated,
+                    color: style.color,
+                    skew: style.skew(),
+                    smear: smear.clone(),
+                });
+            }
+        }
+    }
+
+    // rasterizes the glyphs queued since the last call into the atlas, hands it and the
+    // queue's quads to backend and empties the queue. call the backend's draw inside
+    // your render pass afterwards
+    pub fn upload_to<B: TextGpuBackend>(
+        &mut self,
+        backend: &mut B,
+        upload: &mut B::Upload<'_>,
+        image_extent: [u32; 2],
+    ) {
+        self.update_cache();
+        backend.upload_atlas(
+            upload,
+            &AtlasPixels {
+                pixels: &self.pixels,
+                extent: [CACHE_WIDTH as u32, CACHE_HEIGHT as u32],
+                pages: self.cache.pages(),
+                version: self.version,
+                dirty: &self.dirty,
+                dirty_since: self.dirty_since,
+            },
+        );
+        let quads = self.quads();
+        backend.upload_quads(upload, &quads, image_extent);
+        self.queue.clear();
+    }
+
+    // the same format as DrawText::save_atlas, and loaded by either with the same
+    // fonts, quality and atlas settings
+    pub fn save_atlas(&mut self) -> Vec<u8> {
+        self.update_cache();
+        self.cache.save(&self.layouter.fonts, &self.pixels)
+    }
+
+    // false, with nothing changed, for data DrawText::load_atlas would turn down too
+    pub fn load_atlas(&mut self, data: &[u8]) -> bool {
+        let pixels = match self.cache.load(&self.layouter.fonts, data) {
+            Some(pixels) => pixels,
+            None => return false,
+        };
+        self.pixels = pixels;
+        self.version += 1;
+        self.dirty.clear();
+        self.dirty_since = self.version;
+        true
+    }
+
+    fn update_cache(&mut self) {
+        // new tolerances drop every glyph, the queued ones are put back
+        let quality = self.layouter.quality();
+        if quality != self.quality {
+            let settings = quality.settings();
+            self.cache
+                .set_tolerances(settings.scale_tolerance, settings.position_tolerance);
+            for queued in &self.queue {
+                self.cache.queue_glyph(queued.font, queued.glyph, &[]);
+            }
+            self.quality = quality;
+        }
+
+        let pixels = &mut self.pixels;
+        let dirty = &mut self.dirty;
+        let version = self.version + 1;
+        let mut changed = false;
+        self.cache
+            .cache_queued(&self.layouter.fonts, |page, rect, src_data| {
+                cache::blit(pixels, page, rect, src_data);
+                dirty.push(DirtyRect {
+                    version,
+                    page,
+                    rect,
+                });
+                changed = true;
+            });
+        self.cache.take_dropped();
+        if changed {
+            self.version += 1;
+        }
+        let since = self.version.saturating_sub(DIRTY_VERSIONS);
+        if since > self.dirty_since {
+            self.dirty.retain(|rect| rect.version > since);
+            self.dirty_since = since;
+        }
+    }
+
+    fn quads(&self) -> Vec<GlyphQuad> {
+        let mut quads = vec![];
+        for queued in &self.queue {
+            let (uv, page, rect) = match self.cache.rect_for(queued.font, &queued.glyph) {
+                Some(rects) => rects,
+                None => continue,
+            };
+            let origin = queued.glyph.position;
+            let corners = [
+                point(rect.min.x, rect.max.y),
+                point(rect.min.x, rect.min.y),
+                point(rect.max.x, rect.min.y),
+                point(rect.max.x, rect.max.y),
+            ]
+            .map(|p| {
+                let p = point(p.x as f32, p.y as f32);
+                let p = point(p.x + queued.skew * (origin.y - p.y), p.y);
+                if queued.rotated {
+                    rotate(p, origin)
+                } else {
+                    p
+                }
+            });
+            for offset in &queued.smear {
+                quads.push(GlyphQuad {
+                    corners: corners.map(|p| [p.x + offset[0], p.y + offset[1]]),
+                    uvs: [
+                        [uv.min.x, uv.max.y],
+                        [uv.min.x, uv.min.y],
+                        [uv.max.x, uv.min.y],
+                        [uv.max.x, uv.max.y],
+                    ],
+                    page,
+                    colors: [queued.color; 4],
+                    fade: NO_FADE,
+                    solid: false,
+                });
+            }
+        }
+        quads
+    }
+}
+
+// a quarter turn clockwise around pivot, how sideways glyphs of vertical text turn
+pub(crate) fn rotate(p: Point<f32>, pivot: Point<f32>) -> Point<f32> {
+    point(pivot.x - (p.y - pivot.y), pivot.y + (p.x - pivot.x))
+}
@@ -0,0 +1,55 @@
+#[cfg(feature = "gpu")]
+use super::gpu::compat::Filter;
+
+// named bundles of the rendering knobs, so callers don't have to tune each one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Quality {
+    // pixel aligned glyphs sampled 1:1, sharpest at small sizes
+    Crisp,
+    // subpixel positioned, filtered glyphs, best for animated or scaled text
+    #[default]
+    Smooth,
+    // reuses rasterized glyphs as much as possible, for lots of changing text
+    Performance,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QualitySettings {
+    // how far apart two sizes of a glyph may be and still share one rasterization,
+    // see GlyphCache
+    pub scale_tolerance: f32,
+    // same for subpixel position, in pixels
+    pub position_tolerance: f32,
+    // round glyph origins to whole pixels
+    pub snap_to_pixel: bool,
+    #[cfg(feature = "gpu")]
+    pub filter: Filter,
+}
+
+impl Quality {
+    pub fn settings(&self) -> QualitySettings {
+        match self {
+            Quality::Crisp => QualitySettings {
+                scale_tolerance: 0.1,
+                position_tolerance: 1.0,
+                snap_to_pixel: true,
+                #[cfg(feature = "gpu")]
+                filter: Filter::Nearest,
+            },
+            Quality::Smooth => QualitySettings {
+                scale_tolerance: 0.1,
+                position_tolerance: 0.1,
+                snap_to_pixel: false,
+                #[cfg(feature = "gpu")]
+                filter: Filter::Linear,
+            },
+            Quality::Performance => QualitySettings {
+                scale_tolerance: 0.5,
+                position_tolerance: 1.0,
+                snap_to_pixel: true,
+                #[cfg(feature = "gpu")]
+                filter: Filter::Linear,
+            },
+        }
+    }
+}
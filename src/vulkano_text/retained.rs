@@ -0,0 +1,45 @@
+use super::anchor::{Anchor, Offset};
+use super::drawtext::TextData;
+use super::style::TextStyle;
+
+// where a retained text goes, in logical pixels like queue_text_styled and
+// queue_text_anchored
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Placement {
+    At(f32, f32),
+    Anchored(Anchor, Offset, Offset),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct RetainedTextId(pub(crate) u64);
+
+// a label added once and drawn every frame until it's removed
+pub(crate) struct RetainedText {
+    pub(crate) placement: Placement,
+    pub(crate) style: TextStyle,
+    // palette style of add_retained_themed, style is the one it resolved to last
+    pub(crate) theme: Option<String>,
+    pub(crate) text: String,
+    // the last layout, reused as long as scale factor and target extent are the same
+    pub(crate) laid_out: Option<(LayoutKey, TextData)>,
+}
+
+// what a layout depends on besides the text itself
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct LayoutKey {
+    pub(crate) scale_factor: f64,
+    // glyphs outside the target are dropped when laying out
+    pub(crate) extent: Option<[u32; 2]>,
+}
+
+impl RetainedText {
+    pub(crate) fn new(placement: Placement, style: &TextStyle, text: &str) -> RetainedText {
+        RetainedText {
+            placement,
+            style: style.clone(),
+            theme: None,
+            text: text.to_owned(),
+            laid_out: None,
+        }
+    }
+}
@@ -0,0 +1,70 @@
+#[cfg(all(feature = "normalization", feature = "gpu"))]
+use unicode_normalization::{is_nfc_quick, IsNormalized, UnicodeNormalization};
+
+#[cfg(all(feature = "normalization", feature = "gpu"))]
+use std::borrow::Cow;
+
+// what happens to control chars like '\r', '\0' or escape in queued text, newlines
+// always start a new line. byte offsets stay those of the queued string either way
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ControlChars {
+    // drawn with whatever the font has for them, usually a box
+    #[default]
+    Keep,
+    // take no room
+    Skip,
+    // drawn as their symbol from the Control Pictures block, e.g. '\r' as U+240D
+    Replace,
+}
+
+impl ControlChars {
+    // what to draw for c, None when it takes no room. invisible format chars like
+    // zero-width joiners only mean something to the shaper, without shaping they are
+    // skipped in both Skip and Replace
+    pub(crate) fn apply(self, c: char, shaping: bool) -> Option<char> {
+        if self == ControlChars::Keep || c == '\n' {
+            return Some(c);
+        }
+        if c.is_control() {
+            return match self {
+                ControlChars::Skip => None,
+                _ => Some(picture(c)),
+            };
+        }
+        if !shaping && is_format(c) {
+            return None;
+        }
+        Some(c)
+    }
+}
+
+// C0 controls and delete have a symbol of their own, the C1 controls don't
+fn picture(c: char) -> char {
+    match c as u32 {
+        code @ 0..=0x1f => char::from_u32(0x2400 + code).unwrap(),
+        0x7f => '\u{2421}',
+        _ => char::REPLACEMENT_CHARACTER,
+    }
+}
+
+fn is_format(c: char) -> bool {
+    matches!(
+        c,
+        '\u{ad}'
+            | '\u{200b}'..='\u{200f}'
+            | '\u{202a}'..='\u{202e}'
+            | '\u{2060}'..='\u{2064}'
+            | '\u{feff}'
+    )
+}
+
+// composed, so a letter and a combining accent queued apart look like the precomposed
+// letter. borrowed when the text already is
+#[cfg(all(feature = "normalization", feature = "gpu"))]
+pub(crate) fn nfc(text: &str) -> Cow<'_, str> {
+    if is_nfc_quick(text.chars()) == IsNormalized::Yes {
+        Cow::Borrowed(text)
+    } else {
+        Cow::Owned(text.nfc().collect())
+    }
+}
@@ -0,0 +1,98 @@
+use super::gpu::compat::{self, HostBuffer, Image};
+use super::offscreen::copy_image_to_host;
+
+use vulkano::command_buffer::{AutoCommandBufferBuilder, PrimaryAutoCommandBuffer};
+use vulkano::format::Format;
+use vulkano::memory::allocator::StandardMemoryAllocator;
+
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+// a swapchain image copied into host memory, written to a png once the gpu is done
+// with the frame, see DrawText::capture_next_frame
+pub(crate) struct Screenshot {
+    path: PathBuf,
+    buffer: HostBuffer<u8>,
+    extent: [u32; 2],
+    format: Format,
+}
+
+impl Screenshot {
+    pub(crate) fn record(
+        command_buffer: &mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>,
+        memory_allocator: &Arc<StandardMemoryAllocator>,
+        image: Arc<Image>,
+        format: Format,
+        path: PathBuf,
+    ) -> Option<Screenshot> {
+        // hdr swapchains and the like are skipped rather than written wrong
+        if rgba_converter(format).is_none() {
+            log::warn!(
+                "not capturing {}, screenshots of {:?} images aren't supported",
+                path.display(),
+                format
+            );
+            return None;
+        }
+        let extent = compat::image_extent(&image);
+        Some(Screenshot {
+            path,
+            buffer: copy_image_to_host(command_buffer, memory_allocator, image),
+            extent,
+            format,
+        })
+    }
+
+    // false while the gpu hasn't finished the copy yet. a file that can't be written
+    // is logged and given up on
+    pub(crate) fn try_write(&self) -> bool {
+        let pixels = match self.buffer.read() {
+            Ok(pixels) => pixels,
+            Err(_) => return false,
+        };
+        if let Err(err) = self.write(&pixels) {
+            log::warn!("writing screenshot {} failed: {}", self.path.display(), err);
+        }
+        true
+    }
+
+    fn write(&self, pixels: &[u8]) -> Result<(), png::EncodingError> {
+        // record never makes one of a format without a converter
+        let convert = rgba_converter(self.format).unwrap();
+        let rgba: Vec<u8> = pixels.chunks_exact(4).flat_map(convert).collect();
+
+        let file = BufWriter::new(File::create(&self.path)?);
+        let mut encoder = png::Encoder::new(file, self.extent[0], self.extent[1]);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&rgba)?;
+        writer.finish()
+    }
+}
+
+// a texel of the format to 8 bit rgba. the bytes are already encoded the way they are
+// shown, srgb and unorm alike, so only the channels move. alpha is opaque, what a
+// swapchain holds there isn't shown
+fn rgba_converter(format: Format) -> Option<fn(&[u8]) -> [u8; 4]> {
+    let convert: fn(&[u8]) -> [u8; 4] = match format {
+        Format::R8G8B8A8_UNORM | Format::R8G8B8A8_SRGB => |t| [t[0], t[1], t[2], 255],
+        Format::B8G8R8A8_UNORM | Format::B8G8R8A8_SRGB => |t| [t[2], t[1], t[0], 255],
+        Format::A2B10G10R10_UNORM_PACK32 => {
+            |t| [ten_bits(t, 0), ten_bits(t, 10), ten_bits(t, 20), 255]
+        }
+        Format::A2R10G10B10_UNORM_PACK32 => {
+            |t| [ten_bits(t, 20), ten_bits(t, 10), ten_bits(t, 0), 255]
+        }
+        _ => return None,
+    };
+    Some(convert)
+}
+
+// the top 8 bits of the 10 bit channel at shift of a packed texel
+fn ten_bits(texel: &[u8], shift: u32) -> u8 {
+    let packed = u32::from_le_bytes([texel[0], texel[1], texel[2], texel[3]]);
+    ((packed >> shift & 0x3ff) >> 2) as u8
+}
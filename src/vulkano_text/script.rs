@@ -0,0 +1,131 @@
+use std::ops::RangeInclusive;
+
+pub type FontId = usize;
+
+// the font embedded in DrawText, used for every char without a binding
+pub const DEFAULT_FONT: FontId = 0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Script {
+    Latin,
+    Greek,
+    Cyrillic,
+    Armenian,
+    Hebrew,
+    Arabic,
+    Devanagari,
+    Thai,
+    Hangul,
+    Hiragana,
+    Katakana,
+    Han,
+}
+
+const LATIN: &[RangeInclusive<char>] = &[
+    'A'..='Z',
+    'a'..='z',
+    '\u{00C0}'..='\u{024F}',
+    '\u{1E00}'..='\u{1EFF}',
+];
+const GREEK: &[RangeInclusive<char>] = &['\u{0370}'..='\u{03FF}', '\u{1F00}'..='\u{1FFF}'];
+const CYRILLIC: &[RangeInclusive<char>] = &['\u{0400}'..='\u{052F}'];
+const ARMENIAN: &[RangeInclusive<char>] = &['\u{0530}'..='\u{058F}'];
+const HEBREW: &[RangeInclusive<char>] = &['\u{0590}'..='\u{05FF}'];
+const ARABIC: &[RangeInclusive<char>] = &[
+    '\u{0600}'..='\u{06FF}',
+    '\u{0750}'..='\u{077F}',
+    '\u{FB50}'..='\u{FDFF}',
+    '\u{FE70}'..='\u{FEFF}',
+];
+const DEVANAGARI: &[RangeInclusive<char>] = &['\u{0900}'..='\u{097F}'];
+const THAI: &[RangeInclusive<char>] = &['\u{0E00}'..='\u{0E7F}'];
+const HANGUL: &[RangeInclusive<char>] = &[
+    '\u{1100}'..='\u{11FF}',
+    '\u{3130}'..='\u{318F}',
+    '\u{AC00}'..='\u{D7AF}',
+];
+const HIRAGANA: &[RangeInclusive<char>] = &['\u{3040}'..='\u{309F}'];
+const KATAKANA: &[RangeInclusive<char>] = &[
+    '\u{30A0}'..='\u{30FF}',
+    '\u{31F0}'..='\u{31FF}',
+    '\u{FF66}'..='\u{FF9F}',
+];
+// CJK punctuation and fullwidth forms go with Han so they match the ideographs
+const HAN: &[RangeInclusive<char>] = &[
+    '\u{2E80}'..='\u{2FDF}',
+    '\u{3000}'..='\u{303F}',
+    '\u{3400}'..='\u{4DBF}',
+    '\u{4E00}'..='\u{9FFF}',
+    '\u{F900}'..='\u{FAFF}',
+    '\u{FF00}'..='\u{FF65}',
+    '\u{20000}'..='\u{2FA1F}',
+];
+
+impl Script {
+    pub fn ranges(&self) -> &'static [RangeInclusive<char>] {
+        match self {
+            Script::Latin => LATIN,
+            Script::Greek => GREEK,
+            Script::Cyrillic => CYRILLIC,
+            Script::Armenian => ARMENIAN,
+            Script::Hebrew => HEBREW,
+            Script::Arabic => ARABIC,
+            Script::Devanagari => DEVANAGARI,
+            Script::Thai => THAI,
+            Script::Hangul => HANGUL,
+            Script::Hiragana => HIRAGANA,
+            Script::Katakana => KATAKANA,
+            Script::Han => HAN,
+        }
+    }
+
+    pub fn of(c: char) -> Option<Script> {
+        const ALL: [Script; 12] = [
+            Script::Latin,
+            Script::Greek,
+            Script::Cyrillic,
+            Script::Armenian,
+            Script::Hebrew,
+            Script::Arabic,
+            Script::Devanagari,
+            Script::Thai,
+            Script::Hangul,
+            Script::Hiragana,
+            Script::Katakana,
+            Script::Han,
+        ];
+        ALL.into_iter()
+            .find(|script| script.ranges().iter().any(|range| range.contains(&c)))
+    }
+}
+
+// maps chars to fonts; later bindings take precedence over earlier ones
+#[derive(Debug, Clone, Default)]
+pub struct FontBindings {
+    ranges: Vec<(RangeInclusive<char>, FontId)>,
+}
+
+impl FontBindings {
+    pub fn bind_script(&mut self, script: Script, font: FontId) {
+        for range in script.ranges() {
+            self.ranges.push((range.clone(), font));
+        }
+    }
+
+    pub fn bind_range(&mut self, range: RangeInclusive<char>, font: FontId) {
+        self.ranges.push((range, font));
+    }
+
+    pub fn unbind_font(&mut self, font: FontId) {
+        self.ranges.retain(|(_, f)| *f != font);
+    }
+
+    pub fn font_for(&self, c: char) -> FontId {
+        self.ranges
+            .iter()
+            .rev()
+            .find(|(range, _)| range.contains(&c))
+            .map(|(_, font)| *font)
+            .unwrap_or(DEFAULT_FONT)
+    }
+}
@@ -0,0 +1,112 @@
+use super::gpu::compat;
+
+use shaderc::{Compiler, ShaderKind};
+
+use vulkano::device::Device;
+use vulkano::shader::ShaderModule;
+
+use std::fmt::Debug;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+// the shaders of the text pipelines, the composite, culling and rasterizer ones stay
+// as compiled in
+const SHADERS: [(&str, ShaderKind); 5] = [
+    ("vertex.glsl", ShaderKind::Vertex),
+    ("fragment.glsl", ShaderKind::Fragment),
+    ("textured_fragment.glsl", ShaderKind::Fragment),
+    ("msdf_fragment.glsl", ShaderKind::Fragment),
+    ("instanced.glsl", ShaderKind::Vertex),
+];
+
+// the shader the watcher last reloaded from file, or the compiled in one until there is
+// none or for pipelines built without a watcher
+pub(crate) fn load<E: Debug>(
+    device: Arc<Device>,
+    reload: Option<&ShaderReload>,
+    file: &str,
+    compiled: fn(Arc<Device>) -> Result<Arc<ShaderModule>, E>,
+) -> Arc<ShaderModule> {
+    let reloaded = reload.and_then(|reload| {
+        reload
+            .reloaded
+            .iter()
+            .find(|(name, _)| *name == file)
+            .map(|(_, words)| words)
+    });
+    match reloaded {
+        Some(words) => compat::shader_module(device, words),
+        None => compiled(device).unwrap(),
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+// watches the glsl files in a directory, see DrawText::watch_shaders
+pub(crate) struct ShaderReload {
+    dir: PathBuf,
+    compiler: Compiler,
+    // same order as SHADERS
+    modified: Vec<Option<SystemTime>>,
+    errors: Vec<Option<String>>,
+    // spir-v of the shaders reloaded so far, by file name. only the pipelines of the
+    // DrawText watching them are built from these
+    reloaded: Vec<(&'static str, Arc<[u32]>)>,
+}
+
+impl ShaderReload {
+    pub(crate) fn new(dir: PathBuf) -> ShaderReload {
+        // what's on disk now is what got compiled in, only later changes count
+        let modified = SHADERS
+            .iter()
+            .map(|(file, _)| modified(&dir.join(file)))
+            .collect();
+        ShaderReload {
+            dir,
+            compiler: Compiler::new().expect("shaderc isn't available"),
+            modified,
+            errors: vec![None; SHADERS.len()],
+            reloaded: vec![],
+        }
+    }
+
+    // compiles the files changed since the last call, true when one of them compiled
+    // and the pipelines have to be built again. a file that doesn't compile keeps its
+    // last working version until it's saved again
+    pub(crate) fn poll(&mut self) -> bool {
+        let mut reloaded = false;
+        for (i, &(file, kind)) in SHADERS.iter().enumerate() {
+            let path = self.dir.join(file);
+            let modified = modified(&path);
+            if modified.is_none() || modified == self.modified[i] {
+                continue;
+            }
+            self.modified[i] = modified;
+            let source = match fs::read_to_string(&path) {
+                Ok(source) => source,
+                Err(_) => continue,
+            };
+            match self
+                .compiler
+                .compile_into_spirv(&source, kind, file, "main", None)
+            {
+                Ok(artifact) => {
+                    self.reloaded.retain(|(name, _)| *name != file);
+                    self.reloaded.push((file, artifact.as_binary().into()));
+                    self.errors[i] = None;
+                    reloaded = true;
+                }
+                Err(error) => self.errors[i] = Some(format!("{}: {}", file, error)),
+            }
+        }
+        reloaded
+    }
+
+    pub(crate) fn error(&self) -> Option<&str> {
+        self.errors.iter().flatten().map(String::as_str).next()
+    }
+}
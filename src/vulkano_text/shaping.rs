@@ -0,0 +1,58 @@
+use super::backend::GlyphId;
+#[cfg(feature = "shaping")]
+use super::style::FontFeature;
+
+#[cfg(feature = "shaping")]
+use rustybuzz::{Direction, Face, Feature, Tag, UnicodeBuffer};
+
+// a glyph rustybuzz picked for a run of text, in pixels. without the shaping feature
+// there are none, the layout goes char by char
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(not(feature = "shaping"), allow(dead_code))]
+pub(crate) struct ShapedGlyph {
+    // byte offset into the run of the first char the glyph stands for, ligatures
+    // stand for several
+    pub(crate) cluster: usize,
+    pub(crate) id: GlyphId,
+    pub(crate) advance: f32,
+    // y goes down like everywhere else
+    pub(crate) offset: [f32; 2],
+}
+
+// shapes a run of text in a single font, left to right in text order. right to left
+// runs are mirrored cluster by cluster afterwards. scale turns font units into pixels
+#[cfg(feature = "shaping")]
+pub(crate) fn shape(
+    data: &[u8],
+    text: &str,
+    features: &[FontFeature],
+    scale: f32,
+) -> Vec<ShapedGlyph> {
+    let face = match Face::from_slice(data, 0) {
+        Some(face) => face,
+        None => return vec![],
+    };
+    let features: Vec<Feature> = features
+        .iter()
+        .map(|f| Feature::new(Tag::from_bytes(&f.tag), f.value, ..))
+        .collect();
+
+    let mut buffer = UnicodeBuffer::new();
+    buffer.push_str(text);
+    buffer.set_direction(Direction::LeftToRight);
+    let output = rustybuzz::shape(&face, &features, buffer);
+    output
+        .glyph_infos()
+        .iter()
+        .zip(output.glyph_positions())
+        .map(|(info, position)| ShapedGlyph {
+            cluster: info.cluster as usize,
+            id: GlyphId(info.glyph_id),
+            advance: position.x_advance as f32 * scale,
+            offset: [
+                position.x_offset as f32 * scale,
+                -position.y_offset as f32 * scale,
+            ],
+        })
+        .collect()
+}
@@ -0,0 +1,749 @@
+use super::script::FontId;
+use super::variations::NamedInstance;
+
+#[cfg(feature = "gpu")]
+use rusttype::{Point, Rect};
+
+use std::collections::HashMap;
+use std::hash::Hash;
+#[cfg(feature = "gpu")]
+use std::hash::Hasher;
+
+#[cfg(feature = "gpu")]
+fn hash_floats<H: Hasher>(values: &[f32], state: &mut H) {
+    for value in values {
+        value.to_bits().hash(state);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Shadow {
+    // in pixels, positive y goes down
+    pub offset: [f32; 2],
+    pub color: [f32; 4],
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Outline {
+    pub width: f32,
+    pub color: [f32; 4],
+}
+
+impl Outline {
+    // the glyph is redrawn at these offsets under the fill to fake a stroke
+    #[cfg(feature = "gpu")]
+    pub(crate) fn offsets(&self) -> [[f32; 2]; 8] {
+        let w = self.width;
+        let d = self.width * std::f32::consts::FRAC_1_SQRT_2;
+        [
+            [w, 0.0],
+            [-w, 0.0],
+            [0.0, w],
+            [0.0, -w],
+            [d, d],
+            [d, -d],
+            [-d, d],
+            [-d, -d],
+        ]
+    }
+}
+
+// fills the text instead of its color, spread over the box around its glyphs. colors
+// are interpolated between the corners of every glyph, so radial gradients come out
+// smoother the smaller the glyphs are next to the text
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Gradient {
+    // angle in degrees, 0 runs left to right and 90 top to bottom
+    Linear {
+        start: [f32; 4],
+        end: [f32; 4],
+        angle: f32,
+    },
+    // from the center of the box to its corners
+    Radial {
+        center: [f32; 4],
+        edge: [f32; 4],
+    },
+}
+
+impl Gradient {
+    #[cfg(feature = "gpu")]
+    pub(crate) fn color_at(&self, area: &Rect<f32>, p: Point<f32>) -> [f32; 4] {
+        let (from, to, t) = match *self {
+            Gradient::Linear { start, end, angle } => {
+                let (sin, cos) = angle.to_radians().sin_cos();
+                let along = |x: f32, y: f32| x * cos + y * sin;
+                let corners = [
+                    along(area.min.x, area.min.y),
+                    along(area.max.x, area.min.y),
+                    along(area.min.x, area.max.y),
+                    along(area.max.x, area.max.y),
+                ];
+                let first = corners.iter().copied().fold(f32::MAX, f32::min);
+                let last = corners.iter().copied().fold(f32::MIN, f32::max);
+                let t = (along(p.x, p.y) - first) / (last - first).max(f32::EPSILON);
+                (start, end, t)
+            }
+            Gradient::Radial { center, edge } => {
+                let half = [
+                    (area.max.x - area.min.x) / 2.0,
+                    (area.max.y - area.min.y) / 2.0,
+                ];
+                let dx = p.x - (area.min.x + half[0]);
+                let dy = p.y - (area.min.y + half[1]);
+                let radius = half[0].hypot(half[1]).max(f32::EPSILON);
+                (center, edge, dx.hypot(dy) / radius)
+            }
+        };
+        let t = t.clamp(0.0, 1.0);
+        [0, 1, 2, 3].map(|i| from[i] + (to[i] - from[i]) * t)
+    }
+
+    fn colors_mut(&mut self) -> [&mut [f32; 4]; 2] {
+        match self {
+            Gradient::Linear { start, end, .. } => [start, end],
+            Gradient::Radial { center, edge } => [center, edge],
+        }
+    }
+
+    #[cfg(feature = "gpu")]
+    fn hash_into<H: Hasher>(&self, state: &mut H) {
+        match *self {
+            Gradient::Linear { start, end, angle } => {
+                0u8.hash(state);
+                hash_floats(&start, state);
+                hash_floats(&end, state);
+                hash_floats(&[angle], state);
+            }
+            Gradient::Radial { center, edge } => {
+                1u8.hash(state);
+                hash_floats(&center, state);
+                hash_floats(&edge, state);
+            }
+        }
+    }
+}
+
+// lines drawn along the text, placed with the font's own metrics
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Decorations {
+    pub underline: bool,
+    pub strikethrough: bool,
+    pub overline: bool,
+}
+
+impl Decorations {
+    pub const NONE: Decorations = Decorations {
+        underline: false,
+        strikethrough: false,
+        overline: false,
+    };
+    pub const UNDERLINE: Decorations = Decorations {
+        underline: true,
+        ..Decorations::NONE
+    };
+    pub const STRIKETHROUGH: Decorations = Decorations {
+        strikethrough: true,
+        ..Decorations::NONE
+    };
+    pub const OVERLINE: Decorations = Decorations {
+        overline: true,
+        ..Decorations::NONE
+    };
+
+    pub fn is_empty(&self) -> bool {
+        *self == Decorations::NONE
+    }
+}
+
+// what happens to text running past the style's max_width
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Overflow {
+    // cut off at max_width
+    #[default]
+    Clip,
+    // shortened to fit, ending in "…"
+    Ellipsis,
+    // shortened in the middle, keeps both ends of file paths and the like
+    MiddleEllipsis,
+    // fades out over the last em before max_width
+    Fade,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum WritingMode {
+    #[default]
+    Horizontal,
+    // top to bottom columns going right to left, as in Chinese and Japanese. CJK glyphs
+    // stay upright, everything else is turned sideways. the text's x, y is the center
+    // of the first column's top
+    VerticalRl,
+}
+
+// an OpenType feature like tnum or smcp, set to 1 to turn it on and 0 to turn a default
+// one like liga off. some take a higher value to pick an alternate. they are applied by
+// the shaping backend, without the shaping feature they are ignored
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FontFeature {
+    pub tag: [u8; 4],
+    pub value: u32,
+}
+
+impl FontFeature {
+    // digits of equal width, counters and timers stop jittering
+    pub const TABULAR_FIGURES: FontFeature = FontFeature::on(b"tnum");
+    pub const NO_LIGATURES: FontFeature = FontFeature::off(b"liga");
+    pub const SMALL_CAPS: FontFeature = FontFeature::on(b"smcp");
+
+    pub const fn on(tag: &[u8; 4]) -> FontFeature {
+        FontFeature {
+            tag: *tag,
+            value: 1,
+        }
+    }
+
+    pub const fn off(tag: &[u8; 4]) -> FontFeature {
+        FontFeature {
+            tag: *tag,
+            value: 0,
+        }
+    }
+}
+
+// a coordinate on one axis of a variable font, like wght 650 or wdth 75. axes the
+// font doesn't have are ignored, see DrawText::variation_axes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FontVariation {
+    pub tag: [u8; 4],
+    pub value: f32,
+}
+
+// how glyphs are fitted to the pixel grid, sharper small text for slightly distorted
+// shapes. Slight puts baselines on whole pixels, Full puts glyph origins on whole
+// pixels and runs the font's hinting instructions on backends that support them
+// (the swash one)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Hinting {
+    #[default]
+    None,
+    Slight,
+    Full,
+}
+
+// how a text's glyphs get onto the screen. Tessellated cuts the glyph outlines into
+// triangles every frame instead of drawing them from the atlas, worth it for huge
+// display text that would crowd the atlas. it fades a pixel wide fringe around the
+// outlines for anti-aliasing, TessellatedMsaa leaves that to a multisampled target.
+// glyphs of fonts without outlines aren't drawn by either
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum GlyphRendering {
+    #[default]
+    Atlas,
+    Tessellated,
+    TessellatedMsaa,
+}
+
+// what becomes of text too small to read, e.g. labels of a zoomed out map or graph.
+// the thresholds are glyph sizes on screen in physical pixels, view scale included.
+// below them no glyphs get rasterized, Greeked draws a bar in the text's color along
+// every line instead, with its shadow and outline. vertical text is hidden
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum TinyText {
+    #[default]
+    Glyphs,
+    Greeked(f32),
+    Hidden(f32),
+}
+
+impl TinyText {
+    #[cfg(feature = "gpu")]
+    fn hash_into<H: Hasher>(&self, state: &mut H) {
+        match *self {
+            TinyText::Glyphs => 0u8.hash(state),
+            TinyText::Greeked(below) => {
+                1u8.hash(state);
+                hash_floats(&[below], state);
+            }
+            TinyText::Hidden(below) => {
+                2u8.hash(state);
+                hash_floats(&[below], state);
+            }
+        }
+    }
+}
+
+pub const REGULAR_WEIGHT: u16 = 400;
+pub const BOLD_WEIGHT: u16 = 700;
+
+// tan of the 12 degree slant most oblique faces use
+const ITALIC_SKEW: f32 = 0.21;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextStyle {
+    pub size: f32,
+    pub color: [f32; 4],
+    pub shadow: Option<Shadow>,
+    pub outline: Option<Outline>,
+    // higher layers draw over lower ones, texts of the same layer in queueing order
+    pub layer: i32,
+    // rounds glyph positions to whole pixels so static ui text stays sharp,
+    // leave it off for smoothly moving text. Quality::Crisp snaps everything
+    pub snap_to_pixel: bool,
+    // css style weight, 400 is regular. heavier weights are faked by smearing the
+    // glyph sideways, lighter ones can't be faked and draw as regular
+    pub weight: u16,
+    // faux italic, glyphs get sheared around their baseline
+    pub italic: bool,
+    // drawn in the text color, with the text's shadow and outline
+    pub decorations: Decorations,
+    // filled behind the line, for highlights, tooltips and text over busy scenes
+    pub background: Option<[f32; 4]>,
+    // how far the background reaches past the line box, horizontally and vertically
+    pub background_padding: [f32; 2],
+    // width available to the text, measured from its x, or the height from its y for
+    // vertical text. the ellipsis modes apply to every line of the text, but not to
+    // queue_text_wrapped
+    pub max_width: Option<f32>,
+    pub overflow: Overflow,
+    // decorations and backgrounds are only drawn for horizontal text
+    pub writing_mode: WritingMode,
+    // shaped horizontal text only
+    pub features: Vec<FontFeature>,
+    // axis values for variable fonts, the font's defaults for axes left out. backends
+    // without variation support, like rusttype, draw the default instance
+    pub variations: Vec<FontVariation>,
+    // worth it below 14 pixels or so, larger text looks better unhinted
+    pub hinting: Hinting,
+    // every char in this font instead of the one bound to it, chars it lacks draw as
+    // its missing glyph
+    pub font: Option<FontId>,
+    // replaces color for the glyphs and decorations, shadow, outline and background
+    // stay solid
+    pub gradient: Option<Gradient>,
+    pub rendering: GlyphRendering,
+    pub tiny_text: TinyText,
+}
+
+impl TextStyle {
+    pub fn new(size: f32, color: [f32; 4]) -> TextStyle {
+        TextStyle {
+            size,
+            color,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_shadow(mut self, offset: [f32; 2], color: [f32; 4]) -> TextStyle {
+        self.shadow = Some(Shadow { offset, color });
+        self
+    }
+
+    pub fn with_outline(mut self, width: f32, color: [f32; 4]) -> TextStyle {
+        self.outline = Some(Outline { width, color });
+        self
+    }
+
+    pub fn with_layer(mut self, layer: i32) -> TextStyle {
+        self.layer = layer;
+        self
+    }
+
+    pub fn with_snap_to_pixel(mut self, snap_to_pixel: bool) -> TextStyle {
+        self.snap_to_pixel = snap_to_pixel;
+        self
+    }
+
+    pub fn with_weight(mut self, weight: u16) -> TextStyle {
+        self.weight = weight;
+        self
+    }
+
+    pub fn with_italic(mut self, italic: bool) -> TextStyle {
+        self.italic = italic;
+        self
+    }
+
+    pub fn with_decorations(mut self, decorations: Decorations) -> TextStyle {
+        self.decorations = decorations;
+        self
+    }
+
+    pub fn with_background(mut self, color: [f32; 4]) -> TextStyle {
+        self.background = Some(color);
+        self
+    }
+
+    pub fn with_background_padding(mut self, padding: [f32; 2]) -> TextStyle {
+        self.background_padding = padding;
+        self
+    }
+
+    pub fn with_max_width(mut self, max_width: f32, overflow: Overflow) -> TextStyle {
+        self.max_width = Some(max_width);
+        self.overflow = overflow;
+        self
+    }
+
+    pub fn with_writing_mode(mut self, writing_mode: WritingMode) -> TextStyle {
+        self.writing_mode = writing_mode;
+        self
+    }
+
+    // replaces an earlier value for the same tag
+    pub fn with_feature(mut self, feature: FontFeature) -> TextStyle {
+        self.features.retain(|f| f.tag != feature.tag);
+        self.features.push(feature);
+        self
+    }
+
+    // replaces an earlier value for the same axis
+    pub fn with_variation(mut self, tag: &[u8; 4], value: f32) -> TextStyle {
+        self.variations.retain(|v| v.tag != *tag);
+        self.variations.push(FontVariation { tag: *tag, value });
+        self
+    }
+
+    // all axes of a named instance, see DrawText::named_instances
+    pub fn with_named_instance(mut self, instance: &NamedInstance) -> TextStyle {
+        for variation in &instance.coordinates {
+            self = self.with_variation(&variation.tag, variation.value);
+        }
+        self
+    }
+
+    pub fn with_hinting(mut self, hinting: Hinting) -> TextStyle {
+        self.hinting = hinting;
+        self
+    }
+
+    pub fn with_font(mut self, font: FontId) -> TextStyle {
+        self.font = Some(font);
+        self
+    }
+
+    pub fn with_linear_gradient(mut self, start: [f32; 4], end: [f32; 4], angle: f32) -> TextStyle {
+        self.gradient = Some(Gradient::Linear { start, end, angle });
+        self
+    }
+
+    pub fn with_radial_gradient(mut self, center: [f32; 4], edge: [f32; 4]) -> TextStyle {
+        self.gradient = Some(Gradient::Radial { center, edge });
+        self
+    }
+
+    pub fn with_rendering(mut self, rendering: GlyphRendering) -> TextStyle {
+        self.rendering = rendering;
+        self
+    }
+
+    pub fn with_tiny_text(mut self, tiny_text: TinyText) -> TextStyle {
+        self.tiny_text = tiny_text;
+        self
+    }
+
+    // multiplies the alpha of the text, its shadow, outline and background, for fades
+    pub fn with_opacity(mut self, opacity: f32) -> TextStyle {
+        self.color[3] *= opacity;
+        if let Some(shadow) = &mut self.shadow {
+            shadow.color[3] *= opacity;
+        }
+        if let Some(outline) = &mut self.outline {
+            outline.color[3] *= opacity;
+        }
+        if let Some(background) = &mut self.background {
+            background[3] *= opacity;
+        }
+        if let Some(gradient) = &mut self.gradient {
+            for color in gradient.colors_mut() {
+                color[3] *= opacity;
+            }
+        }
+        self
+    }
+
+    // the colors of other on this style's text and effects. effects other lacks keep
+    // their color, none are added or taken away
+    #[cfg(feature = "gpu")]
+    pub(crate) fn recolored(mut self, other: &TextStyle) -> TextStyle {
+        self.color = other.color;
+        self.gradient = other.gradient;
+        if let (Some(shadow), Some(other)) = (&mut self.shadow, other.shadow) {
+            shadow.color = other.color;
+        }
+        if let (Some(outline), Some(other)) = (&mut self.outline, other.outline) {
+            outline.color = other.color;
+        }
+        if let (Some(background), Some(other)) = (&mut self.background, other.background) {
+            *background = other;
+        }
+        self
+    }
+
+    // the style in physical pixels for a display with the given scale factor
+    pub(crate) fn scaled(&self, factor: f32) -> TextStyle {
+        let mut style = self.clone();
+        style.size *= factor;
+        if let Some(shadow) = &mut style.shadow {
+            shadow.offset = shadow.offset.map(|v| v * factor);
+        }
+        if let Some(outline) = &mut style.outline {
+            outline.width *= factor;
+        }
+        style.background_padding = style.background_padding.map(|v| v * factor);
+        style.max_width = style.max_width.map(|w| w * factor);
+        style
+    }
+
+    pub(crate) fn is_vertical(&self) -> bool {
+        self.writing_mode == WritingMode::VerticalRl
+    }
+
+    // how far faux bold smears glyphs to the right, in pixels
+    pub(crate) fn emboldening(&self) -> f32 {
+        (self.weight as f32 - REGULAR_WEIGHT as f32).max(0.0) / 300.0 * self.size / 20.0
+    }
+
+    // at most a pixel apart so the smear has no gaps
+    pub(crate) fn smear_offsets(&self) -> Vec<[f32; 2]> {
+        let bold = self.emboldening();
+        let steps = bold.ceil() as usize;
+        if steps == 0 {
+            return vec![[0.0, 0.0]];
+        }
+        (0..=steps)
+            .map(|i| [bold * i as f32 / steps as f32, 0.0])
+            .collect()
+    }
+
+    pub(crate) fn skew(&self) -> f32 {
+        if self.italic {
+            ITALIC_SKEW
+        } else {
+            0.0
+        }
+    }
+
+    // bitwise, so it can be used to detect whether anything visible changed
+    #[cfg(feature = "gpu")]
+    pub(crate) fn hash_into<H: Hasher>(&self, state: &mut H) {
+        hash_floats(&[self.size], state);
+        hash_floats(&self.color, state);
+        self.shadow.is_some().hash(state);
+        self.outline.is_some().hash(state);
+        if let Some(shadow) = self.shadow {
+            hash_floats(&shadow.offset, state);
+            hash_floats(&shadow.color, state);
+        }
+        if let Some(outline) = self.outline {
+            hash_floats(&[outline.width], state);
+            hash_floats(&outline.color, state);
+        }
+        self.layer.hash(state);
+        self.snap_to_pixel.hash(state);
+        self.weight.hash(state);
+        self.italic.hash(state);
+        self.decorations.hash(state);
+        self.background.is_some().hash(state);
+        if let Some(background) = self.background {
+            hash_floats(&background, state);
+        }
+        hash_floats(&self.background_padding, state);
+        self.max_width.map(f32::to_bits).hash(state);
+        self.overflow.hash(state);
+        self.writing_mode.hash(state);
+        self.features.hash(state);
+        for variation in &self.variations {
+            variation.tag.hash(state);
+            hash_floats(&[variation.value], state);
+        }
+        self.hinting.hash(state);
+        self.font.hash(state);
+        self.gradient.is_some().hash(state);
+        if let Some(gradient) = self.gradient {
+            gradient.hash_into(state);
+        }
+        self.rendering.hash(state);
+        self.tiny_text.hash_into(state);
+    }
+
+    // what the glyph layout depends on, colors and effects leave it alone
+    #[cfg(feature = "gpu")]
+    pub(crate) fn hash_layout_into<H: Hasher>(&self, state: &mut H) {
+        hash_floats(&[self.size], state);
+        self.snap_to_pixel.hash(state);
+        self.weight.hash(state);
+        self.writing_mode.hash(state);
+        self.features.hash(state);
+        for variation in &self.variations {
+            variation.tag.hash(state);
+            hash_floats(&[variation.value], state);
+        }
+        self.hinting.hash(state);
+        self.font.hash(state);
+    }
+}
+
+impl Default for TextStyle {
+    fn default() -> TextStyle {
+        TextStyle {
+            size: 15.0,
+            color: [1.0, 1.0, 1.0, 1.0],
+            shadow: None,
+            outline: None,
+            layer: 0,
+            snap_to_pixel: false,
+            weight: REGULAR_WEIGHT,
+            italic: false,
+            decorations: Decorations::NONE,
+            background: None,
+            background_padding: [0.0, 0.0],
+            max_width: None,
+            overflow: Overflow::Clip,
+            writing_mode: WritingMode::Horizontal,
+            features: vec![],
+            variations: vec![],
+            hinting: Hinting::None,
+            font: None,
+            gradient: None,
+            rendering: GlyphRendering::Atlas,
+            tiny_text: TinyText::Glyphs,
+        }
+    }
+}
+
+// look of DrawText::queue_progress_bar, for loading screens and health or ammo gauges
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProgressStyle {
+    // the empty part of the bar
+    pub track: [f32; 4],
+    pub fill: [f32; 4],
+    // the label's style, centered on the bar. None draws no label
+    pub label: Option<TextStyle>,
+    // fills bottom up instead of left to right
+    pub vertical: bool,
+    // bar and label go on this layer, whatever the label style's is
+    pub layer: i32,
+}
+
+impl ProgressStyle {
+    pub fn new(track: [f32; 4], fill: [f32; 4]) -> ProgressStyle {
+        ProgressStyle {
+            track,
+            fill,
+            label: None,
+            vertical: false,
+            layer: 0,
+        }
+    }
+
+    pub fn with_label(mut self, style: TextStyle) -> ProgressStyle {
+        self.label = Some(style);
+        self
+    }
+
+    pub fn with_vertical(mut self, vertical: bool) -> ProgressStyle {
+        self.vertical = vertical;
+        self
+    }
+
+    pub fn with_layer(mut self, layer: i32) -> ProgressStyle {
+        self.layer = layer;
+        self
+    }
+}
+
+// ready-made shadow/outline combinations that stay readable on the theme's background
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Theme {
+    Dark,
+    Light,
+}
+
+impl Theme {
+    fn foreground(&self) -> [f32; 4] {
+        match self {
+            Theme::Dark => [1.0, 1.0, 1.0, 1.0],
+            Theme::Light => [0.1, 0.1, 0.1, 1.0],
+        }
+    }
+
+    fn contrast(&self, alpha: f32) -> [f32; 4] {
+        match self {
+            Theme::Dark => [0.0, 0.0, 0.0, alpha],
+            Theme::Light => [1.0, 1.0, 1.0, alpha],
+        }
+    }
+
+    fn error(&self) -> [f32; 4] {
+        match self {
+            Theme::Dark => [1.0, 0.4, 0.4, 1.0],
+            Theme::Light => [0.75, 0.1, 0.1, 1.0],
+        }
+    }
+
+    fn warning(&self) -> [f32; 4] {
+        match self {
+            Theme::Dark => [1.0, 0.8, 0.3, 1.0],
+            Theme::Light => [0.6, 0.45, 0.0, 1.0],
+        }
+    }
+
+    pub fn body(&self, size: f32) -> TextStyle {
+        TextStyle::new(size, self.foreground())
+    }
+
+    pub fn subtitle(&self, size: f32) -> TextStyle {
+        let width = (size / 16.0).max(1.0);
+        TextStyle::new(size, self.foreground())
+            .with_outline(width, self.contrast(1.0))
+            .with_shadow([width, width], self.contrast(0.6))
+    }
+
+    pub fn title(&self, size: f32) -> TextStyle {
+        let offset = (size / 12.0).max(1.0);
+        TextStyle::new(size, self.foreground()).with_shadow([offset, offset], self.contrast(0.5))
+    }
+
+    pub fn hud(&self, size: f32) -> TextStyle {
+        TextStyle::new(size, self.foreground())
+            .with_outline(1.0, self.contrast(0.8))
+            .with_feature(FontFeature::TABULAR_FIGURES)
+    }
+
+    // body, heading, subtitle, hud, error and warning, at size and heading at 1.5 times
+    // that
+    pub fn palette(&self, size: f32) -> Palette {
+        Palette::new()
+            .with_style("body", self.body(size))
+            .with_style("heading", self.title(size * 1.5))
+            .with_style("subtitle", self.subtitle(size))
+            .with_style("hud", self.hud(size))
+            .with_style("error", TextStyle::new(size, self.error()))
+            .with_style("warning", TextStyle::new(size, self.warning()))
+    }
+}
+
+// named styles for DrawText::queue_text_themed, switching palettes switches themes
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Palette {
+    styles: HashMap<String, TextStyle>,
+}
+
+impl Palette {
+    pub fn new() -> Palette {
+        Palette::default()
+    }
+
+    pub fn with_style(mut self, name: &str, style: TextStyle) -> Palette {
+        self.set_style(name, style);
+        self
+    }
+
+    pub fn set_style(&mut self, name: &str, style: TextStyle) {
+        self.styles.insert(name.to_owned(), style);
+    }
+
+    pub fn style(&self, name: &str) -> Option<&TextStyle> {
+        self.styles.get(name)
+    }
+}
@@ -0,0 +1,108 @@
+use super::animate::TextAnimator;
+use super::drawtext::TextRendererCore;
+use super::style::TextStyle;
+
+use std::time::Duration;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Subtitle {
+    pub text: String,
+    // on the caller's clock, e.g. the playback position of a video
+    pub start: Duration,
+    pub duration: Duration,
+    pub style: TextStyle,
+}
+
+// timed captions, drawn centered at the bottom of the screen. captions showing at the
+// same time stack upwards, the one that started last at the bottom
+pub struct SubtitleQueue {
+    subtitles: Vec<Subtitle>,
+    // captions fade in and out over this long, within their duration
+    pub fade: Duration,
+    // between the bottom of the screen and the last caption
+    pub bottom_margin: f32,
+    // between stacked captions
+    pub spacing: f32,
+    // captions wrap at this share of the screen width
+    pub max_width: f32,
+    // in logical pixels, needed for headless renderers. the target's size otherwise
+    pub screen_size: Option<[f32; 2]>,
+}
+
+impl Default for SubtitleQueue {
+    fn default() -> SubtitleQueue {
+        SubtitleQueue {
+            subtitles: vec![],
+            fade: Duration::from_millis(200),
+            bottom_margin: 40.0,
+            spacing: 8.0,
+            max_width: 0.8,
+            screen_size: None,
+        }
+    }
+}
+
+impl SubtitleQueue {
+    pub fn new() -> SubtitleQueue {
+        SubtitleQueue::default()
+    }
+
+    pub fn push(&mut self, text: &str, start: Duration, duration: Duration, style: &TextStyle) {
+        self.subtitles.push(Subtitle {
+            text: text.to_owned(),
+            start,
+            duration,
+            style: style.clone(),
+        });
+    }
+
+    pub fn subtitles(&self) -> &[Subtitle] {
+        &self.subtitles
+    }
+
+    pub fn clear(&mut self) {
+        self.subtitles.clear();
+    }
+
+    // drops captions that are over, so seeking backwards needs them pushed again, and
+    // queues the ones showing at now. draw_text gets asked for a frame when the next
+    // one starts
+    pub fn update_and_queue(&mut self, draw_text: &mut TextRendererCore, now: Duration) {
+        self.subtitles
+            .retain(|subtitle| subtitle.start + subtitle.duration > now);
+        // stable, captions starting together keep the order they were pushed in
+        self.subtitles.sort_by_key(|subtitle| subtitle.start);
+        if let Some(next) = self.subtitles.iter().find(|s| s.start > now) {
+            draw_text.schedule_redraw_in(next.start - now);
+        }
+        // above the bottom of the screen whatever the coordinate origin
+        draw_text.on_screen(|draw_text| self.queue_showing(draw_text, now));
+    }
+
+    fn queue_showing(&self, draw_text: &mut TextRendererCore, now: Duration) {
+        let [width, height] = match self.screen_size.or_else(|| draw_text.screen_size()) {
+            Some(size) => size,
+            None => return,
+        };
+        let mut bottom = height - self.bottom_margin;
+        for subtitle in self.subtitles.iter().rev().filter(|s| s.start <= now) {
+            let style = &subtitle.style;
+            let mut animator = TextAnimator::new()
+                .with_fade_in(self.fade)
+                .with_fade_out(subtitle.duration.saturating_sub(self.fade), self.fade);
+            animator.advance(now - subtitle.start);
+
+            let layout =
+                draw_text.layout_text_wrapped(style, &subtitle.text, width * self.max_width);
+            let top = bottom - layout.height();
+            let baseline = top - layout.line_top(0);
+            for (i, range) in layout.line_ranges().into_iter().enumerate() {
+                let line = subtitle.text[range].trim_end();
+                let line_width = draw_text.layout_text(style, line).width();
+                let y = baseline + i as f32 * layout.line_height();
+                animator.queue(draw_text, (width - line_width) / 2.0, y, style, line);
+            }
+            bottom = top - self.spacing;
+        }
+    }
+}
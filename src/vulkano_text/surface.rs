@@ -0,0 +1,31 @@
+use vulkano::instance::Instance;
+use vulkano::swapchain::{Surface, SurfaceApi};
+use vulkano::{Handle, VulkanObject};
+
+use std::any::Any;
+use std::sync::Arc;
+
+// the raw VkInstance, for windowing libraries that create the surface themselves,
+// e.g. SDL2's Window::vulkan_create_surface
+pub fn raw_instance(instance: &Instance) -> usize {
+    instance.handle().as_raw() as usize
+}
+
+// hands a VkSurfaceKHR created outside vulkano over to it. the surface is destroyed
+// with the returned Surface, window is kept alive until then
+//
+// unsafe: raw has to be a surface of instance created through api, and whatever it
+// was created from has to outlive it when window is None
+pub unsafe fn surface_from_raw(
+    instance: Arc<Instance>,
+    raw: u64,
+    api: SurfaceApi,
+    window: Option<Arc<dyn Any + Send + Sync>>,
+) -> Arc<Surface> {
+    Arc::new(Surface::from_handle(
+        instance,
+        Handle::from_raw(raw),
+        api,
+        window,
+    ))
+}
@@ -0,0 +1,190 @@
+use super::drawtext::TextRendererCore;
+use super::layout::TextLayout;
+use super::style::{Overflow, TextStyle};
+
+use rusttype::{point, Rect};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Align {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColumnWidth {
+    // as wide as its widest cell
+    Auto,
+    // cells that don't fit get an ellipsis
+    Fixed(f32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Column {
+    pub width: ColumnWidth,
+    pub align: Align,
+}
+
+impl Column {
+    pub fn auto(align: Align) -> Column {
+        Column {
+            width: ColumnWidth::Auto,
+            align,
+        }
+    }
+
+    pub fn fixed(width: f32, align: Align) -> Column {
+        Column {
+            width: ColumnWidth::Fixed(width),
+            align,
+        }
+    }
+}
+
+// rows of cells lined up in columns, e.g. for debug stat panels. cells are measured
+// every time the table is queued, rows grow with cells spanning several lines
+#[derive(Debug, Clone)]
+pub struct TextTable {
+    columns: Vec<Column>,
+    rows: Vec<Vec<String>>,
+    pub style: TextStyle,
+    // between neighbouring columns
+    pub spacing: f32,
+    // a line down the middle of the spacing between columns
+    pub separator: Option<[f32; 4]>,
+}
+
+impl TextTable {
+    pub fn new(style: TextStyle, columns: Vec<Column>) -> TextTable {
+        TextTable {
+            columns,
+            rows: vec![],
+            style,
+            spacing: 16.0,
+            separator: None,
+        }
+    }
+
+    pub fn with_spacing(mut self, spacing: f32) -> TextTable {
+        self.spacing = spacing;
+        self
+    }
+
+    pub fn with_separator(mut self, color: [f32; 4]) -> TextTable {
+        self.separator = Some(color);
+        self
+    }
+
+    pub fn columns(&self) -> &[Column] {
+        &self.columns
+    }
+
+    // one cell per column, missing ones stay empty and extra ones are dropped
+    pub fn push_row<S: AsRef<str>>(&mut self, cells: impl IntoIterator<Item = S>) {
+        let mut row: Vec<String> = cells
+            .into_iter()
+            .take(self.columns.len())
+            .map(|cell| cell.as_ref().to_owned())
+            .collect();
+        row.resize(self.columns.len(), String::new());
+        self.rows.push(row);
+    }
+
+    pub fn row_count(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn clear(&mut self) {
+        self.rows.clear();
+    }
+
+    // how wide every column gets when queued
+    pub fn column_widths(&self, draw_text: &TextRendererCore) -> Vec<f32> {
+        self.widths(&self.layouts(draw_text))
+    }
+
+    fn layouts(&self, draw_text: &TextRendererCore) -> Vec<Vec<TextLayout>> {
+        self.rows
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|cell| draw_text.layout_text(&self.style, cell))
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn widths(&self, layouts: &[Vec<TextLayout>]) -> Vec<f32> {
+        self.columns
+            .iter()
+            .enumerate()
+            .map(|(i, column)| match column.width {
+                ColumnWidth::Fixed(width) => width,
+                ColumnWidth::Auto => layouts.iter().map(|row| row[i].width()).fold(0.0, f32::max),
+            })
+            .collect()
+    }
+
+    // x, y is the top left corner. returns the rect the table covers
+    pub fn queue(&self, draw_text: &mut TextRendererCore, x: f32, y: f32) -> Rect<f32> {
+        let rect = draw_text.from_origin(y, |draw_text| self.queue_rows(draw_text, x, y));
+        draw_text.flip_rect(y, rect)
+    }
+
+    fn queue_rows(&self, draw_text: &mut TextRendererCore, x: f32, y: f32) -> Rect<f32> {
+        let layouts = self.layouts(draw_text);
+        let widths = self.widths(&layouts);
+        let line = draw_text.layout_text(&self.style, "");
+        let (line_height, ascent) = (line.line_height(), -line.line_top(0));
+
+        let mut top = y;
+        for (row, layouts) in self.rows.iter().zip(&layouts) {
+            let mut left = x;
+            for (i, (cell, layout)) in row.iter().zip(layouts).enumerate() {
+                let (column, width) = (self.columns[i], widths[i]);
+                let free = (width - layout.width()).max(0.0);
+                let offset = match column.align {
+                    Align::Left => 0.0,
+                    Align::Center => free / 2.0,
+                    Align::Right => free,
+                };
+                let style = match column.width {
+                    ColumnWidth::Fixed(width) => {
+                        self.style.clone().with_max_width(width, Overflow::Ellipsis)
+                    }
+                    ColumnWidth::Auto => self.style.clone(),
+                };
+                draw_text.queue_text_styled(left + offset, top + ascent, &style, cell);
+                left += width + self.spacing;
+            }
+            let lines = layouts
+                .iter()
+                .map(TextLayout::line_count)
+                .max()
+                .unwrap_or(1);
+            top += lines as f32 * line_height;
+        }
+
+        let width =
+            widths.iter().sum::<f32>() + self.spacing * widths.len().saturating_sub(1) as f32;
+        if let Some(color) = self.separator {
+            let mut middle = x - self.spacing / 2.0;
+            let lines = widths[..widths.len().saturating_sub(1)]
+                .iter()
+                .map(|width| {
+                    middle += width + self.spacing;
+                    Rect {
+                        min: point(middle - 0.5, y),
+                        max: point(middle + 0.5, top),
+                    }
+                })
+                .collect();
+            draw_text.queue_rects(lines, color, false, self.style.layer);
+        }
+        Rect {
+            min: point(x, y),
+            max: point(x + width, top),
+        }
+    }
+}
@@ -0,0 +1,258 @@
+use super::drawtext::text_pipeline;
+use super::gpu::compat::{self, Image, ImageView};
+
+use vulkano::device::Device;
+use vulkano::format::Format;
+use vulkano::pipeline::graphics::color_blend::AttachmentBlend;
+use vulkano::pipeline::GraphicsPipeline;
+use vulkano::render_pass::{Framebuffer, FramebufferCreateInfo, RenderPass, Subpass};
+
+use std::error::Error;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+// a target was asked for without any images, its format comes from the first one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NoImagesError;
+
+impl fmt::Display for NoImagesError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a text target needs at least one image")
+    }
+}
+
+impl Error for NoImagesError {}
+
+// tells targets apart, DrawText keeps the atlas copies of a target's frames in flight
+// by it. retargeting keeps it, a window's images change but its frames go on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct TargetId(u64);
+
+impl TargetId {
+    fn next() -> TargetId {
+        static NEXT: AtomicU64 = AtomicU64::new(0);
+        TargetId(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TargetKind {
+    // what ends up on screen
+    Present,
+    // screenshots and other offscreen captures
+    Capture,
+}
+
+// how draw_text gets text onto the target image
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum CompositionMode {
+    // DrawText begins and ends a render pass of its own on the target after the
+    // caller's. the attachment is loaded, so whatever the caller drew stays, and
+    // vulkano transitions the image into and out of ColorAttachmentOptimal
+    #[default]
+    OwnPass,
+    // text is kept in a transparent overlay image that only gets redrawn where it
+    // changed, then blended over the target in a pass of its own, see PartialRedraw
+    Overlay,
+}
+
+// which kinds of targets a layer is drawn into
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LayerTargets {
+    pub present: bool,
+    pub capture: bool,
+}
+
+impl LayerTargets {
+    pub const ALL: LayerTargets = LayerTargets {
+        present: true,
+        capture: true,
+    };
+    // e.g. debug text that must not show up in screenshots
+    pub const PRESENT_ONLY: LayerTargets = LayerTargets {
+        present: true,
+        capture: false,
+    };
+    pub const CAPTURE_ONLY: LayerTargets = LayerTargets {
+        present: false,
+        capture: true,
+    };
+
+    pub fn contains(&self, kind: TargetKind) -> bool {
+        match kind {
+            TargetKind::Present => self.present,
+            TargetKind::Capture => self.capture,
+        }
+    }
+}
+
+// a set of images DrawText can draw into, together with the render pass and
+// pipeline matching their format
+pub struct TextTarget {
+    id: TargetId,
+    kind: TargetKind,
+    format: Format,
+    render_pass: Arc<RenderPass>,
+    pipeline: Arc<GraphicsPipeline>,
+    // for pipelines DrawText builds on the same render pass
+    blend: AttachmentBlend,
+    framebuffers: Vec<Arc<Framebuffer>>,
+}
+
+impl TextTarget {
+    pub fn new(
+        device: Arc<Device>,
+        kind: TargetKind,
+        format: Format,
+        views: Vec<Arc<ImageView>>,
+    ) -> TextTarget {
+        TextTarget::with_blend(device, kind, format, views, AttachmentBlend::alpha())
+    }
+
+    pub(crate) fn with_blend(
+        device: Arc<Device>,
+        kind: TargetKind,
+        format: Format,
+        views: Vec<Arc<ImageView>>,
+        blend: AttachmentBlend,
+    ) -> TextTarget {
+        let render_pass = compat::color_render_pass(device.clone(), format);
+
+        let pipeline = text_pipeline(
+            device,
+            Subpass::from(render_pass.clone(), 0).unwrap(),
+            blend,
+            None,
+        );
+
+        let framebuffers = framebuffers(&render_pass, views);
+
+        TextTarget {
+            id: TargetId::next(),
+            kind,
+            format,
+            render_pass,
+            pipeline,
+            blend,
+            framebuffers,
+        }
+    }
+
+    // same render pass and pipeline, different images of the same format. for new
+    // images of the same window, like after a resize, its frames in flight are still
+    // counted as this target's. other windows get targets of their own from
+    // DrawText::create_target
+    pub fn retarget(&self, views: Vec<Arc<ImageView>>) -> TextTarget {
+        TextTarget {
+            id: self.id,
+            kind: self.kind,
+            format: self.format,
+            render_pass: self.render_pass.clone(),
+            pipeline: self.pipeline.clone(),
+            blend: self.blend,
+            framebuffers: framebuffers(&self.render_pass, views),
+        }
+    }
+
+    // any views of the same format, e.g. swapchain images or images of a second window
+    pub fn from_views(
+        device: Arc<Device>,
+        kind: TargetKind,
+        views: Vec<Arc<ImageView>>,
+    ) -> Result<TextTarget, NoImagesError> {
+        let format = compat::view_format(views.first().ok_or(NoImagesError)?);
+        Ok(TextTarget::new(device, kind, format, views))
+    }
+
+    pub fn from_images(
+        device: Arc<Device>,
+        kind: TargetKind,
+        images: &[Arc<Image>],
+    ) -> Result<TextTarget, NoImagesError> {
+        let views = images
+            .iter()
+            .map(|image| compat::view(image.clone()))
+            .collect();
+        TextTarget::from_views(device, kind, views)
+    }
+
+    // the image needs color_attachment usage, plus transfer_src to be read back
+    pub fn image(device: Arc<Device>, image: Arc<Image>) -> TextTarget {
+        let view = compat::view(image);
+        let format = compat::view_format(&view);
+        TextTarget::new(device, TargetKind::Capture, format, vec![view])
+    }
+
+    // a target of its own for other images, with the same render pass and pipeline
+    pub(crate) fn sibling(&self, views: Vec<Arc<ImageView>>) -> TextTarget {
+        TextTarget {
+            id: TargetId::next(),
+            ..self.retarget(views)
+        }
+    }
+
+    pub(crate) fn id(&self) -> TargetId {
+        self.id
+    }
+
+    pub fn kind(&self) -> TargetKind {
+        self.kind
+    }
+
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    pub fn image_count(&self) -> usize {
+        self.framebuffers.len()
+    }
+
+    pub fn extent(&self, image_num: usize) -> [u32; 2] {
+        self.framebuffers[image_num].extent()
+    }
+
+    pub fn render_pass(&self) -> &Arc<RenderPass> {
+        &self.render_pass
+    }
+
+    pub(crate) fn pipeline(&self) -> &Arc<GraphicsPipeline> {
+        &self.pipeline
+    }
+
+    // whether the format encodes what the shader writes, see ColorSpace
+    pub(crate) fn linear_output(&self) -> bool {
+        compat::linear_output(&Subpass::from(self.render_pass.clone(), 0).unwrap())
+    }
+
+    pub(crate) fn blend(&self) -> AttachmentBlend {
+        self.blend
+    }
+
+    pub(crate) fn framebuffer(&self, image_num: usize) -> &Arc<Framebuffer> {
+        &self.framebuffers[image_num]
+    }
+}
+
+fn framebuffers(
+    render_pass: &Arc<RenderPass>,
+    views: Vec<Arc<ImageView>>,
+) -> Vec<Arc<Framebuffer>> {
+    views
+        .into_iter()
+        .map(|view| {
+            assert!(
+                compat::is_color_attachment(&view),
+                "text target images need color_attachment usage"
+            );
+            Framebuffer::new(
+                render_pass.clone(),
+                FramebufferCreateInfo {
+                    attachments: vec![view],
+                    ..Default::default()
+                },
+            )
+            .unwrap()
+        })
+        .collect()
+}
@@ -0,0 +1,215 @@
+use super::drawtext::TextRendererCore;
+use super::gpu::compat::ImageView;
+use super::inline::InlineObject;
+use super::style::TextStyle;
+
+use rusttype::{point, Rect};
+
+use std::sync::Arc;
+
+// a texture cut into corners, edges and a middle. the corners keep their size, the
+// edges and the middle stretch with the box
+#[derive(Clone)]
+pub struct NineSlice {
+    pub texture: Arc<ImageView>,
+    // how far the corners reach in from the texture's left, top, right and bottom
+    // edges, in uvs
+    pub insets: [f32; 4],
+    // how big the corners are drawn, same order, in pixels
+    pub border: [f32; 4],
+    // multiplied with the texture
+    pub color: [f32; 4],
+}
+
+impl NineSlice {
+    pub fn new(texture: Arc<ImageView>, insets: [f32; 4], border: [f32; 4]) -> NineSlice {
+        NineSlice {
+            texture,
+            insets,
+            border,
+            color: [1.0, 1.0, 1.0, 1.0],
+        }
+    }
+
+    // the nine pieces covering rect, empty ones left out
+    fn pieces(&self, rect: Rect<f32>) -> Vec<(Rect<f32>, InlineObject)> {
+        let [left, top, right, bottom] = self.border;
+        let xs = [
+            rect.min.x,
+            rect.min.x + left,
+            rect.max.x - right,
+            rect.max.x,
+        ];
+        let ys = [
+            rect.min.y,
+            rect.min.y + top,
+            rect.max.y - bottom,
+            rect.max.y,
+        ];
+        let [left, top, right, bottom] = self.insets;
+        let us = [0.0, left, 1.0 - right, 1.0];
+        let vs = [0.0, top, 1.0 - bottom, 1.0];
+        let mut pieces = vec![];
+        for row in 0..3 {
+            for col in 0..3 {
+                if xs[col] >= xs[col + 1] || ys[row] >= ys[row + 1] {
+                    continue;
+                }
+                let piece = Rect {
+                    min: point(xs[col], ys[row]),
+                    max: point(xs[col + 1], ys[row + 1]),
+                };
+                let region = [us[col], vs[row], us[col + 1], vs[row + 1]];
+                let object = InlineObject::new(self.texture.clone())
+                    .with_region(region)
+                    .with_tint(true);
+                pieces.push((piece, object));
+            }
+        }
+        pieces
+    }
+}
+
+#[derive(Clone)]
+pub enum Panel {
+    Solid([f32; 4]),
+    NineSlice(NineSlice),
+}
+
+// the side of the panel the arrow sticks out of
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ArrowSide {
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+// a right-angled triangle pointing away from the panel, size is how far it sticks out
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Arrow {
+    pub side: ArrowSide,
+    pub size: f32,
+    pub color: [f32; 4],
+}
+
+impl Arrow {
+    // strips one physical pixel thick, narrowing towards the tip
+    fn strips(&self, [x, y]: [f32; 2], scale_factor: f64) -> Vec<Rect<f32>> {
+        let count = (self.size * scale_factor as f32).ceil().max(1.0) as usize;
+        let step = self.size / count as f32;
+        (0..count)
+            .map(|i| {
+                // distances from the tip, the half width grows as fast
+                let near = i as f32 * step;
+                let far = near + step;
+                let half = (near + far) / 2.0;
+                let (min, max) = match self.side {
+                    ArrowSide::Bottom => (point(x - half, y - far), point(x + half, y - near)),
+                    ArrowSide::Top => (point(x - half, y + near), point(x + half, y + far)),
+                    ArrowSide::Right => (point(x - far, y - half), point(x - near, y + half)),
+                    ArrowSide::Left => (point(x + near, y - half), point(x + far, y + half)),
+                };
+                Rect { min, max }
+            })
+            .collect()
+    }
+}
+
+// a tooltip: text on a panel sized to fit it, wrapped at max_width, with an optional
+// arrow pointing at what it's about. everything goes on the style's layer
+#[derive(Clone)]
+pub struct TextBox {
+    pub style: TextStyle,
+    // lines wrap at this width, None keeps them as they are
+    pub max_width: Option<f32>,
+    // between the text and the panel's edges, horizontally and vertically
+    pub padding: [f32; 2],
+    pub panel: Panel,
+    pub arrow: Option<Arrow>,
+}
+
+impl TextBox {
+    pub fn new(style: TextStyle, panel: Panel) -> TextBox {
+        TextBox {
+            style,
+            max_width: Some(320.0),
+            padding: [8.0, 6.0],
+            panel,
+            arrow: None,
+        }
+    }
+
+    pub fn with_max_width(mut self, max_width: Option<f32>) -> TextBox {
+        self.max_width = max_width;
+        self
+    }
+
+    pub fn with_padding(mut self, padding: [f32; 2]) -> TextBox {
+        self.padding = padding;
+        self
+    }
+
+    pub fn with_arrow(mut self, side: ArrowSide, size: f32, color: [f32; 4]) -> TextBox {
+        self.arrow = Some(Arrow { side, size, color });
+        self
+    }
+
+    // without an arrow x, y is the panel's top left corner. with one it's where the
+    // arrow's tip points, and the panel is centered on it along the arrow's side.
+    // returns the panel's rect, e.g. to keep the pointer from falling through it
+    pub fn queue(&self, draw_text: &mut TextRendererCore, x: f32, y: f32, text: &str) -> Rect<f32> {
+        let panel = draw_text.from_origin(y, |draw_text| self.queue_panel(draw_text, x, y, text));
+        draw_text.flip_rect(y, panel)
+    }
+
+    fn queue_panel(
+        &self,
+        draw_text: &mut TextRendererCore,
+        x: f32,
+        y: f32,
+        text: &str,
+    ) -> Rect<f32> {
+        let layout = match self.max_width {
+            Some(max_width) => draw_text.layout_text_wrapped(&self.style, text, max_width),
+            None => draw_text.layout_text(&self.style, text),
+        };
+        let [pad_x, pad_y] = self.padding;
+        let [width, height] = [layout.width() + 2.0 * pad_x, layout.height() + 2.0 * pad_y];
+        let min = match self.arrow {
+            None => point(x, y),
+            Some(Arrow { side, size, .. }) => match side {
+                ArrowSide::Bottom => point(x - width / 2.0, y - size - height),
+                ArrowSide::Top => point(x - width / 2.0, y + size),
+                ArrowSide::Right => point(x - size - width, y - height / 2.0),
+                ArrowSide::Left => point(x + size, y - height / 2.0),
+            },
+        };
+        let panel = Rect {
+            min,
+            max: point(min.x + width, min.y + height),
+        };
+
+        let layer = self.style.layer;
+        match &self.panel {
+            Panel::Solid(color) => draw_text.queue_rects(vec![panel], *color, true, layer),
+            Panel::NineSlice(slice) => {
+                draw_text.queue_images(slice.pieces(panel), slice.color, layer)
+            }
+        }
+        if let Some(arrow) = self.arrow {
+            let strips = arrow.strips([x, y], draw_text.scale_factor());
+            draw_text.queue_rects(strips, arrow.color, true, layer);
+        }
+
+        let left = panel.min.x + pad_x;
+        let baseline = panel.min.y + pad_y - layout.line_top(0);
+        match self.max_width {
+            Some(max_width) => {
+                draw_text.queue_text_wrapped(left, baseline, max_width, &self.style, text)
+            }
+            None => draw_text.queue_text_styled(left, baseline, &self.style, text),
+        }
+        panel
+    }
+}
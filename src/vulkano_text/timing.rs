@@ -0,0 +1,66 @@
+use super::gpu::compat;
+
+use vulkano::command_buffer::{AutoCommandBufferBuilder, PrimaryAutoCommandBuffer};
+use vulkano::device::{DeviceOwned, Queue};
+use vulkano::query::QueryPool;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+// a pair of timestamp queries per frame slot, written around the atlas copy and the
+// draw. a slot's results are read without waiting when it comes around again, so
+// the time lags frames_in_flight frames behind
+pub(crate) struct GpuTimer {
+    query_pool: Arc<QueryPool>,
+    // nanoseconds per tick
+    period: f64,
+    // timestamps wrap around past the valid bits
+    mask: u64,
+    // slots with queries written that weren't read yet
+    written: Vec<bool>,
+    last: Option<Duration>,
+}
+
+impl GpuTimer {
+    // None when the queue can't write timestamps
+    pub(crate) fn new(queue: &Queue, slots: usize) -> Option<GpuTimer> {
+        let bits = compat::timestamp_bits(queue)?;
+        let device = queue.device();
+        Some(GpuTimer {
+            query_pool: compat::timestamp_pool(device.clone(), slots as u32 * 2),
+            period: device.physical_device().properties().timestamp_period as f64,
+            mask: u64::MAX >> (64 - bits),
+            written: vec![false; slots],
+            last: None,
+        })
+    }
+
+    pub(crate) fn begin(
+        &mut self,
+        command_buffer: &mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>,
+        slot: usize,
+    ) {
+        let queries = slot as u32 * 2..slot as u32 * 2 + 2;
+        if self.written[slot] {
+            let mut ticks = [0u64; 2];
+            if compat::query_results(&self.query_pool, queries.clone(), &mut ticks) {
+                let ticks = ticks[1].wrapping_sub(ticks[0]) & self.mask;
+                self.last = Some(Duration::from_nanos((ticks as f64 * self.period) as u64));
+            }
+        }
+        compat::begin_timestamps(command_buffer, &self.query_pool, queries);
+    }
+
+    pub(crate) fn end(
+        &mut self,
+        command_buffer: &mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>,
+        slot: usize,
+    ) {
+        compat::end_timestamp(command_buffer, &self.query_pool, slot as u32 * 2 + 1);
+        self.written[slot] = true;
+    }
+
+    pub(crate) fn last(&self) -> Option<Duration> {
+        self.last
+    }
+}
@@ -0,0 +1,26 @@
+// what the positions, sizes and widths handed to DrawText are measured in, and what
+// layouts come back in. the window's scale factor turns them into physical pixels
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Units {
+    // logical pixels, android's density independent pixels: 1/96 inch at a scale factor
+    // of 1
+    #[default]
+    Dp,
+    // typographic points, 1/72 inch, like font sizes in design specs. 12pt is 16dp
+    Points,
+}
+
+impl Units {
+    pub fn dp_per_unit(self) -> f64 {
+        match self {
+            Units::Dp => 1.0,
+            Units::Points => 96.0 / 72.0,
+        }
+    }
+}
+
+// the scale factor of a monitor with this many pixels per inch, for windowing libraries
+// that report dpi instead. 96 is a scale factor of 1
+pub fn scale_factor_for_dpi(dpi: f64) -> f64 {
+    dpi / 96.0
+}
@@ -0,0 +1,104 @@
+use super::metrics::{find_table, read_u16, read_u32};
+use super::style::FontVariation;
+
+// a design axis of a variable font, values are in the axis' own units, e.g. 100 to 900
+// for wght
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VariationAxis {
+    pub tag: [u8; 4],
+    pub min: f32,
+    pub default: f32,
+    pub max: f32,
+}
+
+// a preset the font designer named, like "SemiBold Condensed"
+#[derive(Debug, Clone, PartialEq)]
+pub struct NamedInstance {
+    pub name: Option<String>,
+    pub coordinates: Vec<FontVariation>,
+}
+
+// what the fvar table offers, empty for fonts that aren't variable
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct FontVariations {
+    pub(crate) axes: Vec<VariationAxis>,
+    pub(crate) instances: Vec<NamedInstance>,
+}
+
+fn read_fixed(data: &[u8], offset: usize) -> Option<f32> {
+    read_u32(data, offset).map(|value| value as i32 as f32 / 65536.0)
+}
+
+// english if there is one, otherwise the first unicode entry with that id
+fn read_name(data: &[u8], id: u16) -> Option<String> {
+    let name = find_table(data, b"name")?;
+    let count = read_u16(data, name + 2)? as usize;
+    let strings = name + read_u16(data, name + 4)? as usize;
+    let mut found = None;
+    for record in (0..count).map(|i| name + 6 + i * 12) {
+        let platform = read_u16(data, record)?;
+        let language = read_u16(data, record + 4)?;
+        if read_u16(data, record + 6)? != id || !matches!(platform, 0 | 3) {
+            continue;
+        }
+        let length = read_u16(data, record + 8)? as usize;
+        let start = strings + read_u16(data, record + 10)? as usize;
+        let utf16: Vec<u16> = data
+            .get(start..start + length)?
+            .chunks_exact(2)
+            .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+            .collect();
+        found = String::from_utf16(&utf16).ok();
+        if language == 0x409 {
+            break;
+        }
+    }
+    found
+}
+
+impl FontVariations {
+    pub(crate) fn parse(data: &[u8]) -> FontVariations {
+        FontVariations::parse_fvar(data).unwrap_or_default()
+    }
+
+    fn parse_fvar(data: &[u8]) -> Option<FontVariations> {
+        let fvar = find_table(data, b"fvar")?;
+        let axes_start = fvar + read_u16(data, fvar + 4)? as usize;
+        let axis_count = read_u16(data, fvar + 8)? as usize;
+        let axis_size = read_u16(data, fvar + 10)? as usize;
+        let instance_count = read_u16(data, fvar + 12)? as usize;
+        let instance_size = read_u16(data, fvar + 14)? as usize;
+
+        let mut axes = vec![];
+        for record in (0..axis_count).map(|i| axes_start + i * axis_size) {
+            let tag = data.get(record..record + 4)?;
+            axes.push(VariationAxis {
+                tag: [tag[0], tag[1], tag[2], tag[3]],
+                min: read_fixed(data, record + 4)?,
+                default: read_fixed(data, record + 8)?,
+                max: read_fixed(data, record + 12)?,
+            });
+        }
+
+        let instances_start = axes_start + axis_count * axis_size;
+        let mut instances = vec![];
+        for record in (0..instance_count).map(|i| instances_start + i * instance_size) {
+            let coordinates = axes
+                .iter()
+                .enumerate()
+                .map(|(i, axis)| {
+                    let value = read_fixed(data, record + 4 + i * 4)?;
+                    Some(FontVariation {
+                        tag: axis.tag,
+                        value,
+                    })
+                })
+                .collect::<Option<Vec<_>>>()?;
+            instances.push(NamedInstance {
+                name: read_name(data, read_u16(data, record)?),
+                coordinates,
+            });
+        }
+        Some(FontVariations { axes, instances })
+    }
+}
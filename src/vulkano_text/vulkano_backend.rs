@@ -0,0 +1,131 @@
+use super::drawtext::{atlas_sampler, text_pipeline, TextVertices};
+use super::frames::{upload_vertices, FrameSlot, UploadedVertices, VertexMemory};
+use super::gpu::compat::{self, Filter, Sampler, SamplerAddressMode};
+use super::gpu_backend::{AtlasPixels, TextGpuBackend};
+use super::quads::GlyphQuad;
+
+use vulkano::command_buffer::{AutoCommandBufferBuilder, PrimaryAutoCommandBuffer};
+use vulkano::descriptor_set::allocator::StandardDescriptorSetAllocator;
+use vulkano::descriptor_set::WriteDescriptorSet;
+use vulkano::device::Device;
+use vulkano::memory::allocator::StandardMemoryAllocator;
+use vulkano::pipeline::graphics::color_blend::AttachmentBlend;
+use vulkano::pipeline::{GraphicsPipeline, Pipeline};
+use vulkano::render_pass::Subpass;
+
+use std::sync::Arc;
+
+// the vulkano backend, for drawing into a subpass of your own render pass. keeps an
+// atlas per frame in flight like DrawText does, and copies only the rects changed
+// since a frame's last upload into it
+pub struct VulkanoBackend {
+    memory_allocator: Arc<StandardMemoryAllocator>,
+    descriptor_set_allocator: StandardDescriptorSetAllocator,
+    pipeline: Arc<GraphicsPipeline>,
+    sampler: Arc<Sampler>,
+    frames: Vec<FrameSlot>,
+    frames_in_flight: usize,
+    frame: usize,
+    vertices: Option<UploadedVertices>,
+    extent: [u32; 2],
+}
+
+impl VulkanoBackend {
+    pub fn new(
+        device: Arc<Device>,
+        memory_allocator: Arc<StandardMemoryAllocator>,
+        subpass: Subpass,
+        frames_in_flight: usize,
+    ) -> VulkanoBackend {
+        assert!(
+            frames_in_flight > 0,
+            "at least one frame has to be in flight"
+        );
+        VulkanoBackend {
+            memory_allocator,
+            descriptor_set_allocator: compat::descriptor_set_allocator(device.clone()),
+            pipeline: text_pipeline(device.clone(), subpass, AttachmentBlend::alpha(), None),
+            sampler: atlas_sampler(device, Filter::Linear, SamplerAddressMode::ClampToEdge),
+            frames: vec![],
+            frames_in_flight,
+            frame: 0,
+            vertices: None,
+            extent: [0, 0],
+        }
+    }
+
+    fn slot(&self) -> Option<&FrameSlot> {
+        self.frames.get(self.frame % self.frames_in_flight)
+    }
+}
+
+impl TextGpuBackend for VulkanoBackend {
+    type Upload<'a> = AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>;
+    type Pass<'a> = AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>;
+
+    fn upload_atlas(
+        &mut self,
+        upload: &mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>,
+        atlas: &AtlasPixels,
+    ) {
+        // every upload starts a frame, so the slot of the oldest frame is reused
+        self.frame += 1;
+        if self.frames.len() != self.frames_in_flight
+            || self.frames.iter().any(|slot| slot.pages() != atlas.pages)
+        {
+            self.frames = (0..self.frames_in_flight)
+                .map(|_| FrameSlot::new(&self.memory_allocator, atlas.extent, atlas.pages))
+                .collect();
+        }
+        let slot = &mut self.frames[self.frame % self.frames_in_flight];
+        slot.upload(upload, atlas);
+
+        if slot.descriptor_set.is_none() {
+            let view = compat::array_view(slot.atlas());
+            slot.descriptor_set = Some(compat::descriptor_set(
+                &self.descriptor_set_allocator,
+                self.pipeline.layout().set_layouts()[0].clone(),
+                [WriteDescriptorSet::image_view_sampler(
+                    0,
+                    view,
+                    self.sampler.clone(),
+                )],
+            ));
+        }
+    }
+
+    fn upload_quads(
+        &mut self,
+        upload: &mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>,
+        quads: &[GlyphQuad],
+        extent: [u32; 2],
+    ) {
+        let screen = [extent[0] as f32, extent[1] as f32];
+        let vertices = TextVertices {
+            vertices: quads
+                .iter()
+                .flat_map(|quad| quad.vertices(screen))
+                .collect(),
+            textured: vec![],
+        };
+        self.vertices = upload_vertices(
+            Some(upload),
+            &self.memory_allocator,
+            VertexMemory::DeviceLocal,
+            vertices,
+        );
+        self.extent = extent;
+    }
+
+    fn draw<'a>(&'a self, pass: &mut Self::Pass<'a>) {
+        let set = self.slot().and_then(|slot| slot.descriptor_set.as_ref());
+        let (vertices, set) = match (&self.vertices, set) {
+            (Some(vertices), Some(set)) => (vertices, set),
+            _ => return,
+        };
+        compat::set_viewport_scissor(pass, self.extent, None);
+        compat::bind_graphics(pass, &self.pipeline, set.clone());
+        compat::bind_vertex_buffer(pass, &vertices.buffer);
+        pass.draw(vertices.len, 1, 0, 0).unwrap();
+    }
+}
@@ -0,0 +1,229 @@
+use super::color::ColorSpace;
+use super::gpu_backend::{AtlasPixels, TextGpuBackend};
+use super::quads::{GlyphQuad, Vertex};
+
+use wgpu::util::DeviceExt;
+
+use std::mem;
+use std::num::NonZeroU32;
+
+// what WgpuBackend uploads with, wgpu writes through the queue instead of recording
+pub struct WgpuUpload<'a> {
+    pub device: &'a wgpu::Device,
+    pub queue: &'a wgpu::Queue,
+}
+
+// the wgpu backend, draws into render passes on color targets of the given format
+pub struct WgpuBackend {
+    pipeline: wgpu::RenderPipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    // the texture with its page count, and the version last written into it
+    atlas: Option<(wgpu::Texture, u32)>,
+    version: Option<u64>,
+    bind_group: Option<wgpu::BindGroup>,
+    vertices: Option<(wgpu::Buffer, u32)>,
+    // the format encodes linear values to srgb itself, so the quads' srgb colors are
+    // linearized first like DrawText does for such targets
+    linear: bool,
+}
+
+// the same as vulkano's AttachmentBlend::alpha
+const BLEND: wgpu::BlendComponent = wgpu::BlendComponent {
+    src_factor: wgpu::BlendFactor::SrcAlpha,
+    dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+    operation: wgpu::BlendOperation::Add,
+};
+
+impl WgpuBackend {
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat) -> WgpuBackend {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("text"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../shaders/text.wgsl").into()),
+        });
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("text atlas"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2Array,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("text"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        // position, tex_position, color and fade of Vertex, the fill uv is left out
+        let attributes = wgpu::vertex_attr_array![
+            0 => Float32x2,
+            1 => Float32x3,
+            2 => Float32x4,
+            3 => Float32x3
+        ];
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("text"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[wgpu::VertexBufferLayout {
+                    array_stride: mem::size_of::<Vertex>() as wgpu::BufferAddress,
+                    step_mode: wgpu::VertexStepMode::Vertex,
+                    attributes: &attributes,
+                }],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState {
+                        color: BLEND,
+                        alpha: BLEND,
+                    }),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("text atlas"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        WgpuBackend {
+            pipeline,
+            bind_group_layout,
+            sampler,
+            atlas: None,
+            version: None,
+            bind_group: None,
+            vertices: None,
+            linear: format.describe().srgb
+                || matches!(
+                    format,
+                    wgpu::TextureFormat::Rgba16Float
+                        | wgpu::TextureFormat::Rgba32Float
+                        | wgpu::TextureFormat::Rg11b10Float
+                ),
+        }
+    }
+}
+
+impl TextGpuBackend for WgpuBackend {
+    type Upload<'a> = WgpuUpload<'a>;
+    type Pass<'a> = wgpu::RenderPass<'a>;
+
+    fn upload_atlas(&mut self, upload: &mut WgpuUpload<'_>, atlas: &AtlasPixels) {
+        if self.version == Some(atlas.version) {
+            return;
+        }
+
+        let [width, height] = atlas.extent;
+        let size = wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: atlas.pages,
+        };
+        // a new page means a new texture, the old one can't grow
+        if self.atlas.as_ref().map(|(_, pages)| *pages) != Some(atlas.pages) {
+            let texture = upload.device.create_texture(&wgpu::TextureDescriptor {
+                label: Some("text atlas"),
+                size,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::R8Unorm,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            });
+            let view = texture.create_view(&wgpu::TextureViewDescriptor {
+                dimension: Some(wgpu::TextureViewDimension::D2Array),
+                ..Default::default()
+            });
+            self.bind_group = Some(upload.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("text atlas"),
+                layout: &self.bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(&view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(&self.sampler),
+                    },
+                ],
+            }));
+            self.atlas = Some((texture, atlas.pages));
+        }
+
+        let (texture, _) = self.atlas.as_ref().unwrap();
+        upload.queue.write_texture(
+            texture.as_image_copy(),
+            atlas.pixels,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: NonZeroU32::new(width),
+                rows_per_image: NonZeroU32::new(height),
+            },
+            size,
+        );
+        self.version = Some(atlas.version);
+    }
+
+    fn upload_quads(&mut self, upload: &mut WgpuUpload<'_>, quads: &[GlyphQuad], extent: [u32; 2]) {
+        let screen = [extent[0] as f32, extent[1] as f32];
+        let mut vertices: Vec<Vertex> = quads
+            .iter()
+            .flat_map(|quad| quad.vertices(screen))
+            .collect();
+        if self.linear {
+            for vertex in &mut vertices {
+                vertex.color = ColorSpace::Srgb.convert(vertex.color, ColorSpace::Linear);
+            }
+        }
+        if vertices.is_empty() {
+            self.vertices = None;
+            return;
+        }
+
+        let buffer = upload
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("text vertices"),
+                contents: bytemuck::cast_slice(&vertices),
+                usage: wgpu::BufferUsages::VERTEX,
+            });
+        self.vertices = Some((buffer, vertices.len() as u32));
+    }
+
+    fn draw<'a>(&'a self, pass: &mut Self::Pass<'a>) {
+        let (buffer, len, bind_group) = match (&self.vertices, &self.bind_group) {
+            (Some((buffer, len)), Some(bind_group)) => (buffer, *len, bind_group),
+            _ => return,
+        };
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, bind_group, &[]);
+        pass.set_vertex_buffer(0, buffer.slice(..));
+        pass.draw(0..len, 0..1);
+    }
+}
@@ -0,0 +1,94 @@
+use super::input::{InputEvent, Key, Modifiers};
+
+use winit::event::{
+    ElementState, Ime, KeyboardInput, MouseScrollDelta, VirtualKeyCode, WindowEvent,
+};
+
+impl InputEvent {
+    // None for events the text input doesn't care about
+    pub fn from_winit(event: &WindowEvent) -> Option<InputEvent> {
+        Some(match event {
+            WindowEvent::ModifiersChanged(modifiers) => InputEvent::Modifiers(Modifiers {
+                shift: modifiers.shift(),
+                ctrl: modifiers.ctrl(),
+            }),
+            WindowEvent::ReceivedCharacter(c) => InputEvent::Char(*c),
+            WindowEvent::Ime(Ime::Preedit(text, cursor)) => {
+                InputEvent::Preedit(text.clone(), cursor.map(|(start, _)| start))
+            }
+            WindowEvent::Ime(Ime::Commit(text)) => InputEvent::Commit(text.clone()),
+            WindowEvent::Ime(Ime::Disabled) => InputEvent::ImeDisabled,
+            WindowEvent::KeyboardInput {
+                input:
+                    KeyboardInput {
+                        state: ElementState::Pressed,
+                        virtual_keycode: Some(key),
+                        ..
+                    },
+                ..
+            } => InputEvent::KeyPressed(key_from_winit(*key)?),
+            WindowEvent::MouseWheel {
+                delta: MouseScrollDelta::LineDelta(_, lines),
+                ..
+            } => InputEvent::Scroll(*lines),
+            _ => return None,
+        })
+    }
+}
+
+fn key_from_winit(key: VirtualKeyCode) -> Option<Key> {
+    Some(match key {
+        VirtualKeyCode::Left => Key::Left,
+        VirtualKeyCode::Right => Key::Right,
+        VirtualKeyCode::Home => Key::Home,
+        VirtualKeyCode::End => Key::End,
+        VirtualKeyCode::Back => Key::Backspace,
+        VirtualKeyCode::Delete => Key::Delete,
+        VirtualKeyCode::Return | VirtualKeyCode::NumpadEnter => Key::Enter,
+        VirtualKeyCode::PageUp => Key::PageUp,
+        VirtualKeyCode::PageDown => Key::PageDown,
+        _ => Key::Character(char_from_winit(key)?),
+    })
+}
+
+fn char_from_winit(key: VirtualKeyCode) -> Option<char> {
+    Some(match key {
+        VirtualKeyCode::A => 'a',
+        VirtualKeyCode::B => 'b',
+        VirtualKeyCode::C => 'c',
+        VirtualKeyCode::D => 'd',
+        VirtualKeyCode::E => 'e',
+        VirtualKeyCode::F => 'f',
+        VirtualKeyCode::G => 'g',
+        VirtualKeyCode::H => 'h',
+        VirtualKeyCode::I => 'i',
+        VirtualKeyCode::J => 'j',
+        VirtualKeyCode::K => 'k',
+        VirtualKeyCode::L => 'l',
+        VirtualKeyCode::M => 'm',
+        VirtualKeyCode::N => 'n',
+        VirtualKeyCode::O => 'o',
+        VirtualKeyCode::P => 'p',
+        VirtualKeyCode::Q => 'q',
+        VirtualKeyCode::R => 'r',
+        VirtualKeyCode::S => 's',
+        VirtualKeyCode::T => 't',
+        VirtualKeyCode::U => 'u',
+        VirtualKeyCode::V => 'v',
+        VirtualKeyCode::W => 'w',
+        VirtualKeyCode::X => 'x',
+        VirtualKeyCode::Y => 'y',
+        VirtualKeyCode::Z => 'z',
+        VirtualKeyCode::Key0 | VirtualKeyCode::Numpad0 => '0',
+        VirtualKeyCode::Key1 | VirtualKeyCode::Numpad1 => '1',
+        VirtualKeyCode::Key2 | VirtualKeyCode::Numpad2 => '2',
+        VirtualKeyCode::Key3 | VirtualKeyCode::Numpad3 => '3',
+        VirtualKeyCode::Key4 | VirtualKeyCode::Numpad4 => '4',
+        VirtualKeyCode::Key5 | VirtualKeyCode::Numpad5 => '5',
+        VirtualKeyCode::Key6 | VirtualKeyCode::Numpad6 => '6',
+        VirtualKeyCode::Key7 | VirtualKeyCode::Numpad7 => '7',
+        VirtualKeyCode::Key8 | VirtualKeyCode::Numpad8 => '8',
+        VirtualKeyCode::Key9 | VirtualKeyCode::Numpad9 => '9',
+        _ => return None,
+    })
+}
@@ -0,0 +1,88 @@
+// saved atlases loaded back through TextQuads, which packs glyphs like DrawText without
+// a device. a loaded atlas has the glyphs of the saved one, so queueing the same text
+// rasterizes nothing
+use font_render::vulkano_text::{
+    AtlasPixels, GlyphQuad, TextGpuBackend, TextLayouter, TextQuads, TextStyle,
+};
+
+const EXTENT: [u32; 2] = [800, 600];
+const TEXT: &str = "Hello, World! 0123456789";
+
+// keeps what it's handed instead of drawing it
+#[derive(Default)]
+struct Recorder {
+    pixels: Vec<u8>,
+    version: u64,
+    quads: Vec<GlyphQuad>,
+}
+
+impl TextGpuBackend for Recorder {
+    type Upload<'a> = ();
+    type Pass<'a> = ();
+
+    fn upload_atlas(&mut self, _upload: &mut (), atlas: &AtlasPixels) {
+        self.pixels = atlas.pixels.to_vec();
+        self.version = atlas.version;
+    }
+
+    fn upload_quads(&mut self, _upload: &mut (), quads: &[GlyphQuad], _extent: [u32; 2]) {
+        self.quads = quads.to_vec();
+    }
+
+    fn draw(&self, _pass: &mut ()) {}
+}
+
+fn queue(quads: &mut TextQuads) {
+    quads.queue_text_styled(10.0, 20.0, &TextStyle::new(16.0, [1.0; 4]), TEXT);
+    quads.queue_text_styled(10.0, 60.0, &TextStyle::new(32.0, [1.0; 4]), TEXT);
+}
+
+#[test]
+fn save_and_load_round_trip() {
+    let mut saved = TextQuads::new();
+    queue(&mut saved);
+    let mut drawn = Recorder::default();
+    saved.upload_to(&mut drawn, &mut (), EXTENT);
+    let data = saved.save_atlas();
+
+    let mut loaded = TextQuads::new();
+    assert!(loaded.load_atlas(&data));
+    assert!(
+        loaded.save_atlas() == data,
+        "saving a loaded atlas changed it"
+    );
+
+    let mut backend = Recorder::default();
+    loaded.upload_to(&mut backend, &mut (), EXTENT);
+    let version = backend.version;
+    queue(&mut loaded);
+    loaded.upload_to(&mut backend, &mut (), EXTENT);
+    assert_eq!(
+        backend.version, version,
+        "glyphs of the loaded atlas were rasterized again"
+    );
+    assert!(backend.pixels == drawn.pixels);
+    assert_eq!(backend.quads, drawn.quads);
+}
+
+#[test]
+fn load_rejects_other_fonts_and_garbage() {
+    // saved with a font the loading side doesn't have
+    let mut layouter = TextLayouter::new();
+    layouter
+        .add_font(include_bytes!("../src/font/DejaVuSans.ttf").to_vec())
+        .unwrap();
+    let mut saved = TextQuads::with_layouter(layouter);
+    queue(&mut saved);
+    let data = saved.save_atlas();
+
+    let mut quads = TextQuads::new();
+    assert!(!quads.load_atlas(&data));
+
+    let mut saved = TextQuads::new();
+    queue(&mut saved);
+    let data = saved.save_atlas();
+
+    assert!(!quads.load_atlas(b"not an atlas"));
+    assert!(!quads.load_atlas(&data[..data.len() / 2]));
+}
@@ -0,0 +1,53 @@
+// carets, clicks and selections in text mixing left to right and right to left runs
+use font_render::vulkano_text::{MissingGlyphPolicy, TextLayout, TextLayouter, TextStyle};
+
+fn layout(text: &str) -> TextLayout {
+    let mut layouter = TextLayouter::new();
+    // every char takes room, whether the bundled font has it or not
+    layouter.set_missing_glyph_policy(MissingGlyphPolicy::Tofu);
+    layouter.layout_text(&TextStyle::new(16.0, [1.0; 4]), text)
+}
+
+fn caret_x(layout: &TextLayout, byte: usize) -> f32 {
+    layout.caret_position(byte).0
+}
+
+#[test]
+fn right_to_left_run_in_left_to_right_text() {
+    // a b c space alef bet gimel, hebrew chars take two bytes
+    let layout = layout("abc אבג");
+    assert!(caret_x(&layout, 0) < caret_x(&layout, 3));
+    // in front of alef is the right end of the run, after gimel its left end
+    assert!((caret_x(&layout, 4) - layout.width()).abs() < 0.01);
+    assert!(caret_x(&layout, 10) < caret_x(&layout, 8));
+    assert!(caret_x(&layout, 8) < caret_x(&layout, 6));
+    assert!(caret_x(&layout, 6) < caret_x(&layout, 4));
+    assert!(caret_x(&layout, 10) > caret_x(&layout, 3));
+
+    let y = layout.caret_position(0).1 + 1.0;
+    assert_eq!(layout.hit_test(layout.width() - 0.5, y), 4);
+    assert_eq!(layout.hit_test(caret_x(&layout, 10) + 0.5, y), 10);
+    assert_eq!(layout.hit_test(-5.0, y), 0);
+
+    // abc and the space on the left, alef alone on the right
+    assert_eq!(layout.selection_rects(0..5).len(), 2);
+    assert_eq!(layout.selection_rects(4..10).len(), 1);
+}
+
+#[test]
+fn numbers_in_right_to_left_text_read_left_to_right() {
+    // shin lamed space 1 2, the paragraph runs right to left
+    let layout = layout("של 12");
+    assert!(caret_x(&layout, 5) < 0.01);
+    assert!(caret_x(&layout, 5) < caret_x(&layout, 6));
+    assert!(caret_x(&layout, 6) < caret_x(&layout, 2));
+    assert!((caret_x(&layout, 0) - layout.width()).abs() < 0.01);
+}
+
+#[test]
+fn left_to_right_text_keeps_its_order() {
+    let layout = layout("abc def");
+    let carets: Vec<f32> = (0..=7).map(|byte| caret_x(&layout, byte)).collect();
+    assert!(carets.windows(2).all(|pair| pair[0] < pair[1]));
+    assert_eq!(layout.selection_rects(1..6).len(), 1);
+}
@@ -0,0 +1,13 @@
+// fonts added from bytes the caller can't check beforehand
+use font_render::vulkano_text::TextLayouter;
+
+#[test]
+fn add_font_rejects_data_that_isnt_a_font() {
+    let mut layouter = TextLayouter::new();
+    assert!(layouter.add_font(b"not a font".to_vec()).is_err());
+    assert!(layouter.add_font(vec![]).is_err());
+
+    let font = include_bytes!("../src/font/DejaVuSans.ttf");
+    assert!(layouter.add_font(font[..font.len() / 8].to_vec()).is_err());
+    assert!(layouter.add_font(font.to_vec()).is_ok());
+}
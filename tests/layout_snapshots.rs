@@ -0,0 +1,71 @@
+// layouts of the bundled font compared against tests/snapshots, so changes to the
+// layout show up as a diff. TextLayouter lays out like DrawText without a device. a
+// missing snapshot fails like a changed one, UPDATE_SNAPSHOTS=1 writes all of them
+use font_render::vulkano_text::{TextLayout, TextLayouter, TextStyle, WritingMode};
+
+use std::path::PathBuf;
+
+fn check(name: &str, layout: TextLayout) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/snapshots")
+        .join(format!("{}.txt", name));
+    let actual = layout.snapshot().to_string();
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, &actual).unwrap();
+        return;
+    }
+    let expected = match std::fs::read_to_string(&path) {
+        Ok(expected) => expected.replace("\r\n", "\n"),
+        Err(_) => panic!(
+            "no snapshot {}, run with UPDATE_SNAPSHOTS=1 to write it",
+            path.display()
+        ),
+    };
+    assert!(
+        actual == expected,
+        "layout {} changed, run with UPDATE_SNAPSHOTS=1 if that's intended\n--- expected\n{}--- actual\n{}",
+        name,
+        expected,
+        actual
+    );
+}
+
+#[test]
+fn layout_snapshots() {
+    let layouter = TextLayouter::new();
+    let style = TextStyle::new(16.0, [1.0; 4]);
+
+    check("single_line", layouter.layout_text(&style, "Hello, World!"));
+    check(
+        "kerning",
+        layouter.layout_text(&TextStyle::new(48.0, [1.0; 4]), "AVATAR Ty. WAVE"),
+    );
+    check(
+        "newlines",
+        layouter.layout_text(&style, "first line\n\nthird line\n"),
+    );
+    check(
+        "wrapped",
+        layouter.layout_text_wrapped(
+            &style,
+            "The quick brown fox jumps over the lazy dog, then keeps running far past the edge.",
+            160.0,
+        ),
+    );
+    check(
+        "clusters",
+        layouter.layout_text(&style, "e\u{301}te\u{301} n\u{303}o 👍🏽 🇯🇵"),
+    );
+    check(
+        "vertical",
+        layouter.layout_text(
+            &style.clone().with_writing_mode(WritingMode::VerticalRl),
+            "縦書き\nabc",
+        ),
+    );
+    check(
+        "bidi",
+        layouter.layout_text(&style, "abc אבג 123, def\nשלום 42!"),
+    );
+}
@@ -0,0 +1,72 @@
+// is_dirty across frames of a headless DrawText. needs a Vulkan device, so it's ignored
+// by default: run it with cargo test --test redraw -- --ignored on a machine with a gpu
+use font_render::vulkano_text::{Anchor, DrawText, Offset, TextStyle};
+
+use vulkano::command_buffer::allocator::StandardCommandBufferAllocator;
+use vulkano::device::{Device, DeviceCreateInfo, Queue, QueueCreateInfo, QueueFlags};
+use vulkano::instance::{Instance, InstanceCreateInfo};
+use vulkano::memory::allocator::StandardMemoryAllocator;
+use vulkano::VulkanLibrary;
+
+use std::sync::Arc;
+
+const EXTENT: [u32; 2] = [800, 600];
+
+// any device with a graphics queue, nothing is presented
+fn device() -> Option<(Arc<Device>, Arc<Queue>)> {
+    let library = VulkanLibrary::new().ok()?;
+    let instance = Instance::new(library, InstanceCreateInfo::default()).ok()?;
+    let (physical_device, queue_family_index) =
+        instance.enumerate_physical_devices().ok()?.find_map(|p| {
+            p.queue_family_properties()
+                .iter()
+                .position(|q| q.queue_flags.intersects(QueueFlags::GRAPHICS))
+                .map(|i| (p, i as u32))
+        })?;
+    let (device, mut queues) = Device::new(
+        physical_device,
+        DeviceCreateInfo {
+            queue_create_infos: vec![QueueCreateInfo {
+                queue_family_index,
+                ..Default::default()
+            }],
+            ..Default::default()
+        },
+    )
+    .ok()?;
+    Some((device, queues.next().unwrap()))
+}
+
+// a plain and an anchored label, anchors move texts when they are drawn
+fn queue(draw_text: &mut DrawText, label: &str) {
+    let style = TextStyle::new(16.0, [1.0; 4]);
+    draw_text.queue_text_styled(10.0, 20.0, &style, label);
+    draw_text.queue_text_anchored(
+        Anchor::BottomRight,
+        Offset::Pixels(8.0),
+        Offset::Pixels(8.0),
+        &style,
+        "fps 60",
+    );
+}
+
+#[test]
+#[ignore = "needs a Vulkan device"]
+fn same_text_twice_is_not_dirty() {
+    let (device, graphics_queue) = device().expect("no Vulkan device with a graphics queue");
+    let memory_allocator = Arc::new(StandardMemoryAllocator::new_default(device.clone()));
+    let command_buffer_allocator =
+        StandardCommandBufferAllocator::new(device.clone(), Default::default());
+    let mut draw_text = DrawText::headless(device, graphics_queue);
+
+    queue(&mut draw_text, "Hello, World!");
+    assert!(draw_text.is_dirty());
+    draw_text.generate_quads(&memory_allocator, &command_buffer_allocator, EXTENT);
+
+    queue(&mut draw_text, "Hello, World!");
+    assert!(!draw_text.is_dirty());
+    draw_text.generate_quads(&memory_allocator, &command_buffer_allocator, EXTENT);
+
+    queue(&mut draw_text, "Hello, World?");
+    assert!(draw_text.is_dirty());
+}